@@ -9,10 +9,12 @@ use std::{
 };
 
 use ethsign::KeyFile;
-use raiden_blockchain::keys::PrivateKey;
+use raiden_blockchain::keys::{
+	KeystoreSigner,
+	Signer,
+};
 use raiden_primitives::types::Address;
 use web3::{
-	signing::Key,
 	transports::Http,
 	Web3,
 };
@@ -36,8 +38,8 @@ pub async fn unlock_private_key(
 	web3: Web3<Http>,
 	key_filename: String,
 	password: String,
-) -> Result<PrivateKey, String> {
-	let private_key = PrivateKey::new(key_filename.clone(), password.clone())
+) -> Result<KeystoreSigner, String> {
+	let private_key = KeystoreSigner::new(key_filename.clone(), password.clone())
 		.map_err(|e| format!("Could not unlock private key: {:?}", e))?;
 
 	if !password.is_empty() {
@@ -9,9 +9,14 @@ use std::{
 	},
 	path::PathBuf,
 	str::FromStr,
+	sync::Arc,
 };
 
-use raiden_blockchain::keys::PrivateKey;
+use raiden_blockchain::keys::{
+	KeystoreSigner,
+	RemoteSigner,
+	Signer,
+};
 use raiden_primitives::types::Address;
 use web3::{
 	transports::Http,
@@ -56,7 +61,7 @@ pub async fn init_private_key(
 	keystore_path: PathBuf,
 	address: Option<Address>,
 	password_file: Option<PathBuf>,
-) -> Result<PrivateKey, String> {
+) -> Result<KeystoreSigner, String> {
 	let keys = list_keys(&keystore_path).map_err(|e| format!("Could not list accounts: {}", e))?;
 
 	let key_filename = if let Some(address) = address {
@@ -79,3 +84,27 @@ pub async fn init_private_key(
 
 	unlock_private_key(web3, key_filename, password).await
 }
+
+/// Connect to the remote signer at `remote_signer_url` if set, otherwise unlock a key of the
+/// keystore.
+pub async fn init_signer(
+	web3: Web3<Http>,
+	keystore_path: Option<PathBuf>,
+	address: Option<Address>,
+	password_file: Option<PathBuf>,
+	remote_signer_url: Option<String>,
+) -> Result<Arc<dyn Signer>, String> {
+	if let Some(remote_signer_url) = remote_signer_url {
+		let address =
+			address.ok_or_else(|| "An address is required with a remote signer".to_owned())?;
+		let signer = RemoteSigner::connect(&remote_signer_url, address)
+			.await
+			.map_err(|e| format!("Could not connect to remote signer: {}", e))?;
+		return Ok(Arc::new(signer))
+	}
+
+	let keystore_path = keystore_path
+		.ok_or_else(|| "A keystore path is required without a remote signer".to_owned())?;
+	let private_key = init_private_key(web3, keystore_path, address, password_file).await?;
+	Ok(Arc::new(private_key))
+}
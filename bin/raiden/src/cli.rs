@@ -1,13 +1,26 @@
 use std::{
 	error::Error,
+	net::SocketAddr,
 	path::PathBuf,
+	sync::Arc,
 };
 
 use raiden_bin_common::parse_address;
+use raiden_blockchain::gas::{
+	CappedGasStrategy,
+	FixedGasStrategy,
+	GasFees,
+	GasStrategies,
+	GasStrategy,
+	OracleGasStrategy,
+	PercentileGasStrategy,
+};
 use raiden_network_transport::{
 	config::{
 		MatrixTransportConfig,
+		TcpTransportConfig,
 		TransportConfig,
+		TransportMode,
 	},
 	matrix::constants::MATRIX_AUTO_SELECT_SERVER,
 	types::EnvironmentType,
@@ -24,6 +37,7 @@ use raiden_primitives::{
 	},
 	types::{
 		Address,
+		GasPrice,
 		TokenAmount,
 		U256,
 	},
@@ -33,6 +47,8 @@ use structopt::{
 	StructOpt,
 };
 
+use crate::metrics::TransportQueueMetrics;
+
 /// Parse a single key-value pair
 fn parse_key_val<T, U>(s: &str) -> Result<(T, U), Box<dyn Error + Send + Sync + 'static>>
 where
@@ -78,6 +94,34 @@ fn parse_routing_mode(src: &str) -> Result<ArgRoutingMode, Box<dyn Error + Send
 	}
 }
 
+fn parse_transport_mode(
+	src: &str,
+) -> Result<ArgTransportMode, Box<dyn Error + Send + Sync + 'static>> {
+	match src {
+		"matrix" => Ok(ArgTransportMode::Matrix),
+		"tcp" => Ok(ArgTransportMode::Tcp),
+		"combined" => Ok(ArgTransportMode::Combined),
+		_ => Err("Invalid transport mode".to_owned().into()),
+	}
+}
+
+fn parse_gas_strategy(src: &str) -> Result<ArgGasStrategy, Box<dyn Error + Send + Sync + 'static>> {
+	match src {
+		"fixed" => Ok(ArgGasStrategy::Fixed),
+		"oracle" => Ok(ArgGasStrategy::Oracle),
+		"percentile" => Ok(ArgGasStrategy::Percentile),
+		_ => Err("Invalid gas strategy".to_owned().into()),
+	}
+}
+
+fn parse_rebalance_threshold(src: &str) -> Result<u8, Box<dyn Error + Send + Sync + 'static>> {
+	let threshold: u8 = src.parse().map_err(|e| format!("Invalid rebalance threshold: {:?}", e))?;
+	if !(51..=100).contains(&threshold) {
+		return Err("Rebalance threshold has to be between 51 and 100".to_owned().into())
+	}
+	Ok(threshold)
+}
+
 arg_enum! {
 	#[derive(Debug, PartialEq)]
 	pub enum ArgEnvironmentType {
@@ -112,6 +156,25 @@ impl From<ArgRoutingMode> for RoutingMode {
 	}
 }
 
+arg_enum! {
+	#[derive(Debug, Clone, PartialEq)]
+	pub enum ArgTransportMode {
+		Matrix,
+		Tcp,
+		Combined,
+	}
+}
+
+impl From<ArgTransportMode> for TransportMode {
+	fn from(m: ArgTransportMode) -> Self {
+		match m {
+			ArgTransportMode::Matrix => TransportMode::Matrix,
+			ArgTransportMode::Tcp => TransportMode::Tcp,
+			ArgTransportMode::Combined => TransportMode::Combined,
+		}
+	}
+}
+
 #[derive(StructOpt, Debug)]
 pub struct CliMediationConfig {
 	#[structopt(long, parse(try_from_str = parse_key_val), number_of_values = 1)]
@@ -190,6 +253,23 @@ pub struct CliMatrixTransportConfig {
 	pub retry_timeout: u8,
 	#[structopt(long, default_value = "60")]
 	pub retry_timeout_max: u8,
+	/// The transports used to exchange messages: `matrix`, `tcp` or `combined`, where peers
+	/// advertising a socket address are reached over TCP and all others through matrix.
+	#[structopt(
+		long,
+		parse(try_from_str = parse_transport_mode),
+		default_value = "matrix",
+		required = false,
+		takes_value = true
+	)]
+	pub transport: ArgTransportMode,
+	/// Socket address on which direct TCP connections from peers are accepted.
+	#[structopt(long, required = false, takes_value = true, default_value = "0.0.0.0:5001")]
+	pub tcp_listen_address: SocketAddr,
+	/// Socket address advertised to peers for direct TCP connections, required by the `tcp` and
+	/// `combined` transports. It has to be an address peers can connect to, not `0.0.0.0`.
+	#[structopt(long, takes_value = true)]
+	pub tcp_advertised_address: Option<SocketAddr>,
 }
 
 #[allow(clippy::from_over_into)]
@@ -199,11 +279,136 @@ impl Into<TransportConfig> for CliMatrixTransportConfig {
 			retry_timeout: self.retry_timeout,
 			retry_timeout_max: self.retry_timeout_max,
 			retry_count: self.retry_count,
+			mode: self.transport.into(),
 			matrix: MatrixTransportConfig { homeserver_url: self.matrix_server },
+			tcp: TcpTransportConfig {
+				listen_address: self.tcp_listen_address,
+				advertised_address: self.tcp_advertised_address,
+			},
+			metrics: Some(Arc::new(TransportQueueMetrics)),
 		}
 	}
 }
 
+arg_enum! {
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	pub enum ArgGasStrategy {
+		Fixed,
+		Oracle,
+		Percentile,
+	}
+}
+
+/// Build a gas strategy, capped at `max_fee` unless it is zero.
+fn gas_strategy(
+	strategy: ArgGasStrategy,
+	percentile: f64,
+	max_fee: GasPrice,
+	priority_fee: GasPrice,
+) -> Result<Arc<dyn GasStrategy>, String> {
+	let strategy: Arc<dyn GasStrategy> = match strategy {
+		ArgGasStrategy::Fixed => {
+			if max_fee.is_zero() {
+				return Err("The fixed gas strategy requires a max fee".to_owned())
+			}
+			Arc::new(FixedGasStrategy { fees: GasFees::new(max_fee, priority_fee) })
+		},
+		ArgGasStrategy::Oracle => Arc::new(OracleGasStrategy),
+		ArgGasStrategy::Percentile => {
+			if !(0.0..=100.0).contains(&percentile) {
+				return Err("The gas percentile has to be between 0 and 100".to_owned())
+			}
+			Arc::new(PercentileGasStrategy { percentile })
+		},
+	};
+	if max_fee.is_zero() {
+		return Ok(strategy)
+	}
+	Ok(Arc::new(CappedGasStrategy { inner: strategy, max_fee_per_gas: max_fee }))
+}
+
+#[derive(StructOpt, Clone, Debug)]
+pub struct CliGasConfig {
+	/// How the fees of on-chain transactions are chosen: `fixed`, `oracle` for the suggestion
+	/// of the node or `percentile` for the priority fees paid in recent blocks.
+	#[structopt(
+		long,
+		parse(try_from_str = parse_gas_strategy),
+		default_value = "oracle",
+		required = false,
+		takes_value = true
+	)]
+	pub gas_strategy: ArgGasStrategy,
+	/// Percentile of the priority fees paid in recent blocks used by the `percentile` strategy.
+	#[structopt(long, required = false, takes_value = true, default_value = "50")]
+	pub gas_percentile: f64,
+	/// Highest fee per gas paid, in wei, 0 leaves it uncapped. The fee of the `fixed` strategy.
+	#[structopt(
+		long,
+		parse(try_from_str = U256::from_dec_str),
+		default_value = "0",
+		required = false,
+		takes_value = true
+	)]
+	pub gas_max_fee: GasPrice,
+	/// Priority fee per gas of the `fixed` strategy, in wei.
+	#[structopt(
+		long,
+		parse(try_from_str = U256::from_dec_str),
+		default_value = "0",
+		required = false,
+		takes_value = true
+	)]
+	pub gas_priority_fee: GasPrice,
+	/// Gas strategy of time-critical transactions, such as updating the balance proof of a
+	/// closed channel and registering secrets.
+	#[structopt(
+		long,
+		parse(try_from_str = parse_gas_strategy),
+		default_value = "percentile",
+		required = false,
+		takes_value = true
+	)]
+	pub time_critical_gas_strategy: ArgGasStrategy,
+	/// Percentile of the `percentile` strategy of time-critical transactions.
+	#[structopt(long, required = false, takes_value = true, default_value = "90")]
+	pub time_critical_gas_percentile: f64,
+	/// Highest fee per gas paid for time-critical transactions, in wei, 0 leaves it uncapped.
+	#[structopt(
+		long,
+		parse(try_from_str = U256::from_dec_str),
+		default_value = "0",
+		required = false,
+		takes_value = true
+	)]
+	pub time_critical_gas_max_fee: GasPrice,
+	/// Priority fee per gas of the `fixed` strategy of time-critical transactions, in wei.
+	#[structopt(
+		long,
+		parse(try_from_str = U256::from_dec_str),
+		default_value = "0",
+		required = false,
+		takes_value = true
+	)]
+	pub time_critical_gas_priority_fee: GasPrice,
+}
+
+impl TryFrom<CliGasConfig> for GasStrategies {
+	type Error = String;
+
+	fn try_from(g: CliGasConfig) -> Result<GasStrategies, String> {
+		let normal =
+			gas_strategy(g.gas_strategy, g.gas_percentile, g.gas_max_fee, g.gas_priority_fee)?;
+		let time_critical = gas_strategy(
+			g.time_critical_gas_strategy,
+			g.time_critical_gas_percentile,
+			g.time_critical_gas_max_fee,
+			g.time_critical_gas_priority_fee,
+		)?;
+		Ok(GasStrategies::new(normal, time_critical))
+	}
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "Raiden unofficial rust client")]
 pub struct Opt {
@@ -243,12 +448,42 @@ pub struct Opt {
 	#[structopt(long, required = true, takes_value = true, default_value = "127.0.0.1:3000")]
 	pub api_address: String,
 
-	#[structopt(short("k"), long, parse(from_os_str), required = true, takes_value = true)]
-	pub keystore_path: PathBuf,
+	/// File containing the bearer token granting full access to the HTTP API.
+	#[structopt(long, parse(from_os_str), takes_value = true)]
+	pub api_token_file: Option<PathBuf>,
+
+	/// File containing the bearer token granting read-only access to the HTTP API.
+	#[structopt(long, parse(from_os_str), takes_value = true)]
+	pub api_read_only_token_file: Option<PathBuf>,
+
+	/// PKCS #12 archive with the certificate and private key used to serve the HTTP API over TLS.
+	#[structopt(long, parse(from_os_str), takes_value = true)]
+	pub api_tls_identity: Option<PathBuf>,
+
+	/// File containing the password of the TLS identity archive.
+	#[structopt(long, parse(from_os_str), takes_value = true, requires = "api-tls-identity")]
+	pub api_tls_identity_password_file: Option<PathBuf>,
+
+	#[structopt(
+		short("k"),
+		long,
+		parse(from_os_str),
+		required_unless = "remote-signer",
+		takes_value = true
+	)]
+	pub keystore_path: Option<PathBuf>,
+
+	/// URL of a JSON-RPC signer holding the keys of the accounts, used instead of the keystore.
+	#[structopt(long, takes_value = true, requires = "address")]
+	pub remote_signer: Option<String>,
 
 	#[structopt(short("a"), long, parse(try_from_str = parse_address), takes_value = true)]
 	pub address: Option<Address>,
 
+	/// Additional accounts to be served by this node, unlocked with the same password.
+	#[structopt(long, parse(try_from_str = parse_address), takes_value = true, multiple = true)]
+	pub additional_addresses: Vec<Address>,
+
 	#[structopt(long, parse(from_os_str), takes_value = true)]
 	pub password_file: Option<PathBuf>,
 
@@ -262,6 +497,11 @@ pub struct Opt {
 	)]
 	pub datadir: PathBuf,
 
+	/// PostgreSQL connection string, the state is kept in SQLite inside the data directory if
+	/// not set. Requires building with the `postgresql` feature.
+	#[structopt(long, takes_value = true)]
+	pub database_url: Option<String>,
+
 	// The number of occurrences of the `v/verbose` flag
 	/// Verbose mode (-v, -vv, -vvv, etc.)
 	#[structopt(short, long, parse(from_occurrences))]
@@ -276,11 +516,72 @@ pub struct Opt {
 	#[structopt(flatten)]
 	pub services_config: CliServicesConfig,
 
+	#[structopt(flatten)]
+	pub gas_config: CliGasConfig,
+
 	#[structopt(long, required = false, takes_value = true, default_value = "20")]
 	pub default_reveal_timeout: u64,
 
 	#[structopt(long, required = false, takes_value = true, default_value = "40")]
 	pub default_settle_timeout: u64,
+
+	/// Number of blocks a block should be deep before it is considered confirmed.
+	#[structopt(long, required = false, takes_value = true, default_value = "5")]
+	pub confirmation_blocks: u64,
+
+	/// Seconds between two compactions of the state database, 0 disables compaction.
+	///
+	/// Compaction deletes the payment history served by the API, it is disabled by default.
+	#[structopt(long, required = false, takes_value = true, default_value = "0")]
+	pub compaction_interval: u64,
+
+	/// Number of chain state snapshots kept when compacting the state database.
+	#[structopt(long, required = false, takes_value = true, default_value = "2")]
+	pub snapshots_to_keep: u32,
+
+	/// Seconds between two runs of the connection managers of joined token networks, 0 disables
+	/// them.
+	#[structopt(long, required = false, takes_value = true, default_value = "60")]
+	pub connection_manager_interval: u64,
+
+	/// Seconds between two checks for imbalanced channels, 0 disables rebalancing.
+	#[structopt(long, required = false, takes_value = true, default_value = "0")]
+	pub rebalance_interval: u64,
+
+	/// Percentage of the capacity of a channel held by one side above which the channel is
+	/// rebalanced through a payment to this node, between 51 and 100.
+	#[structopt(
+		long,
+		parse(try_from_str = parse_rebalance_threshold),
+		default_value = "80",
+		required = false,
+		takes_value = true
+	)]
+	pub rebalance_threshold: u8,
+
+	/// Highest mediation fee paid for a single rebalancing payment.
+	#[structopt(
+		long,
+		parse(try_from_str = U256::from_dec_str),
+		default_value = "0",
+		required = false,
+		takes_value = true
+	)]
+	pub rebalance_max_fee: TokenAmount,
+
+	/// Number of blocks after which a transaction which was not mined is sent again with bumped
+	/// fees.
+	#[structopt(long, required = false, takes_value = true, default_value = "5")]
+	pub transaction_replacement_blocks: u64,
+
+	/// Percentage by which the fees of a replaced transaction are raised, at least 10.
+	#[structopt(long, required = false, takes_value = true, default_value = "12")]
+	pub transaction_fee_bump: u8,
+
+	/// Seconds between two checks for the receipt of a sent transaction.
+	#[structopt(long, required = false, takes_value = true, default_value = "3")]
+	pub transaction_poll_interval: u64,
+
 	#[structopt(long, required = false, takes_value = true, default_value = "info")]
 	pub log_config: String,
 
@@ -0,0 +1,93 @@
+use hyper::{
+	header,
+	Body,
+	Method,
+	Request,
+};
+use routerify::ext::RequestExt;
+use subtle::ConstantTimeEq;
+
+use super::error::Error;
+
+/// The endpoint stopping the node, which is served over GET.
+const SHUTDOWN_PATH: &str = "/api/v1/shutdown";
+
+/// Access scope granted by an API token.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Scope {
+	/// Allows only requests which do not change the node's state.
+	ReadOnly,
+	/// Allows all requests.
+	FullAccess,
+}
+
+/// Bearer tokens accepted by the HTTP API.
+///
+/// Authentication is disabled if no token is configured.
+#[derive(Clone, Debug, Default)]
+pub struct ApiAuth {
+	pub full_access_token: Option<String>,
+	pub read_only_token: Option<String>,
+}
+
+impl ApiAuth {
+	/// Returns true if at least one token is configured.
+	pub fn enabled(&self) -> bool {
+		self.full_access_token.is_some() || self.read_only_token.is_some()
+	}
+
+	/// Returns the scope granted by `token` if it matches one of the configured tokens.
+	fn scope(&self, token: &str) -> Option<Scope> {
+		let matches = |expected: &Option<String>| {
+			expected
+				.as_ref()
+				.map(|expected| bool::from(expected.as_bytes().ct_eq(token.as_bytes())))
+				.unwrap_or(false)
+		};
+		if matches(&self.full_access_token) {
+			Some(Scope::FullAccess)
+		} else if matches(&self.read_only_token) {
+			Some(Scope::ReadOnly)
+		} else {
+			None
+		}
+	}
+
+	/// Check that the bearer token in the `Authorization` header grants access to the request.
+	pub fn authorize(&self, req: &Request<Body>) -> Result<(), Error> {
+		if !self.enabled() {
+			return Ok(())
+		}
+
+		let token = req
+			.headers()
+			.get(header::AUTHORIZATION)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.strip_prefix("Bearer "))
+			.ok_or(Error::Unauthorized)?;
+		let scope = self.scope(token.trim()).ok_or(Error::Unauthorized)?;
+		if scope < required_scope(req) {
+			return Err(Error::Forbidden)
+		}
+		Ok(())
+	}
+}
+
+/// Returns the scope required to serve the request.
+fn required_scope(req: &Request<Body>) -> Scope {
+	// The query string is not part of the path, a trailing slash is ignored.
+	let path = req.uri().path().trim_end_matches('/');
+	match *req.method() {
+		// Shutdown is served over GET but stops the node.
+		Method::GET | Method::HEAD if path != SHUTDOWN_PATH => Scope::ReadOnly,
+		_ => Scope::FullAccess,
+	}
+}
+
+/// Authenticate the request using the bearer token in the `Authorization` header.
+pub(crate) async fn authenticate(req: Request<Body>) -> Result<Request<Body>, Error> {
+	if let Some(auth) = req.data::<ApiAuth>() {
+		auth.authorize(&req)?;
+	}
+	Ok(req)
+}
@@ -6,11 +6,15 @@ use std::{
 use hyper::{
 	header,
 	Body,
-	Error as HttpError,
 	Request,
 	Response,
 	StatusCode,
 };
+use raiden_api::api::{
+	Api,
+	ApiError,
+	PaymentProgress,
+};
 use raiden_blockchain::contracts::{
 	self,
 	ContractsManager,
@@ -20,37 +24,56 @@ use raiden_primitives::{
 	types::{
 		Address,
 		CanonicalIdentifier,
+		PaymentIdentifier,
 		TokenAddress,
 		TokenAmount,
 	},
 };
 use raiden_state_machine::{
-	types::Event,
+	types::{
+		ChannelState,
+		Event,
+	},
 	views::{
 		self,
 		get_token_network_by_token_address,
 	},
 };
 use routerify::ext::RequestExt;
-use tracing::debug;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{
+	debug,
+	warn,
+};
 
 use super::{
 	error::Error,
 	request::{
+		ConnectionParams,
+		CreateInvoiceParams,
+		EventStreamParams,
 		InitiatePaymentParams,
 		MintTokenParams,
+		PayInvoiceParams,
 		UserDepositParams,
 	},
 	response::{
 		ConnectionManager,
+		InvoiceResponse,
+		NotificationResponse,
+		PaymentStatusResponse,
 		ResponseEvent,
 		ResponsePaymentSentSuccess,
+		ResponseReachabilityChange,
 		SettingsResponse,
 	},
 	utils::{
+		accounts_manager,
 		api,
 		body_to_params,
 		contracts_manager,
+		event_filter,
+		query_params,
 		state_manager,
 	},
 };
@@ -80,7 +103,7 @@ use crate::{
 	unwrap_result_or_error,
 };
 
-pub async fn address(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn address(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let our_address = state_manager.read().current_state.our_address;
 
@@ -89,14 +112,22 @@ pub async fn address(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 	json_response!(response, StatusCode::OK)
 }
 
-pub async fn version(_req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn accounts(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let accounts_manager = accounts_manager(&req);
+	let mut addresses = accounts_manager.addresses();
+	addresses.sort();
+
+	json_response!(addresses, StatusCode::OK)
+}
+
+pub async fn version(_req: Request<Body>) -> Result<Response<Body>, Error> {
 	const CARGO_PKG_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 	let response = VersionResponse { version: CARGO_PKG_VERSION };
 
 	json_response!(response, StatusCode::OK)
 }
 
-pub async fn contracts(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn contracts(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let contracts_manager = contracts_manager(&req);
 
 	let response = unwrap_result_or_error!(
@@ -107,7 +138,7 @@ pub async fn contracts(req: Request<Body>) -> Result<Response<Body>, HttpError>
 	json_response!(response, StatusCode::OK)
 }
 
-pub async fn settings(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn settings(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 
 	let response =
@@ -116,12 +147,63 @@ pub async fn settings(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 	json_response!(response, StatusCode::OK)
 }
 
-pub async fn notifications(_req: Request<Body>) -> Result<Response<Body>, HttpError> {
-	let notifications: Vec<String> = vec![];
+pub async fn notifications(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let state_manager = state_manager(&req);
+
+	let notifications: Vec<NotificationResponse> = unwrap_result_or_error!(
+		state_manager
+			.read()
+			.storage
+			.get_notifications(false)
+			.map_err(|e| Error::Other(format!("{:?}", e))),
+		StatusCode::INTERNAL_SERVER_ERROR
+	)
+	.into_iter()
+	.map(|n| n.into())
+	.collect();
+
 	json_response!(notifications, StatusCode::OK)
 }
 
-pub async fn pending_transfers(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn acknowledge_notification(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let state_manager = state_manager(&req);
+	let notification_id =
+		unwrap_option_or_error!(req.param("notification_id").cloned(), StatusCode::BAD_REQUEST);
+
+	let acknowledged = unwrap_result_or_error!(
+		state_manager
+			.read()
+			.storage
+			.acknowledge_notification(notification_id.clone())
+			.map_err(|e| Error::Other(format!("{:?}", e))),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+	let notification_id =
+		unwrap_option_or_error!(acknowledged.then_some(notification_id), StatusCode::NOT_FOUND);
+
+	json_response!(notification_id, StatusCode::OK)
+}
+
+pub async fn dismiss_notification(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let state_manager = state_manager(&req);
+	let notification_id =
+		unwrap_option_or_error!(req.param("notification_id").cloned(), StatusCode::BAD_REQUEST);
+
+	let dismissed = unwrap_result_or_error!(
+		state_manager
+			.read()
+			.storage
+			.dismiss_notification(notification_id.clone())
+			.map_err(|e| Error::Other(format!("{:?}", e))),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+	let notification_id =
+		unwrap_option_or_error!(dismissed.then_some(notification_id), StatusCode::NOT_FOUND);
+
+	json_response!(notification_id, StatusCode::OK)
+}
+
+pub async fn pending_transfers(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -185,7 +267,8 @@ pub async fn pending_transfers(req: Request<Body>) -> Result<Response<Body>, Htt
 	json_response!(view, StatusCode::OK)
 }
 
-pub async fn channels(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn channels(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -197,7 +280,7 @@ pub async fn channels(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 
 	let chain_state = &state_manager.read().current_state;
 
-	let channels: Vec<response::ChannelResponse> = if token_address.is_some() {
+	let channels: Vec<ChannelResponse> = if token_address.is_some() {
 		let token_address: TokenAddress = Address::from_slice(unwrap_result_or_error!(
 			&hex::decode(token_address.unwrap().trim_start_matches("0x"))
 				.map_err(|_| Error::Other(format!("Invalid token address"))),
@@ -216,16 +299,20 @@ pub async fn channels(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 		token_network
 			.channelidentifiers_to_channels
 			.values()
-			.map(|c| c.clone().into())
+			.map(|c| channel_response(&api, c.clone()))
 			.collect()
 	} else {
-		views::get_channels(chain_state).iter().map(|c| c.clone().into()).collect()
+		views::get_channels(chain_state)
+			.into_iter()
+			.map(|c| channel_response(&api, c))
+			.collect()
 	};
 
 	json_response!(&channels, StatusCode::OK)
 }
 
-pub async fn channel_by_partner_address(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn channel_by_partner_address(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -258,7 +345,7 @@ pub async fn channel_by_partner_address(req: Request<Body>) -> Result<Response<B
 	);
 
 	if let Some(channel_state) = channel_state {
-		let channel_state: ChannelResponse = channel_state.clone().into();
+		let channel_state = channel_response(&api, channel_state.clone());
 		json_response!(channel_state, StatusCode::OK)
 	} else {
 		unwrap_result_or_error!(
@@ -267,7 +354,51 @@ pub async fn channel_by_partner_address(req: Request<Body>) -> Result<Response<B
 		)
 	}
 }
-pub async fn connections_leave(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn connections_connect(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let contracts_manager = contracts_manager(&req);
+	let addresses = unwrap_result_or_error!(
+		contracts_manager.deployed_addresses(),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+
+	let token_address = req.param("token_address");
+	let token_address: TokenAddress = Address::from_slice(unwrap_result_or_error!(
+		&hex::decode(token_address.unwrap().trim_start_matches("0x"))
+			.map_err(|_| Error::Other(format!("Invalid token address"))),
+		StatusCode::BAD_REQUEST
+	));
+
+	let params: ConnectionParams =
+		unwrap_result_or_error!(body_to_params(req).await, StatusCode::BAD_REQUEST);
+
+	debug!(
+		message = "Connecting to token network",
+		registry_address = addresses.token_network_registry.checksum(),
+		token_address = token_address.checksum(),
+	);
+
+	let connection_manager = unwrap_result_or_error!(
+		api.token_network_connect(
+			addresses.token_network_registry,
+			token_address,
+			params.funds,
+			params.initial_channel_target,
+			params.joinable_funds_target,
+		)
+		.await,
+		StatusCode::CONFLICT
+	);
+
+	let response = ConnectionManager {
+		funds: connection_manager.funds,
+		sum_deposits: TokenAmount::zero(),
+		channels: 0,
+	};
+	json_response!(response, StatusCode::ACCEPTED)
+}
+
+pub async fn connections_leave(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -297,14 +428,14 @@ pub async fn connections_leave(req: Request<Body>) -> Result<Response<Body>, Htt
 
 	let mut closed_channel_result = vec![];
 	for channel_state in closed_channels {
-		let result: ChannelResponse = channel_state.into();
+		let result = channel_response(&api, channel_state);
 		closed_channel_result.push(result);
 	}
 
 	json_response!(closed_channel_result, StatusCode::OK)
 }
 
-pub async fn connections_info(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn connections_info(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -318,9 +449,18 @@ pub async fn connections_info(req: Request<Body>) -> Result<Response<Body>, Http
 	for token in views::get_token_identifiers(chain_state, addresses.token_network_registry) {
 		let open_channels =
 			views::get_channelstate_open(chain_state, addresses.token_network_registry, token);
+		let funds = views::get_token_network_by_token_address(
+			chain_state,
+			addresses.token_network_registry,
+			token,
+		)
+		.and_then(|token_network| chain_state.connection_managers.get(&token_network.address))
+		.map(|connection_manager| connection_manager.funds)
+		.unwrap_or_default();
 		connection_managers.insert(
 			token.checksum(),
 			ConnectionManager {
+				funds,
 				sum_deposits: open_channels
 					.iter()
 					.map(|c| c.our_state.contract_balance)
@@ -333,7 +473,7 @@ pub async fn connections_info(req: Request<Body>) -> Result<Response<Body>, Http
 	json_response!(connection_managers, StatusCode::OK)
 }
 
-pub async fn tokens(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn tokens(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses =
@@ -350,7 +490,7 @@ pub async fn tokens(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 	json_response!(tokens, StatusCode::OK)
 }
 
-pub async fn get_token_network_by_token(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn get_token_network_by_token(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -380,7 +520,7 @@ pub async fn get_token_network_by_token(req: Request<Body>) -> Result<Response<B
 	json_response!(token_network_address, StatusCode::OK)
 }
 
-pub async fn register_token(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn register_token(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -411,7 +551,7 @@ pub async fn register_token(req: Request<Body>) -> Result<Response<Body>, HttpEr
 	json_response!(token_network_address, StatusCode::CREATED)
 }
 
-pub async fn partners_by_token_address(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn partners_by_token_address(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -456,7 +596,7 @@ pub async fn partners_by_token_address(req: Request<Body>) -> Result<Response<Bo
 	json_response!(&channels, StatusCode::OK)
 }
 
-pub async fn user_deposit(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn user_deposit(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -522,13 +662,24 @@ pub async fn user_deposit(req: Request<Body>) -> Result<Response<Body>, HttpErro
 	json_response!((), StatusCode::OK)
 }
 
-pub async fn status(_req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn status(_req: Request<Body>) -> Result<Response<Body>, Error> {
 	let mut status = HashMap::new();
 	status.insert("status", "ready");
 	json_response!(status, StatusCode::OK)
 }
 
-pub async fn create_channel(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn metrics(_req: Request<Body>) -> Result<Response<Body>, Error> {
+	let metrics =
+		unwrap_result_or_error!(raiden_metrics::gather(), StatusCode::INTERNAL_SERVER_ERROR);
+
+	Ok(Response::builder()
+		.header(header::CONTENT_TYPE, raiden_metrics::content_type())
+		.status(StatusCode::OK)
+		.body(Body::from(metrics))
+		.unwrap())
+}
+
+pub async fn create_channel(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let account = account(&req);
 	let state_manager = state_manager(&req);
@@ -595,7 +746,7 @@ pub async fn create_channel(req: Request<Body>) -> Result<Response<Body>, HttpEr
 			channel_identifier,
 		},
 	) {
-		let channel_state: ChannelResponse = channel_state.clone().into();
+		let channel_state = channel_response(&api, channel_state.clone());
 		json_response!(channel_state, StatusCode::CREATED)
 	} else {
 		unwrap_result_or_error!(
@@ -605,7 +756,7 @@ pub async fn create_channel(req: Request<Body>) -> Result<Response<Body>, HttpEr
 	}
 }
 
-pub async fn channel_update(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn channel_update(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let account = account(&req);
 	let state_manager = state_manager(&req);
@@ -666,7 +817,7 @@ pub async fn channel_update(req: Request<Body>) -> Result<Response<Body>, HttpEr
 		token_network.address,
 		partner_address,
 	) {
-		let channel_state: ChannelResponse = channel_state.clone().into();
+		let channel_state = channel_response(&api, channel_state.clone());
 		json_response!(channel_state, StatusCode::OK)
 	} else {
 		unwrap_result_or_error!(
@@ -676,7 +827,7 @@ pub async fn channel_update(req: Request<Body>) -> Result<Response<Body>, HttpEr
 	}
 }
 
-pub async fn payments(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn payments(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
 	let contracts_manager = contracts_manager(&req);
 	let addresses = unwrap_result_or_error!(
@@ -723,12 +874,18 @@ pub async fn payments(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 		None
 	};
 
+	let filter =
+		unwrap_result_or_error!(event_filter(&query_params(&req)), StatusCode::BAD_REQUEST);
 	let token_network_address = token_network.map(|n| n.address);
 	let events = unwrap_result_or_error!(
 		state_manager
 			.read()
 			.storage
-			.get_events_payment_history_with_timestamps(token_network_address, partner_address,)
+			.get_events_payment_history_with_timestamps(
+				token_network_address,
+				partner_address,
+				&filter,
+			)
 			.map_err(|e| Error::Other(format!("{:?}", e))),
 		StatusCode::INTERNAL_SERVER_ERROR
 	);
@@ -778,7 +935,7 @@ pub async fn payments(req: Request<Body>) -> Result<Response<Body>, HttpError> {
 	json_response!(payment_history, StatusCode::OK)
 }
 
-pub async fn mint_token(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn mint_token(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 
 	let token_address: TokenAddress = Address::from_slice(unwrap_result_or_error!(
@@ -797,14 +954,16 @@ pub async fn mint_token(req: Request<Body>) -> Result<Response<Body>, HttpError>
 	json_response!(transaction_hash, StatusCode::OK)
 }
 
-pub async fn raiden_events(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn raiden_events(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let state_manager = state_manager(&req);
+	let filter =
+		unwrap_result_or_error!(event_filter(&query_params(&req)), StatusCode::BAD_REQUEST);
 
 	let events: Vec<ResponseEvent> = unwrap_result_or_error!(
 		state_manager
 			.read()
 			.storage
-			.get_events_with_timestamps()
+			.get_events_with_timestamps(&filter)
 			.map_err(|e| Error::Other(format!("{:?}", e))),
 		StatusCode::INTERNAL_SERVER_ERROR
 	)
@@ -815,13 +974,115 @@ pub async fn raiden_events(req: Request<Body>) -> Result<Response<Body>, HttpErr
 	json_response!(events, StatusCode::OK)
 }
 
-pub async fn shutdown(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn events_stream(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let query = query_params(&req);
+
+	let mut params = EventStreamParams::default();
+	if let Some(event_types) = query.get("event_types") {
+		params.event_types = event_types
+			.split(',')
+			.map(|t| t.trim().to_owned())
+			.filter(|t| !t.is_empty())
+			.collect();
+	}
+	if let Some(token_network_address) = query.get("token_network_address") {
+		params.token_network_address = Some(Address::from_slice(unwrap_result_or_error!(
+			&hex::decode(token_network_address.trim_start_matches("0x"))
+				.map_err(|_| Error::Other("Invalid token network address".to_owned())),
+			StatusCode::BAD_REQUEST
+		)));
+	}
+	if let Some(partner_address) = query.get("partner_address") {
+		params.partner_address = Some(Address::from_slice(unwrap_result_or_error!(
+			&hex::decode(partner_address.trim_start_matches("0x"))
+				.map_err(|_| Error::Other("Invalid partner address".to_owned())),
+			StatusCode::BAD_REQUEST
+		)));
+	}
+
+	let mut events = api.subscribe_events();
+	let mut reachability_changes = api.subscribe_reachability();
+	let (mut sender, body) = Body::channel();
+	tokio::spawn(async move {
+		loop {
+			let (type_name, data) = tokio::select! {
+				event = events.recv() => {
+					let event = match event {
+						Ok(event) => event,
+						Err(RecvError::Lagged(skipped)) => {
+							warn!(
+								message = "Event stream subscriber lagging behind",
+								skipped = skipped
+							);
+							continue
+						},
+						Err(RecvError::Closed) => break,
+					};
+					if !params.matches(&event) {
+						continue
+					}
+					let type_name = event.type_name();
+					let response_event = ResponseEvent {
+						identifier: None,
+						event,
+						log_time: chrono::Utc::now().naive_local(),
+					};
+					(type_name, serde_json::to_string(&response_event))
+				},
+				change = reachability_changes.recv() => {
+					let change = match change {
+						Ok(change) => change,
+						Err(RecvError::Lagged(skipped)) => {
+							warn!(
+								message = "Reachability stream subscriber lagging behind",
+								skipped = skipped
+							);
+							continue
+						},
+						Err(RecvError::Closed) => break,
+					};
+					if !params.matches_reachability(&change) {
+						continue
+					}
+					let response_change = ResponseReachabilityChange {
+						change,
+						log_time: chrono::Utc::now().naive_local(),
+					};
+					let data = serde_json::to_string(&response_change);
+					(EventStreamParams::REACHABILITY_CHANGED, data)
+				},
+			};
+			let data = match data {
+				Ok(data) => data,
+				Err(e) => {
+					debug!(message = "Could not serialize event", error = format!("{:?}", e));
+					continue
+				},
+			};
+			let chunk = format!("event: {}\ndata: {}\n\n", type_name, data);
+			// The client has disconnected.
+			if sender.send_data(chunk.into()).await.is_err() {
+				break
+			}
+		}
+	});
+
+	Ok(Response::builder()
+		.header(header::CONTENT_TYPE, "text/event-stream")
+		.header(header::CACHE_CONTROL, "no-cache")
+		.status(StatusCode::OK)
+		.body(body)
+		.unwrap())
+}
+
+pub async fn shutdown(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let stop_sender = stop_sender(&req);
 	let _ = stop_sender.send(true).await;
 	json_response!("", StatusCode::OK)
 }
 
-pub async fn initiate_payment(req: Request<Body>) -> Result<Response<Body>, HttpError> {
+pub async fn initiate_payment(req: Request<Body>) -> Result<Response<Body>, Error> {
 	let api = api(&req);
 	let account = account(&req);
 	let contracts_manager = contracts_manager(&req);
@@ -870,6 +1131,7 @@ pub async fn initiate_payment(req: Request<Body>) -> Result<Response<Body>, Http
 			params.secret,
 			params.secret_hash,
 			params.lock_timeout,
+			params.wait,
 		)
 		.await,
 		StatusCode::CONFLICT
@@ -885,13 +1147,188 @@ pub async fn initiate_payment(req: Request<Body>) -> Result<Response<Body>, Http
 		secret: hex::encode(payment.secret.0),
 		secret_hash: hex::encode(payment.secrethash),
 	};
+	let status_code = if params.wait { StatusCode::OK } else { StatusCode::ACCEPTED };
 
 	json_response!(
 		unwrap_result_or_error!(serde_json::to_string(&result), StatusCode::INTERNAL_SERVER_ERROR),
-		StatusCode::OK
+		status_code
 	)
 }
 
+pub async fn payment_status(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let contracts_manager = contracts_manager(&req);
+
+	let (token_address, partner_address, identifier) =
+		unwrap_result_or_error!(payment_path_params(&req), StatusCode::BAD_REQUEST);
+
+	let default_token_network_registry = unwrap_result_or_error!(
+		get_default_token_network_registry(contracts_manager.clone()),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+
+	let progress = unwrap_result_or_error!(
+		api.payment_status(
+			default_token_network_registry,
+			token_address,
+			partner_address,
+			identifier
+		)
+		.await,
+		StatusCode::NOT_FOUND
+	);
+
+	let (status, error) = match progress {
+		PaymentProgress::Pending => ("pending", None),
+		PaymentProgress::Locked => ("locked", None),
+		PaymentProgress::Unlocked => ("unlocked", None),
+		PaymentProgress::Failed(error) => ("failed", Some(error)),
+		PaymentProgress::Cancelled => ("cancelled", None),
+	};
+	let response =
+		PaymentStatusResponse { target_address: partner_address, identifier, status, error };
+
+	json_response!(response, StatusCode::OK)
+}
+
+pub async fn cancel_payment(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let contracts_manager = contracts_manager(&req);
+
+	let (token_address, partner_address, identifier) =
+		unwrap_result_or_error!(payment_path_params(&req), StatusCode::BAD_REQUEST);
+
+	let default_token_network_registry = unwrap_result_or_error!(
+		get_default_token_network_registry(contracts_manager.clone()),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+
+	let result = api
+		.cancel_payment(default_token_network_registry, token_address, partner_address, identifier)
+		.await;
+	let status_code = match result {
+		Err(ApiError::Param(_)) => StatusCode::NOT_FOUND,
+		_ => StatusCode::CONFLICT,
+	};
+	unwrap_result_or_error!(result, status_code);
+
+	let response = PaymentStatusResponse {
+		target_address: partner_address,
+		identifier,
+		status: "cancelled",
+		error: None,
+	};
+
+	json_response!(response, StatusCode::OK)
+}
+
+pub async fn invoices(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+
+	let invoices: Vec<InvoiceResponse> = api.invoices().into_iter().map(Into::into).collect();
+
+	json_response!(invoices, StatusCode::OK)
+}
+
+pub async fn create_invoice(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let contracts_manager = contracts_manager(&req);
+
+	let params: CreateInvoiceParams =
+		unwrap_result_or_error!(body_to_params(req).await, StatusCode::BAD_REQUEST);
+
+	let default_token_network_registry = unwrap_result_or_error!(
+		get_default_token_network_registry(contracts_manager.clone()),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+
+	let (invoice, encoded) = unwrap_result_or_error!(
+		api.create_invoice(
+			default_token_network_registry,
+			params.token_address,
+			params.amount,
+			params.payment_identifier,
+			params.expiration,
+		)
+		.await,
+		StatusCode::CONFLICT
+	);
+
+	let mut response: InvoiceResponse = invoice.into();
+	response.invoice = Some(encoded);
+
+	json_response!(response, StatusCode::CREATED)
+}
+
+pub async fn pay_invoice(req: Request<Body>) -> Result<Response<Body>, Error> {
+	let api = api(&req);
+	let account = account(&req);
+	let contracts_manager = contracts_manager(&req);
+
+	let params: PayInvoiceParams =
+		unwrap_result_or_error!(body_to_params(req).await, StatusCode::BAD_REQUEST);
+
+	let default_token_network_registry = unwrap_result_or_error!(
+		get_default_token_network_registry(contracts_manager.clone()),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+	let default_secret_registry = unwrap_result_or_error!(
+		get_default_secret_registry(contracts_manager.clone()),
+		StatusCode::INTERNAL_SERVER_ERROR
+	);
+
+	let payment = unwrap_result_or_error!(
+		api.pay_invoice(
+			account.clone(),
+			default_token_network_registry,
+			default_secret_registry,
+			&params.invoice,
+			params.wait,
+		)
+		.await,
+		StatusCode::CONFLICT
+	);
+
+	let response = PaymentStatusResponse {
+		target_address: payment.target,
+		identifier: payment.payment_identifier,
+		status: if params.wait { "unlocked" } else { "pending" },
+		error: None,
+	};
+	let status_code = if params.wait { StatusCode::OK } else { StatusCode::ACCEPTED };
+
+	json_response!(response, status_code)
+}
+
+/// Returns the response of a channel along with the reachability of its partner.
+fn channel_response(api: &Api, channel_state: ChannelState) -> ChannelResponse {
+	let reachability = api.reachability(channel_state.partner_state.address);
+	ChannelResponse::from(channel_state).with_reachability(reachability)
+}
+
+fn payment_path_params(
+	req: &Request<Body>,
+) -> Result<(TokenAddress, Address, PaymentIdentifier), Error> {
+	let token_address = req.param("token_address").ok_or(Error::Uri("Missing token address"))?;
+	let partner_address =
+		req.param("partner_address").ok_or(Error::Uri("Missing partner address"))?;
+	let identifier = req.param("identifier").ok_or(Error::Uri("Missing payment identifier"))?;
+
+	let token_address: TokenAddress = Address::from_slice(
+		&hex::decode(token_address.trim_start_matches("0x"))
+			.map_err(|_| Error::Other(format!("Invalid token address")))?,
+	);
+	let partner_address: Address = Address::from_slice(
+		&hex::decode(partner_address.trim_start_matches("0x"))
+			.map_err(|_| Error::Other(format!("Invalid partner address")))?,
+	);
+	let identifier: PaymentIdentifier = identifier
+		.parse()
+		.map_err(|_| Error::Other(format!("Invalid payment identifier")))?;
+
+	Ok((token_address, partner_address, identifier))
+}
+
 fn get_default_token_network_registry(
 	contracts_manager: Arc<ContractsManager>,
 ) -> Result<Address, Error> {
@@ -1,7 +1,7 @@
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub(crate) enum Error {
+pub enum Error {
 	#[error("Invalid URL `{0}`")]
 	Uri(&'static str),
 	#[error("`{0}`")]
@@ -12,4 +12,12 @@ pub(crate) enum Error {
 	Param(String),
 	#[error("Error: `{0}`")]
 	Other(String),
+	#[error("Missing or invalid API token")]
+	Unauthorized,
+	#[error("The API token does not grant access to this endpoint")]
+	Forbidden,
+	#[error("Account `{0:?}` is not served by this node")]
+	UnknownAccount(raiden_primitives::types::Address),
+	#[error("TLS error: `{0}`")]
+	Tls(String),
 }
@@ -1,11 +1,17 @@
+pub mod auth;
 pub mod server;
+pub mod tls;
 
 mod endpoints;
-mod error;
+pub(crate) mod error;
 #[macro_use]
 mod macros;
-mod request;
+pub(crate) mod request;
 mod response;
 mod utils;
 
-pub use self::server::*;
+pub use self::{
+	auth::ApiAuth,
+	server::*,
+	tls::TlsConfig,
+};
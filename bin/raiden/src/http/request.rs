@@ -3,6 +3,7 @@ use raiden_primitives::{
 		u256_from_optional_str,
 		u256_from_str,
 	},
+	reachability::ReachabilityChange,
 	types::{
 		Address,
 		BlockTimeout,
@@ -12,9 +13,13 @@ use raiden_primitives::{
 		SettleTimeout,
 		TokenAddress,
 		TokenAmount,
+		TokenNetworkAddress,
 	},
 };
-use raiden_state_machine::types::ChannelStatus;
+use raiden_state_machine::types::{
+	ChannelStatus,
+	Event,
+};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -41,6 +46,15 @@ pub struct ChannelPatchParams {
 	pub state: Option<ChannelStatus>,
 }
 
+#[derive(Deserialize)]
+pub struct ConnectionParams {
+	#[serde(deserialize_with = "u256_from_str")]
+	pub funds: TokenAmount,
+	pub initial_channel_target: Option<u16>,
+	/// Percentage of the funds kept for channels opened by partners.
+	pub joinable_funds_target: Option<u8>,
+}
+
 #[derive(Deserialize)]
 pub struct UserDepositParams {
 	#[serde(default)]
@@ -62,6 +76,30 @@ pub struct InitiatePaymentParams {
 	pub secret: Option<String>,
 	pub secret_hash: Option<SecretHash>,
 	pub lock_timeout: Option<BlockTimeout>,
+	/// Wait for the payment to complete before responding.
+	#[serde(default = "default_wait")]
+	pub wait: bool,
+}
+
+fn default_wait() -> bool {
+	true
+}
+
+#[derive(Deserialize)]
+pub struct CreateInvoiceParams {
+	pub token_address: TokenAddress,
+	#[serde(deserialize_with = "u256_from_str")]
+	pub amount: TokenAmount,
+	pub payment_identifier: Option<PaymentIdentifier>,
+	pub expiration: Option<BlockTimeout>,
+}
+
+#[derive(Deserialize)]
+pub struct PayInvoiceParams {
+	pub invoice: String,
+	/// Wait for the payment to complete before responding.
+	#[serde(default = "default_wait")]
+	pub wait: bool,
 }
 
 #[derive(Deserialize)]
@@ -70,3 +108,51 @@ pub struct MintTokenParams {
 	pub value: TokenAmount,
 	pub to: Address,
 }
+
+/// Filters applied to the event stream, parsed from the query string.
+#[derive(Default)]
+pub struct EventStreamParams {
+	pub event_types: Vec<String>,
+	pub token_network_address: Option<TokenNetworkAddress>,
+	pub partner_address: Option<Address>,
+}
+
+impl EventStreamParams {
+	/// The type name of reachability changes in the event stream.
+	pub const REACHABILITY_CHANGED: &'static str = "ReachabilityChanged";
+
+	/// Returns true if the event passes all filters.
+	pub fn matches(&self, event: &Event) -> bool {
+		if !self.event_types.is_empty() && !self.event_types.iter().any(|t| t == event.type_name())
+		{
+			return false
+		}
+		if let Some(token_network_address) = self.token_network_address {
+			if event.token_network_address() != Some(token_network_address) {
+				return false
+			}
+		}
+		if let Some(partner_address) = self.partner_address {
+			if event.partner() != Some(partner_address) {
+				return false
+			}
+		}
+		true
+	}
+
+	/// Returns true if the change of reachability passes all filters.
+	///
+	/// Reachability is not specific to a token network, changes are left out when filtering by
+	/// token network.
+	pub fn matches_reachability(&self, change: &ReachabilityChange) -> bool {
+		if !self.event_types.is_empty() &&
+			!self.event_types.iter().any(|t| t == Self::REACHABILITY_CHANGED)
+		{
+			return false
+		}
+		if self.token_network_address.is_some() {
+			return false
+		}
+		self.partner_address.map_or(true, |address| address == change.address)
+	}
+}
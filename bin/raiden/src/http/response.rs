@@ -1,9 +1,14 @@
 use raiden_primitives::{
+	reachability::{
+		Reachability,
+		ReachabilityChange,
+	},
 	serializers::{
 		to_checksum_str,
 		u256_to_str,
 	},
 	types::{
+		BlockExpiration,
 		ChannelIdentifier,
 		PaymentIdentifier,
 		RevealTimeout,
@@ -16,7 +21,11 @@ use raiden_primitives::{
 };
 use raiden_state_machine::{
 	storage::{
-		types::EventRecord,
+		types::{
+			EventRecord,
+			NotificationRecord,
+			NotificationUrgency,
+		},
 		NaiveDateTime,
 	},
 	types::{
@@ -24,6 +33,8 @@ use raiden_state_machine::{
 		ChannelStatus,
 		ErrorPaymentSentFailed,
 		Event,
+		InvoiceState,
+		InvoiceStatus,
 		PaymentReceivedSuccess,
 		PaymentSentSuccess,
 	},
@@ -52,6 +63,8 @@ pub struct SettingsResponse {
 
 #[derive(Serialize)]
 pub struct ConnectionManager {
+	#[serde(serialize_with = "u256_to_str")]
+	pub funds: TokenAmount,
 	#[serde(serialize_with = "u256_to_str")]
 	pub sum_deposits: TokenAmount,
 	pub channels: u32,
@@ -96,6 +109,15 @@ pub struct ChannelResponse {
 	total_deposit: TokenAmount,
 	#[serde(serialize_with = "u256_to_str")]
 	total_withdraw: TokenAmount,
+	partner_reachability: Reachability,
+}
+
+impl ChannelResponse {
+	/// Set the reachability of the partner, as observed by the transport.
+	pub fn with_reachability(mut self, reachability: Reachability) -> Self {
+		self.partner_reachability = reachability;
+		self
+	}
 }
 
 #[derive(Serialize)]
@@ -120,6 +142,7 @@ impl From<ChannelState> for ChannelResponse {
 			total_withdraw: channel.our_state.total_withdraw(),
 			state: channel.status(),
 			balance: views::channel_balance(&channel.our_state, &channel.partner_state),
+			partner_reachability: Reachability::Unknown,
 		}
 	}
 }
@@ -141,6 +164,49 @@ pub struct PaymentSuccess {
 	pub secret_hash: String,
 }
 
+#[derive(Serialize)]
+pub struct InvoiceResponse {
+	#[serde(serialize_with = "to_checksum_str")]
+	pub token_network_address: TokenNetworkAddress,
+	pub payment_identifier: PaymentIdentifier,
+	#[serde(serialize_with = "u256_to_str")]
+	pub amount: TokenAmount,
+	pub secret_hash: String,
+	pub expiration: BlockExpiration,
+	pub status: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub invoice: Option<String>,
+}
+
+impl From<InvoiceState> for InvoiceResponse {
+	fn from(invoice: InvoiceState) -> Self {
+		let status = match invoice.status {
+			InvoiceStatus::Pending => "pending",
+			InvoiceStatus::Paid => "paid",
+			InvoiceStatus::Expired => "expired",
+		};
+		Self {
+			token_network_address: invoice.token_network_address,
+			payment_identifier: invoice.payment_identifier,
+			amount: invoice.amount,
+			secret_hash: hex::encode(invoice.secrethash),
+			expiration: invoice.expiration,
+			status,
+			invoice: None,
+		}
+	}
+}
+
+#[derive(Serialize)]
+pub struct PaymentStatusResponse {
+	#[serde(serialize_with = "to_checksum_str")]
+	pub target_address: Address,
+	pub identifier: PaymentIdentifier,
+	pub status: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
 #[derive(Serialize)]
 pub struct ResponsePaymentSentSuccess {
 	pub event: String,
@@ -228,12 +294,48 @@ pub enum ResponsePaymentHistory {
 
 #[derive(Serialize)]
 pub struct ResponseEvent {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub identifier: Option<String>,
 	pub event: Event,
 	pub log_time: NaiveDateTime,
 }
 
+#[derive(Serialize)]
+pub struct ResponseReachabilityChange {
+	#[serde(flatten)]
+	pub change: ReachabilityChange,
+	pub log_time: NaiveDateTime,
+}
+
 impl From<EventRecord> for ResponseEvent {
 	fn from(value: EventRecord) -> Self {
-		Self { event: value.data, log_time: value.timestamp }
+		Self {
+			identifier: Some(value.identifier.to_string()),
+			event: value.data,
+			log_time: value.timestamp,
+		}
+	}
+}
+
+#[derive(Serialize)]
+pub struct NotificationResponse {
+	pub id: String,
+	pub summary: String,
+	pub body: String,
+	pub urgency: NotificationUrgency,
+	pub acknowledged: bool,
+	pub timestamp: NaiveDateTime,
+}
+
+impl From<NotificationRecord> for NotificationResponse {
+	fn from(value: NotificationRecord) -> Self {
+		Self {
+			id: value.identifier,
+			summary: value.summary,
+			body: value.body,
+			urgency: value.urgency,
+			acknowledged: value.acknowledged,
+			timestamp: value.timestamp,
+		}
 	}
 }
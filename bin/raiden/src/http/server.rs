@@ -1,12 +1,17 @@
 use std::{
+	collections::HashMap,
+	convert::Infallible,
+	future::Future,
 	net::SocketAddr,
+	pin::Pin,
+	str::FromStr,
 	sync::Arc,
 };
 
 use hyper::{
-	server::conn::AddrIncoming,
+	header,
+	service::make_service_fn,
 	Body,
-	Error,
 	Request,
 	Response,
 	Server,
@@ -16,22 +21,41 @@ use raiden_api::{
 	api::Api,
 	raiden::Raiden,
 };
+use raiden_multi_account::manager::AccountsManager;
+use raiden_primitives::types::Address;
 use routerify::{
+	ext::RequestExt,
 	Middleware,
 	RequestInfo,
+	RequestServiceBuilder,
 	Router,
 	RouterService,
 };
-use tokio::sync::mpsc::Sender;
+use tokio::{
+	net::TcpStream,
+	sync::mpsc::Sender,
+};
+use tokio_native_tls::TlsStream;
 use tracing::{
 	error,
 	info,
 };
 
-use super::endpoints;
+use super::{
+	auth::{
+		self,
+		ApiAuth,
+	},
+	endpoints,
+	error::Error,
+	tls::{
+		TlsConfig,
+		TlsIncoming,
+	},
+};
 
 pub struct HttpServer {
-	inner: Server<AddrIncoming, RouterService<Body, Error>>,
+	inner: Pin<Box<dyn Future<Output = Result<(), hyper::Error>> + Send>>,
 }
 
 impl HttpServer {
@@ -39,17 +63,44 @@ impl HttpServer {
 		socket: SocketAddr,
 		raiden: Arc<Raiden>,
 		api: Arc<Api>,
+		accounts_manager: Arc<AccountsManager>,
 		stop_sender: Sender<bool>,
-	) -> Self {
-		let router = router(raiden, api, stop_sender);
+		auth: ApiAuth,
+		tls: Option<TlsConfig>,
+	) -> Result<Self, String> {
+		let router = router(raiden, api, accounts_manager, stop_sender, auth);
 
-		// Create a Service from the router above to handle incoming requests.
-		let service = RouterService::new(router).unwrap();
+		// Create a server by passing a service created from the router to `.serve` method.
+		let inner: Pin<Box<dyn Future<Output = Result<(), hyper::Error>> + Send>> = match tls {
+			Some(tls) => {
+				let acceptor = tls.acceptor().map_err(|e| e.to_string())?;
+				let listener = std::net::TcpListener::bind(socket)
+					.and_then(|listener| {
+						listener.set_nonblocking(true)?;
+						tokio::net::TcpListener::from_std(listener)
+					})
+					.map_err(|e| format!("Could not bind {}: {}", socket, e))?;
+				let builder =
+					Arc::new(RequestServiceBuilder::new(router).map_err(|e| e.to_string())?);
+				let make_service = make_service_fn(move |stream: &TlsStream<TcpStream>| {
+					let remote_address = stream
+						.get_ref()
+						.get_ref()
+						.get_ref()
+						.peer_addr()
+						.unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)));
+					let service = builder.build(remote_address);
+					async move { Ok::<_, Infallible>(service) }
+				});
+				Box::pin(Server::builder(TlsIncoming::new(listener, acceptor)).serve(make_service))
+			},
+			None => {
+				let service = RouterService::new(router).map_err(|e| e.to_string())?;
+				Box::pin(Server::try_bind(&socket).map_err(|e| e.to_string())?.serve(service))
+			},
+		};
 
-		// Create a server by passing the created service to `.serve` method.
-		let server = Server::bind(&socket).serve(service);
-
-		Self { inner: server }
+		Ok(Self { inner })
 	}
 
 	pub async fn start(self) {
@@ -69,61 +120,121 @@ async fn log_request(req: Request<Body>) -> Result<Request<Body>, Error> {
 	Ok(req)
 }
 
+/// Attach the local account addressed by `/api/v1/accounts/:account_address/` routes to the
+/// request context. Other routes are served by the primary account.
+async fn select_account(req: Request<Body>) -> Result<Request<Body>, Error> {
+	let account_address = match req.uri().path().strip_prefix("/api/v1/accounts/") {
+		Some(path) => path.split('/').next().unwrap_or_default().to_owned(),
+		None => return Ok(req),
+	};
+	let address = Address::from_str(account_address.trim_start_matches("0x"))
+		.map_err(|_| Error::Param(format!("Invalid account address `{}`", account_address)))?;
+	let account = req
+		.data::<Arc<AccountsManager>>()
+		.and_then(|accounts_manager| accounts_manager.get(&address))
+		.ok_or(Error::UnknownAccount(address))?;
+	req.set_context(account);
+	Ok(req)
+}
+
 #[tracing::instrument(skip(err))]
 async fn error_handler(err: routerify::RouteError, _: RequestInfo) -> Response<Body> {
-	error!(message = "Error handling request", error = err);
-	Response::builder()
-		.status(StatusCode::INTERNAL_SERVER_ERROR)
-		.body(Body::from(format!("Something went wrong: {}", err)))
-		.unwrap()
+	let status = match err.downcast_ref::<Error>() {
+		Some(Error::Unauthorized) => StatusCode::UNAUTHORIZED,
+		Some(Error::Forbidden) => StatusCode::FORBIDDEN,
+		Some(Error::UnknownAccount(_)) => StatusCode::NOT_FOUND,
+		Some(Error::Param(_)) => StatusCode::BAD_REQUEST,
+		_ => {
+			error!(message = "Error handling request", error = err);
+			return Response::builder()
+				.status(StatusCode::INTERNAL_SERVER_ERROR)
+				.body(Body::from(format!("Something went wrong: {}", err)))
+				.unwrap()
+		},
+	};
+
+	let mut error_data = HashMap::new();
+	error_data.insert("errors", err.to_string());
+	let mut response = Response::builder()
+		.header(header::CONTENT_TYPE, "application/json")
+		.status(status);
+	if status == StatusCode::UNAUTHORIZED {
+		response = response.header(header::WWW_AUTHENTICATE, "Bearer");
+	}
+	response.body(Body::from(serde_json::to_string(&error_data).unwrap())).unwrap()
 }
 
-fn router(raiden: Arc<Raiden>, api: Arc<Api>, stop_sender: Sender<bool>) -> Router<Body, Error> {
+fn router(
+	raiden: Arc<Raiden>,
+	api: Arc<Api>,
+	accounts_manager: Arc<AccountsManager>,
+	stop_sender: Sender<bool>,
+	auth: ApiAuth,
+) -> Router<Body, Error> {
 	Router::builder()
 		// Specify the state data which will be available to every route handlers,
 		// error handler and middlewares.
 		.middleware(Middleware::pre(log_request))
+		.middleware(Middleware::pre(auth::authenticate))
+		.data(auth)
 		.data(api)
 		.data(raiden.config.account.clone())
 		.data(raiden.state_manager.clone())
 		.data(raiden.contracts_manager.clone())
 		.data(raiden.proxy_manager.clone())
+		.data(accounts_manager)
 		.data(stop_sender)
-		.get("/api/v1/address", endpoints::address)
-		.get("/api/v1/contracts", endpoints::contracts)
-		.get("/api/v1/channels", endpoints::channels)
-		.put("/api/v1/channels", endpoints::create_channel)
-		.get("/api/v1/channels/:token_address", endpoints::channels)
-		.get(
-			"/api/v1/channels/:token_address/:partner_address",
-			endpoints::channel_by_partner_address,
-		)
-		.patch("/api/v1/channels/:token_address/:partner_address", endpoints::channel_update)
-		.get("/api/v1/connections", endpoints::connections_info)
-		.delete("/api/v1/connections/:token_address", endpoints::connections_leave)
-		.get("/api/v1/notifications", endpoints::notifications)
-		.get("/api/v1/payments", endpoints::payments)
-		.get("/api/v1/payments/:token_address", endpoints::payments)
-		.get("/api/v1/payments/:token_address/:partner_address", endpoints::payments)
-		.post("/api/v1/payments/:token_address/:partner_address", endpoints::initiate_payment)
-		.get("/api/v1/pending_transfers", endpoints::pending_transfers)
-		.get("/api/v1/pending_transfers/:token_address", endpoints::pending_transfers)
-		.get(
-			"/api/v1/pending_transfers/:token_address/:partner_address",
-			endpoints::pending_transfers,
-		)
-		.get("/api/v1/settings", endpoints::settings)
-		.get("/api/v1/tokens", endpoints::tokens)
-		.put("/api/v1/tokens/:token_address", endpoints::register_token)
-		.get("/api/v1/tokens/:token_address", endpoints::get_token_network_by_token)
-		.get("/api/v1/tokens/:token_address/partners", endpoints::partners_by_token_address)
-		.post("/api/v1/user_deposit", endpoints::user_deposit)
-		.get("/api/v1/status", endpoints::status)
-		.get("/api/v1/version", endpoints::version)
+		.get("/api/v1/accounts", endpoints::accounts)
 		.get("/api/v1/shutdown", endpoints::shutdown)
-		.get("/api/v1/_debug/raiden_events", endpoints::raiden_events)
-		.post("/api/v1/_testing/tokens/:token_address/mint", endpoints::mint_token)
+		.get("/metrics", endpoints::metrics)
+		// Routes of the primary account.
+		.scope("/api/v1", account_router())
+		// Routes scoped by the address of a local account.
+		.scope("/api/v1/accounts/:account_address", account_router())
 		.err_handler_with_info(error_handler)
 		.build()
 		.unwrap()
 }
+
+fn account_router() -> Router<Body, Error> {
+	Router::builder()
+		.middleware(Middleware::pre(select_account))
+		.get("/address", endpoints::address)
+		.get("/contracts", endpoints::contracts)
+		.get("/channels", endpoints::channels)
+		.put("/channels", endpoints::create_channel)
+		.get("/channels/:token_address", endpoints::channels)
+		.get("/channels/:token_address/:partner_address", endpoints::channel_by_partner_address)
+		.patch("/channels/:token_address/:partner_address", endpoints::channel_update)
+		.get("/connections", endpoints::connections_info)
+		.put("/connections/:token_address", endpoints::connections_connect)
+		.delete("/connections/:token_address", endpoints::connections_leave)
+		.get("/invoices", endpoints::invoices)
+		.post("/invoices", endpoints::create_invoice)
+		.post("/invoices/pay", endpoints::pay_invoice)
+		.get("/notifications", endpoints::notifications)
+		.patch("/notifications/:notification_id", endpoints::acknowledge_notification)
+		.delete("/notifications/:notification_id", endpoints::dismiss_notification)
+		.get("/payments", endpoints::payments)
+		.get("/payments/:token_address", endpoints::payments)
+		.get("/payments/:token_address/:partner_address", endpoints::payments)
+		.post("/payments/:token_address/:partner_address", endpoints::initiate_payment)
+		.get("/payments/:token_address/:partner_address/:identifier", endpoints::payment_status)
+		.delete("/payments/:token_address/:partner_address/:identifier", endpoints::cancel_payment)
+		.get("/pending_transfers", endpoints::pending_transfers)
+		.get("/pending_transfers/:token_address", endpoints::pending_transfers)
+		.get("/pending_transfers/:token_address/:partner_address", endpoints::pending_transfers)
+		.get("/settings", endpoints::settings)
+		.get("/tokens", endpoints::tokens)
+		.put("/tokens/:token_address", endpoints::register_token)
+		.get("/tokens/:token_address", endpoints::get_token_network_by_token)
+		.get("/tokens/:token_address/partners", endpoints::partners_by_token_address)
+		.post("/user_deposit", endpoints::user_deposit)
+		.get("/status", endpoints::status)
+		.get("/version", endpoints::version)
+		.get("/_debug/raiden_events", endpoints::raiden_events)
+		.get("/events/stream", endpoints::events_stream)
+		.post("/_testing/tokens/:token_address/mint", endpoints::mint_token)
+		.build()
+		.unwrap()
+}
@@ -0,0 +1,111 @@
+use std::{
+	fs,
+	path::PathBuf,
+	pin::Pin,
+	task::{
+		Context,
+		Poll,
+	},
+	time::Duration,
+};
+
+use hyper::server::accept::Accept;
+use tokio::{
+	net::{
+		TcpListener,
+		TcpStream,
+	},
+	sync::mpsc,
+};
+use tokio_native_tls::{
+	native_tls::{
+		self,
+		Identity,
+	},
+	TlsAcceptor,
+	TlsStream,
+};
+use tracing::debug;
+
+use super::error::Error;
+
+/// Number of established TLS connections waiting to be served.
+const TLS_CONNECTIONS_BUFFER: usize = 64;
+
+/// Delay before accepting connections again after a failure, such as running out of file
+/// descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// TLS configuration of the HTTP server.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+	/// Path to a PKCS #12 archive holding the certificate chain and private key.
+	pub identity: PathBuf,
+	pub password: String,
+}
+
+impl TlsConfig {
+	/// Load the identity and create a TLS acceptor.
+	pub(crate) fn acceptor(&self) -> Result<TlsAcceptor, Error> {
+		let identity = fs::read(&self.identity)
+			.map_err(|e| Error::Tls(format!("Could not read TLS identity: {}", e)))?;
+		let identity = Identity::from_pkcs12(&identity, &self.password)
+			.map_err(|e| Error::Tls(format!("Invalid TLS identity: {}", e)))?;
+		let acceptor = native_tls::TlsAcceptor::new(identity)
+			.map_err(|e| Error::Tls(format!("Could not create TLS acceptor: {}", e)))?;
+		Ok(acceptor.into())
+	}
+}
+
+/// Accepts TCP connections and performs the TLS handshakes.
+///
+/// Handshakes run in their own tasks so that a slow client does not block other connections.
+/// Failed handshakes are dropped instead of stopping the server.
+pub(crate) struct TlsIncoming {
+	receiver: mpsc::Receiver<TlsStream<TcpStream>>,
+}
+
+impl TlsIncoming {
+	pub(crate) fn new(listener: TcpListener, acceptor: TlsAcceptor) -> Self {
+		let (sender, receiver) = mpsc::channel(TLS_CONNECTIONS_BUFFER);
+		tokio::spawn(async move {
+			loop {
+				let (stream, remote_address) = match listener.accept().await {
+					Ok(connection) => connection,
+					Err(e) => {
+						debug!(message = "Could not accept connection", error = e.to_string());
+						tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+						continue
+					},
+				};
+				let acceptor = acceptor.clone();
+				let sender = sender.clone();
+				tokio::spawn(async move {
+					match acceptor.accept(stream).await {
+						Ok(stream) => {
+							let _ = sender.send(stream).await;
+						},
+						Err(e) => debug!(
+							message = "TLS handshake failed",
+							remote_address = remote_address.to_string(),
+							error = e.to_string(),
+						),
+					}
+				});
+			}
+		});
+		Self { receiver }
+	}
+}
+
+impl Accept for TlsIncoming {
+	type Conn = TlsStream<TcpStream>;
+	type Error = Error;
+
+	fn poll_accept(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+	) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+		self.receiver.poll_recv(cx).map(|stream| stream.map(Ok))
+	}
+}
@@ -13,12 +13,19 @@ use raiden_blockchain::{
 	contracts::ContractsManager,
 	proxies::Account,
 };
+use raiden_multi_account::manager::{
+	AccountsManager,
+	LocalAccount,
+};
 use raiden_primitives::types::{
 	ChannelIdentifier,
 	SecretHash,
 	TokenAddress,
 };
-use raiden_state_machine::types::TransferTask;
+use raiden_state_machine::{
+	storage::types::EventFilter,
+	types::TransferTask,
+};
 use raiden_transition::manager::StateManager;
 use routerify::ext::RequestExt;
 use serde::de::DeserializeOwned;
@@ -30,16 +37,34 @@ use super::{
 	response::TransferView,
 };
 
+/// Returns the local account selected by an account scoped route.
+fn local_account(req: &Request<Body>) -> Option<Arc<LocalAccount>> {
+	req.context::<Arc<LocalAccount>>()
+}
+
 pub(crate) fn account(req: &Request<Body>) -> Account<Http> {
-	req.data::<Account<Http>>().unwrap().clone()
+	match local_account(req) {
+		Some(local_account) => local_account.raiden.config.account.clone(),
+		None => req.data::<Account<Http>>().unwrap().clone(),
+	}
 }
 
 pub(crate) fn api(req: &Request<Body>) -> Arc<Api> {
-	req.data::<Arc<Api>>().unwrap().clone()
+	match local_account(req) {
+		Some(local_account) => local_account.api.clone(),
+		None => req.data::<Arc<Api>>().unwrap().clone(),
+	}
 }
 
 pub(crate) fn state_manager(req: &Request<Body>) -> Arc<RwLock<StateManager>> {
-	req.data::<Arc<RwLock<StateManager>>>().unwrap().clone()
+	match local_account(req) {
+		Some(local_account) => local_account.raiden.state_manager.clone(),
+		None => req.data::<Arc<RwLock<StateManager>>>().unwrap().clone(),
+	}
+}
+
+pub(crate) fn accounts_manager(req: &Request<Body>) -> Arc<AccountsManager> {
+	req.data::<Arc<AccountsManager>>().unwrap().clone()
 }
 
 pub(crate) fn contracts_manager(req: &Request<Body>) -> Arc<ContractsManager> {
@@ -50,6 +75,49 @@ pub(crate) fn stop_sender(req: &Request<Body>) -> Sender<bool> {
 	req.data::<Sender<bool>>().unwrap().clone()
 }
 
+pub(crate) fn query_params(req: &Request<Body>) -> HashMap<String, String> {
+	req.uri()
+		.query()
+		.map(|query| url::form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+		.unwrap_or_default()
+}
+
+/// Build an event filter from the `after`, `limit`, `from`, `to` and `event_types` query
+/// parameters.
+pub(crate) fn event_filter(query: &HashMap<String, String>) -> Result<EventFilter, Error> {
+	let mut filter = EventFilter::default();
+	if let Some(after) = query.get("after") {
+		filter.after = Some(
+			after
+				.clone()
+				.try_into()
+				.map_err(|_| Error::Param(format!("Invalid cursor `{}`", after)))?,
+		);
+	}
+	if let Some(limit) = query.get("limit") {
+		filter.limit =
+			Some(limit.parse().map_err(|_| Error::Param(format!("Invalid limit `{}`", limit)))?);
+	}
+	if let Some(from) = query.get("from") {
+		filter.from = Some(
+			from.parse()
+				.map_err(|_| Error::Param(format!("Invalid timestamp `{}`", from)))?,
+		);
+	}
+	if let Some(to) = query.get("to") {
+		filter.to =
+			Some(to.parse().map_err(|_| Error::Param(format!("Invalid timestamp `{}`", to)))?);
+	}
+	if let Some(event_types) = query.get("event_types") {
+		filter.event_types = event_types
+			.split(',')
+			.map(|event_type| event_type.trim().to_owned())
+			.filter(|event_type| !event_type.is_empty())
+			.collect();
+	}
+	Ok(filter)
+}
+
 pub(crate) async fn body_to_params<T: DeserializeOwned>(req: Request<Body>) -> Result<T, Error> {
 	let body = hyper::body::to_bytes(req.into_body()).await.map_err(Error::Http)?;
 	let params: T = serde_json::from_slice(&body).map_err(Error::Serialization)?;
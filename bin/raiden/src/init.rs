@@ -1,49 +1,94 @@
 use std::{
+	fs,
+	net::SocketAddr,
 	path::PathBuf,
 	sync::Arc,
 };
 
 use parking_lot::RwLock as SyncRwLock;
+use raiden_api::{
+	api::Api,
+	raiden::{
+		Raiden,
+		RaidenConfig,
+	},
+};
 use raiden_blockchain::{
 	contracts::{
 		ContractIdentifier,
 		ContractsManager,
 	},
+	gas::GasStrategies,
+	keys::Signer,
 	proxies::{
 		Account,
 		ProxyManager,
 		ServiceRegistryProxy,
+		TransactionTracker,
+		TransactionTrackerConfig,
+	},
+};
+use raiden_multi_account::{
+	manager::{
+		AccountsManager,
+		LocalAccount,
 	},
+	router::LocalRouter,
 };
 use raiden_network_messages::messages::TransportServiceMessage;
 use raiden_network_transport::{
-	config::TransportConfig,
+	config::{
+		TransportConfig,
+		TransportMode,
+	},
 	matrix::{
 		constants::MATRIX_AUTO_SELECT_SERVER,
 		storage::MatrixStorage,
 		utils::{
 			get_default_matrix_servers,
-			select_best_server,
+			rank_servers,
 		},
 		MatrixClient,
 		MatrixService,
 	},
+	tcp::TcpService,
 	types::EnvironmentType,
 };
-use raiden_pathfinding::config::{
-	PFSInfo,
-	ServicesConfig,
+use raiden_pathfinding::{
+	config::{
+		PFSConfig,
+		PFSInfo,
+		ServicesConfig,
+	},
+	graph::NetworkGraph,
+	types::RoutingMode,
+	PFS,
 };
-use raiden_primitives::types::{
-	Address,
-	AddressMetadata,
-	BlockNumber,
-	ChainID,
-	DefaultAddresses,
+use raiden_primitives::{
+	payments::PaymentsRegistry,
+	reachability::ReachabilityTracker,
+	traits::{
+		Checksum,
+		ToPexAddress,
+	},
+	types::{
+		Address,
+		AddressMetadata,
+		BlockNumber,
+		ChainID,
+		DefaultAddresses,
+		RevealTimeout,
+		SettleTimeout,
+	},
 };
+#[cfg(feature = "postgresql")]
+use raiden_state_machine::storage::PostgresStorage;
 use raiden_state_machine::{
 	machine::channel::calculate_imbalance_fees,
-	storage::StateStorage,
+	storage::{
+		StateStorage,
+		Storage,
+	},
 	types::{
 		ChannelStatus,
 		Event,
@@ -56,12 +101,59 @@ use raiden_state_machine::{
 use raiden_transition::{
 	events::EventHandler,
 	manager::StateManager,
+	messages::MessageHandler,
+	Transitioner,
 };
 use rusqlite::Connection;
-use tokio::sync::mpsc::UnboundedSender;
-use web3::transports::Http;
+use tokio::sync::{
+	mpsc::UnboundedSender,
+	RwLock,
+};
+use tracing::info;
+use web3::{
+	transports::Http,
+	Web3,
+};
+
+use crate::{
+	http::{
+		ApiAuth,
+		TlsConfig,
+	},
+	services::SyncService,
+};
+
+pub fn init_storage(
+	datadir: PathBuf,
+	log_config: String,
+	database_url: Option<String>,
+	schema: String,
+) -> Result<Arc<dyn Storage>, String> {
+	let storage: Arc<dyn Storage> = match database_url {
+		Some(database_url) => init_postgres_storage(&database_url, &schema)?,
+		None => Arc::new(init_sqlite_storage(datadir, log_config)?),
+	};
+	storage
+		.setup_database()
+		.map_err(|e| format!("Failed to setup storage: {}", e))?;
+
+	Ok(storage)
+}
 
-pub fn init_storage(datadir: PathBuf, log_config: String) -> Result<Arc<StateStorage>, String> {
+#[cfg(feature = "postgresql")]
+fn init_postgres_storage(database_url: &str, schema: &str) -> Result<Arc<dyn Storage>, String> {
+	let storage = PostgresStorage::connect(database_url, schema)
+		.map_err(|e| format!("Could not connect to database: {}", e))?;
+	Ok(Arc::new(storage))
+}
+
+#[cfg(not(feature = "postgresql"))]
+fn init_postgres_storage(_database_url: &str, _schema: &str) -> Result<Arc<dyn Storage>, String> {
+	Err("PostgreSQL storage is not supported, raiden has to be built with the `postgresql` feature"
+		.to_owned())
+}
+
+fn init_sqlite_storage(datadir: PathBuf, log_config: String) -> Result<StateStorage, String> {
 	let mut conn = Connection::open(datadir.join("raiden.db"))
 		.map_err(|e| format!("Could not connect to database: {}", e))?;
 	if log_config.to_lowercase() == "trace" {
@@ -70,18 +162,13 @@ pub fn init_storage(datadir: PathBuf, log_config: String) -> Result<Arc<StateSto
 		}
 		conn.trace(Some(log_query));
 	}
-	let storage = Arc::new(StateStorage::new(conn));
-	storage
-		.setup_database()
-		.map_err(|e| format!("Failed to setup storage: {}", e))?;
-
-	Ok(storage)
+	Ok(StateStorage::new(conn))
 }
 
 pub fn init_state_manager(
 	contracts_manager: Arc<ContractsManager>,
 	default_addresses: DefaultAddresses,
-	storage: Arc<StateStorage>,
+	storage: Arc<dyn Storage>,
 	chain_id: ChainID,
 	account: Account<Http>,
 ) -> Result<(Arc<SyncRwLock<StateManager>>, BlockNumber), String> {
@@ -159,47 +246,350 @@ pub async fn init_channel_fees(
 	state_manager.write().current_state = chain_state.clone();
 }
 
+/// A transport service of an account.
+pub enum TransportService {
+	Matrix(MatrixService),
+	Tcp(TcpService),
+}
+
+impl TransportService {
+	/// Run the service, handing the incoming messages to `message_handler`.
+	pub async fn run(self, message_handler: MessageHandler) {
+		match self {
+			TransportService::Matrix(service) => service.run(message_handler).await,
+			TransportService::Tcp(service) => service.run(message_handler).await,
+		}
+	}
+}
+
+/// Initialize the transport services of an account according to the transport mode.
+///
+/// Returns the services to run along with the sender to reach them and the metadata advertised
+/// to other nodes, which the matrix transport updates when it fails over to another home server.
 pub async fn init_transport(
 	environment_type: EnvironmentType,
 	transport_config: TransportConfig,
 	account: Account<Http>,
 	storage_path: PathBuf,
 	service_registry_proxy: ServiceRegistryProxy<Http>,
-) -> Result<(MatrixService, UnboundedSender<TransportServiceMessage>, AddressMetadata), String> {
-	let homeserver_url = if transport_config.matrix.homeserver_url == MATRIX_AUTO_SELECT_SERVER {
+	reachability: Arc<SyncRwLock<ReachabilityTracker>>,
+) -> Result<
+	(
+		Vec<TransportService>,
+		UnboundedSender<TransportServiceMessage>,
+		Arc<SyncRwLock<AddressMetadata>>,
+	),
+	String,
+> {
+	match transport_config.mode {
+		TransportMode::Matrix => {
+			let (matrix_service, sender, our_metadata) = init_matrix_transport(
+				environment_type,
+				transport_config,
+				account,
+				storage_path,
+				service_registry_proxy,
+				None,
+				reachability,
+			)
+			.await?;
+			Ok((vec![TransportService::Matrix(matrix_service)], sender, our_metadata))
+		},
+		TransportMode::Tcp => {
+			let socket_address = advertised_socket_address(&transport_config)?;
+			let (tcp_service, sender) =
+				TcpService::new(transport_config, account.signer(), None, reachability)
+					.await
+					.map_err(|e| format!("Failed to initialize TCP transport: {}", e))?;
+			let mut our_metadata = AddressMetadata::default();
+			our_metadata.set_socket_address(socket_address);
+			Ok((
+				vec![TransportService::Tcp(tcp_service)],
+				sender,
+				Arc::new(SyncRwLock::new(our_metadata)),
+			))
+		},
+		TransportMode::Combined => {
+			let socket_address = advertised_socket_address(&transport_config)?;
+			let (matrix_service, matrix_sender, our_metadata) = init_matrix_transport(
+				environment_type,
+				transport_config.clone(),
+				account.clone(),
+				storage_path,
+				service_registry_proxy,
+				Some(socket_address),
+				reachability.clone(),
+			)
+			.await?;
+			let (tcp_service, sender) = TcpService::new(
+				transport_config,
+				account.signer(),
+				Some(matrix_sender),
+				reachability,
+			)
+			.await
+			.map_err(|e| format!("Failed to initialize TCP transport: {}", e))?;
+			Ok((
+				vec![TransportService::Matrix(matrix_service), TransportService::Tcp(tcp_service)],
+				sender,
+				our_metadata,
+			))
+		},
+	}
+}
+
+/// Returns the socket address advertised to peers for direct connections.
+///
+/// It has to be set explicitly, since the listen address is usually not the one peers reach the
+/// node at, and peers can not connect to an unspecified address.
+fn advertised_socket_address(transport_config: &TransportConfig) -> Result<SocketAddr, String> {
+	let socket_address = transport_config.tcp.advertised_address.ok_or_else(|| {
+		"The TCP transport requires --tcp-advertised-address to be set".to_owned()
+	})?;
+	if socket_address.ip().is_unspecified() {
+		return Err(format!("Can not advertise the unspecified address {} to peers", socket_address))
+	}
+	Ok(socket_address)
+}
+
+async fn init_matrix_transport(
+	environment_type: EnvironmentType,
+	transport_config: TransportConfig,
+	account: Account<Http>,
+	storage_path: PathBuf,
+	service_registry_proxy: ServiceRegistryProxy<Http>,
+	socket_address: Option<SocketAddr>,
+	reachability: Arc<SyncRwLock<ReachabilityTracker>>,
+) -> Result<
+	(MatrixService, UnboundedSender<TransportServiceMessage>, Arc<SyncRwLock<AddressMetadata>>),
+	String,
+> {
+	// Automatically selected home servers are ranked by round trip time, the others are used
+	// when the fastest one fails.
+	let mut homeserver_urls = if transport_config.matrix.homeserver_url == MATRIX_AUTO_SELECT_SERVER
+	{
 		let servers = get_default_matrix_servers(environment_type)
 			.await
 			.map_err(|e| format!("Could not fetch default matrix servers: {:?}", e))?;
-		select_best_server(servers)
+		let servers = rank_servers(servers).await;
+		if servers.is_empty() {
+			return Err("None of the default matrix servers is reachable".to_owned())
+		}
+		servers
 	} else {
-		transport_config.matrix.homeserver_url.clone()
+		vec![transport_config.matrix.homeserver_url.clone()]
 	};
+	let homeserver_url = homeserver_urls.remove(0);
+
+	let mut matrix_client = MatrixClient::new(homeserver_url, account.signer()).await;
 
 	let conn = Connection::open(storage_path.join("matrix.db"))
 		.map_err(|e| format!("Could not connect to database: {}", e))?;
 	let storage = MatrixStorage::new(conn);
 	storage
-		.setup_database()
+		.setup_database(&matrix_client.server_name())
 		.map_err(|e| format!("Failed to setup storage: {}", e))?;
 
-	let mut matrix_client = MatrixClient::new(homeserver_url, account.private_key()).await;
 	let _ = matrix_client.populate_services_addresses(service_registry_proxy).await;
+	if let Some(socket_address) = socket_address {
+		matrix_client.advertise_socket_address(socket_address);
+	}
 
 	matrix_client
 		.init()
 		.await
 		.map_err(|e| format!("Failed to initialize Matrix client: {}", e))?;
 
-	let our_metadata = matrix_client.address_metadata();
+	let our_metadata = Arc::new(SyncRwLock::new(matrix_client.address_metadata()));
 
-	let (mut transport_service, sender) =
-		MatrixService::new(transport_config, matrix_client, storage);
+	let (mut transport_service, sender) = MatrixService::new(
+		transport_config,
+		matrix_client,
+		storage,
+		our_metadata.clone(),
+		reachability,
+	);
+	transport_service.set_fallback_servers(homeserver_urls);
 
 	transport_service.init_from_storage()?;
 
 	Ok((transport_service, sender, our_metadata))
 }
 
+/// Configuration shared by all local accounts.
+pub struct AccountsConfig {
+	pub chain_id: ChainID,
+	pub datadir: PathBuf,
+	pub database_url: Option<String>,
+	pub log_config: String,
+	pub environment_type: EnvironmentType,
+	pub transport_config: TransportConfig,
+	pub default_addresses: DefaultAddresses,
+	pub monitoring_enabled: bool,
+	pub routing_mode: RoutingMode,
+	pub pfs_config: PFSConfig,
+	pub mediation_config: MediationFeeConfig,
+	pub default_settle_timeout: SettleTimeout,
+	pub default_reveal_timeout: RevealTimeout,
+	pub gas_strategies: GasStrategies,
+	pub transaction_tracker: TransactionTrackerConfig,
+}
+
+/// An initialized local account along with the services which have to be run for it.
+pub struct AccountServices {
+	pub account: Arc<LocalAccount>,
+	pub event_handler: EventHandler,
+	pub transport_services: Vec<(TransportService, MessageHandler)>,
+	pub sync_service: SyncService,
+	pub sync_start_block_number: BlockNumber,
+}
+
+/// Initialize the storage, state, transport and handlers of an account and register it as a
+/// local account.
+pub async fn init_account(
+	config: &AccountsConfig,
+	web3: Web3<Http>,
+	signer: Arc<dyn Signer>,
+	accounts_manager: &AccountsManager,
+	local_router: &LocalRouter,
+) -> Result<AccountServices, String> {
+	info!(message = "Using account", address = signer.address().checksum());
+
+	let node_dir = format!("node_{}", signer.address().pex());
+	let netid_dir = format!("netid_{}", config.chain_id.to_string());
+	let network_dir = format!("network_{}", config.default_addresses.token_network_registry.pex());
+	let mut datadir = config.datadir.clone();
+	datadir.push(&node_dir);
+	datadir.push(&netid_dir);
+	datadir.push(format!("{}/", network_dir));
+	let datadir = crate::setup_data_directory(datadir)
+		.map_err(|e| format!("Error initializing data directory: {}", e))?;
+
+	let storage = init_storage(
+		datadir.clone(),
+		config.log_config.clone(),
+		config.database_url.clone(),
+		format!("{}_{}_{}", node_dir, netid_dir, network_dir),
+	)?;
+
+	let nonce = web3
+		.eth()
+		.transaction_count(signer.address(), None)
+		.await
+		.map_err(|e| format!("Failed to fetch nonce: {}", e))?;
+	let tracker =
+		TransactionTracker::new(config.transaction_tracker.clone(), Some(storage.clone()));
+	let account =
+		Account::new(web3.clone(), signer, nonce, config.gas_strategies.clone(), Arc::new(tracker));
+	// Transactions sent before a restart may still be pending and keep their nonces.
+	account.sync_nonce().await.map_err(|e| format!("Failed to sync nonce: {}", e))?;
+
+	let (state_manager, sync_start_block_number) = init_state_manager(
+		accounts_manager.contracts_manager(),
+		config.default_addresses.clone(),
+		storage,
+		config.chain_id,
+		account.clone(),
+	)?;
+
+	let proxy_manager = accounts_manager.proxy_manager();
+	let services_registry_proxy = proxy_manager
+		.service_registry(config.default_addresses.service_registry)
+		.await
+		.map_err(|e| format!("Could not instantiate services registry: {:?}", e))?;
+	// The partners of the existing channels are checked for reachability from the start.
+	let mut reachability = ReachabilityTracker::new();
+	for channel in views::get_channels(&state_manager.read().current_state) {
+		if channel.status() != ChannelStatus::Settled {
+			reachability.watch(channel.partner_state.address);
+		}
+	}
+	let reachability = Arc::new(SyncRwLock::new(reachability));
+
+	let (transport_services, transport_sender, our_metadata) = init_transport(
+		config.environment_type,
+		config.transport_config.clone(),
+		account.clone(),
+		datadir,
+		services_registry_proxy,
+		reachability.clone(),
+	)
+	.await?;
+	let transport_sender = local_router.intercept(account.address(), transport_sender);
+
+	let raiden_config = RaidenConfig {
+		chain_id: config.chain_id,
+		mediation_config: config.mediation_config.clone(),
+		account: account.clone(),
+		metadata: our_metadata,
+		monitoring_enabled: config.monitoring_enabled,
+		pfs_config: config.pfs_config.clone(),
+		addresses: config.default_addresses.clone(),
+		default_settle_timeout: config.default_settle_timeout,
+		default_reveal_timeout: config.default_reveal_timeout,
+	};
+	let pfs = PFS::new(config.chain_id, raiden_config.pfs_config.clone(), account.signer());
+	let network_graph = (config.routing_mode == RoutingMode::Private)
+		.then(|| Arc::new(SyncRwLock::new(NetworkGraph::new())));
+	let raiden = Arc::new(Raiden {
+		web3: web3.clone(),
+		config: raiden_config,
+		contracts_manager: accounts_manager.contracts_manager(),
+		proxy_manager: proxy_manager.clone(),
+		state_manager: state_manager.clone(),
+		transport: transport_sender.clone(),
+		pfs: Arc::new(pfs),
+		network_graph: network_graph.clone(),
+		reachability,
+	});
+
+	let payments_registry = Arc::new(RwLock::new(PaymentsRegistry::new()));
+	let event_handler = EventHandler::new(
+		web3,
+		account.clone(),
+		state_manager.clone(),
+		proxy_manager,
+		transport_sender.clone(),
+		config.default_addresses.clone(),
+		payments_registry.clone(),
+		network_graph.is_some(),
+	);
+	let transition_service = Arc::new(Transitioner::new(
+		state_manager.clone(),
+		event_handler.clone(),
+		config.monitoring_enabled,
+	));
+	let new_message_handler = || {
+		MessageHandler::new(
+			account.signer(),
+			config.pfs_config.url.clone(),
+			transport_sender.clone(),
+			state_manager.clone(),
+			transition_service.clone(),
+			network_graph.clone(),
+		)
+	};
+	local_router.register(account.address(), new_message_handler());
+	let transport_services = transport_services
+		.into_iter()
+		.map(|transport_service| (transport_service, new_message_handler()))
+		.collect();
+
+	let sync_service = SyncService::new(raiden.clone(), transition_service.clone());
+	let api = Api::new(raiden.clone(), transition_service.clone(), payments_registry);
+	let account = accounts_manager
+		.add(LocalAccount { raiden, transition_service, api: Arc::new(api) })
+		.map_err(|e| e.to_string())?;
+
+	Ok(AccountServices {
+		account,
+		event_handler,
+		transport_services,
+		sync_service,
+		sync_start_block_number,
+	})
+}
+
 pub async fn init_pfs_info(
 	default_addresses: DefaultAddresses,
 	proxy_manager: Arc<ProxyManager>,
@@ -214,3 +604,42 @@ pub async fn init_pfs_info(
 		.await
 		.map_err(|e| format!("Failed to initialize PFS: {}", e))
 }
+
+fn read_secret_file(path: PathBuf) -> Result<String, String> {
+	let secret = fs::read_to_string(&path)
+		.map_err(|e| format!("Error reading {:?}: {:?}", path, e))?
+		.trim()
+		.to_owned();
+	if secret.is_empty() {
+		return Err(format!("File {:?} is empty", path))
+	}
+	Ok(secret)
+}
+
+pub fn init_api_auth(
+	token_file: Option<PathBuf>,
+	read_only_token_file: Option<PathBuf>,
+) -> Result<ApiAuth, String> {
+	Ok(ApiAuth {
+		full_access_token: token_file.map(read_secret_file).transpose()?,
+		read_only_token: read_only_token_file.map(read_secret_file).transpose()?,
+	})
+}
+
+pub fn init_api_tls(
+	identity: Option<PathBuf>,
+	password_file: Option<PathBuf>,
+) -> Result<Option<TlsConfig>, String> {
+	let identity = match identity {
+		Some(identity) => identity,
+		None => return Ok(None),
+	};
+	let password = match password_file {
+		Some(password_file) => fs::read_to_string(&password_file)
+			.map_err(|e| format!("Error reading {:?}: {:?}", password_file, e))?
+			.trim()
+			.to_owned(),
+		None => String::new(),
+	};
+	Ok(Some(TlsConfig { identity, password }))
+}
@@ -1,49 +1,47 @@
 use std::{
 	fs,
+	iter,
 	net::SocketAddr,
 	path::PathBuf,
 	process,
 	str::FromStr,
 	sync::Arc,
+	time::Duration,
 };
 
-use futures::FutureExt;
-use raiden_api::{
-	api::Api,
-	raiden::{
-		Raiden,
-		RaidenConfig,
-	},
+use futures::{
+	future::join_all,
+	FutureExt,
 };
-use raiden_bin_common::init_private_key;
+use raiden_bin_common::init_signer;
 use raiden_blockchain::{
 	contracts,
+	gas::GasStrategies,
 	proxies::{
-		Account,
 		ProxyManager,
+		TransactionTrackerConfig,
 	},
 };
+use raiden_multi_account::{
+	manager::AccountsManager,
+	router::LocalRouter,
+};
+use raiden_network_transport::config::TransportMode;
 use raiden_pathfinding::{
 	self,
 	config::{
 		PFSConfig,
 		ServicesConfig,
 	},
-	PFS,
+	types::RoutingMode,
 };
 use raiden_primitives::{
-	payments::PaymentsRegistry,
-	traits::{
-		Checksum,
-		ToPexAddress,
-	},
+	traits::Checksum,
 	types::ChainID,
 };
-use raiden_state_machine::types::MediationFeeConfig;
-use raiden_transition::{
-	events::EventHandler,
-	messages::MessageHandler,
-	Transitioner,
+use raiden_state_machine::{
+	storage::types::RetentionPolicy,
+	types::MediationFeeConfig,
 };
 use structopt::StructOpt;
 use tokio::{
@@ -52,10 +50,7 @@ use tokio::{
 		signal,
 		SignalKind,
 	},
-	sync::{
-		mpsc,
-		RwLock,
-	},
+	sync::mpsc,
 };
 use tracing::info;
 use tracing_subscriber::{
@@ -63,7 +58,6 @@ use tracing_subscriber::{
 	prelude::*,
 };
 use web3::{
-	signing::Key,
 	transports::WebSocket,
 	types::Address,
 };
@@ -72,7 +66,12 @@ use crate::{
 	cli::Opt,
 	services::{
 		BlockMonitorService,
-		SyncService,
+		CompactionService,
+		ConnectionManagerService,
+		NotificationService,
+		RebalanceConfig,
+		RebalanceService,
+		TransactionMonitorService,
 	},
 	traits::{
 		ToHTTPEndpoint,
@@ -83,8 +82,13 @@ use crate::{
 mod cli;
 mod http;
 mod init;
+mod metrics;
 mod services;
 mod traits;
+
+#[cfg(test)]
+mod tests;
+
 use init::*;
 
 #[tokio::main]
@@ -163,33 +167,27 @@ async fn main() {
 	// #
 	let http = web3::transports::Http::new(&eth_rpc_http_endpoint).unwrap();
 	let web3 = web3::Web3::new(http);
-	let private_key = match init_private_key(
-		web3.clone(),
-		cli.keystore_path.clone(),
-		cli.address,
-		cli.password_file,
-	)
-	.await
-	{
-		Ok(key) => key,
-		Err(e) => {
-			tracing::error!("{}", e);
-			process::exit(1);
-		},
-	};
-	let nonce = match web3.eth().transaction_count(private_key.address(), None).await {
-		Ok(nonce) => nonce,
-		Err(e) => {
-			tracing::error!("Failed to fetch nonce: {}", e);
-			process::exit(1);
-		},
-	};
-	let account = Account::new(web3.clone(), private_key, nonce);
-
-	info!(message = "Using account", address = account.address().checksum());
+	let mut signers = vec![];
+	for address in iter::once(cli.address).chain(cli.additional_addresses.into_iter().map(Some)) {
+		match init_signer(
+			web3.clone(),
+			cli.keystore_path.clone(),
+			address,
+			cli.password_file.clone(),
+			cli.remote_signer.clone(),
+		)
+		.await
+		{
+			Ok(signer) => signers.push(signer),
+			Err(e) => {
+				tracing::error!("{}", e);
+				process::exit(1);
+			},
+		};
+	}
 
 	// #
-	// # Initialize state manager
+	// # Initialize shared components
 	// #
 	let contracts_manager = match contracts::ContractsManager::new(chain_id) {
 		Ok(contracts_manager) => Arc::new(contracts_manager),
@@ -206,47 +204,14 @@ async fn main() {
 		},
 	};
 
-	let mut datadir = match expanduser::expanduser(cli.datadir.to_string_lossy()) {
+	let datadir = match expanduser::expanduser(cli.datadir.to_string_lossy()) {
 		Ok(p) => p,
 		Err(e) => {
 			tracing::error!("Error expanding data directory: {}", e);
 			process::exit(1);
 		},
 	};
-	datadir.push(format!("node_{}", account.address().pex()));
-	datadir.push(format!("netid_{}", chain_id.to_string()));
-	datadir.push(format!("network_{}/", default_addresses.token_network_registry.pex()));
-
-	if let Err(e) = setup_data_directory(datadir.clone()) {
-		tracing::error!("Error initializing data directory: {}", e);
-		process::exit(1);
-	};
-
-	let storage = match init_storage(datadir.clone(), cli.log_config.clone()) {
-		Ok(storage) => storage,
-		Err(e) => {
-			tracing::error!("Error creating contracts manager: {}", e);
-			process::exit(1);
-		},
-	};
-
-	let (state_manager, sync_start_block_number) = match init_state_manager(
-		contracts_manager.clone(),
-		default_addresses.clone(),
-		storage,
-		chain_id,
-		account.clone(),
-	) {
-		Ok(result) => result,
-		Err(e) => {
-			tracing::error!("Error initializing state: {:?}", e);
-			process::exit(1);
-		},
-	};
 
-	// #
-	// # Initialize PFS
-	// #
 	let mediation_config = MediationFeeConfig {
 		token_to_flat_fee: cli
 			.mediation_fees
@@ -278,57 +243,48 @@ async fn main() {
 	};
 
 	// #
-	// # Initialize transport
+	// # Initialize PFS
 	// #
-	let services_registry_proxy =
-		match proxy_manager.service_registry(default_addresses.service_registry).await {
-			Ok(proxy) => proxy,
+	let services_config: ServicesConfig = cli.services_config.clone().into();
+	let pfs_info = if services_config.routing_mode == RoutingMode::PFS {
+		match init_pfs_info(
+			default_addresses.clone(),
+			proxy_manager.clone(),
+			services_config.clone(),
+		)
+		.await
+		{
+			Ok(info) => Some(info),
 			Err(e) => {
-				tracing::error!("Could not instantiate services registry: {:?}", e);
+				tracing::error!("{}", e);
 				process::exit(1);
 			},
-		};
-	let (transport_service, transport_sender, our_metadata) = match init_transport(
-		cli.environment_type.into(),
-		cli.matrix_transport_config.into(),
-		account.clone(),
-		datadir,
-		services_registry_proxy,
-	)
-	.await
-	{
-		Ok(result) => result,
-		Err(e) => {
-			tracing::error!("{}", e);
-			process::exit(1);
-		},
+		}
+	} else {
+		None
 	};
 
-	let services_config: ServicesConfig = cli.services_config.clone().into();
-	let pfs_info = match init_pfs_info(
-		default_addresses.clone(),
-		proxy_manager.clone(),
-		services_config.clone(),
-	)
-	.await
-	{
-		Ok(info) => info,
+	let gas_strategies = match GasStrategies::try_from(cli.gas_config.clone()) {
+		Ok(gas_strategies) => gas_strategies,
 		Err(e) => {
-			tracing::error!("{}", e);
+			tracing::error!("Invalid gas configuration: {}", e);
 			process::exit(1);
 		},
 	};
 
 	// #
-	// # Initialize Raiden
+	// # Initialize Raiden accounts
 	// #
-	//
-	let config = RaidenConfig {
+	let accounts_config = AccountsConfig {
 		chain_id,
-		mediation_config,
-		account: account.clone(),
-		metadata: our_metadata,
+		datadir,
+		database_url: cli.database_url.clone(),
+		log_config: cli.log_config.clone(),
+		environment_type: cli.environment_type.into(),
+		transport_config: cli.matrix_transport_config.into(),
+		default_addresses: default_addresses.clone(),
 		monitoring_enabled: services_config.monitoring_enabled,
+		routing_mode: services_config.routing_mode,
 		pfs_config: PFSConfig {
 			url: cli.services_config.pathfinding_service_address.clone(),
 			info: pfs_info,
@@ -336,43 +292,35 @@ async fn main() {
 			iou_timeout: services_config.pathfinding_iou_timeout,
 			max_paths: services_config.pathfinding_max_paths,
 		},
-		addresses: default_addresses.clone(),
+		mediation_config,
 		default_settle_timeout: cli.default_settle_timeout.into(),
 		default_reveal_timeout: cli.default_reveal_timeout.into(),
+		gas_strategies,
+		transaction_tracker: TransactionTrackerConfig {
+			replacement_blocks: cli.transaction_replacement_blocks,
+			fee_bump: cli.transaction_fee_bump.max(10),
+			poll_interval: Duration::from_secs(cli.transaction_poll_interval.max(1)),
+		},
 	};
-	let pfs = PFS::new(chain_id, config.pfs_config.clone(), account.private_key());
-	let raiden = Arc::new(Raiden {
-		web3,
-		config: config.clone(),
-		contracts_manager,
-		proxy_manager: proxy_manager.clone(),
-		state_manager: state_manager.clone(),
-		transport: transport_sender.clone(),
-		pfs: Arc::new(pfs),
-	});
+	if accounts_config.transport_config.mode != TransportMode::Matrix && signers.len() > 1 {
+		tracing::error!("The TCP transport can only serve a single account");
+		process::exit(1);
+	}
+	let accounts_manager = Arc::new(AccountsManager::new(contracts_manager, proxy_manager));
+	let local_router = LocalRouter::new(accounts_config.transport_config.clone());
 
-	let payments_registry = Arc::new(RwLock::new(PaymentsRegistry::new()));
-	let event_handler = EventHandler::new(
-		raiden.web3.clone(),
-		account.clone(),
-		state_manager.clone(),
-		proxy_manager.clone(),
-		transport_sender.clone(),
-		default_addresses.clone(),
-		payments_registry.clone(),
-	);
-	let transitioner = Arc::new(Transitioner::new(
-		state_manager.clone(),
-		event_handler.clone(),
-		raiden.config.monitoring_enabled,
-	));
-	let message_handler = MessageHandler::new(
-		account.private_key(),
-		cli.services_config.pathfinding_service_address,
-		transport_sender.clone(),
-		state_manager.clone(),
-		transitioner.clone(),
-	);
+	let mut accounts = vec![];
+	for signer in signers {
+		match init_account(&accounts_config, web3.clone(), signer, &accounts_manager, &local_router)
+			.await
+		{
+			Ok(account) => accounts.push(account),
+			Err(e) => {
+				tracing::error!("Error initializing account: {}", e);
+				process::exit(1);
+			},
+		}
+	}
 
 	let ws = match WebSocket::new(&eth_rpc_socket_endpoint).await {
 		Ok(ws) => ws,
@@ -382,22 +330,93 @@ async fn main() {
 		},
 	};
 
-	let mut sync_service = SyncService::new(raiden.clone(), transitioner.clone());
-	let latest_block_number = raiden.web3.eth().block_number().await.unwrap();
-
-	info!("Performing initial sync from {} to {}", sync_start_block_number, latest_block_number);
-	sync_service.sync(sync_start_block_number, latest_block_number.into()).await;
-	init_channel_fees(
-		state_manager,
-		event_handler,
-		default_addresses.token_network_registry,
-		config.mediation_config.clone(),
-	)
-	.await;
+	let latest_block_number = web3.eth().block_number().await.unwrap();
+	let confirmed_block_number = latest_block_number.saturating_sub(cli.confirmation_blocks.into());
 
-	let block_monitor_service =
-		BlockMonitorService::new(raiden.clone(), ws, transitioner.clone(), sync_service);
-	let api = Api::new(raiden.clone(), transitioner.clone(), payments_registry);
+	let mut block_monitor_service: Option<BlockMonitorService> = None;
+	let mut transport_services = vec![];
+	let mut notification_services = vec![];
+	let mut compaction_services = vec![];
+	let mut connection_manager_services = vec![];
+	let mut rebalance_services = vec![];
+	let mut transaction_monitor_services = vec![];
+	let retention_policy = RetentionPolicy { snapshots_to_keep: cli.snapshots_to_keep };
+	let rebalance_config = RebalanceConfig {
+		interval: Duration::from_secs(cli.rebalance_interval),
+		threshold: cli.rebalance_threshold,
+		max_fee: cli.rebalance_max_fee,
+	};
+	for account_services in accounts.iter_mut() {
+		info!(
+			"Performing initial sync of {} from {} to {}",
+			account_services.account.address().checksum(),
+			account_services.sync_start_block_number,
+			confirmed_block_number
+		);
+		account_services
+			.sync_service
+			.sync(account_services.sync_start_block_number, confirmed_block_number.into())
+			.await;
+		init_channel_fees(
+			account_services.account.raiden.state_manager.clone(),
+			account_services.event_handler.clone(),
+			default_addresses.token_network_registry,
+			accounts_config.mediation_config.clone(),
+		)
+		.await;
+	}
+	let primary_account = accounts[0].account.clone();
+	for account_services in accounts {
+		let AccountServices {
+			account, transport_services: account_transports, sync_service, ..
+		} = account_services;
+		match block_monitor_service {
+			Some(ref mut block_monitor_service) => block_monitor_service.add_account(
+				account.raiden.clone(),
+				account.transition_service.clone(),
+				sync_service,
+			),
+			None =>
+				block_monitor_service = Some(BlockMonitorService::new(
+					account.raiden.clone(),
+					ws.clone(),
+					account.transition_service.clone(),
+					sync_service,
+					cli.confirmation_blocks,
+				)),
+		}
+		for (transport_service, message_handler) in account_transports {
+			transport_services.push(transport_service.run(message_handler));
+		}
+		notification_services.push(NotificationService::new(account.raiden.clone()).start());
+		compaction_services.push(
+			CompactionService::new(
+				account.raiden.clone(),
+				Duration::from_secs(cli.compaction_interval),
+				retention_policy.clone(),
+			)
+			.start(),
+		);
+		connection_manager_services.push(
+			ConnectionManagerService::new(
+				account.raiden.clone(),
+				account.api.clone(),
+				Duration::from_secs(cli.connection_manager_interval),
+			)
+			.start(),
+		);
+		rebalance_services.push(
+			RebalanceService::new(
+				account.raiden.clone(),
+				account.api.clone(),
+				rebalance_config.clone(),
+			)
+			.start(),
+		);
+		transaction_monitor_services
+			.push(TransactionMonitorService::new(account.raiden.clone()).start());
+	}
+	let block_monitor_service = block_monitor_service.expect("At least one account is initialized");
 
 	let socket: SocketAddr = match cli.api_address.parse() {
 		Ok(socket) => socket,
@@ -406,8 +425,39 @@ async fn main() {
 			process::exit(1);
 		},
 	};
+	let api_auth = match init_api_auth(cli.api_token_file, cli.api_read_only_token_file) {
+		Ok(api_auth) => api_auth,
+		Err(e) => {
+			tracing::error!("Error reading API tokens: {}", e);
+			process::exit(1);
+		},
+	};
+	let api_tls = match init_api_tls(cli.api_tls_identity, cli.api_tls_identity_password_file) {
+		Ok(api_tls) => api_tls,
+		Err(e) => {
+			tracing::error!("Error reading TLS configuration: {}", e);
+			process::exit(1);
+		},
+	};
+	if !api_auth.enabled() && !socket.ip().is_loopback() {
+		tracing::warn!("The HTTP API is exposed on {} without authentication", socket);
+	}
 	let (stop_sender, mut stop_receiver) = mpsc::channel(1);
-	let http_service = crate::http::HttpServer::new(socket, raiden, Arc::new(api), stop_sender);
+	let http_service = match crate::http::HttpServer::new(
+		socket,
+		primary_account.raiden.clone(),
+		primary_account.api.clone(),
+		accounts_manager,
+		stop_sender,
+		api_auth,
+		api_tls,
+	) {
+		Ok(http_service) => http_service,
+		Err(e) => {
+			tracing::error!("Error starting HTTP server: {}", e);
+			process::exit(1);
+		},
+	};
 
 	info!("Raiden is starting");
 
@@ -420,7 +470,12 @@ async fn main() {
 	};
 	select! {
 		_ = block_monitor_service.start().fuse() => {},
-		_ = transport_service.run(message_handler).fuse() => {},
+		_ = join_all(transport_services).fuse() => {},
+		_ = join_all(notification_services).fuse() => {},
+		_ = join_all(compaction_services).fuse() => {},
+		_ = join_all(connection_manager_services).fuse() => {},
+		_ = join_all(rebalance_services).fuse() => {},
+		_ = join_all(transaction_monitor_services).fuse() => {},
 		_ = http_service.start().fuse() => {},
 		_ = stop_receiver.recv().fuse() => {
 			println!("Raiden is stopping");
@@ -431,7 +486,7 @@ async fn main() {
 			println!("Raiden is stopping");
 			#[allow(clippy::needless_return)]
 			return
-		},
+		}
 	};
 }
 
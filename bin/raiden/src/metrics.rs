@@ -0,0 +1,23 @@
+use raiden_network_transport::metrics::QueueMetrics;
+use raiden_primitives::types::{
+	MessageIdentifier,
+	QueueIdentifier,
+};
+
+/// Exports the metrics of the transport queues to Prometheus.
+pub struct TransportQueueMetrics;
+
+impl QueueMetrics for TransportQueueMetrics {
+	fn queue_depth(&self, queue_identifier: &QueueIdentifier, depth: usize) {
+		raiden_metrics::set_queue_depth(&queue_identifier.to_string(), depth);
+	}
+
+	fn message_retried(
+		&self,
+		_queue_identifier: &QueueIdentifier,
+		_message_identifier: MessageIdentifier,
+		_retries: u32,
+	) {
+		raiden_metrics::message_retried();
+	}
+}
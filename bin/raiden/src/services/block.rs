@@ -1,25 +1,124 @@
-use std::sync::Arc;
+use std::{
+	collections::VecDeque,
+	sync::Arc,
+};
 
 use futures::StreamExt;
 use raiden_api::raiden::Raiden;
+use raiden_primitives::types::{
+	BlockHash,
+	BlockNumber,
+	GasLimit,
+};
 use raiden_state_machine::types::Block;
 use raiden_transition::Transitioner;
-use tracing::error;
+use tracing::{
+	debug,
+	error,
+	warn,
+};
 use web3::{
 	transports::WebSocket,
+	types::BlockId,
 	Web3,
 };
 
 use super::SyncService;
 
-/// Sync with an Ethereum node on latest blocks and dispatch block state changes.
-pub struct BlockMonitorService {
+/// Information about a block which has been seen but not yet confirmed.
+#[derive(Clone, Debug)]
+pub(crate) struct UnconfirmedBlock {
+	pub(crate) number: BlockNumber,
+	pub(crate) hash: BlockHash,
+	pub(crate) parent_hash: BlockHash,
+	pub(crate) gas_limit: GasLimit,
+}
+
+/// The window of blocks which are not `confirmation_blocks` deep yet, along with the latest
+/// confirmed block.
+pub(crate) struct UnconfirmedBlocks {
+	confirmation_blocks: u64,
+	blocks: VecDeque<UnconfirmedBlock>,
+}
+
+impl UnconfirmedBlocks {
+	/// Create an instance of `UnconfirmedBlocks`.
+	pub(crate) fn new(confirmation_blocks: u64) -> Self {
+		Self { confirmation_blocks, blocks: VecDeque::new() }
+	}
+
+	/// Add new blocks, ordered from the newest to the oldest, to the window.
+	///
+	/// Blocks of the window which are not ancestors of the new blocks have been orphaned by a
+	/// reorg and are dropped. Returns the hash of the parent of the oldest new block if it does
+	/// not connect to the window yet, in which case nothing is added.
+	pub(crate) fn add(&mut self, new_blocks: &[UnconfirmedBlock]) -> Option<BlockHash> {
+		let parent_hash = new_blocks.last()?.parent_hash;
+		if !self.blocks.is_empty() {
+			if let Some(position) = self.blocks.iter().position(|b| b.hash == parent_hash) {
+				let orphaned = self.blocks.split_off(position + 1);
+				if !orphaned.is_empty() {
+					warn!(
+						message = "Chain reorganization detected",
+						depth = orphaned.len(),
+						from_block = orphaned.front().map(|b| b.number.to_string()),
+						to_block = orphaned.back().map(|b| b.number.to_string()),
+					);
+				}
+			} else if new_blocks.len() as u64 > self.confirmation_blocks {
+				// The window is already filled by the new blocks, older blocks are irrelevant.
+				self.blocks.clear();
+			} else {
+				return Some(parent_hash)
+			}
+		}
+
+		self.blocks.extend(new_blocks.iter().rev().cloned());
+
+		// Keep the confirmed block in the window so that it can be dispatched.
+		while self.blocks.len() as u64 > self.confirmation_blocks + 1 {
+			self.blocks.pop_front();
+		}
+		None
+	}
+
+	/// Returns the number of the oldest block of the window.
+	pub(crate) fn oldest_number(&self) -> BlockNumber {
+		self.blocks.front().map(|b| b.number).unwrap_or_default()
+	}
+
+	/// Forget all blocks, the next added blocks start a new window.
+	pub(crate) fn clear(&mut self) {
+		self.blocks.clear();
+	}
+
+	/// Returns the latest block which has at least `confirmation_blocks` confirmations.
+	pub(crate) fn confirmed(&self) -> Option<UnconfirmedBlock> {
+		let latest = self.blocks.back()?;
+		let confirmed_number: BlockNumber =
+			latest.number.as_u64().checked_sub(self.confirmation_blocks)?.into();
+		self.blocks.iter().find(|b| b.number == confirmed_number).cloned()
+	}
+}
+
+/// An account whose state machine is fed with confirmed blocks.
+struct MonitoredAccount {
 	raiden: Arc<Raiden>,
-	web3: Web3<WebSocket>,
 	transition_service: Arc<Transitioner>,
 	sync_service: SyncService,
 }
 
+/// Sync with an Ethereum node on latest blocks and dispatch block state changes.
+///
+/// Blocks are only dispatched to the state machine once they are `confirmation_blocks` deep.
+/// Headers within the confirmation window are kept to detect chain reorganizations through the
+/// ancestry of newly received headers. A single block subscription serves all local accounts.
+pub struct BlockMonitorService {
+	web3: Web3<WebSocket>,
+	accounts: Vec<MonitoredAccount>,
+	unconfirmed_blocks: UnconfirmedBlocks,
+}
+
 impl BlockMonitorService {
 	/// Create an instance of `BlockMonitoringService'.
 	pub fn new(
@@ -27,10 +126,26 @@ impl BlockMonitorService {
 		socket: WebSocket,
 		transition_service: Arc<Transitioner>,
 		sync_service: SyncService,
+		confirmation_blocks: u64,
 	) -> Self {
 		let web3 = web3::Web3::new(socket);
 
-		Self { raiden, web3, transition_service, sync_service }
+		Self {
+			web3,
+			accounts: vec![MonitoredAccount { raiden, transition_service, sync_service }],
+			unconfirmed_blocks: UnconfirmedBlocks::new(confirmation_blocks),
+		}
+	}
+
+	/// Dispatch confirmed blocks to another local account.
+	pub fn add_account(
+		&mut self,
+		raiden: Arc<Raiden>,
+		transition_service: Arc<Transitioner>,
+		sync_service: SyncService,
+	) {
+		self.accounts
+			.push(MonitoredAccount { raiden, transition_service, sync_service });
 	}
 
 	/// Start the service.
@@ -52,20 +167,80 @@ impl BlockMonitorService {
 					Some(hash) => hash,
 					None => continue,
 				};
-				let current_block_number =
-					self.raiden.state_manager.read().current_state.block_number;
-				let block_state_change = Block {
-					block_number: block_number.into(),
-					block_hash,
+				self.track_block(UnconfirmedBlock {
+					number: block_number.into(),
+					hash: block_hash,
+					parent_hash: header.parent_hash,
 					gas_limit: header.gas_limit,
+				})
+				.await;
+
+				let confirmed_block = match self.unconfirmed_blocks.confirmed() {
+					Some(block) => block,
+					None => continue,
 				};
-				if let Err(e) =
-					self.transition_service.transition(vec![block_state_change.into()]).await
-				{
-					error!("{}", e);
+				for account in self.accounts.iter_mut() {
+					let current_block_number =
+						account.raiden.state_manager.read().current_state.block_number;
+					if confirmed_block.number <= current_block_number {
+						continue
+					}
+					let block_state_change = Block {
+						block_number: confirmed_block.number,
+						block_hash: confirmed_block.hash,
+						gas_limit: confirmed_block.gas_limit,
+					};
+					if let Err(e) =
+						account.transition_service.transition(vec![block_state_change.into()]).await
+					{
+						error!("{}", e);
+					}
+					account.sync_service.sync(current_block_number, confirmed_block.number).await;
 				}
-				self.sync_service.sync(current_block_number, block_number.into()).await;
 			}
 		}
 	}
+
+	/// Add a new block to the unconfirmed window.
+	///
+	/// The ancestry of the block is fetched until it connects to a known block.
+	async fn track_block(&mut self, block: UnconfirmedBlock) {
+		let mut new_blocks = vec![block];
+		while let Some(parent_hash) = self.unconfirmed_blocks.add(&new_blocks) {
+			let parent = match self.web3.eth().block(BlockId::Hash(parent_hash)).await {
+				Ok(Some(parent)) => parent,
+				Ok(None) | Err(_) => {
+					error!(
+						message = "Could not fetch parent block, resetting unconfirmed blocks",
+						parent_hash = parent_hash.to_string(),
+					);
+					self.unconfirmed_blocks.clear();
+					continue
+				},
+			};
+			let (number, hash): (BlockNumber, BlockHash) = match (parent.number, parent.hash) {
+				(Some(number), Some(hash)) => (number.into(), hash),
+				_ => {
+					self.unconfirmed_blocks.clear();
+					continue
+				},
+			};
+			if number < self.unconfirmed_blocks.oldest_number() {
+				error!(
+					message = "Chain reorganization deeper than the confirmation window",
+					block_number = number.to_string(),
+				);
+				self.unconfirmed_blocks.clear();
+				continue
+			}
+
+			debug!(message = "Fetched ancestor block", block_number = number.to_string());
+			new_blocks.push(UnconfirmedBlock {
+				number,
+				hash,
+				parent_hash: parent.parent_hash,
+				gas_limit: parent.gas_limit,
+			});
+		}
+	}
 }
@@ -0,0 +1,67 @@
+use std::{
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::future;
+use raiden_api::raiden::Raiden;
+use raiden_state_machine::{
+	storage::types::RetentionPolicy,
+	views,
+};
+use tracing::{
+	debug,
+	error,
+};
+
+/// Periodically delete the history which is no longer needed from storage.
+///
+/// Balance proofs of the channels known to the current chain state are always kept, along with
+/// the history needed to restore the channels for on-chain unlocks.
+pub struct CompactionService {
+	raiden: Arc<Raiden>,
+	interval: Duration,
+	policy: RetentionPolicy,
+}
+
+impl CompactionService {
+	/// Create an instance of `CompactionService`.
+	pub fn new(raiden: Arc<Raiden>, interval: Duration, policy: RetentionPolicy) -> Self {
+		Self { raiden, interval, policy }
+	}
+
+	/// Start the service, a zero interval disables compaction.
+	pub async fn start(self) {
+		if self.interval.is_zero() {
+			return future::pending().await
+		}
+
+		let mut interval = tokio::time::interval(self.interval);
+		loop {
+			interval.tick().await;
+			self.compact();
+		}
+	}
+
+	/// Compact the storage according to the retention policy.
+	fn compact(&self) {
+		let (storage, channels) = {
+			let state_manager = self.raiden.state_manager.read();
+			let channels = views::get_channels(&state_manager.current_state)
+				.into_iter()
+				.map(|channel| channel.canonical_identifier)
+				.collect();
+			(state_manager.storage.clone(), channels)
+		};
+
+		match storage.compact(&self.policy, channels) {
+			Ok(stats) => debug!(
+				message = "Compacted storage",
+				snapshots = stats.snapshots,
+				state_changes = stats.state_changes,
+				events = stats.events,
+			),
+			Err(e) => error!("Could not compact storage: {}", e),
+		}
+	}
+}
@@ -0,0 +1,228 @@
+use std::{
+	collections::HashSet,
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::future;
+use raiden_api::{
+	api::Api,
+	raiden::Raiden,
+};
+use raiden_pathfinding::routing;
+use raiden_primitives::{
+	traits::Checksum,
+	types::{
+		Address,
+		TokenAddress,
+		TokenAmount,
+	},
+};
+use raiden_state_machine::{
+	types::{
+		ChannelState,
+		ChannelStatus,
+		ConnectionManagerState,
+	},
+	views,
+};
+use tracing::{
+	debug,
+	info,
+	warn,
+};
+
+/// Keep the channels of the token networks joined with `Api::token_network_connect` open and
+/// funded.
+///
+/// Channels opened by partners are joined with a deposit, and new channels are opened until
+/// the channel target of the connection manager is reached, as long as its funds last.
+pub struct ConnectionManagerService {
+	raiden: Arc<Raiden>,
+	api: Arc<Api>,
+	interval: Duration,
+	/// Partners a channel could not be opened with, they are not retried.
+	failed_partners: HashSet<Address>,
+}
+
+impl ConnectionManagerService {
+	/// Create an instance of `ConnectionManagerService`.
+	pub fn new(raiden: Arc<Raiden>, api: Arc<Api>, interval: Duration) -> Self {
+		Self { raiden, api, interval, failed_partners: HashSet::new() }
+	}
+
+	/// Start the service, a zero interval disables the connection managers.
+	pub async fn start(mut self) {
+		if self.interval.is_zero() {
+			return future::pending().await
+		}
+
+		let mut interval = tokio::time::interval(self.interval);
+		loop {
+			interval.tick().await;
+			let connection_managers = self.api.connection_managers();
+			for connection_manager in connection_managers {
+				self.maintain(connection_manager).await;
+			}
+		}
+	}
+
+	/// Join the channels opened by partners and open new channels up to the channel target.
+	async fn maintain(&mut self, connection_manager: ConnectionManagerState) {
+		let (registry_address, token_address, channels) = {
+			let chain_state = &self.raiden.state_manager.read().current_state;
+			let token_network = match views::get_token_network_by_address(
+				chain_state,
+				connection_manager.token_network_address,
+			) {
+				Some(token_network) => token_network,
+				None => return,
+			};
+			let registry_address = match views::get_token_network_registry_by_token_network_address(
+				chain_state,
+				token_network.address,
+			) {
+				Some(registry) => registry.address,
+				None => return,
+			};
+			let channels: Vec<ChannelState> =
+				token_network.channelidentifiers_to_channels.values().cloned().collect();
+			(registry_address, token_network.token_address, channels)
+		};
+
+		let open_channels: Vec<&ChannelState> = channels
+			.iter()
+			.filter(|channel| channel.status() == ChannelStatus::Opened)
+			.collect();
+		let funds_used = open_channels.iter().fold(TokenAmount::zero(), |used, channel| {
+			used.saturating_add(channel.our_state.contract_balance)
+		});
+		let mut funds_remaining = connection_manager.funds.saturating_sub(funds_used);
+		let funding_per_partner = connection_manager.initial_funding_per_partner();
+
+		// Channels opened by partners are joined with at most the deposit of the partner.
+		for channel in open_channels.iter() {
+			if !channel.our_state.contract_balance.is_zero() ||
+				channel.partner_state.contract_balance.is_zero()
+			{
+				continue
+			}
+			let deposit = channel
+				.partner_state
+				.contract_balance
+				.min(funding_per_partner)
+				.min(funds_remaining);
+			if deposit.is_zero() {
+				break
+			}
+			match self
+				.api
+				.channel_deposit(self.raiden.config.account.clone(), channel, deposit, None)
+				.await
+			{
+				Ok(_) => funds_remaining -= deposit,
+				Err(e) => warn!(
+					message = "Could not join channel",
+					partner = channel.partner_state.address.checksum(),
+					error = e.to_string(),
+				),
+			}
+		}
+
+		let channel_target = usize::from(connection_manager.initial_channel_target);
+		if open_channels.len() >= channel_target {
+			return
+		}
+
+		let candidates = match routing::get_partner_candidates(
+			self.raiden.pfs.clone(),
+			self.raiden.network_graph.clone(),
+			connection_manager.token_network_address,
+			self.raiden.config.account.address(),
+		)
+		.await
+		{
+			Ok(candidates) => candidates,
+			Err(e) => {
+				warn!(message = "Could not find partners for new channels", error = e.to_string());
+				return
+			},
+		};
+
+		// A partner may still have a channel which is being closed or settled.
+		let known_partners: HashSet<Address> =
+			channels.iter().map(|channel| channel.partner_state.address).collect();
+		let candidates: Vec<Address> = candidates
+			.into_iter()
+			.filter(|partner| {
+				!known_partners.contains(partner) && !self.failed_partners.contains(partner)
+			})
+			.take(channel_target - open_channels.len())
+			.collect();
+		if candidates.is_empty() {
+			debug!("No new partners available for the connection manager");
+		}
+
+		for partner_address in candidates {
+			let deposit = funding_per_partner.min(funds_remaining);
+			if deposit.is_zero() {
+				break
+			}
+			match self
+				.open_channel(registry_address, token_address, partner_address, deposit)
+				.await
+			{
+				Ok(()) => funds_remaining -= deposit,
+				Err(e) => {
+					warn!(
+						message = "Could not open channel",
+						partner = partner_address.checksum(),
+						error = e,
+					);
+					self.failed_partners.insert(partner_address);
+				},
+			}
+		}
+	}
+
+	/// Open a channel with `partner_address` and deposit `deposit` into it.
+	async fn open_channel(
+		&self,
+		registry_address: Address,
+		token_address: TokenAddress,
+		partner_address: Address,
+		deposit: TokenAmount,
+	) -> Result<(), String> {
+		info!(
+			message = "Connection manager opens channel",
+			partner = partner_address.checksum(),
+			deposit = deposit.to_string(),
+		);
+		let account = self.raiden.config.account.clone();
+		self.api
+			.create_channel(
+				account.clone(),
+				registry_address,
+				token_address,
+				partner_address,
+				None,
+				None,
+				None,
+			)
+			.await
+			.map_err(|e| e.to_string())?;
+
+		let channel_state = views::get_channel_state_for(
+			&self.raiden.state_manager.read().current_state,
+			registry_address,
+			token_address,
+			partner_address,
+		)
+		.cloned()
+		.ok_or_else(|| "Channel was not found".to_owned())?;
+		self.api
+			.channel_deposit(account, &channel_state, deposit, None)
+			.await
+			.map_err(|e| e.to_string())
+	}
+}
@@ -1,7 +1,17 @@
 mod block;
+mod compaction;
+mod connection_manager;
+mod notifications;
+mod rebalance;
 mod sync;
+mod transactions;
 
 pub use self::{
 	block::*,
+	compaction::*,
+	connection_manager::*,
+	notifications::*,
+	rebalance::*,
 	sync::*,
+	transactions::*,
 };
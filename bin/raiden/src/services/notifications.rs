@@ -0,0 +1,239 @@
+use std::{
+	sync::Arc,
+	time::Duration,
+};
+
+use raiden_api::raiden::Raiden;
+use raiden_blockchain::proxies::GasReserve;
+use raiden_primitives::{
+	constants::MONITORING_REWARD,
+	traits::Checksum,
+	types::TokenAmount,
+};
+use raiden_state_machine::{
+	storage::types::NotificationUrgency,
+	types::{
+		ChainState,
+		ChannelStatus,
+	},
+	views,
+};
+use tracing::{
+	debug,
+	error,
+};
+
+/// Interval between two checks of the node's conditions.
+const NOTIFICATIONS_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const LOW_UDC_BALANCE: &str = "low_udc_balance";
+const MISSING_GAS_RESERVE: &str = "missing_gas_reserve";
+const PFS_UNREACHABLE: &str = "pfs_unreachable";
+const CHANNEL_SETTLE_WINDOW_ENDING: &str = "channel_settle_window_ending";
+
+/// A notification derived from the current conditions of the node.
+struct Notification {
+	identifier: String,
+	summary: String,
+	body: String,
+	urgency: NotificationUrgency,
+}
+
+/// Periodically derive notifications about conditions which need the user's attention and
+/// persist them into storage.
+///
+/// Notifications whose condition no longer applies are removed from storage.
+pub struct NotificationService {
+	raiden: Arc<Raiden>,
+}
+
+impl NotificationService {
+	/// Create an instance of `NotificationService`.
+	pub fn new(raiden: Arc<Raiden>) -> Self {
+		Self { raiden }
+	}
+
+	/// Start the service.
+	pub async fn start(self) {
+		let mut interval = tokio::time::interval(NOTIFICATIONS_CHECK_INTERVAL);
+		loop {
+			interval.tick().await;
+			self.check().await;
+		}
+	}
+
+	/// Derive the active notifications, store them and remove the stale ones.
+	async fn check(&self) {
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+
+		let mut notifications = vec![];
+		notifications.extend(self.check_udc_balance(&chain_state).await);
+		notifications.extend(self.check_gas_reserve(&chain_state).await);
+		notifications.extend(self.check_pfs().await);
+		notifications.extend(self.check_channels(&chain_state));
+
+		let storage = self.raiden.state_manager.read().storage.clone();
+		let identifiers: Vec<String> = notifications.iter().map(|n| n.identifier.clone()).collect();
+
+		for notification in notifications {
+			debug!(message = "Storing notification", identifier = notification.identifier);
+			if let Err(e) = storage.store_notification(
+				notification.identifier,
+				notification.summary,
+				notification.body,
+				notification.urgency,
+			) {
+				error!("Could not store notification: {:?}", e);
+			}
+		}
+
+		let stored_notifications = match storage.get_notifications(true) {
+			Ok(notifications) => notifications,
+			Err(e) => {
+				error!("Could not fetch notifications: {:?}", e);
+				return
+			},
+		};
+		for notification in stored_notifications {
+			if identifiers.contains(&notification.identifier) {
+				continue
+			}
+			if let Err(e) = storage.delete_notification(notification.identifier) {
+				error!("Could not delete notification: {:?}", e);
+			}
+		}
+	}
+
+	/// Check that the UDC balance covers the cost of the enabled services.
+	async fn check_udc_balance(&self, chain_state: &ChainState) -> Option<Notification> {
+		let config = &self.raiden.config;
+		let user_deposit =
+			match self.raiden.proxy_manager.user_deposit(config.addresses.user_deposit).await {
+				Ok(user_deposit) => user_deposit,
+				Err(e) => {
+					error!("Could not create user deposit proxy: {:?}", e);
+					return None
+				},
+			};
+		let effective_balance = match user_deposit
+			.effective_balance(config.account.address(), Some(chain_state.block_hash))
+			.await
+		{
+			Ok(balance) => balance,
+			Err(e) => {
+				error!("Could not fetch UDC effective balance: {:?}", e);
+				return None
+			},
+		};
+
+		let mut required_balance = TokenAmount::zero();
+		if let Some(info) = &config.pfs_config.info {
+			required_balance += info.price;
+		}
+		if config.monitoring_enabled {
+			required_balance += *MONITORING_REWARD;
+		}
+		if effective_balance >= required_balance {
+			return None
+		}
+
+		Some(Notification {
+			identifier: LOW_UDC_BALANCE.to_owned(),
+			summary: "UDC balance too low".to_owned(),
+			body: format!(
+				"The effective balance of {} in the user deposit contract is below the {} \
+				required to pay for the enabled services.",
+				effective_balance, required_balance,
+			),
+			urgency: NotificationUrgency::Normal,
+		})
+	}
+
+	/// Check that the account holds enough ETH to finish the lifecycle of all channels.
+	async fn check_gas_reserve(&self, chain_state: &ChainState) -> Option<Notification> {
+		let gas_reserve = GasReserve::new(
+			self.raiden.proxy_manager.clone(),
+			self.raiden.config.addresses.token_network_registry,
+		);
+		let (has_enough_reserve, estimated_required_reserve) = match gas_reserve
+			.has_enough(self.raiden.config.account.clone(), chain_state, 0)
+			.await
+		{
+			Ok(result) => result,
+			Err(e) => {
+				error!("Could not estimate gas reserve: {:?}", e);
+				return None
+			},
+		};
+		if has_enough_reserve {
+			return None
+		}
+
+		Some(Notification {
+			identifier: MISSING_GAS_RESERVE.to_owned(),
+			summary: "ETH balance too low".to_owned(),
+			body: format!(
+				"The account balance is below the estimated amount necessary to finish the \
+				lifecycles of all active channels. A balance of at least {} wei is required.",
+				estimated_required_reserve,
+			),
+			urgency: NotificationUrgency::Normal,
+		})
+	}
+
+	/// Check that the configured pathfinding service is reachable.
+	async fn check_pfs(&self) -> Option<Notification> {
+		let pfs_url = &self.raiden.config.pfs_config.url;
+		if pfs_url.is_empty() {
+			return None
+		}
+		let error = self.raiden.pfs.get_pfs_info().await.err()?;
+
+		Some(Notification {
+			identifier: PFS_UNREACHABLE.to_owned(),
+			summary: "Pathfinding service unreachable".to_owned(),
+			body: format!("Could not reach the pathfinding service at {}: {:?}", pfs_url, error),
+			urgency: NotificationUrgency::Normal,
+		})
+	}
+
+	/// Check for closed channels whose settlement window is about to end.
+	fn check_channels(&self, chain_state: &ChainState) -> Vec<Notification> {
+		let mut notifications = vec![];
+		for channel in views::get_channels(chain_state) {
+			if channel.status() != ChannelStatus::Closed {
+				continue
+			}
+			let closed_block_number = match channel
+				.close_transaction
+				.as_ref()
+				.and_then(|transaction| transaction.finished_block_number)
+			{
+				Some(block_number) => block_number,
+				None => continue,
+			};
+			let settle_block_number = closed_block_number + channel.settle_timeout;
+			if chain_state.block_number + channel.reveal_timeout < settle_block_number {
+				continue
+			}
+
+			notifications.push(Notification {
+				identifier: format!(
+					"{}_{}_{}",
+					CHANNEL_SETTLE_WINDOW_ENDING,
+					channel.canonical_identifier.token_network_address.checksum(),
+					channel.canonical_identifier.channel_identifier,
+				),
+				summary: "Channel settlement window ending".to_owned(),
+				body: format!(
+					"The settlement window of channel {} with {} ends at block {}.",
+					channel.canonical_identifier.channel_identifier,
+					channel.partner_state.address.checksum(),
+					settle_block_number,
+				),
+				urgency: NotificationUrgency::Low,
+			});
+		}
+		notifications
+	}
+}
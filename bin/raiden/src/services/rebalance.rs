@@ -0,0 +1,173 @@
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::future;
+use raiden_api::{
+	api::Api,
+	raiden::Raiden,
+};
+use raiden_primitives::{
+	traits::Checksum,
+	types::{
+		TokenAmount,
+		TokenNetworkAddress,
+	},
+};
+use raiden_state_machine::{
+	types::{
+		ChannelState,
+		ChannelStatus,
+	},
+	views,
+};
+use tracing::{
+	debug,
+	info,
+	warn,
+};
+
+/// Configuration of the channel rebalancer.
+#[derive(Clone, Debug)]
+pub struct RebalanceConfig {
+	/// Time between two checks of the channels, zero disables rebalancing.
+	pub interval: Duration,
+	/// Percentage of the capacity of a channel held by one side above which the channel is
+	/// imbalanced, between 51 and 100 so that no channel is both heavy and light.
+	pub threshold: u8,
+	/// Highest mediation fee paid for a single rebalancing payment.
+	pub max_fee: TokenAmount,
+}
+
+/// Periodically move balance from channels where most of the capacity is ours to channels
+/// where most of it belongs to the partner, so the node can keep mediating in both directions.
+///
+/// Balance is moved with a payment to ourselves, leaving through the first channel and coming
+/// back through the second one.
+pub struct RebalanceService {
+	raiden: Arc<Raiden>,
+	api: Arc<Api>,
+	config: RebalanceConfig,
+}
+
+impl RebalanceService {
+	/// Create an instance of `RebalanceService`.
+	pub fn new(raiden: Arc<Raiden>, api: Arc<Api>, config: RebalanceConfig) -> Self {
+		Self { raiden, api, config }
+	}
+
+	/// Start the service, a zero interval disables rebalancing.
+	pub async fn start(self) {
+		if self.config.interval.is_zero() {
+			return future::pending().await
+		}
+		if self.raiden.network_graph.is_none() {
+			warn!("Rebalancing requires the private routing mode, it is disabled");
+			return future::pending().await
+		}
+
+		let mut interval = tokio::time::interval(self.config.interval);
+		loop {
+			interval.tick().await;
+			self.rebalance().await;
+		}
+	}
+
+	/// Rebalance the most imbalanced pair of channels of every token network.
+	async fn rebalance(&self) {
+		let candidates = {
+			let chain_state = &self.raiden.state_manager.read().current_state;
+			let mut token_networks: HashMap<TokenNetworkAddress, Vec<ChannelState>> =
+				HashMap::new();
+			for channel in views::get_channels(chain_state) {
+				if channel.status() == ChannelStatus::Opened {
+					token_networks
+						.entry(channel.canonical_identifier.token_network_address)
+						.or_default()
+						.push(channel);
+				}
+			}
+			token_networks
+				.into_values()
+				.filter_map(|channels| self.pick_channels(channels))
+				.collect::<Vec<_>>()
+		};
+
+		for (heavy, light, amount) in candidates {
+			info!(
+				message = "Rebalancing channels",
+				from = heavy.partner_state.address.checksum(),
+				to = light.partner_state.address.checksum(),
+				amount = amount.to_string(),
+			);
+			// Sent payments end with a payment event, failures to start one are only logged.
+			if let Err(e) = self
+				.api
+				.initiate_circular_payment(
+					heavy.token_network_registry_address,
+					heavy.token_address,
+					heavy.partner_state.address,
+					light.partner_state.address,
+					amount,
+					self.config.max_fee,
+					true,
+				)
+				.await
+			{
+				warn!(
+					message = "Rebalancing failed",
+					from = heavy.partner_state.address.checksum(),
+					to = light.partner_state.address.checksum(),
+					error = e.to_string(),
+				);
+			}
+		}
+	}
+
+	/// Pick the channel with the largest share of ours and the one with the smallest share, if
+	/// both pass the threshold, along with the amount which moves both closer to the middle.
+	fn pick_channels(
+		&self,
+		channels: Vec<ChannelState>,
+	) -> Option<(ChannelState, ChannelState, TokenAmount)> {
+		let hundred = TokenAmount::from(100);
+		let threshold = TokenAmount::from(self.config.threshold);
+		let shares: Vec<(ChannelState, TokenAmount, TokenAmount)> = channels
+			.into_iter()
+			.filter_map(|channel| {
+				let ours = views::channel_balance(&channel.our_state, &channel.partner_state);
+				let total =
+					ours + views::channel_balance(&channel.partner_state, &channel.our_state);
+				if total.is_zero() {
+					return None
+				}
+				Some((channel, ours, total))
+			})
+			.collect();
+
+		let share = |ours: TokenAmount, total: TokenAmount| ours * hundred / total;
+		let (heavy, heavy_ours, heavy_total) = shares
+			.iter()
+			.max_by_key(|(_, ours, total)| share(*ours, *total))
+			.filter(|(_, ours, total)| share(*ours, *total) >= threshold)?;
+		let (light, light_ours, light_total) = shares
+			.iter()
+			.min_by_key(|(_, ours, total)| share(*ours, *total))
+			.filter(|(_, ours, total)| share(*ours, *total) <= hundred - threshold)?;
+		if heavy.canonical_identifier == light.canonical_identifier {
+			return None
+		}
+
+		let excess = heavy_ours.saturating_sub(*heavy_total / 2);
+		let deficit = (*light_total / 2).saturating_sub(*light_ours);
+		let amount = excess.min(deficit);
+		if amount.is_zero() {
+			debug!("Imbalanced channels found, but there is nothing to move");
+			return None
+		}
+
+		Some((heavy.clone(), light.clone(), amount))
+	}
+}
@@ -9,7 +9,10 @@ use raiden_blockchain::{
 	events::Event,
 	filters::filters_from_chain_state,
 };
-use raiden_primitives::types::U64;
+use raiden_primitives::{
+	traits::Checksum,
+	types::U64,
+};
 use raiden_state_machine::types::Block;
 use raiden_transition::Transitioner;
 use tracing::{
@@ -95,9 +98,19 @@ impl SyncService {
 	/// Sync with the blockchain for events between start and end blocks.
 	pub async fn sync(&mut self, start_block_number: U64, end_block_number: U64) {
 		info!("Sync started: {} -> {}", start_block_number, end_block_number);
+		self.update_sync_lag(end_block_number);
 		self.poll_contract_filters(start_block_number, end_block_number).await;
 	}
 
+	/// Report how many blocks the chain state is behind `chain_head`.
+	fn update_sync_lag(&self, chain_head: U64) {
+		let block_number = self.raiden.state_manager.read().current_state.block_number;
+		raiden_metrics::set_sync_lag(
+			&self.raiden.config.account.address().checksum(),
+			chain_head.saturating_sub(block_number).as_u64(),
+		);
+	}
+
 	/// Poll the blockchain, fetch events and convert them into state changes.
 	pub async fn poll_contract_filters(&mut self, start_block_number: U64, end_block_number: U64) {
 		let mut from_block = start_block_number;
@@ -183,6 +196,7 @@ impl SyncService {
 			{
 				error!("{}", e);
 			}
+			self.update_sync_lag(end_block_number);
 
 			from_block = to_block + 1u64.into();
 			self.block_batch_size_adjuster.increase();
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use futures::future;
+use raiden_api::raiden::Raiden;
+
+/// Watch the transactions which were still pending when the node stopped, replacing them with
+/// bumped fees until they are mined.
+///
+/// Transactions sent while the node runs are watched by the call which sent them.
+pub struct TransactionMonitorService {
+	raiden: Arc<Raiden>,
+}
+
+impl TransactionMonitorService {
+	/// Create an instance of `TransactionMonitorService`.
+	pub fn new(raiden: Arc<Raiden>) -> Self {
+		Self { raiden }
+	}
+
+	/// Start the service.
+	pub async fn start(self) {
+		self.raiden.config.account.resume_pending_transactions().await;
+		future::pending().await
+	}
+}
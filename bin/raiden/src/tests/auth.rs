@@ -0,0 +1,101 @@
+use hyper::{
+	header,
+	Body,
+	Method,
+	Request,
+};
+
+use crate::http::{
+	auth::ApiAuth,
+	error::Error,
+};
+
+const FULL_ACCESS_TOKEN: &str = "full-access-token";
+const READ_ONLY_TOKEN: &str = "read-only-token";
+
+fn auth() -> ApiAuth {
+	ApiAuth {
+		full_access_token: Some(FULL_ACCESS_TOKEN.to_owned()),
+		read_only_token: Some(READ_ONLY_TOKEN.to_owned()),
+	}
+}
+
+fn request(method: Method, uri: &str, token: Option<&str>) -> Request<Body> {
+	let mut builder = Request::builder().method(method).uri(uri);
+	if let Some(token) = token {
+		builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+	}
+	builder.body(Body::empty()).expect("Should build request")
+}
+
+#[test]
+fn test_auth_disabled_without_tokens() {
+	let auth = ApiAuth::default();
+	assert!(!auth.enabled());
+	assert!(auth.authorize(&request(Method::POST, "/api/v1/payments", None)).is_ok());
+	assert!(auth.authorize(&request(Method::GET, "/api/v1/shutdown", None)).is_ok());
+}
+
+#[test]
+fn test_auth_rejects_missing_or_invalid_tokens() {
+	let auth = auth();
+	assert!(matches!(
+		auth.authorize(&request(Method::GET, "/api/v1/channels", None)),
+		Err(Error::Unauthorized)
+	));
+	assert!(matches!(
+		auth.authorize(&request(Method::GET, "/api/v1/channels", Some("invalid"))),
+		Err(Error::Unauthorized)
+	));
+
+	// Only bearer tokens are accepted.
+	let mut req = request(Method::GET, "/api/v1/channels", None);
+	req.headers_mut().insert(
+		header::AUTHORIZATION,
+		format!("Basic {}", FULL_ACCESS_TOKEN)
+			.parse()
+			.expect("Should be a valid header"),
+	);
+	assert!(matches!(auth.authorize(&req), Err(Error::Unauthorized)));
+}
+
+#[test]
+fn test_auth_read_only_scope() {
+	let auth = auth();
+	let token = Some(READ_ONLY_TOKEN);
+	assert!(auth.authorize(&request(Method::GET, "/api/v1/channels", token)).is_ok());
+	assert!(auth.authorize(&request(Method::HEAD, "/api/v1/channels", token)).is_ok());
+	for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+		assert!(matches!(
+			auth.authorize(&request(method, "/api/v1/channels", token)),
+			Err(Error::Forbidden)
+		));
+	}
+}
+
+#[test]
+fn test_auth_full_access_scope() {
+	let auth = auth();
+	let token = Some(FULL_ACCESS_TOKEN);
+	assert!(auth.authorize(&request(Method::GET, "/api/v1/channels", token)).is_ok());
+	assert!(auth.authorize(&request(Method::PATCH, "/api/v1/channels", token)).is_ok());
+	assert!(auth.authorize(&request(Method::GET, "/api/v1/shutdown", token)).is_ok());
+}
+
+#[test]
+fn test_auth_shutdown_requires_full_access() {
+	let auth = auth();
+	let token = Some(READ_ONLY_TOKEN);
+	for uri in [
+		"/api/v1/shutdown",
+		"/api/v1/shutdown/",
+		"/api/v1/shutdown?now=1",
+		"/api/v1/shutdown/?now=1",
+	] {
+		assert!(
+			matches!(auth.authorize(&request(Method::GET, uri, token)), Err(Error::Forbidden)),
+			"{} should require full access",
+			uri
+		);
+	}
+}
@@ -0,0 +1,136 @@
+use raiden_primitives::types::{
+	BlockHash,
+	BlockNumber,
+	GasLimit,
+};
+
+use crate::services::{
+	UnconfirmedBlock,
+	UnconfirmedBlocks,
+};
+
+/// A block of the chain identified by `fork`, whose parent is of the same chain.
+fn block(number: u64, fork: u8) -> UnconfirmedBlock {
+	block_with_parent(number, fork, fork)
+}
+
+/// A block of the chain identified by `fork`, whose parent is of the chain `parent_fork`.
+fn block_with_parent(number: u64, fork: u8, parent_fork: u8) -> UnconfirmedBlock {
+	UnconfirmedBlock {
+		number: BlockNumber::from(number),
+		hash: hash(number, fork),
+		parent_hash: hash(number - 1, parent_fork),
+		gas_limit: GasLimit::from(30_000_000u64),
+	}
+}
+
+fn hash(number: u64, fork: u8) -> BlockHash {
+	let mut hash = BlockHash::repeat_byte(fork);
+	hash.as_bytes_mut()[..8].copy_from_slice(&number.to_be_bytes());
+	hash
+}
+
+fn confirmed_hash(blocks: &UnconfirmedBlocks) -> Option<BlockHash> {
+	blocks.confirmed().map(|block| block.hash)
+}
+
+#[test]
+fn test_confirmation_depth() {
+	let mut blocks = UnconfirmedBlocks::new(3);
+
+	for number in 10..13 {
+		assert_eq!(blocks.add(&[block(number, 0)]), None);
+		assert_eq!(confirmed_hash(&blocks), None);
+	}
+	assert_eq!(blocks.add(&[block(13, 0)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(10, 0)));
+	assert_eq!(blocks.add(&[block(14, 0)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(11, 0)));
+
+	// Only the confirmed block is kept beyond the window.
+	assert_eq!(blocks.oldest_number(), BlockNumber::from(11));
+}
+
+#[test]
+fn test_no_confirmations() {
+	let mut blocks = UnconfirmedBlocks::new(0);
+
+	assert_eq!(blocks.add(&[block(10, 0)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(10, 0)));
+	assert_eq!(blocks.add(&[block(11, 0)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(11, 0)));
+	assert_eq!(blocks.oldest_number(), BlockNumber::from(11));
+}
+
+#[test]
+fn test_reorg_drops_orphaned_blocks() {
+	let mut blocks = UnconfirmedBlocks::new(3);
+	for number in 10..15 {
+		blocks.add(&[block(number, 0)]);
+	}
+	assert_eq!(confirmed_hash(&blocks), Some(hash(11, 0)));
+
+	// Blocks 13 and 14 are replaced by another fork which is not deep enough to be confirmed.
+	assert_eq!(blocks.add(&[block_with_parent(13, 1, 0)]), None);
+	assert_eq!(confirmed_hash(&blocks), None);
+
+	// The confirmed blocks follow the new fork.
+	assert_eq!(blocks.add(&[block(14, 1)]), None);
+	assert_eq!(blocks.add(&[block(15, 1)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(12, 0)));
+	assert_eq!(blocks.add(&[block(16, 1)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(13, 1)));
+}
+
+#[test]
+fn test_reorg_with_unknown_ancestors() {
+	let mut blocks = UnconfirmedBlocks::new(3);
+	for number in 10..14 {
+		blocks.add(&[block(number, 0)]);
+	}
+	assert_eq!(confirmed_hash(&blocks), Some(hash(10, 0)));
+
+	// The new head is on another fork branching off block 11, its parents have to be fetched.
+	let mut new_blocks = vec![block(14, 1)];
+	assert_eq!(blocks.add(&new_blocks), Some(hash(13, 1)));
+	new_blocks.push(block(13, 1));
+	assert_eq!(blocks.add(&new_blocks), Some(hash(12, 1)));
+	new_blocks.push(block_with_parent(12, 1, 0));
+
+	// Nothing is added until the ancestry connects.
+	assert_eq!(confirmed_hash(&blocks), Some(hash(10, 0)));
+	assert_eq!(blocks.add(&new_blocks), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(11, 0)));
+	assert_eq!(blocks.add(&[block(15, 1)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(12, 1)));
+}
+
+#[test]
+fn test_gap_larger_than_window_resets_blocks() {
+	let mut blocks = UnconfirmedBlocks::new(2);
+	for number in 10..13 {
+		blocks.add(&[block(number, 0)]);
+	}
+
+	// The fetched ancestry fills the whole window, the older blocks are not needed.
+	let new_blocks = vec![block(20, 0), block(19, 0), block(18, 0)];
+	assert_eq!(blocks.add(&new_blocks), None);
+	assert_eq!(blocks.oldest_number(), BlockNumber::from(18));
+	assert_eq!(confirmed_hash(&blocks), Some(hash(18, 0)));
+}
+
+#[test]
+fn test_clear_starts_new_window() {
+	let mut blocks = UnconfirmedBlocks::new(1);
+	blocks.add(&[block(10, 0)]);
+	blocks.add(&[block(11, 0)]);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(10, 0)));
+
+	// After a reorg deeper than the window, blocks are confirmed again from the new head.
+	blocks.clear();
+	assert_eq!(confirmed_hash(&blocks), None);
+	assert_eq!(blocks.add(&[block(11, 1)]), None);
+	assert_eq!(confirmed_hash(&blocks), None);
+	assert_eq!(blocks.add(&[block(12, 1)]), None);
+	assert_eq!(confirmed_hash(&blocks), Some(hash(11, 1)));
+}
@@ -0,0 +1,40 @@
+use raiden_primitives::types::TokenAmount;
+use structopt::StructOpt;
+
+use crate::cli::Opt;
+
+fn parse(args: &[&str]) -> Result<Opt, structopt::clap::Error> {
+	let required = [
+		"raiden",
+		"--eth-rpc-endpoint",
+		"http://127.0.0.1:8545",
+		"--eth-rpc-socket-endpoint",
+		"ws://127.0.0.1:8546",
+		"--keystore-path",
+		"/tmp/keystore",
+	];
+	Opt::from_iter_safe(required.iter().chain(args))
+}
+
+#[test]
+fn test_rebalance_max_fee_is_decimal() {
+	let opt = parse(&["--rebalance-max-fee", "100"]).expect("Should parse arguments");
+	assert_eq!(opt.rebalance_max_fee, TokenAmount::from(100));
+
+	let opt = parse(&[]).expect("Should parse arguments");
+	assert_eq!(opt.rebalance_max_fee, TokenAmount::zero());
+}
+
+#[test]
+fn test_rebalance_threshold_bounds() {
+	assert_eq!(parse(&[]).expect("Should parse arguments").rebalance_threshold, 80);
+	for threshold in ["51", "100"] {
+		let opt = parse(&["--rebalance-threshold", threshold]).expect("Should parse arguments");
+		assert_eq!(opt.rebalance_threshold.to_string(), threshold);
+	}
+
+	// Heavy and light channels would overlap below 51.
+	for threshold in ["0", "30", "50", "101", "256"] {
+		assert!(parse(&["--rebalance-threshold", threshold]).is_err());
+	}
+}
@@ -0,0 +1,179 @@
+use raiden_primitives::{
+	reachability::{
+		Reachability,
+		ReachabilityChange,
+	},
+	types::{
+		Address,
+		Bytes,
+		CanonicalIdentifier,
+		ChainID,
+		PaymentIdentifier,
+		QueueIdentifier,
+		SecretHash,
+		TokenAmount,
+		TokenNetworkAddress,
+		U256,
+	},
+};
+use raiden_state_machine::types::{
+	ErrorRouteFailed,
+	Event,
+	PaymentSentSuccess,
+	SendMessageEventInner,
+	SendProcessed,
+	UpdatedServicesAddresses,
+};
+
+use crate::http::request::EventStreamParams;
+
+fn payment_sent(token_network_address: TokenNetworkAddress, target: Address) -> Event {
+	PaymentSentSuccess {
+		token_network_registry_address: Address::zero(),
+		token_network_address,
+		identifier: PaymentIdentifier::from(1u64),
+		amount: TokenAmount::from(10),
+		target,
+		secret: Bytes(vec![1; 32]),
+		route: vec![],
+	}
+	.into()
+}
+
+fn processed(token_network_address: TokenNetworkAddress, recipient: Address) -> Event {
+	SendProcessed {
+		inner: SendMessageEventInner {
+			recipient,
+			recipient_metadata: None,
+			canonical_identifier: CanonicalIdentifier {
+				chain_identifier: ChainID::Private(U256::from(61)),
+				token_network_address,
+				channel_identifier: U256::one(),
+			},
+			message_identifier: 1,
+		},
+	}
+	.into()
+}
+
+fn route_failed(token_network_address: TokenNetworkAddress, route: Vec<Address>) -> Event {
+	ErrorRouteFailed { secrethash: SecretHash::random(), route, token_network_address }.into()
+}
+
+fn clear_messages(token_network_address: TokenNetworkAddress, recipient: Address) -> Event {
+	Event::ClearMessages(QueueIdentifier {
+		recipient,
+		canonical_identifier: CanonicalIdentifier {
+			chain_identifier: ChainID::Private(U256::from(61)),
+			token_network_address,
+			channel_identifier: U256::one(),
+		},
+	})
+}
+
+fn services_updated() -> Event {
+	UpdatedServicesAddresses { service_address: Address::random(), validity: U256::from(100) }
+		.into()
+}
+
+fn reachability_changed(address: Address) -> ReachabilityChange {
+	ReachabilityChange { address, reachability: Reachability::Reachable }
+}
+
+#[test]
+fn test_event_stream_without_filters() {
+	let params = EventStreamParams::default();
+	assert!(params.matches(&payment_sent(Address::random(), Address::random())));
+	assert!(params.matches(&processed(Address::random(), Address::random())));
+	assert!(params.matches(&services_updated()));
+	assert!(params.matches_reachability(&reachability_changed(Address::random())));
+}
+
+#[test]
+fn test_event_stream_filters_event_types() {
+	let params = EventStreamParams {
+		event_types: vec!["PaymentSentSuccess".to_owned(), "SendProcessed".to_owned()],
+		..Default::default()
+	};
+	assert!(params.matches(&payment_sent(Address::random(), Address::random())));
+	assert!(params.matches(&processed(Address::random(), Address::random())));
+	assert!(!params.matches(&services_updated()));
+	assert!(!params.matches_reachability(&reachability_changed(Address::random())));
+
+	let params = EventStreamParams {
+		event_types: vec![EventStreamParams::REACHABILITY_CHANGED.to_owned()],
+		..Default::default()
+	};
+	assert!(!params.matches(&payment_sent(Address::random(), Address::random())));
+	assert!(params.matches_reachability(&reachability_changed(Address::random())));
+}
+
+#[test]
+fn test_event_stream_filters_token_network() {
+	let token_network_address = TokenNetworkAddress::random();
+	let params = EventStreamParams {
+		token_network_address: Some(token_network_address),
+		..Default::default()
+	};
+
+	assert!(params.matches(&payment_sent(token_network_address, Address::random())));
+	assert!(params.matches(&processed(token_network_address, Address::random())));
+	assert!(!params.matches(&payment_sent(TokenNetworkAddress::random(), Address::random())));
+	assert!(!params.matches(&processed(TokenNetworkAddress::random(), Address::random())));
+	// Events without a token network are left out.
+	assert!(!params.matches(&services_updated()));
+	assert!(!params.matches_reachability(&reachability_changed(Address::random())));
+}
+
+#[test]
+fn test_event_stream_filters_partner() {
+	let partner_address = Address::random();
+	let params = EventStreamParams { partner_address: Some(partner_address), ..Default::default() };
+
+	assert!(params.matches(&payment_sent(TokenNetworkAddress::random(), partner_address)));
+	assert!(params.matches(&processed(TokenNetworkAddress::random(), partner_address)));
+	assert!(!params.matches(&payment_sent(TokenNetworkAddress::random(), Address::random())));
+	assert!(!params.matches(&processed(TokenNetworkAddress::random(), Address::random())));
+	assert!(!params.matches(&services_updated()));
+	assert!(params.matches_reachability(&reachability_changed(partner_address)));
+	assert!(!params.matches_reachability(&reachability_changed(Address::random())));
+}
+
+#[test]
+fn test_event_stream_combines_filters() {
+	let token_network_address = TokenNetworkAddress::random();
+	let partner_address = Address::random();
+	let params = EventStreamParams {
+		event_types: vec!["PaymentSentSuccess".to_owned()],
+		token_network_address: Some(token_network_address),
+		partner_address: Some(partner_address),
+	};
+
+	assert!(params.matches(&payment_sent(token_network_address, partner_address)));
+	assert!(!params.matches(&processed(token_network_address, partner_address)));
+	assert!(!params.matches(&payment_sent(token_network_address, Address::random())));
+	assert!(!params.matches(&payment_sent(TokenNetworkAddress::random(), partner_address)));
+}
+
+#[test]
+fn test_event_stream_filters_event_attributes() {
+	let token_network_address = TokenNetworkAddress::random();
+	let partner_address = Address::random();
+	let params = EventStreamParams {
+		token_network_address: Some(token_network_address),
+		partner_address: Some(partner_address),
+		..Default::default()
+	};
+
+	assert!(params.matches(&clear_messages(token_network_address, partner_address)));
+	assert!(!params.matches(&clear_messages(TokenNetworkAddress::random(), partner_address)));
+	assert!(!params.matches(&clear_messages(token_network_address, Address::random())));
+	// A route is not a channel partner.
+	assert!(!params.matches(&route_failed(token_network_address, vec![partner_address])));
+
+	let params = EventStreamParams {
+		token_network_address: Some(token_network_address),
+		..Default::default()
+	};
+	assert!(params.matches(&route_failed(token_network_address, vec![partner_address])));
+}
@@ -0,0 +1,4 @@
+mod auth;
+mod block;
+mod cli;
+mod events;
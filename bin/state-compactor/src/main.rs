@@ -0,0 +1,120 @@
+use std::{
+	path::PathBuf,
+	process,
+	sync::Arc,
+};
+
+#[cfg(feature = "postgresql")]
+use raiden_state_machine::storage::PostgresStorage;
+use raiden_state_machine::{
+	storage::{
+		types::RetentionPolicy,
+		StateStorage,
+		Storage,
+	},
+	views,
+};
+use raiden_transition::utils::latest_state;
+use rusqlite::Connection;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "State Compactor")]
+pub struct Opt {
+	/// Path to the SQLite state database.
+	#[structopt(
+		short("d"),
+		long,
+		parse(from_os_str),
+		takes_value = true,
+		required_unless = "database-url"
+	)]
+	pub dbpath: Option<PathBuf>,
+
+	/// PostgreSQL connection string of the state database, requires the `postgresql` feature.
+	#[structopt(long, takes_value = true, requires = "schema")]
+	pub database_url: Option<String>,
+
+	/// PostgreSQL schema holding the state of the node.
+	#[structopt(long, takes_value = true)]
+	pub schema: Option<String>,
+
+	/// Number of chain state snapshots to keep.
+	#[structopt(long, required = false, takes_value = true, default_value = "2")]
+	pub snapshots_to_keep: u32,
+}
+
+fn main() {
+	let cli = Opt::from_args();
+
+	let storage: Arc<dyn Storage> = match (cli.database_url, cli.schema, cli.dbpath) {
+		(Some(database_url), Some(schema), _) => match postgres_storage(&database_url, &schema) {
+			Ok(storage) => storage,
+			Err(e) => {
+				eprintln!("{}", e);
+				process::exit(1);
+			},
+		},
+		(_, _, Some(dbpath)) => {
+			let dbpath = match expanduser::expanduser(dbpath.to_string_lossy()) {
+				Ok(p) => p,
+				Err(e) => {
+					eprintln!("Error expanding db path: {}", e);
+					process::exit(1);
+				},
+			};
+			match Connection::open(dbpath) {
+				Ok(conn) => Arc::new(StateStorage::new(conn)),
+				Err(e) => {
+					eprintln!("Could not connect to database: {}", e);
+					process::exit(1);
+				},
+			}
+		},
+		_ => {
+			eprintln!("Either a database path or a database URL is required");
+			process::exit(1);
+		},
+	};
+
+	if let Err(e) = storage.setup_database() {
+		eprintln!("Could not migrate database: {}", e);
+		process::exit(1);
+	}
+
+	let chain_state = match latest_state(storage.clone()) {
+		Some(chain_state) => chain_state,
+		None => {
+			eprintln!("Could not restore the chain state, no snapshot found");
+			process::exit(1);
+		},
+	};
+	let channels = views::get_channels(&chain_state)
+		.into_iter()
+		.map(|channel| channel.canonical_identifier)
+		.collect();
+
+	let policy = RetentionPolicy { snapshots_to_keep: cli.snapshots_to_keep };
+	match storage.compact(&policy, channels) {
+		Ok(stats) => println!(
+			"Deleted {} snapshots, {} state changes and {} events",
+			stats.snapshots, stats.state_changes, stats.events
+		),
+		Err(e) => {
+			eprintln!("Could not compact database: {}", e);
+			process::exit(1);
+		},
+	}
+}
+
+#[cfg(feature = "postgresql")]
+fn postgres_storage(database_url: &str, schema: &str) -> Result<Arc<dyn Storage>, String> {
+	let storage = PostgresStorage::connect(database_url, schema)
+		.map_err(|e| format!("Could not connect to database: {}", e))?;
+	Ok(Arc::new(storage))
+}
+
+#[cfg(not(feature = "postgresql"))]
+fn postgres_storage(_database_url: &str, _schema: &str) -> Result<Arc<dyn Storage>, String> {
+	Err("PostgreSQL storage is not supported, build with the `postgresql` feature".to_owned())
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+use colored::Colorize;
+use serde_json::Value;
+
+/// A value changed at a path of a serialized structure.
+pub struct Change {
+	pub path: String,
+	pub old: Option<Value>,
+	pub new: Option<Value>,
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let path = if self.path.is_empty() { "." } else { &self.path };
+		match (&self.old, &self.new) {
+			(Some(old), Some(new)) => write!(
+				f,
+				"{}: {} -> {}",
+				path.bold(),
+				old.to_string().red(),
+				new.to_string().green()
+			),
+			(Some(old), None) => write!(f, "{}: {}", path.bold(), format!("- {}", old).red()),
+			(None, Some(new)) => write!(f, "{}: {}", path.bold(), format!("+ {}", new).green()),
+			(None, None) => write!(f, "{}", path.bold()),
+		}
+	}
+}
+
+/// Return the changes of the leaves between two serialized structures.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+	let mut changes = vec![];
+	diff_at(String::new(), old, new, &mut changes);
+	changes
+}
+
+/// Collect the changes between `old` and `new` found at `path`.
+fn diff_at(path: String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+	match (old, new) {
+		(Value::Object(old_fields), Value::Object(new_fields)) => {
+			for (key, old_value) in old_fields {
+				let path = format!("{}.{}", path, key);
+				match new_fields.get(key) {
+					Some(new_value) => diff_at(path, old_value, new_value, changes),
+					None => changes.push(Change { path, old: Some(old_value.clone()), new: None }),
+				}
+			}
+			for (key, new_value) in new_fields {
+				if !old_fields.contains_key(key) {
+					changes.push(Change {
+						path: format!("{}.{}", path, key),
+						old: None,
+						new: Some(new_value.clone()),
+					});
+				}
+			}
+		},
+		(Value::Array(old_items), Value::Array(new_items)) => {
+			for index in 0..old_items.len().max(new_items.len()) {
+				let path = format!("{}[{}]", path, index);
+				match (old_items.get(index), new_items.get(index)) {
+					(Some(old_item), Some(new_item)) => diff_at(path, old_item, new_item, changes),
+					(old_item, new_item) => changes.push(Change {
+						path,
+						old: old_item.cloned(),
+						new: new_item.cloned(),
+					}),
+				}
+			}
+		},
+		_ =>
+			if old != new {
+				changes.push(Change { path, old: Some(old.clone()), new: Some(new.clone()) });
+			},
+	}
+}
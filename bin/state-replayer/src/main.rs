@@ -18,8 +18,10 @@ use raiden_primitives::types::{
 use raiden_state_machine::{
 	machine::chain,
 	storage::{
+		migrations,
 		types::StorageID,
 		StateStorage,
+		Storage,
 	},
 	types::{
 		ChainState,
@@ -29,9 +31,20 @@ use raiden_state_machine::{
 		TokenNetworkRegistryState,
 	},
 };
+use replayer::{
+	Focus,
+	Replayer,
+	Until,
+};
 use rusqlite::Connection;
 use structopt::StructOpt;
 
+mod diff;
+mod replayer;
+
+#[cfg(test)]
+mod tests;
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "State Replayer")]
 pub struct Opt {
@@ -39,6 +52,32 @@ pub struct Opt {
 	pub dbpath: PathBuf,
 	#[structopt(short("a"), long, parse(try_from_str = parse_address), takes_value = true)]
 	pub address: Address,
+
+	/// Stop after the state change with this identifier, or at this block number.
+	#[structopt(long, parse(try_from_str = parse_until), takes_value = true)]
+	pub until: Option<Until>,
+
+	/// Step through the state changes interactively.
+	#[structopt(short("i"), long)]
+	pub interactive: bool,
+
+	/// Only diff the state of the channel with this identifier.
+	#[structopt(long, parse(try_from_str = U256::from_dec_str), takes_value = true)]
+	pub channel: Option<U256>,
+
+	/// Only diff the state of the payment with this secrethash.
+	#[structopt(long, conflicts_with = "channel", takes_value = true)]
+	pub secrethash: Option<H256>,
+}
+
+/// Parse a block number, or a state change identifier otherwise.
+pub(crate) fn parse_until(value: &str) -> Result<Until, String> {
+	if let Ok(block_number) = value.parse::<u64>() {
+		return Ok(Until::Block(BlockNumber::from(block_number)))
+	}
+	StorageID::try_from(value.to_owned())
+		.map(Until::StateChange)
+		.map_err(|e| format!("Invalid block number or state change identifier: {}", e))
 }
 
 fn main() {
@@ -77,7 +116,23 @@ fn main() {
 		},
 	};
 
+	// The database is only read, it is left to the node to migrate it.
 	let storage = StateStorage::new(conn);
+	let version = match storage.get_version() {
+		Ok(version) => version.unwrap_or(migrations::INITIAL_VERSION),
+		Err(e) => {
+			eprintln!("Could not read database version: {}", e);
+			process::exit(1);
+		},
+	};
+	let supported_version = migrations::latest_version(migrations::MIGRATIONS);
+	if version != supported_version {
+		eprintln!(
+			"Database version {} does not match the supported version {}, migrate it by starting the node first.",
+			version, supported_version
+		);
+		process::exit(1);
+	}
 	let state_change_records =
 		match storage.get_state_changes_in_range(StorageID::zero(), StorageID::max()) {
 			Ok(state_changes) => state_changes,
@@ -87,15 +142,20 @@ fn main() {
 			},
 		};
 
-	let mut chain_state = ChainState {
+	let chain_state = ChainState {
 		chain_id,
 		block_number: BlockNumber::from(0),
 		block_hash: BlockHash::random(),
 		our_address: cli.address,
 		identifiers_to_tokennetworkregistries: HashMap::new(),
-		payment_mapping: PaymentMappingState { secrethashes_to_task: HashMap::new() },
+		payment_mapping: PaymentMappingState {
+			secrethashes_to_task: HashMap::new(),
+			secrethashes_to_circular_target: HashMap::new(),
+		},
 		pending_transactions: vec![],
 		pseudo_random_number_generator: Random::new(),
+		invoices: HashMap::new(),
+		connection_managers: HashMap::new(),
 	};
 
 	let token_network_registry_state_change = ContractReceiveTokenNetworkRegistry {
@@ -118,30 +178,17 @@ fn main() {
 			process::exit(1);
 		},
 	};
-	chain_state = result.new_state;
-
-	for state_change_record in state_change_records {
-		let state_change = state_change_record.data;
-		println!();
-		print!("{}", "StateChange ->".red().bold());
-		println!(" {:#?}", state_change);
-		let result = match chain::state_transition(chain_state.clone(), state_change) {
-			Ok(transition) => transition,
-			Err(e) => {
-				eprintln!("\tError: {:?}", e.msg);
-				continue
-			},
-		};
-		for event in result.events {
-			let event_str = format!("{:#?}", event).replace('\n', "\n\t");
-			print!("\t{}", "<- Event ".yellow().bold());
-			println!("{}", event_str);
-			continue
-		}
-		println!();
-		chain_state = result.new_state;
-	}
+	let chain_state = result.new_state;
 
-	println!("{}", "FINAL STATE:".green().on_white().bold());
-	println!("{:#?}", chain_state);
+	let focus = match (cli.channel, cli.secrethash) {
+		(Some(channel_identifier), _) => Focus::Channel(channel_identifier),
+		(_, Some(secrethash)) => Focus::Payment(secrethash),
+		_ => Focus::ChainState,
+	};
+	let mut replayer = Replayer::new(chain_state, state_change_records, cli.until, focus);
+	if cli.interactive {
+		replayer.interactive();
+	} else {
+		replayer.run();
+	}
 }
@@ -0,0 +1,247 @@
+use std::io::{
+	self,
+	Write,
+};
+
+use colored::Colorize;
+use raiden_primitives::types::{
+	BlockNumber,
+	ChannelIdentifier,
+	SecretHash,
+};
+use raiden_state_machine::{
+	machine::chain,
+	storage::types::{
+		StateChangeRecord,
+		StorageID,
+	},
+	types::{
+		ChainState,
+		Event,
+	},
+	views,
+};
+use serde_json::Value;
+
+use crate::diff::diff;
+
+/// Where the replay stops.
+#[derive(Clone, Copy, Debug)]
+pub enum Until {
+	/// Stop once the state change with this identifier is applied.
+	StateChange(StorageID),
+	/// Stop once the chain state reaches this block.
+	Block(BlockNumber),
+}
+
+/// The part of the chain state which is inspected.
+#[derive(Clone, Debug)]
+pub enum Focus {
+	ChainState,
+	Channel(ChannelIdentifier),
+	Payment(SecretHash),
+}
+
+impl Focus {
+	/// Serialize the focused part of the chain state.
+	fn view(&self, chain_state: &ChainState) -> Value {
+		let view = match self {
+			Focus::ChainState => serde_json::to_value(chain_state),
+			Focus::Channel(channel_identifier) =>
+				serde_json::to_value(views::get_channels(chain_state).into_iter().find(|channel| {
+					channel.canonical_identifier.channel_identifier == *channel_identifier
+				})),
+			Focus::Payment(secrethash) => serde_json::to_value(
+				chain_state.payment_mapping.secrethashes_to_task.get(secrethash),
+			),
+		};
+		view.unwrap_or(Value::Null)
+	}
+}
+
+/// Replays state changes on top of a chain state one at a time.
+pub struct Replayer {
+	chain_state: ChainState,
+	records: Vec<StateChangeRecord>,
+	position: usize,
+	until: Option<Until>,
+	focus: Focus,
+}
+
+impl Replayer {
+	/// Create an instance of `Replayer`.
+	pub fn new(
+		chain_state: ChainState,
+		records: Vec<StateChangeRecord>,
+		until: Option<Until>,
+		focus: Focus,
+	) -> Self {
+		Self { chain_state, records, position: 0, until, focus }
+	}
+
+	/// Replay all state changes up to the stop, printing the diff of the focused part of the
+	/// chain state if a channel or payment is focused.
+	pub fn run(&mut self) {
+		let print_diff = !matches!(self.focus, Focus::ChainState);
+		while self.step(print_diff) {}
+		self.print_state();
+	}
+
+	/// Read commands from stdin to step through the state changes.
+	pub fn interactive(&mut self) {
+		println!("{} state changes to replay, type `help` for the commands.", self.records.len());
+		loop {
+			print!("[{}/{}]> ", self.position, self.records.len());
+			let _ = io::stdout().flush();
+
+			let mut line = String::new();
+			match io::stdin().read_line(&mut line) {
+				Ok(0) | Err(_) => return,
+				Ok(_) => {},
+			}
+			let mut words = line.split_whitespace();
+			let command = words.next().unwrap_or("next");
+			let argument = words.next();
+
+			match command {
+				"n" | "next" => {
+					let count = match argument.map(str::parse::<usize>).transpose() {
+						Ok(count) => count.unwrap_or(1),
+						Err(e) => {
+							eprintln!("Invalid count: {}", e);
+							continue
+						},
+					};
+					for _ in 0..count {
+						if !self.step(true) {
+							break
+						}
+					}
+				},
+				"c" | "continue" => {
+					if let Some(argument) = argument {
+						match crate::parse_until(argument) {
+							Ok(until) => self.until = Some(until),
+							Err(e) => {
+								eprintln!("Invalid stop: {}", e);
+								continue
+							},
+						}
+					}
+					while self.step(false) {}
+					println!("Stopped at {}/{}", self.position, self.records.len());
+				},
+				"s" | "state" => self.print_state(),
+				"f" | "focus" => match parse_focus(argument, words.next()) {
+					Ok(focus) => self.focus = focus,
+					Err(e) => eprintln!("{}", e),
+				},
+				"q" | "quit" => return,
+				"h" | "help" => print_help(),
+				command =>
+					eprintln!("Unknown command `{}`, type `help` for the commands.", command),
+			}
+		}
+	}
+
+	/// Apply the next state change unless the stop is reached.
+	///
+	/// Returns false if no state change was applied.
+	fn step(&mut self, print_diff: bool) -> bool {
+		if self.stopped() {
+			return false
+		}
+		let record = self.records[self.position].clone();
+		self.position += 1;
+
+		println!();
+		print!("{}", format!("StateChange {} ->", record.identifier).red().bold());
+		println!(" {:#?}", record.data);
+
+		let before = self.focus.view(&self.chain_state);
+		let result = match chain::state_transition(self.chain_state.clone(), record.data) {
+			Ok(transition) => transition,
+			Err(e) => {
+				eprintln!("\tError: {:?}", e.msg);
+				return true
+			},
+		};
+		print_events(&result.events);
+		self.chain_state = result.new_state;
+
+		if print_diff {
+			let changes = diff(&before, &self.focus.view(&self.chain_state));
+			if !changes.is_empty() {
+				println!("\t{}", "<> Diff".cyan().bold());
+				for change in changes {
+					println!("\t{}", change);
+				}
+			}
+		}
+		true
+	}
+
+	/// Whether all state changes were applied or the stop was reached.
+	fn stopped(&self) -> bool {
+		if self.position >= self.records.len() {
+			return true
+		}
+		match self.until {
+			Some(Until::StateChange(identifier)) => self.records[..self.position]
+				.last()
+				.map(|record| record.identifier == identifier)
+				.unwrap_or(false),
+			Some(Until::Block(block_number)) => self.chain_state.block_number >= block_number,
+			None => false,
+		}
+	}
+
+	/// Print the focused part of the chain state.
+	fn print_state(&self) {
+		println!("{}", "STATE:".green().on_white().bold());
+		match self.focus {
+			Focus::ChainState => println!("{:#?}", self.chain_state),
+			_ => println!(
+				"{}",
+				serde_json::to_string_pretty(&self.focus.view(&self.chain_state))
+					.unwrap_or_default()
+			),
+		}
+	}
+}
+
+/// Parse a focus from the kind of object and its identifier.
+pub fn parse_focus(kind: Option<&str>, identifier: Option<&str>) -> Result<Focus, String> {
+	match (kind, identifier) {
+		(None, _) | (Some("none"), _) => Ok(Focus::ChainState),
+		(Some("channel"), Some(identifier)) => ChannelIdentifier::from_dec_str(identifier)
+			.map(Focus::Channel)
+			.map_err(|e| format!("Invalid channel identifier: {:?}", e)),
+		(Some("payment"), Some(identifier)) => identifier
+			.trim_start_matches("0x")
+			.parse()
+			.map(Focus::Payment)
+			.map_err(|e| format!("Invalid secrethash: {:?}", e)),
+		_ => Err("Usage: focus none | channel <identifier> | payment <secrethash>".to_owned()),
+	}
+}
+
+/// Print the events emitted by a state change.
+fn print_events(events: &[Event]) {
+	for event in events {
+		let event_str = format!("{:#?}", event).replace('\n', "\n\t");
+		print!("\t{}", "<- Event ".yellow().bold());
+		println!("{}", event_str);
+	}
+}
+
+/// Print the commands of the interactive mode.
+fn print_help() {
+	println!("n, next [count]        Apply the next state change(s)");
+	println!("c, continue [until]    Apply state changes up to a state change ID or block number");
+	println!("s, state               Print the focused state");
+	println!(
+		"f, focus <kind> [id]   Focus on `none`, `channel <identifier>` or `payment <secrethash>`"
+	);
+	println!("q, quit                Exit the replayer");
+}
@@ -0,0 +1,96 @@
+use serde_json::{
+	json,
+	Value,
+};
+
+use crate::diff::{
+	diff,
+	Change,
+};
+
+/// The changes as `(path, old, new)` tuples.
+fn changes(old: &Value, new: &Value) -> Vec<(String, Option<Value>, Option<Value>)> {
+	diff(old, new)
+		.into_iter()
+		.map(|change| (change.path, change.old, change.new))
+		.collect()
+}
+
+#[test]
+fn test_diff_equal_values() {
+	let value = json!({ "block_number": 10, "channels": [{ "nonce": 1 }, { "nonce": 2 }] });
+	assert!(diff(&value, &value).is_empty());
+}
+
+#[test]
+fn test_diff_changed_leaves() {
+	let old = json!({ "block_number": 10, "channel": { "nonce": 1, "status": "opened" } });
+	let new = json!({ "block_number": 11, "channel": { "nonce": 1, "status": "closed" } });
+
+	assert_eq!(
+		changes(&old, &new),
+		vec![
+			(".block_number".to_owned(), Some(json!(10)), Some(json!(11))),
+			(".channel.status".to_owned(), Some(json!("opened")), Some(json!("closed"))),
+		]
+	);
+}
+
+#[test]
+fn test_diff_added_and_removed_fields() {
+	let old = json!({ "kept": 1, "removed": { "nonce": 1 } });
+	let new = json!({ "kept": 1, "added": [1, 2] });
+
+	assert_eq!(
+		changes(&old, &new),
+		vec![
+			(".removed".to_owned(), Some(json!({ "nonce": 1 })), None),
+			(".added".to_owned(), None, Some(json!([1, 2]))),
+		]
+	);
+}
+
+#[test]
+fn test_diff_arrays() {
+	let old = json!({ "locks": [{ "amount": 1 }, { "amount": 2 }, { "amount": 3 }] });
+	let new = json!({ "locks": [{ "amount": 1 }, { "amount": 5 }] });
+
+	assert_eq!(
+		changes(&old, &new),
+		vec![
+			(".locks[1].amount".to_owned(), Some(json!(2)), Some(json!(5))),
+			(".locks[2]".to_owned(), Some(json!({ "amount": 3 })), None),
+		]
+	);
+	assert_eq!(
+		changes(&new, &old)[1],
+		(".locks[2]".to_owned(), None, Some(json!({ "amount": 3 })))
+	);
+}
+
+#[test]
+fn test_diff_changed_types() {
+	// A structure replaced by a leaf is a single change.
+	assert_eq!(
+		changes(&json!({ "route": [1, 2] }), &json!({ "route": null })),
+		vec![(".route".to_owned(), Some(json!([1, 2])), Some(Value::Null))]
+	);
+	// Changed roots have an empty path.
+	assert_eq!(
+		changes(&json!(1), &json!("1")),
+		vec![(String::new(), Some(json!(1)), Some(json!("1")))]
+	);
+}
+
+#[test]
+fn test_display_change() {
+	colored::control::set_override(false);
+
+	let change = |path: &str, old: Option<Value>, new: Option<Value>| {
+		Change { path: path.to_owned(), old, new }.to_string()
+	};
+	assert_eq!(change(".nonce", Some(json!(1)), Some(json!(2))), ".nonce: 1 -> 2");
+	assert_eq!(change(".lock", Some(json!("a")), None), r#".lock: - "a""#);
+	assert_eq!(change(".lock", None, Some(json!("b"))), r#".lock: + "b""#);
+	assert_eq!(change("", Some(json!(1)), Some(json!(2))), ".: 1 -> 2");
+}
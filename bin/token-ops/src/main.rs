@@ -11,17 +11,18 @@ use raiden_bin_common::{
 };
 use raiden_blockchain::{
 	contracts::ContractsManager,
+	gas::GasStrategies,
+	keys::Signer,
 	proxies::{
 		Account,
 		ProxyManager,
+		TransactionTracker,
+		TransactionTrackerConfig,
 	},
 };
 use raiden_primitives::types::ChainID;
 use structopt::StructOpt;
-use web3::{
-	signing::Key,
-	types::Address,
-};
+use web3::types::Address;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "Token Ops")]
@@ -101,7 +102,14 @@ async fn main() {
 			process::exit(1);
 		},
 	};
-	let account = Account::new(web3.clone(), private_key.clone(), nonce);
+	let tracker = TransactionTracker::new(TransactionTrackerConfig::default(), None);
+	let account = Account::new(
+		web3.clone(),
+		Arc::new(private_key.clone()),
+		nonce,
+		GasStrategies::default(),
+		Arc::new(tracker),
+	);
 	let proxy_manager = match ProxyManager::new(web3.clone(), contracts_manager) {
 		Ok(proxy_manager) => proxy_manager,
 		Err(e) => {
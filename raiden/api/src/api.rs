@@ -25,6 +25,10 @@ use raiden_primitives::{
 		PaymentStatus,
 		PaymentsRegistry,
 	},
+	reachability::{
+		Reachability,
+		ReachabilityChange,
+	},
 	traits::Checksum,
 	types::{
 		Address,
@@ -49,28 +53,47 @@ use raiden_primitives::{
 use raiden_state_machine::{
 	constants::{
 		ABSENT_SECRET,
+		DEFAULT_INITIAL_CHANNEL_TARGET,
+		DEFAULT_INVOICE_TIMEOUT,
+		DEFAULT_JOINABLE_FUNDS_TARGET,
 		DEFAULT_RETRY_TIMEOUT,
 		MIN_REVEAL_TIMEOUT,
+		PAYMENT_CANCELED_REASON,
 		SECRET_LENGTH,
 	},
 	errors::StateTransitionError,
+	machine::initiator_manager,
 	types::{
+		ActionCancelPayment,
 		ActionChannelClose,
 		ActionChannelCoopSettle,
 		ActionChannelSetRevealTimeout,
 		ActionChannelWithdraw,
+		ActionCreateInvoice,
 		ActionInitInitiator,
+		ActionTokenNetworkConnect,
+		ActionTokenNetworkLeave,
+		ChainState,
 		ChannelState,
 		ChannelStatus,
+		ConnectionManagerState,
+		Event,
+		InitiatorTransferState,
+		InvoiceState,
+		InvoiceStatus,
 		RouteState,
 		StateChange,
 		TransferDescriptionWithSecretState,
+		TransferTask,
 	},
 	views,
 };
 use raiden_transition::Transitioner;
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{
+	broadcast,
+	RwLock,
+};
 use tracing::{
 	debug,
 	error,
@@ -79,6 +102,7 @@ use tracing::{
 use web3::transports::Http;
 
 use crate::{
+	invoice::Invoice,
 	raiden::Raiden,
 	utils::{
 		random_identifier,
@@ -118,7 +142,23 @@ pub struct Payment {
 	pub secrethash: SecretHash,
 }
 
+/// The progress of a payment sent by this node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PaymentProgress {
+	/// The payment was accepted and routes are being computed.
+	Pending,
+	/// A locked transfer was sent, the lock is waiting to be unlocked.
+	Locked,
+	/// The payment was unlocked and completed successfully.
+	Unlocked,
+	/// The payment failed with a reason.
+	Failed(String),
+	/// The payment was cancelled by the user.
+	Cancelled,
+}
+
 /// The interface which enables initiating payments and interacting with contracts.
+#[derive(Clone)]
 pub struct Api {
 	pub raiden: Arc<Raiden>,
 	transition_service: Arc<Transitioner>,
@@ -135,6 +175,21 @@ impl Api {
 		Self { raiden, transition_service, payments_registry }
 	}
 
+	/// Subscribe to the stream of events produced by the state machine.
+	pub fn subscribe_events(&self) -> broadcast::Receiver<Event> {
+		self.transition_service.subscribe()
+	}
+
+	/// Subscribe to the changes of reachability of the other nodes.
+	pub fn subscribe_reachability(&self) -> broadcast::Receiver<ReachabilityChange> {
+		self.raiden.reachability.read().subscribe()
+	}
+
+	/// Returns the reachability of `address` as observed by the transport.
+	pub fn reachability(&self, address: Address) -> Reachability {
+		self.raiden.reachability.read().get(address)
+	}
+
 	/// Creates a new channel with the current account being one participant.
 	#[allow(clippy::too_many_arguments)]
 	pub async fn create_channel(
@@ -804,6 +859,71 @@ impl Api {
 		Ok(token_network_address)
 	}
 
+	/// Let channels in a token network be opened and funded automatically with up to `funds`.
+	///
+	/// `initial_channel_target` channels are opened by this node, `joinable_funds_target` is the
+	/// percentage of the funds kept to deposit in channels opened by partners.
+	pub async fn token_network_connect(
+		&self,
+		registry_address: Address,
+		token_address: TokenAddress,
+		funds: TokenAmount,
+		initial_channel_target: Option<u16>,
+		joinable_funds_target: Option<u8>,
+	) -> Result<ConnectionManagerState, ApiError> {
+		info!(
+			message = "Connect to token network.",
+			registry_address = registry_address.checksum(),
+			token_address = token_address.checksum(),
+			funds = funds.to_string(),
+		);
+		if funds == TokenAmount::zero() {
+			return Err(ApiError::Param(format!("Funds should not be zero")))
+		}
+
+		let initial_channel_target =
+			initial_channel_target.unwrap_or(DEFAULT_INITIAL_CHANNEL_TARGET);
+		let joinable_funds_target = joinable_funds_target.unwrap_or(DEFAULT_JOINABLE_FUNDS_TARGET);
+		if joinable_funds_target > 100 {
+			return Err(ApiError::Param(format!("Joinable funds target is a percentage")))
+		}
+
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+		let token_network = views::get_token_network_by_token_address(
+			&chain_state,
+			registry_address,
+			token_address,
+		)
+		.ok_or(ApiError::State(format!(
+			"Token {} is not registered with network {}",
+			token_address.checksum(),
+			registry_address.checksum()
+		)))?;
+
+		let connection_manager = ConnectionManagerState {
+			token_network_address: token_network.address,
+			funds,
+			initial_channel_target,
+			joinable_funds_target,
+		};
+		self.transition_service
+			.transition(vec![ActionTokenNetworkConnect {
+				connection_manager: connection_manager.clone(),
+			}
+			.into()])
+			.await
+			.map_err(ApiError::State)?;
+
+		Ok(connection_manager)
+	}
+
+	/// Returns the connection managers of the token networks joined with
+	/// `token_network_connect`.
+	pub fn connection_managers(&self) -> Vec<ConnectionManagerState> {
+		let chain_state = &self.raiden.state_manager.read().current_state;
+		chain_state.connection_managers.values().cloned().collect()
+	}
+
 	/// Leave token network by token address.
 	pub async fn token_network_leave(
 		&self,
@@ -816,14 +936,12 @@ impl Api {
 			token_address = token_address.checksum(),
 		);
 		let chain_state = self.raiden.state_manager.read().current_state.clone();
-		let channels: Vec<ChannelState> = match views::get_token_network_by_token_address(
+		let token_network = match views::get_token_network_by_token_address(
 			&chain_state,
 			registry_address,
 			token_address,
-		)
-		.map(|t| t.channelidentifiers_to_channels.values().cloned().collect())
-		{
-			Some(channels) => channels,
+		) {
+			Some(token_network) => token_network,
 			None =>
 				return Err(ApiError::State(format!(
 					"Token {} is not registered with network {}",
@@ -831,6 +949,19 @@ impl Api {
 					registry_address.checksum()
 				))),
 		};
+		let channels: Vec<ChannelState> =
+			token_network.channelidentifiers_to_channels.values().cloned().collect();
+
+		// Stop the connection manager first, so that it does not replace the closed channels.
+		if chain_state.connection_managers.contains_key(&token_network.address) {
+			self.transition_service
+				.transition(vec![ActionTokenNetworkLeave {
+					token_network_address: token_network.address,
+				}
+				.into()])
+				.await
+				.map_err(ApiError::State)?;
+		}
 
 		self.channel_batch_close(
 			registry_address,
@@ -1210,6 +1341,7 @@ impl Api {
 		secret: Option<String>,
 		secret_hash: Option<SecretHash>,
 		lock_timeout: Option<BlockTimeout>,
+		wait: bool,
 	) -> Result<Payment, ApiError> {
 		info!(
 			message = "Initiate payment",
@@ -1263,15 +1395,16 @@ impl Api {
 			None => SecretHash::from_slice(&hash_secret(&secret.0)),
 		};
 
+		// Without a secret, the target holds it and reveals it once the payment arrives.
 		if !secret.0.is_empty() {
 			let secrethash_from_secret = SecretHash::from_slice(&hash_secret(&secret.0));
 			if secret_hash != secrethash_from_secret {
 				return Err(ApiError::Param(format!("Provided secret and secret_hash do not match")))
 			}
-		}
 
-		if secret.0.len() != SECRET_LENGTH as usize {
-			return Err(ApiError::Param(format!("Secret of invalid length")))
+			if secret.0.len() != SECRET_LENGTH as usize {
+				return Err(ApiError::Param(format!("Secret of invalid length")))
+			}
 		}
 
 		let secret_registry_proxy = self
@@ -1299,7 +1432,7 @@ impl Api {
 		{
 			let matches =
 				payment.token_network_address == token_network_address && payment.amount == amount;
-			if matches {
+			if matches && payment.status.is_none() {
 				return Err(ApiError::Param(format!(
 					"Another payment with the same id is in flight"
 				)))
@@ -1313,11 +1446,193 @@ impl Api {
 			amount,
 		);
 
-		let action_initiator_init = self
+		if !wait {
+			let api = self.clone();
+			let secret = secret.clone();
+			tokio::spawn(async move {
+				let _ = api
+					.dispatch_payment(
+						payment_identifier,
+						amount,
+						secret,
+						secret_hash,
+						token_network_registry_address,
+						token_network_address,
+						partner_address,
+						lock_timeout,
+					)
+					.await;
+			});
+			return Ok(Payment {
+				target: partner_address,
+				payment_identifier,
+				secret,
+				secrethash: secret_hash,
+			})
+		}
+
+		self.dispatch_payment(
+			payment_identifier,
+			amount,
+			secret.clone(),
+			secret_hash,
+			token_network_registry_address,
+			token_network_address,
+			partner_address,
+			lock_timeout,
+		)
+		.await?;
+
+		match payment_completed.await {
+			Ok(status) => match status {
+				PaymentStatus::Success(target, identifier) => Ok(Payment {
+					target,
+					payment_identifier: identifier,
+					secret,
+					secrethash: secret_hash,
+				}),
+				PaymentStatus::Error(_target, _identifier, error) => Err(ApiError::State(error)),
+				PaymentStatus::Cancelled(_target, _identifier) =>
+					Err(ApiError::State(format!("Payment was cancelled"))),
+			},
+			Err(e) => Err(ApiError::State(format!("Could not receive payment status: {:?}", e))),
+		}
+	}
+
+	/// Returns the progress of a payment sent to `partner_address`.
+	pub async fn payment_status(
+		&self,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		token_address: TokenAddress,
+		partner_address: Address,
+		payment_identifier: PaymentIdentifier,
+	) -> Result<PaymentProgress, ApiError> {
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+		let token_network = views::get_token_network_by_token_address(
+			&chain_state,
+			token_network_registry_address,
+			token_address,
+		)
+		.ok_or(ApiError::Param(format!(
+			"Token {} is not registered with network {}",
+			token_address, token_network_registry_address
+		)))?;
+
+		let transfers = initiator_transfers(
+			&chain_state,
+			token_network.address,
+			partner_address,
+			payment_identifier,
+		);
+
+		// Completed payments are dropped from the registry, their status is read from the
+		// stored events.
+		let registered =
+			match self.payments_registry.read().await.get(partner_address, payment_identifier) {
+				Some(payment) if payment.token_network_address == token_network.address =>
+					Some(payment.status.clone()),
+				_ => None,
+			};
+		let status = match registered {
+			Some(status) => status,
+			None if !transfers.is_empty() => None,
+			None => {
+				let storage = self.raiden.state_manager.read().storage.clone();
+				let event = storage
+					.get_payment_sent_result(
+						token_network.address,
+						partner_address,
+						payment_identifier,
+					)
+					.map_err(|e| ApiError::State(e.to_string()))?;
+				match event.and_then(payment_sent_status) {
+					Some(status) => Some(status),
+					None => return Err(ApiError::Param(format!("Payment not found"))),
+				}
+			},
+		};
+
+		Ok(payment_progress(status, &transfers))
+	}
+
+	/// Cancel a payment sent to `partner_address` which secret was not revealed yet.
+	pub async fn cancel_payment(
+		&self,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		token_address: TokenAddress,
+		partner_address: Address,
+		payment_identifier: PaymentIdentifier,
+	) -> Result<(), ApiError> {
+		info!(
+			message = "Cancel payment",
+			token_address = token_address.checksum(),
+			partner_address = partner_address.checksum(),
+			payment_identifier = payment_identifier.to_string(),
+		);
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+		let token_network = views::get_token_network_by_token_address(
+			&chain_state,
+			token_network_registry_address,
+			token_address,
+		)
+		.ok_or(ApiError::Param(format!(
+			"Token {} is not registered with network {}",
+			token_address, token_network_registry_address
+		)))?;
+
+		let transfers = initiator_transfers(
+			&chain_state,
+			token_network.address,
+			partner_address,
+			payment_identifier,
+		);
+		if transfers.is_empty() {
+			return Err(ApiError::Param(format!("Payment not found")))
+		}
+		if !transfers.iter().any(initiator_manager::can_cancel) {
+			return Err(ApiError::State(format!(
+				"Payment can not be cancelled, the secret was already revealed"
+			)))
+		}
+
+		let cancel_payment = ActionCancelPayment {
+			token_network_address: token_network.address,
+			target: partner_address,
+			payment_identifier,
+		};
+		self.transition_service
+			.transition(vec![cancel_payment.into()])
+			.await
+			.map_err(ApiError::State)?;
+
+		self.payments_registry
+			.write()
+			.await
+			.complete(PaymentStatus::Cancelled(partner_address, payment_identifier));
+
+		Ok(())
+	}
+
+	/// Compute the routes of a registered payment and send it.
+	///
+	/// The payment is completed with an error in the registry if it could not be sent.
+	#[allow(clippy::too_many_arguments)]
+	async fn dispatch_payment(
+		&self,
+		payment_identifier: PaymentIdentifier,
+		amount: TokenAmount,
+		secret: Secret,
+		secret_hash: SecretHash,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		token_network_address: TokenNetworkAddress,
+		partner_address: Address,
+		lock_timeout: Option<BlockTimeout>,
+	) -> Result<(), ApiError> {
+		let result = match self
 			.initiator_init(
 				payment_identifier,
 				amount,
-				secret.clone(),
+				secret,
 				secret_hash,
 				token_network_registry_address,
 				token_network_address,
@@ -1325,37 +1640,253 @@ impl Api {
 				lock_timeout,
 				None,
 			)
-			.await;
-
-		match action_initiator_init {
-			Ok(action_init_initiator) => {
-				if let Err(e) =
-					self.transition_service.transition(vec![action_init_initiator.into()]).await
-				{
+			.await
+		{
+			Ok(action_init_initiator) => self
+				.transition_service
+				.transition(vec![action_init_initiator.into()])
+				.await
+				.map_err(|e| {
 					error!("{}", e);
-					return Err(ApiError::State(e))
-				}
-			},
-			Err(e) => {
-				self.payments_registry.write().await.complete(PaymentStatus::Error(
-					partner_address,
-					payment_identifier,
-					e.to_string(),
-				));
-				return Err(e)
-			},
+					ApiError::State(e)
+				}),
+			Err(e) => Err(e),
+		};
+
+		if let Err(e) = &result {
+			self.payments_registry.write().await.fail(
+				partner_address,
+				payment_identifier,
+				e.to_string(),
+			);
+		}
+		result
+	}
+
+	/// Create an invoice for a payment to this node, the secret is kept until the payment arrives.
+	///
+	/// Returns the invoice and its encoding signed by this node.
+	pub async fn create_invoice(
+		&self,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		token_address: TokenAddress,
+		amount: TokenAmount,
+		payment_identifier: Option<PaymentIdentifier>,
+		expiration: Option<BlockTimeout>,
+	) -> Result<(InvoiceState, String), ApiError> {
+		info!(
+			message = "Create invoice",
+			token_address = token_address.checksum(),
+			amount = amount.to_string(),
+		);
+		if amount == TokenAmount::zero() {
+			return Err(ApiError::Param(format!("Amount should not be zero")))
+		}
+
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+		let token_network = views::get_token_network_by_token_address(
+			&chain_state,
+			token_network_registry_address,
+			token_address,
+		)
+		.ok_or(ApiError::Param(format!(
+			"Token {} is not registered with network {}",
+			token_address, token_network_registry_address
+		)))?;
+
+		let secret = Bytes(random_secret().as_bytes().to_vec());
+		let secrethash = SecretHash::from_slice(&hash_secret(&secret.0));
+		let invoice_state = InvoiceState {
+			token_network_address: token_network.address,
+			payment_identifier: payment_identifier.unwrap_or_else(random_identifier),
+			amount,
+			secret,
+			secrethash,
+			expiration: chain_state.block_number +
+				expiration.unwrap_or_else(|| BlockTimeout::from(DEFAULT_INVOICE_TIMEOUT)),
+			status: InvoiceStatus::Pending,
+		};
+
+		let invoice = Invoice {
+			token_address,
+			target: chain_state.our_address,
+			amount,
+			payment_identifier: invoice_state.payment_identifier,
+			expiration: invoice_state.expiration,
+			secrethash,
+		};
+		let encoded = invoice
+			.encode(self.raiden.config.account.signer().as_ref())
+			.map_err(|e| ApiError::State(format!("Could not sign invoice: {:?}", e)))?;
+
+		self.transition_service
+			.transition(vec![ActionCreateInvoice { invoice: invoice_state.clone() }.into()])
+			.await
+			.map_err(ApiError::State)?;
+
+		Ok((invoice_state, encoded))
+	}
+
+	/// Returns the invoices created by this node.
+	pub fn invoices(&self) -> Vec<InvoiceState> {
+		let chain_state = &self.raiden.state_manager.read().current_state;
+		chain_state.invoices.values().cloned().collect()
+	}
+
+	/// Pay an invoice encoded by its target.
+	pub async fn pay_invoice(
+		&self,
+		account: Account<Http>,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		secret_registry_address: SecretRegistryAddress,
+		encoded_invoice: &str,
+		wait: bool,
+	) -> Result<Payment, ApiError> {
+		let invoice =
+			Invoice::decode(encoded_invoice).map_err(|e| ApiError::Param(e.to_string()))?;
+
+		let block_number = self.raiden.state_manager.read().current_state.block_number;
+		if invoice.expiration < block_number {
+			return Err(ApiError::Param(format!("Invoice expired")))
+		}
+
+		self.initiate_payment(
+			account,
+			token_network_registry_address,
+			secret_registry_address,
+			invoice.token_address,
+			invoice.target,
+			invoice.amount,
+			Some(invoice.payment_identifier),
+			None,
+			Some(invoice.secrethash),
+			None,
+			wait,
+		)
+		.await
+	}
+
+	/// Move `amount` from our side of the channel with `first_hop` to our side of the channel
+	/// with `last_hop` by paying it to ourselves through the network.
+	///
+	/// This node holds the secret as the target of the payment. Routes whose estimated fee
+	/// exceeds `max_fee` are not used.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn initiate_circular_payment(
+		&self,
+		token_network_registry_address: TokenNetworkRegistryAddress,
+		token_address: TokenAddress,
+		first_hop: Address,
+		last_hop: Address,
+		amount: TokenAmount,
+		max_fee: TokenAmount,
+		wait: bool,
+	) -> Result<Payment, ApiError> {
+		info!(
+			message = "Initiate circular payment",
+			token_address = token_address.checksum(),
+			first_hop = first_hop.checksum(),
+			last_hop = last_hop.checksum(),
+			amount = amount.to_string(),
+		);
+		if first_hop == last_hop {
+			return Err(ApiError::Param(format!("First and last hop must be different")))
+		}
+
+		if amount == TokenAmount::zero() {
+			return Err(ApiError::Param(format!("Amount should not be zero")))
+		}
+
+		let network_graph = self.raiden.network_graph.clone().ok_or(ApiError::State(format!(
+			"Circular payments require routing with the local network graph"
+		)))?;
+
+		let chain_state = self.raiden.state_manager.read().current_state.clone();
+		let our_address = chain_state.our_address;
+		let token_network = views::get_token_network_by_token_address(
+			&chain_state,
+			token_network_registry_address,
+			token_address,
+		)
+		.ok_or(ApiError::Param(format!(
+			"Token {} is not registered with network {}",
+			token_address, token_network_registry_address
+		)))?;
+		let token_network_address = token_network.address;
+
+		let route_states: Vec<RouteState> = routing::get_circular_routes_local(
+			self.raiden.pfs.clone(),
+			network_graph,
+			chain_state,
+			self.raiden.config.metadata.read().clone(),
+			token_network_address,
+			first_hop,
+			last_hop,
+			amount,
+		)
+		.await
+		.map_err(ApiError::Routing)?
+		.into_iter()
+		.filter(|route_state| route_state.estimated_fee <= max_fee)
+		.collect();
+		if route_states.is_empty() {
+			return Err(ApiError::State(format!("No circular route within the fee budget")))
+		}
+
+		let (invoice, _) = self
+			.create_invoice(token_network_registry_address, token_address, amount, None, None)
+			.await?;
+		let payment_identifier = invoice.payment_identifier;
+		let secrethash = invoice.secrethash;
+
+		let payment_completed = self.payments_registry.write().await.register(
+			token_network_address,
+			our_address,
+			payment_identifier,
+			amount,
+		);
+
+		let result = match self
+			.initiator_init(
+				payment_identifier,
+				amount,
+				ABSENT_SECRET,
+				secrethash,
+				token_network_registry_address,
+				token_network_address,
+				our_address,
+				None,
+				Some(route_states),
+			)
+			.await
+		{
+			Ok(action_init_initiator) => self
+				.transition_service
+				.transition(vec![action_init_initiator.into()])
+				.await
+				.map_err(ApiError::State),
+			Err(e) => Err(e),
+		};
+		if let Err(e) = result {
+			self.payments_registry.write().await.complete(PaymentStatus::Error(
+				our_address,
+				payment_identifier,
+				e.to_string(),
+			));
+			return Err(e)
+		}
+
+		let payment =
+			Payment { target: our_address, payment_identifier, secret: invoice.secret, secrethash };
+		if !wait {
+			return Ok(payment)
 		}
 
 		match payment_completed.await {
-			Ok(status) => match status {
-				PaymentStatus::Success(target, identifier) => Ok(Payment {
-					target,
-					payment_identifier: identifier,
-					secret,
-					secrethash: secret_hash,
-				}),
-				PaymentStatus::Error(_target, _identifier, error) => Err(ApiError::State(error)),
-			},
+			Ok(PaymentStatus::Success(_, _)) => Ok(payment),
+			Ok(PaymentStatus::Error(_target, _identifier, error)) => Err(ApiError::State(error)),
+			Ok(PaymentStatus::Cancelled(_target, _identifier)) =>
+				Err(ApiError::State(format!("Payment was cancelled"))),
 			Err(e) => Err(ApiError::State(format!("Could not receive payment status: {:?}", e))),
 		}
 	}
@@ -1452,7 +1983,7 @@ impl Api {
 			secrethash: transfer_secrethash,
 		};
 
-		let our_address_metadata = self.raiden.config.metadata.clone();
+		let our_address_metadata = self.raiden.config.metadata.read().clone();
 		let one_to_n_address = self.raiden.config.addresses.one_to_n;
 		let from_address = self.raiden.config.account.address();
 
@@ -1461,6 +1992,7 @@ impl Api {
 		} else {
 			let (routes, _feedback_token) = routing::get_best_routes(
 				self.raiden.pfs.clone(),
+				self.raiden.network_graph.clone(),
 				chain_state,
 				our_address_metadata,
 				token_network_address,
@@ -1469,6 +2001,7 @@ impl Api {
 				target_address,
 				transfer_amount,
 				None,
+				self.raiden.reachability.clone(),
 			)
 			.await
 			.map_err(ApiError::Routing)?;
@@ -1479,3 +2012,61 @@ impl Api {
 		Ok(ActionInitInitiator { transfer: transfer_state, routes: route_states })
 	}
 }
+
+/// Returns the progress of a payment from its final status, if it completed, or from its
+/// transfers otherwise.
+pub(crate) fn payment_progress(
+	status: Option<PaymentStatus>,
+	transfers: &[InitiatorTransferState],
+) -> PaymentProgress {
+	match status {
+		Some(PaymentStatus::Success(_, _)) => return PaymentProgress::Unlocked,
+		Some(PaymentStatus::Error(_, _, error)) => return PaymentProgress::Failed(error),
+		Some(PaymentStatus::Cancelled(_, _)) => return PaymentProgress::Cancelled,
+		None => {},
+	}
+
+	// Rerouted payments keep their previous transfers, any of them means a lock was sent.
+	if transfers.is_empty() {
+		return PaymentProgress::Pending
+	}
+	PaymentProgress::Locked
+}
+
+/// Returns the final status of a payment from the event completing it.
+pub(crate) fn payment_sent_status(event: Event) -> Option<PaymentStatus> {
+	match event {
+		Event::PaymentSentSuccess(inner) =>
+			Some(PaymentStatus::Success(inner.target, inner.identifier)),
+		Event::ErrorPaymentSentFailed(inner) if inner.reason == PAYMENT_CANCELED_REASON =>
+			Some(PaymentStatus::Cancelled(inner.target, inner.identifier)),
+		Event::ErrorPaymentSentFailed(inner) =>
+			Some(PaymentStatus::Error(inner.target, inner.identifier, inner.reason)),
+		_ => None,
+	}
+}
+
+/// Returns the transfers sent by the initiator of a payment.
+pub(crate) fn initiator_transfers(
+	chain_state: &ChainState,
+	token_network_address: TokenNetworkAddress,
+	partner_address: Address,
+	payment_identifier: PaymentIdentifier,
+) -> Vec<InitiatorTransferState> {
+	chain_state
+		.payment_mapping
+		.secrethashes_to_task
+		.values()
+		.filter_map(|task| match task {
+			TransferTask::Initiator(task) => Some(task),
+			_ => None,
+		})
+		.flat_map(|task| task.manager_state.initiator_transfers.values())
+		.filter(|transfer| {
+			transfer.transfer_description.token_network_address == token_network_address &&
+				transfer.transfer_description.target == partner_address &&
+				transfer.transfer_description.payment_identifier == payment_identifier
+		})
+		.cloned()
+		.collect()
+}
@@ -0,0 +1,98 @@
+//! Invoices let the receiver of a payment keep the secret until the payment arrives.
+//!
+//! The receiver signs the invoice so that the payer can verify who requested the payment, the
+//! payer then initiates the payment with the secrethash only.
+use raiden_blockchain::keys::Signer;
+use raiden_primitives::{
+	signing::recover,
+	traits::ToBytes,
+	types::{
+		Address,
+		BlockExpiration,
+		PaymentIdentifier,
+		SecretHash,
+		TokenAddress,
+		TokenAmount,
+	},
+};
+use thiserror::Error;
+use web3::signing::SigningError;
+
+/// Length of the signed part of an encoded invoice.
+const INVOICE_DATA_LENGTH: usize = 20 + 20 + 32 + 8 + 8 + 32;
+
+/// Length of the signature of an encoded invoice.
+const SIGNATURE_LENGTH: usize = 65;
+
+/// Invoice error type.
+#[derive(Error, Debug)]
+pub enum InvoiceError {
+	#[error("Invalid invoice encoding")]
+	Encoding,
+	#[error("Invoice is not signed by its target")]
+	Signature,
+}
+
+/// A payment requested by its target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invoice {
+	pub token_address: TokenAddress,
+	pub target: Address,
+	pub amount: TokenAmount,
+	pub payment_identifier: PaymentIdentifier,
+	pub expiration: BlockExpiration,
+	pub secrethash: SecretHash,
+}
+
+impl Invoice {
+	/// Encode the invoice with the signature of the target as a hex string.
+	pub fn encode(&self, signer: &dyn Signer) -> Result<String, SigningError> {
+		let mut bytes = self.bytes_to_sign();
+		bytes.extend(signer.sign_message(&bytes)?.to_bytes());
+		Ok(hex::encode(bytes))
+	}
+
+	/// Decode an invoice and verify it was signed by its target.
+	pub fn decode(encoded: &str) -> Result<Self, InvoiceError> {
+		let bytes =
+			hex::decode(encoded.trim_start_matches("0x")).map_err(|_| InvoiceError::Encoding)?;
+		if bytes.len() != INVOICE_DATA_LENGTH + SIGNATURE_LENGTH {
+			return Err(InvoiceError::Encoding)
+		}
+		let (data, signature) = bytes.split_at(INVOICE_DATA_LENGTH);
+
+		let invoice = Self {
+			token_address: TokenAddress::from_slice(&data[0..20]),
+			target: Address::from_slice(&data[20..40]),
+			amount: TokenAmount::from_big_endian(&data[40..72]),
+			payment_identifier: PaymentIdentifier::from(u64_from_slice(&data[72..80])),
+			expiration: BlockExpiration::from(u64_from_slice(&data[80..88])),
+			secrethash: SecretHash::from_slice(&data[88..120]),
+		};
+
+		let signer = recover(data, signature).map_err(|_| InvoiceError::Signature)?;
+		if signer != invoice.target {
+			return Err(InvoiceError::Signature)
+		}
+		Ok(invoice)
+	}
+
+	/// Returns the bytes signed by the target.
+	fn bytes_to_sign(&self) -> Vec<u8> {
+		let mut bytes = vec![];
+		bytes.extend_from_slice(self.token_address.as_bytes());
+		bytes.extend_from_slice(self.target.as_bytes());
+		bytes.extend(self.amount.to_bytes());
+		bytes.extend(self.payment_identifier.as_bytes());
+		bytes.extend(self.expiration.as_bytes());
+		bytes.extend_from_slice(self.secrethash.as_bytes());
+		bytes
+	}
+}
+
+/// Read a big endian `u64` from an 8 bytes slice.
+fn u64_from_slice(bytes: &[u8]) -> u64 {
+	let mut buffer = [0u8; 8];
+	buffer.copy_from_slice(bytes);
+	u64::from_be_bytes(buffer)
+}
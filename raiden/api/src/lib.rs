@@ -2,6 +2,10 @@
 //! Raiden specific functionality such as opening / closing channels, deposit & withdraw as well as
 //! initiating payments .. etc.
 pub mod api;
+pub mod invoice;
 pub mod raiden;
 pub mod utils;
 pub mod waiting;
+
+#[cfg(test)]
+mod tests;
@@ -11,14 +11,18 @@ use raiden_blockchain::{
 use raiden_network_messages::messages::TransportServiceMessage;
 use raiden_pathfinding::{
 	config::PFSConfig,
+	graph::NetworkGraph,
 	PFS,
 };
-use raiden_primitives::types::{
-	AddressMetadata,
-	ChainID,
-	DefaultAddresses,
-	RevealTimeout,
-	SettleTimeout,
+use raiden_primitives::{
+	reachability::ReachabilityTracker,
+	types::{
+		AddressMetadata,
+		ChainID,
+		DefaultAddresses,
+		RevealTimeout,
+		SettleTimeout,
+	},
 };
 use raiden_state_machine::types::MediationFeeConfig;
 use raiden_transition::manager::StateManager;
@@ -36,7 +40,8 @@ pub struct RaidenConfig {
 	pub mediation_config: MediationFeeConfig,
 	pub monitoring_enabled: bool,
 	pub pfs_config: PFSConfig,
-	pub metadata: AddressMetadata,
+	/// The metadata other nodes reach us with, it changes with the matrix home server.
+	pub metadata: Arc<RwLock<AddressMetadata>>,
 	/// Default addresses
 	pub addresses: DefaultAddresses,
 	pub default_settle_timeout: SettleTimeout,
@@ -58,4 +63,8 @@ pub struct Raiden {
 	pub transport: UnboundedSender<TransportServiceMessage>,
 	/// Pathfinding
 	pub pfs: Arc<PFS>,
+	/// Network graph used for routing when no PFS is used
+	pub network_graph: Option<Arc<RwLock<NetworkGraph>>>,
+	/// Reachability of the other nodes, as observed by the transport
+	pub reachability: Arc<RwLock<ReachabilityTracker>>,
 }
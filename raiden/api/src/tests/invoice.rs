@@ -0,0 +1,87 @@
+use raiden_blockchain::keys::{
+	KeystoreSigner,
+	Signer,
+};
+use raiden_primitives::types::{
+	BlockExpiration,
+	PaymentIdentifier,
+	SecretHash,
+	TokenAddress,
+	TokenAmount,
+};
+
+use crate::invoice::{
+	Invoice,
+	InvoiceError,
+};
+
+fn signer(key: u8) -> KeystoreSigner {
+	KeystoreSigner::from_raw(&[key; 32]).expect("Should be a valid private key")
+}
+
+fn invoice(signer: &KeystoreSigner) -> Invoice {
+	Invoice {
+		token_address: TokenAddress::random(),
+		target: signer.address(),
+		amount: TokenAmount::from(1_000_000_000_000_000_000u64),
+		payment_identifier: PaymentIdentifier::from(42u64),
+		expiration: BlockExpiration::from(12_345u64),
+		secrethash: SecretHash::random(),
+	}
+}
+
+/// Flip a bit of the byte at `index` of an encoded invoice.
+fn tamper(encoded: &str, index: usize) -> String {
+	let mut bytes = hex::decode(encoded).expect("Should be hex");
+	bytes[index] ^= 1;
+	hex::encode(bytes)
+}
+
+#[test]
+fn test_invoice_round_trip() {
+	let signer = signer(1);
+	let invoice = invoice(&signer);
+
+	let encoded = invoice.encode(&signer).expect("Should encode invoice");
+	let decoded = Invoice::decode(&encoded).expect("Should decode invoice");
+	assert_eq!(decoded, invoice);
+	// The signature recovers the target.
+	assert_eq!(decoded.target, signer.address());
+
+	let decoded = Invoice::decode(&format!("0x{}", encoded)).expect("Should decode invoice");
+	assert_eq!(decoded, invoice);
+}
+
+#[test]
+fn test_invoice_rejects_tampered_data() {
+	let signer = signer(1);
+	let encoded = invoice(&signer).encode(&signer).expect("Should encode invoice");
+
+	// Token address, target, amount, payment identifier, expiration and secrethash.
+	for index in [0, 20, 71, 79, 87, 119] {
+		assert!(matches!(Invoice::decode(&tamper(&encoded, index)), Err(InvoiceError::Signature)));
+	}
+	// The signature itself.
+	assert!(matches!(Invoice::decode(&tamper(&encoded, 130)), Err(InvoiceError::Signature)));
+}
+
+#[test]
+fn test_invoice_rejects_other_signer() {
+	let target = signer(1);
+	let other = signer(2);
+
+	// The invoice names a target which did not sign it.
+	let encoded = invoice(&target).encode(&other).expect("Should encode invoice");
+	assert!(matches!(Invoice::decode(&encoded), Err(InvoiceError::Signature)));
+}
+
+#[test]
+fn test_invoice_rejects_invalid_encoding() {
+	let signer = signer(1);
+	let encoded = invoice(&signer).encode(&signer).expect("Should encode invoice");
+
+	assert!(matches!(Invoice::decode(""), Err(InvoiceError::Encoding)));
+	assert!(matches!(Invoice::decode("not an invoice"), Err(InvoiceError::Encoding)));
+	assert!(matches!(Invoice::decode(&encoded[..encoded.len() - 2]), Err(InvoiceError::Encoding)));
+	assert!(matches!(Invoice::decode(&format!("{}00", encoded)), Err(InvoiceError::Encoding)));
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+
+use raiden_primitives::{
+	payments::{
+		PaymentStatus,
+		PaymentsRegistry,
+	},
+	types::{
+		Address,
+		BalanceHash,
+		BlockExpiration,
+		BlockHash,
+		BlockNumber,
+		Bytes,
+		CanonicalIdentifier,
+		ChainID,
+		Locksroot,
+		PaymentIdentifier,
+		SecretHash,
+		TokenAmount,
+		TokenNetworkAddress,
+		TokenNetworkRegistryAddress,
+		U256,
+	},
+};
+use raiden_state_machine::{
+	constants::PAYMENT_CANCELED_REASON,
+	types::{
+		BalanceProofState,
+		ChainState,
+		ErrorPaymentSentFailed,
+		Event,
+		HashTimeLockState,
+		InitiatorPaymentState,
+		InitiatorTask,
+		InitiatorTransferState,
+		LockedTransferState,
+		PaymentSentSuccess,
+		RouteState,
+		TransferDescriptionWithSecretState,
+		TransferRole,
+		TransferState,
+		TransferTask,
+	},
+};
+
+use crate::api::{
+	initiator_transfers,
+	payment_progress,
+	payment_sent_status,
+	PaymentProgress,
+};
+
+/// A payment sent by `our_address`, tracked the way `Api::payment_status` does.
+struct Payment {
+	token_network_address: TokenNetworkAddress,
+	target: Address,
+	identifier: PaymentIdentifier,
+}
+
+impl Payment {
+	fn new(identifier: u64) -> Self {
+		Self {
+			token_network_address: TokenNetworkAddress::random(),
+			target: Address::random(),
+			identifier: identifier.into(),
+		}
+	}
+
+	/// Progress as reported while polling the payment, `stored` being the event completing the
+	/// payment found in the storage.
+	fn progress(
+		&self,
+		registry: &PaymentsRegistry,
+		chain_state: &ChainState,
+		stored: Option<Event>,
+	) -> Option<PaymentProgress> {
+		let transfers = initiator_transfers(
+			chain_state,
+			self.token_network_address,
+			self.target,
+			self.identifier,
+		);
+		let status = match registry.get(self.target, self.identifier) {
+			Some(payment) => payment.status.clone(),
+			None if !transfers.is_empty() => None,
+			None => Some(stored.and_then(payment_sent_status)?),
+		};
+		Some(payment_progress(status, &transfers))
+	}
+
+	fn register(&self, registry: &mut PaymentsRegistry) {
+		let _ = registry.register(
+			self.token_network_address,
+			self.target,
+			self.identifier,
+			TokenAmount::from(10),
+		);
+	}
+
+	fn sent_success(&self) -> Event {
+		Event::PaymentSentSuccess(PaymentSentSuccess {
+			token_network_registry_address: TokenNetworkRegistryAddress::zero(),
+			token_network_address: self.token_network_address,
+			identifier: self.identifier,
+			amount: TokenAmount::from(10),
+			target: self.target,
+			secret: Bytes(vec![1; 32]),
+			route: vec![],
+		})
+	}
+
+	fn sent_failed(&self, reason: &str) -> Event {
+		Event::ErrorPaymentSentFailed(ErrorPaymentSentFailed {
+			token_network_registry_address: TokenNetworkRegistryAddress::zero(),
+			token_network_address: self.token_network_address,
+			identifier: self.identifier,
+			target: self.target,
+			reason: reason.to_owned(),
+		})
+	}
+
+	/// Start a transfer for the payment, as the initiator does once a route was found.
+	fn start_transfer(&self, chain_state: &mut ChainState) -> SecretHash {
+		let secrethash = SecretHash::random();
+		let route = RouteState {
+			route: vec![chain_state.our_address, self.target],
+			address_to_metadata: HashMap::new(),
+			swaps: HashMap::new(),
+			estimated_fee: TokenAmount::zero(),
+		};
+		let transfer = InitiatorTransferState {
+			route: route.clone(),
+			transfer_description: TransferDescriptionWithSecretState {
+				token_network_registry_address: TokenNetworkRegistryAddress::zero(),
+				payment_identifier: self.identifier,
+				amount: TokenAmount::from(10),
+				token_network_address: self.token_network_address,
+				initiator: chain_state.our_address,
+				target: self.target,
+				secret: Bytes(vec![1; 32]),
+				secrethash,
+				lock_timeout: None,
+			},
+			channel_identifier: U256::one(),
+			transfer: LockedTransferState {
+				payment_identifier: self.identifier,
+				token: Address::zero(),
+				lock: HashTimeLockState::create(
+					TokenAmount::from(10),
+					BlockExpiration::from(100),
+					secrethash,
+				),
+				initiator: chain_state.our_address,
+				target: self.target,
+				message_identifier: 1,
+				route_states: vec![route.clone()],
+				balance_proof: BalanceProofState {
+					nonce: U256::one(),
+					transferred_amount: TokenAmount::zero(),
+					locked_amount: TokenAmount::from(10),
+					locksroot: Locksroot::zero(),
+					canonical_identifier: CanonicalIdentifier {
+						chain_identifier: chain_state.chain_id,
+						token_network_address: self.token_network_address,
+						channel_identifier: U256::one(),
+					},
+					balance_hash: BalanceHash::zero(),
+					message_hash: None,
+					signature: None,
+					sender: Some(chain_state.our_address),
+				},
+				secret: None,
+			},
+			received_secret_request: false,
+			transfer_state: TransferState::Pending,
+		};
+		let task = InitiatorTask {
+			role: TransferRole::Initiator,
+			token_network_address: self.token_network_address,
+			manager_state: InitiatorPaymentState {
+				routes: vec![route],
+				initiator_transfers: HashMap::from([(secrethash, transfer)]),
+				cancelled_channels: vec![],
+			},
+		};
+		chain_state
+			.payment_mapping
+			.secrethashes_to_task
+			.insert(secrethash, TransferTask::Initiator(task));
+		secrethash
+	}
+}
+
+fn chain_state() -> ChainState {
+	ChainState::new(
+		ChainID::Private(U256::from(61)),
+		BlockNumber::from(10),
+		BlockHash::zero(),
+		Address::random(),
+	)
+}
+
+fn initiator_transfer(
+	chain_state: &mut ChainState,
+	secrethash: SecretHash,
+) -> &mut InitiatorTransferState {
+	match chain_state.payment_mapping.secrethashes_to_task.get_mut(&secrethash) {
+		Some(TransferTask::Initiator(task)) => task
+			.manager_state
+			.initiator_transfers
+			.get_mut(&secrethash)
+			.expect("Transfer should exist"),
+		_ => panic!("Initiator task should exist"),
+	}
+}
+
+#[test]
+fn test_poll_payment_until_unlocked() {
+	let mut chain_state = chain_state();
+	let mut registry = PaymentsRegistry::new();
+	let payment = Payment::new(1);
+
+	// Without waiting, the payment is only registered when the API returns.
+	payment.register(&mut registry);
+	assert_eq!(payment.progress(&registry, &chain_state, None), Some(PaymentProgress::Pending));
+
+	// The lock is pending as soon as the locked transfer was sent.
+	let secrethash = payment.start_transfer(&mut chain_state);
+	assert_eq!(payment.progress(&registry, &chain_state, None), Some(PaymentProgress::Locked));
+
+	initiator_transfer(&mut chain_state, secrethash).received_secret_request = true;
+	assert_eq!(payment.progress(&registry, &chain_state, None), Some(PaymentProgress::Locked));
+
+	initiator_transfer(&mut chain_state, secrethash).transfer_state = TransferState::SecretRevealed;
+	assert_eq!(payment.progress(&registry, &chain_state, None), Some(PaymentProgress::Locked));
+
+	// The completed payment is dropped, its status is read from the stored event.
+	registry.complete(PaymentStatus::Success(payment.target, payment.identifier));
+	chain_state.payment_mapping.secrethashes_to_task.clear();
+	assert!(registry.get(payment.target, payment.identifier).is_none());
+	assert_eq!(
+		payment.progress(&registry, &chain_state, Some(payment.sent_success())),
+		Some(PaymentProgress::Unlocked)
+	);
+	assert_eq!(payment.progress(&registry, &chain_state, None), None);
+}
+
+#[test]
+fn test_poll_rerouted_payment() {
+	let mut chain_state = chain_state();
+	let mut registry = PaymentsRegistry::new();
+	let payment = Payment::new(1);
+	payment.register(&mut registry);
+
+	// The first transfer expired, the payment was sent again through another route.
+	for transfer_state in [TransferState::Expired, TransferState::Pending] {
+		let secrethash = payment.start_transfer(&mut chain_state);
+		initiator_transfer(&mut chain_state, secrethash).transfer_state = transfer_state;
+	}
+	assert_eq!(
+		initiator_transfers(
+			&chain_state,
+			payment.token_network_address,
+			payment.target,
+			payment.identifier
+		)
+		.len(),
+		2
+	);
+	assert_eq!(payment.progress(&registry, &chain_state, None), Some(PaymentProgress::Locked));
+}
+
+#[test]
+fn test_poll_payment_failed_or_cancelled() {
+	let chain_state = chain_state();
+	let mut registry = PaymentsRegistry::new();
+	let rejected = Payment::new(1);
+	let failed = Payment::new(2);
+	let cancelled = Payment::new(3);
+	for payment in [&rejected, &failed, &cancelled] {
+		payment.register(&mut registry);
+	}
+
+	// Nothing is stored for a payment which did not reach the state machine.
+	registry.fail(rejected.target, rejected.identifier, "no route available".to_owned());
+	assert_eq!(
+		rejected.progress(&registry, &chain_state, None),
+		Some(PaymentProgress::Failed("no route available".to_owned()))
+	);
+
+	registry.complete(PaymentStatus::Error(
+		failed.target,
+		failed.identifier,
+		"lock expired".to_owned(),
+	));
+	assert_eq!(
+		failed.progress(&registry, &chain_state, Some(failed.sent_failed("lock expired"))),
+		Some(PaymentProgress::Failed("lock expired".to_owned()))
+	);
+
+	registry.complete(PaymentStatus::Cancelled(cancelled.target, cancelled.identifier));
+	assert_eq!(
+		cancelled.progress(
+			&registry,
+			&chain_state,
+			Some(cancelled.sent_failed(PAYMENT_CANCELED_REASON))
+		),
+		Some(PaymentProgress::Cancelled)
+	);
+}
+
+#[test]
+fn test_poll_payment_after_restart() {
+	let mut chain_state = chain_state();
+	let registry = PaymentsRegistry::new();
+	let sent = Payment::new(1);
+	let ongoing = Payment::new(2);
+	let unknown = Payment::new(3);
+
+	// The ongoing payment is restored with the chain state.
+	ongoing.start_transfer(&mut chain_state);
+
+	assert_eq!(
+		sent.progress(&registry, &chain_state, Some(sent.sent_success())),
+		Some(PaymentProgress::Unlocked)
+	);
+	assert_eq!(ongoing.progress(&registry, &chain_state, None), Some(PaymentProgress::Locked));
+	assert_eq!(unknown.progress(&registry, &chain_state, None), None);
+}
+
+#[test]
+fn test_complete_notifies_once() {
+	let mut registry = PaymentsRegistry::new();
+	let payment = Payment::new(1);
+	let mut completed = registry.register(
+		payment.token_network_address,
+		payment.target,
+		payment.identifier,
+		TokenAmount::from(10),
+	);
+
+	// The first status wins, a cancelled payment also fails in the state machine.
+	registry.complete(PaymentStatus::Cancelled(payment.target, payment.identifier));
+	registry.complete(PaymentStatus::Error(
+		payment.target,
+		payment.identifier,
+		PAYMENT_CANCELED_REASON.to_owned(),
+	));
+	assert!(matches!(completed.try_recv(), Ok(PaymentStatus::Cancelled(_, _))));
+	assert!(registry.get(payment.target, payment.identifier).is_none());
+}
+
+#[test]
+fn test_poll_payment_ignores_other_transfers() {
+	let mut chain_state = chain_state();
+	let payment = Payment::new(1);
+	payment.start_transfer(&mut chain_state);
+
+	// Same target and identifier, but another token network.
+	let other_network = Payment {
+		token_network_address: TokenNetworkAddress::random(),
+		target: payment.target,
+		identifier: payment.identifier,
+	};
+	// Same token network and identifier, but another target.
+	let other_target = Payment {
+		token_network_address: payment.token_network_address,
+		target: Address::random(),
+		identifier: payment.identifier,
+	};
+	// Same token network and target, but another identifier.
+	let other_identifier = Payment {
+		token_network_address: payment.token_network_address,
+		target: payment.target,
+		identifier: PaymentIdentifier::from(2),
+	};
+
+	assert_eq!(
+		initiator_transfers(
+			&chain_state,
+			payment.token_network_address,
+			payment.target,
+			payment.identifier
+		)
+		.len(),
+		1
+	);
+	for other in [other_network, other_target, other_identifier] {
+		assert!(initiator_transfers(
+			&chain_state,
+			other.token_network_address,
+			other.target,
+			other.identifier
+		)
+		.is_empty());
+	}
+}
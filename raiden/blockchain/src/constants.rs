@@ -2,3 +2,8 @@ pub const TRANSACTION_GAS_LIMIT_UPPER_BOUND: u64 = 1_256_636; // int(0.4 * 3_141
 pub const TRANSACTION_INTRINSIC_GAS: u64 = 21_000;
 pub const UNLOCK_TX_GAS_LIMIT: u64 = TRANSACTION_GAS_LIMIT_UPPER_BOUND;
 pub const GAS_RESERVE_ESTIMATE_SECURITY_FACTOR: f64 = 1.1;
+pub const FEE_HISTORY_BLOCK_COUNT: u64 = 10;
+pub const MAX_FEE_BASE_FEE_MULTIPLIER: u64 = 2;
+pub const TIME_CRITICAL_REWARD_PERCENTILE: f64 = 90.0;
+pub const EIP1559_TRANSACTION_TYPE: u64 = 2;
+pub const MIN_REPLACEMENT_FEE_BUMP: u64 = 10;
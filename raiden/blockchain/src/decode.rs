@@ -14,7 +14,7 @@ use raiden_primitives::{
 	},
 };
 use raiden_state_machine::{
-	storage::StateStorage,
+	storage::Storage,
 	types::{
 		ChainState,
 		ChannelState,
@@ -71,7 +71,7 @@ impl EventDecoder {
 		&self,
 		event: Event,
 		chain_state: &ChainState,
-		storage: Arc<StateStorage>,
+		storage: Arc<dyn Storage>,
 	) -> Result<Option<StateChange>> {
 		debug!(message = "Decoding blockchain event", name = event.name);
 		match event.name.as_ref() {
@@ -581,7 +581,7 @@ impl EventDecoder {
 		&self,
 		chain_state: &ChainState,
 		event: Event,
-		storage: Arc<StateStorage>,
+		storage: Arc<dyn Storage>,
 	) -> Result<Option<StateChange>> {
 		let token_network_address = event.address;
 		let participant1 = match event.data.get("sender") {
@@ -24,3 +24,14 @@ impl From<ethabi::Error> for ContractDefError {
 		Self::ABI(e)
 	}
 }
+
+/// The signer error type
+#[derive(Error, Debug)]
+pub enum SignerError {
+	#[error("Could not sign: `{0}`")]
+	Signing(String),
+	#[error("Could not decrypt: `{0}`")]
+	Decryption(String),
+	#[error("Signer request failed: `{0}`")]
+	Remote(String),
+}
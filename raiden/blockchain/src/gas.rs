@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use raiden_primitives::types::GasPrice;
+use web3::{
+	contract::Options,
+	types::{
+		BlockNumber,
+		U64,
+	},
+	Transport,
+	Web3,
+};
+
+use crate::{
+	constants::{
+		EIP1559_TRANSACTION_TYPE,
+		FEE_HISTORY_BLOCK_COUNT,
+		MAX_FEE_BASE_FEE_MULTIPLIER,
+		TIME_CRITICAL_REWARD_PERCENTILE,
+	},
+	proxies::ProxyError,
+};
+
+/// How urgently a transaction has to be mined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionPriority {
+	/// Transactions which may wait for cheaper fees, such as opening a channel.
+	Normal,
+	/// Transactions which lose funds when mined too late, such as updating the balance proof
+	/// of a closed channel or registering a secret before the lock expires.
+	TimeCritical,
+}
+
+/// The fees of an EIP-1559 transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GasFees {
+	pub max_fee_per_gas: GasPrice,
+	pub max_priority_fee_per_gas: GasPrice,
+}
+
+impl GasFees {
+	/// Returns a new instance of `GasFees`, the priority fee never exceeds the max fee.
+	pub fn new(max_fee_per_gas: GasPrice, max_priority_fee_per_gas: GasPrice) -> Self {
+		Self {
+			max_fee_per_gas,
+			max_priority_fee_per_gas: max_priority_fee_per_gas.min(max_fee_per_gas),
+		}
+	}
+
+	/// Returns the fees for the next block's base fee and the given priority fee, leaving
+	/// headroom for the base fee to rise over the next blocks.
+	pub fn from_base_fee(base_fee: GasPrice, max_priority_fee_per_gas: GasPrice) -> Self {
+		let max_fee_per_gas = base_fee
+			.saturating_mul(MAX_FEE_BASE_FEE_MULTIPLIER.into())
+			.saturating_add(max_priority_fee_per_gas);
+		Self::new(max_fee_per_gas, max_priority_fee_per_gas)
+	}
+
+	/// Set the fees of a type-2 transaction on the transaction options.
+	pub fn apply(&self, opt: &mut Options) {
+		opt.transaction_type = Some(U64::from(EIP1559_TRANSACTION_TYPE));
+		opt.max_fee_per_gas = Some(self.max_fee_per_gas);
+		opt.max_priority_fee_per_gas = Some(self.max_priority_fee_per_gas);
+	}
+}
+
+/// The state of the fee market over the most recent blocks.
+#[derive(Clone, Debug, Default)]
+pub struct FeeMarket {
+	/// Base fee of the next block.
+	pub base_fee: GasPrice,
+	/// Gas price suggested by the gas price oracle of the node.
+	pub suggested_gas_price: GasPrice,
+	/// Priority fees paid in the recent blocks at the reward percentile of the strategy.
+	pub rewards: Vec<GasPrice>,
+}
+
+impl FeeMarket {
+	/// Fetch the fee market from `eth_feeHistory`, along with the priority fees paid at
+	/// `reward_percentile` if set.
+	pub async fn fetch<T: Transport>(
+		web3: &Web3<T>,
+		reward_percentile: Option<f64>,
+	) -> Result<Self, ProxyError> {
+		let history = web3
+			.eth()
+			.fee_history(
+				FEE_HISTORY_BLOCK_COUNT.into(),
+				BlockNumber::Latest,
+				reward_percentile.map(|percentile| vec![percentile]),
+			)
+			.await?;
+		let suggested_gas_price = web3.eth().gas_price().await?;
+
+		let base_fee = history.base_fee_per_gas.last().copied().unwrap_or_default();
+		let rewards = history
+			.reward
+			.unwrap_or_default()
+			.into_iter()
+			.filter_map(|block_rewards| block_rewards.first().copied())
+			.collect();
+
+		Ok(Self { base_fee, suggested_gas_price, rewards })
+	}
+
+	/// The median of the priority fees paid in the recent blocks.
+	pub fn median_reward(&self) -> GasPrice {
+		let mut rewards = self.rewards.clone();
+		rewards.sort();
+		rewards.get(rewards.len() / 2).copied().unwrap_or_default()
+	}
+}
+
+/// Computes the fees of transactions from the state of the fee market.
+pub trait GasStrategy: Send + Sync {
+	/// The percentile of the priority fees paid in recent blocks needed by the strategy.
+	fn reward_percentile(&self) -> Option<f64> {
+		None
+	}
+
+	/// Returns the fees of a transaction sent now.
+	fn fees(&self, market: &FeeMarket) -> GasFees;
+
+	/// The highest fee per gas the strategy pays, which replacements must not exceed either.
+	fn max_fee_per_gas(&self) -> Option<GasPrice> {
+		None
+	}
+}
+
+/// Always use the same fees, regardless of the fee market.
+pub struct FixedGasStrategy {
+	pub fees: GasFees,
+}
+
+impl GasStrategy for FixedGasStrategy {
+	fn fees(&self, _market: &FeeMarket) -> GasFees {
+		self.fees
+	}
+}
+
+/// Tip what the gas price oracle of the node suggests on top of the base fee.
+pub struct OracleGasStrategy;
+
+impl GasStrategy for OracleGasStrategy {
+	fn fees(&self, market: &FeeMarket) -> GasFees {
+		let priority_fee = market.suggested_gas_price.saturating_sub(market.base_fee);
+		GasFees::from_base_fee(market.base_fee, priority_fee)
+	}
+}
+
+/// Tip the median of the priority fees paid at a percentile of the recent blocks.
+pub struct PercentileGasStrategy {
+	pub percentile: f64,
+}
+
+impl GasStrategy for PercentileGasStrategy {
+	fn reward_percentile(&self) -> Option<f64> {
+		Some(self.percentile)
+	}
+
+	fn fees(&self, market: &FeeMarket) -> GasFees {
+		GasFees::from_base_fee(market.base_fee, market.median_reward())
+	}
+}
+
+/// Never pay more than `max_fee_per_gas` for the fees computed by another strategy.
+pub struct CappedGasStrategy {
+	pub inner: Arc<dyn GasStrategy>,
+	pub max_fee_per_gas: GasPrice,
+}
+
+impl GasStrategy for CappedGasStrategy {
+	fn reward_percentile(&self) -> Option<f64> {
+		self.inner.reward_percentile()
+	}
+
+	fn fees(&self, market: &FeeMarket) -> GasFees {
+		let fees = self.inner.fees(market);
+		GasFees::new(fees.max_fee_per_gas.min(self.max_fee_per_gas), fees.max_priority_fee_per_gas)
+	}
+
+	fn max_fee_per_gas(&self) -> Option<GasPrice> {
+		let inner = self.inner.max_fee_per_gas().unwrap_or(self.max_fee_per_gas);
+		Some(inner.min(self.max_fee_per_gas))
+	}
+}
+
+/// The gas strategies used for each transaction priority.
+#[derive(Clone)]
+pub struct GasStrategies {
+	normal: Arc<dyn GasStrategy>,
+	time_critical: Arc<dyn GasStrategy>,
+}
+
+impl GasStrategies {
+	/// Returns a new instance of `GasStrategies`.
+	pub fn new(normal: Arc<dyn GasStrategy>, time_critical: Arc<dyn GasStrategy>) -> Self {
+		Self { normal, time_critical }
+	}
+
+	/// Returns the strategy used for transactions of `priority`.
+	pub fn strategy(&self, priority: TransactionPriority) -> Arc<dyn GasStrategy> {
+		match priority {
+			TransactionPriority::Normal => self.normal.clone(),
+			TransactionPriority::TimeCritical => self.time_critical.clone(),
+		}
+	}
+
+	/// Returns the highest fee per gas paid for transactions of `priority`, if capped.
+	pub fn max_fee_per_gas(&self, priority: TransactionPriority) -> Option<GasPrice> {
+		self.strategy(priority).max_fee_per_gas()
+	}
+
+	/// Fetch the fee market and compute the fees of a transaction of `priority`.
+	pub async fn fees<T: Transport>(
+		&self,
+		web3: &Web3<T>,
+		priority: TransactionPriority,
+	) -> Result<GasFees, ProxyError> {
+		let strategy = self.strategy(priority);
+		let market = FeeMarket::fetch(web3, strategy.reward_percentile()).await?;
+		Ok(strategy.fees(&market))
+	}
+}
+
+impl Default for GasStrategies {
+	fn default() -> Self {
+		Self {
+			normal: Arc::new(OracleGasStrategy),
+			time_critical: Arc::new(PercentileGasStrategy {
+				percentile: TIME_CRITICAL_REWARD_PERCENTILE,
+			}),
+		}
+	}
+}
@@ -1,4 +1,7 @@
-use std::fs::File;
+use std::{
+	fs::File,
+	future,
+};
 
 pub use ecies::SecpError;
 use ethsign::{
@@ -10,34 +13,82 @@ use raiden_primitives::{
 	signing::hash_data,
 	types::{
 		Address,
+		Bytes,
 		H256,
 	},
 };
-use web3::signing::{
-	Key,
-	Signature,
-	SigningError,
+use serde_json::{
+	json,
+	Value,
+};
+use tokio::{
+	runtime::{
+		Handle,
+		RuntimeFlavor,
+	},
+	task,
+};
+use tracing::error;
+use web3::{
+	error::TransportError,
+	signing::{
+		Key,
+		Signature,
+		SigningError,
+	},
+	transports::Http,
+	types::{
+		TransactionParameters,
+		TransactionRequest,
+		U64,
+	},
+	RequestId,
+	Transport,
+	Web3,
 };
 
+use crate::errors::SignerError;
+
 /// Encrypt bytes with the receiver's public key.
 pub fn encrypt(receiver_pub: &[u8], data: &[u8]) -> Result<Vec<u8>, SecpError> {
 	ecies::encrypt(receiver_pub, data)
 }
 
-/// Decrypts bytes using the current account's private key.
-pub fn decrypt(private_key: &PrivateKey, data: &[u8]) -> Result<Vec<u8>, SecpError> {
-	ecies::decrypt(private_key.plain.as_ref(), data)
+/// Signs messages and transactions and decrypts secrets on behalf of an account.
+///
+/// Signers may need a round trip to another process, the synchronous methods block on it and
+/// therefore have to be called from within a multi-threaded Tokio runtime. They fail instead on
+/// any other thread.
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync {
+	/// The address of the account.
+	fn address(&self) -> Address;
+
+	/// Sign `message` as an Ethereum signed message, the recovery id of the signature is 27 or
+	/// 28.
+	fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError>;
+
+	/// Sign a transaction and return it encoded for `eth_sendRawTransaction`.
+	///
+	/// Every parameter of the transaction, including the chain ID, has to be set.
+	async fn sign_transaction(
+		&self,
+		transaction: TransactionParameters,
+	) -> Result<Bytes, SignerError>;
+
+	/// Decrypt data encrypted with the public key of the account.
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, SignerError>;
 }
 
-/// A wrapper of `SecretKey` to use for signing.
+/// Signs with a private key unlocked from a keystore file.
 #[derive(Clone)]
-pub struct PrivateKey {
+pub struct KeystoreSigner {
 	plain: Protected,
 	inner: SecretKey,
 }
 
-impl PrivateKey {
-	/// Creates a new instance of `PrivateKey`.
+impl KeystoreSigner {
+	/// Creates a new instance of `KeystoreSigner`.
 	pub fn new(filename: String, password: String) -> Result<Self, String> {
 		let file = File::open(&filename)
 			.map_err(|e| format!("Could not open file {}: {}", filename, e))?;
@@ -50,17 +101,61 @@ impl PrivateKey {
 			.decrypt(&password.into())
 			.map_err(|e| format!("Could not decrypt private key file {}: {}", filename, e))?;
 
-		let inner = SecretKey::from_raw(&plain)
-			.map_err(|e| format!("Could not generate secret key from file {}: {}", filename, e))?;
+		Self::from_raw(&plain)
+			.map_err(|e| format!("Could not generate secret key from file {}: {}", filename, e))
+	}
+
+	/// Creates a signer of the raw private key `plain`.
+	pub fn from_raw(plain: &[u8]) -> Result<Self, String> {
+		let inner = SecretKey::from_raw(plain).map_err(|e| e.to_string())?;
+		Ok(Self { plain: plain.to_vec().into(), inner })
+	}
+}
+
+#[async_trait::async_trait]
+impl Signer for KeystoreSigner {
+	fn address(&self) -> Address {
+		Address::from(self.inner.public().address())
+	}
+
+	fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError> {
+		let data_hash = hash_data(message);
+		let signature = self.inner.sign(&data_hash).map_err(|_| SigningError::InvalidMessage)?;
 
-		Ok(Self { plain: plain.into(), inner })
+		Ok(Signature {
+			r: H256::from(signature.r),
+			s: H256::from(signature.s),
+			v: signature.v as u64 + 27,
+		})
+	}
+
+	async fn sign_transaction(
+		&self,
+		transaction: TransactionParameters,
+	) -> Result<Bytes, SignerError> {
+		let signed = Web3::new(OfflineTransport)
+			.accounts()
+			.sign_transaction(transaction, TransactionKey(&self.inner))
+			.await
+			.map_err(|e| SignerError::Signing(e.to_string()))?;
+		Ok(signed.raw_transaction)
+	}
+
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+		ecies::decrypt(self.plain.as_ref(), data)
+			.map_err(|e| SignerError::Decryption(format!("{:?}", e)))
 	}
 }
 
-impl Key for PrivateKey {
-	/// Signs bytes using the inner `SecretKey` with chain ID.
+/// Signs the hashes of transactions encoded by web3.
+///
+/// Unlike messages, the hashes are signed as they are, and the recovery id of typed
+/// transactions has no offset.
+struct TransactionKey<'a>(&'a SecretKey);
+
+impl Key for TransactionKey<'_> {
 	fn sign(&self, message: &[u8], chain_id: Option<u64>) -> Result<Signature, SigningError> {
-		let signature = self.inner.sign(message).map_err(|_| SigningError::InvalidMessage)?;
+		let signature = self.0.sign(message).map_err(|_| SigningError::InvalidMessage)?;
 
 		let standard_v = signature.v as u64;
 		let v = if let Some(chain_id) = chain_id {
@@ -71,19 +166,169 @@ impl Key for PrivateKey {
 		Ok(Signature { r: H256::from(signature.r), s: H256::from(signature.s), v })
 	}
 
-	/// Signs message bytes using the inner `SecretKey`.
 	fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError> {
-		let data_hash = hash_data(message);
-		let signature = self.inner.sign(&data_hash).map_err(|_| SigningError::InvalidMessage)?;
+		let signature = self.0.sign(message).map_err(|_| SigningError::InvalidMessage)?;
 
 		Ok(Signature {
 			r: H256::from(signature.r),
 			s: H256::from(signature.s),
-			v: signature.v as u64 + 27,
+			v: signature.v as u64,
 		})
 	}
 
 	fn address(&self) -> Address {
-		Address::from(self.inner.public().address())
+		Address::from(self.0.public().address())
 	}
 }
+
+/// A transport which rejects every request.
+///
+/// web3 encodes and signs a transaction without using its transport once every parameter is
+/// set.
+#[derive(Clone, Debug)]
+struct OfflineTransport;
+
+impl Transport for OfflineTransport {
+	type Out = future::Ready<web3::Result<Value>>;
+
+	fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, jsonrpc_core::Call) {
+		(0, web3::helpers::build_request(0, method, params))
+	}
+
+	fn send(&self, _id: RequestId, request: jsonrpc_core::Call) -> Self::Out {
+		future::ready(Err(web3::Error::Transport(TransportError::Message(format!(
+			"Request {:?} needs a connection to a node",
+			request
+		)))))
+	}
+}
+
+/// Signs with an external signer speaking JSON-RPC, in the style of Clef and Web3Signer, so
+/// that the private key never enters this process.
+///
+/// Messages are signed with `eth_sign` and transactions with `eth_signTransaction`, which
+/// returns the raw transaction, either as it is or in the `raw` field of an object. Secrets are
+/// decrypted with `raiden_decrypt`, which takes the address and the encrypted data and returns the
+/// plain data, all hex encoded.
+#[derive(Clone)]
+pub struct RemoteSigner {
+	transport: Http,
+	address: Address,
+}
+
+impl RemoteSigner {
+	/// Connect to the signer at `url`, which has to hold the account of `address`.
+	pub async fn connect(url: &str, address: Address) -> Result<Self, SignerError> {
+		let transport = Http::new(url).map_err(|e| SignerError::Remote(e.to_string()))?;
+		let signer = Self { transport, address };
+
+		let accounts: Vec<Address> =
+			serde_json::from_value(signer.request("eth_accounts", vec![]).await?)
+				.map_err(|e| SignerError::Remote(format!("Invalid accounts: {}", e)))?;
+		if !accounts.contains(&address) {
+			return Err(SignerError::Remote(format!("Signer does not hold account {:#x}", address)))
+		}
+		Ok(signer)
+	}
+
+	/// Send a request to the signer.
+	async fn request(&self, method: &str, params: Vec<Value>) -> Result<Value, SignerError> {
+		self.transport
+			.execute(method, params)
+			.await
+			.map_err(|e| SignerError::Remote(format!("{} failed: {}", method, e)))
+	}
+
+	/// Send a request from synchronous code, messages are signed and secrets decrypted while
+	/// handling state changes.
+	///
+	/// Fails outside of a multi-threaded runtime, where blocking on the request is not possible.
+	fn request_blocking(&self, method: &str, params: Vec<Value>) -> Result<Value, SignerError> {
+		let handle = Handle::try_current().map_err(|_| {
+			SignerError::Remote(format!("{} has to be called from a Tokio runtime", method))
+		})?;
+		if handle.runtime_flavor() != RuntimeFlavor::MultiThread {
+			return Err(SignerError::Remote(format!(
+				"{} has to be called from a multi-threaded Tokio runtime",
+				method
+			)))
+		}
+		task::block_in_place(|| handle.block_on(self.request(method, params)))
+	}
+}
+
+#[async_trait::async_trait]
+impl Signer for RemoteSigner {
+	fn address(&self) -> Address {
+		self.address
+	}
+
+	fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError> {
+		let signature = self
+			.request_blocking("eth_sign", vec![json!(self.address), hex_value(message)])
+			.and_then(decode_hex)
+			.map_err(|e| {
+				error!(message = "Remote signer could not sign message", error = e.to_string());
+				SigningError::InvalidMessage
+			})?;
+		if signature.len() != 65 {
+			return Err(SigningError::InvalidMessage)
+		}
+
+		let v = signature[64] as u64;
+		Ok(Signature {
+			r: H256::from_slice(&signature[..32]),
+			s: H256::from_slice(&signature[32..64]),
+			v: if v < 27 { v + 27 } else { v },
+		})
+	}
+
+	async fn sign_transaction(
+		&self,
+		transaction: TransactionParameters,
+	) -> Result<Bytes, SignerError> {
+		let request = TransactionRequest {
+			from: self.address,
+			to: transaction.to,
+			gas: Some(transaction.gas),
+			gas_price: transaction.gas_price,
+			value: Some(transaction.value),
+			data: Some(transaction.data),
+			nonce: transaction.nonce,
+			transaction_type: transaction.transaction_type,
+			access_list: transaction.access_list,
+			max_fee_per_gas: transaction.max_fee_per_gas,
+			max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+			..Default::default()
+		};
+		let mut request = serde_json::to_value(request)
+			.map_err(|e| SignerError::Signing(format!("Could not encode transaction: {}", e)))?;
+		request["chainId"] = json!(transaction.chain_id.map(U64::from));
+
+		// Clef returns the raw transaction along with the decoded one.
+		let mut response = self.request("eth_signTransaction", vec![request]).await?;
+		if let Some(raw) = response.get_mut("raw") {
+			response = raw.take();
+		}
+		Ok(Bytes(decode_hex(response)?))
+	}
+
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+		self.request_blocking("raiden_decrypt", vec![json!(self.address), hex_value(data)])
+			.and_then(decode_hex)
+	}
+}
+
+/// Encode bytes as a hex string value.
+fn hex_value(data: &[u8]) -> Value {
+	Value::String(format!("0x{}", hex::encode(data)))
+}
+
+/// Decode a hex string value returned by the signer.
+fn decode_hex(value: Value) -> Result<Vec<u8>, SignerError> {
+	let hex_string = value
+		.as_str()
+		.ok_or_else(|| SignerError::Remote(format!("Expected a hex string, got {}", value)))?;
+	hex::decode(hex_string.trim_start_matches("0x"))
+		.map_err(|e| SignerError::Remote(format!("Invalid hex string: {}", e)))
+}
@@ -12,11 +12,15 @@ pub mod errors;
 pub mod events;
 /// Filters module.
 pub mod filters;
+/// Gas price strategies module.
+pub mod gas;
 /// Keys module.
 pub mod keys;
 /// Proxies module.
 pub mod proxies;
 /// Secret module.
 pub mod secret;
+#[cfg(test)]
+mod tests;
 /// Transactions module.
 pub mod transactions;
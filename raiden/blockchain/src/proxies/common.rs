@@ -4,19 +4,42 @@ use raiden_primitives::types::{
 	Address,
 	U256,
 };
+use raiden_state_machine::storage::types::TransactionRecord;
 use tokio::sync::Mutex;
+use tracing::{
+	info,
+	warn,
+};
 use web3::{
-	signing::Key,
+	contract::{
+		tokens::Tokenize,
+		Contract,
+		Options,
+	},
 	types::{
 		BlockNumber,
+		TransactionParameters,
+		TransactionReceipt,
+		H256,
 		U64,
 	},
 	Transport,
 	Web3,
 };
 
-use super::ProxyError;
-use crate::keys::PrivateKey;
+use super::{
+	ProxyError,
+	TransactionTracker,
+};
+use crate::{
+	constants::EIP1559_TRANSACTION_TYPE,
+	gas::{
+		GasFees,
+		GasStrategies,
+		TransactionPriority,
+	},
+	keys::Signer,
+};
 
 /// Proxies error type.
 pub type Result<T> = std::result::Result<T, ProxyError>;
@@ -33,11 +56,12 @@ impl Nonce {
 		Self { inner: Arc::new(Mutex::new(current)) }
 	}
 
-	/// Retrieves the next nonce
+	/// Hands out the next nonce and advances it.
 	pub async fn next(&self) -> U256 {
 		let mut inner = self.inner.lock().await;
+		let nonce = *inner;
 		*inner += U256::from(1);
-		*inner
+		nonce
 	}
 
 	/// Immutably get the next nonce.
@@ -45,33 +69,48 @@ impl Nonce {
 		let inner = self.inner.lock().await;
 		*inner
 	}
+
+	/// Set the next nonce.
+	pub async fn set(&self, next: U256) {
+		let mut inner = self.inner.lock().await;
+		*inner = next;
+	}
 }
 
-/// The account type holding nonce and private key.
+/// The account type holding nonce, signer, the gas strategies of its transactions and the
+/// tracker of the ones which were not mined yet.
 #[derive(Clone)]
 pub struct Account<T: Transport> {
 	web3: Web3<T>,
-	private_key: PrivateKey,
+	signer: Arc<dyn Signer>,
 	nonce: Nonce,
+	gas_strategies: GasStrategies,
+	tracker: Arc<TransactionTracker>,
 }
 
 impl<T: Transport> Account<T> {
 	/// Returns a new instance of `Account`.
-	pub fn new(web3: Web3<T>, private_key: PrivateKey, nonce: U256) -> Self {
-		Self { web3, private_key, nonce: Nonce::new(nonce) }
+	pub fn new(
+		web3: Web3<T>,
+		signer: Arc<dyn Signer>,
+		nonce: U256,
+		gas_strategies: GasStrategies,
+		tracker: Arc<TransactionTracker>,
+	) -> Self {
+		Self { web3, signer, nonce: Nonce::new(nonce), gas_strategies, tracker }
 	}
 
-	/// Returns a copy of the private key.
-	pub fn private_key(&self) -> PrivateKey {
-		self.private_key.clone()
+	/// Returns the signer of the account.
+	pub fn signer(&self) -> Arc<dyn Signer> {
+		self.signer.clone()
 	}
 
 	/// Returns the ethereum address of a key.
 	pub fn address(&self) -> Address {
-		self.private_key.address()
+		self.signer.address()
 	}
 
-	/// Retrieves the next usable nonce.
+	/// Hands out the next usable nonce to a transaction sent outside of `signed_call`.
 	pub async fn next_nonce(&self) -> U256 {
 		self.nonce.next().await
 	}
@@ -81,15 +120,233 @@ impl<T: Transport> Account<T> {
 		self.nonce.peek_next().await
 	}
 
-	/// Check account's balance and check if eth balance is insufficient.
-	pub async fn check_for_insufficient_eth(&self, required_gas: U256, block: U64) -> Result<()> {
+	/// Returns the fees of a transaction of `priority` sent now.
+	pub async fn gas_fees(&self, priority: TransactionPriority) -> Result<GasFees> {
+		self.gas_strategies.fees(&self.web3, priority).await
+	}
+
+	/// Resynchronize the next nonce with the chain after a restart.
+	///
+	/// Transactions still tracked keep their nonces reserved. This must not be called while
+	/// transactions are being sent, since the pending count does not include the ones which are
+	/// still on their way to the node.
+	pub async fn sync_nonce(&self) -> Result<()> {
+		let pending_count = self
+			.web3
+			.eth()
+			.transaction_count(self.address(), Some(BlockNumber::Pending))
+			.await?;
+		let tracked_next = self
+			.tracker
+			.pending()
+			.last()
+			.map(|transaction| transaction.nonce + 1)
+			.unwrap_or_default();
+		self.nonce.set(pending_count.max(tracked_next)).await;
+		Ok(())
+	}
+
+	/// Sign and send a call of `func` on `contract`, then wait for it to be mined.
+	///
+	/// The options have to hold the gas limit and fees of the transaction. The nonce is assigned
+	/// here and held until the transaction is broadcast, so that a failed broadcast does not leave
+	/// a gap nor shift the nonces of concurrent transactions. The transaction is replaced with
+	/// bumped fees each time it is not mined within the configured number of blocks.
+	pub async fn signed_call<P: Tokenize>(
+		&self,
+		contract: &Contract<T>,
+		func: &str,
+		params: P,
+		options: Options,
+		priority: TransactionPriority,
+	) -> Result<TransactionReceipt> {
+		let data = contract
+			.abi()
+			.function(func)
+			.and_then(|function| function.encode_input(&params.into_tokens()))
+			.map_err(|e| ProxyError::ChainError(e.into()))?;
+		let block_number = self.web3.eth().block_number().await?;
+
+		let mut next_nonce = self.nonce.inner.lock().await;
+		let mut transaction = TransactionRecord {
+			nonce: *next_nonce,
+			hashes: vec![],
+			purpose: func.to_owned(),
+			time_critical: priority == TransactionPriority::TimeCritical,
+			to: contract.address(),
+			value: options.value.unwrap_or_default(),
+			data: data.into(),
+			gas_limit: options.gas.unwrap_or_default(),
+			max_fee_per_gas: options.max_fee_per_gas.unwrap_or_default(),
+			max_priority_fee_per_gas: options.max_priority_fee_per_gas.unwrap_or_default(),
+			sent_at_block: block_number.into(),
+		};
+		self.broadcast(&mut transaction).await?;
+		*next_nonce += U256::from(1);
+		drop(next_nonce);
+
+		self.wait_for_receipt(transaction).await
+	}
+
+	/// Keep watching the transactions which were still pending when the node stopped.
+	pub async fn resume_pending_transactions(&self) {
+		let pending = self.tracker.pending();
+		if !pending.is_empty() {
+			info!(message = "Resuming pending transactions", count = pending.len());
+		}
+		for transaction in pending {
+			let purpose = transaction.purpose.clone();
+			if let Err(e) = self.wait_for_receipt(transaction).await {
+				warn!(message = "Pending transaction failed", purpose, error = e.to_string());
+			}
+		}
+	}
+
+	/// Sign the transaction with its current fees and send it, recording the hash.
+	async fn broadcast(&self, transaction: &mut TransactionRecord) -> Result<H256> {
+		let chain_id = self.web3.eth().chain_id().await?;
+		let parameters = TransactionParameters {
+			nonce: Some(transaction.nonce),
+			to: Some(transaction.to),
+			gas: transaction.gas_limit,
+			value: transaction.value,
+			data: transaction.data.clone(),
+			transaction_type: Some(U64::from(EIP1559_TRANSACTION_TYPE)),
+			max_fee_per_gas: Some(transaction.max_fee_per_gas),
+			max_priority_fee_per_gas: Some(transaction.max_priority_fee_per_gas),
+			chain_id: Some(chain_id.as_u64()),
+			..Default::default()
+		};
+		let raw_transaction = self.signer.sign_transaction(parameters).await?;
+		let hash = self.web3.eth().send_raw_transaction(raw_transaction).await?;
+		transaction.hashes.push(hash);
+		self.tracker.track(transaction);
+		Ok(hash)
+	}
+
+	/// Wait until one of the versions of the transaction is mined, replacing it with bumped fees
+	/// whenever it waited for too long.
+	///
+	/// Errors of the node are retried at the next poll, the transaction is still out there.
+	async fn wait_for_receipt(
+		&self,
+		mut transaction: TransactionRecord,
+	) -> Result<TransactionReceipt> {
+		let mut interval = tokio::time::interval(self.tracker.config().poll_interval);
+		loop {
+			interval.tick().await;
+			match self.poll_transaction(&mut transaction).await {
+				Ok(Some(result)) => return result,
+				Ok(None) => {},
+				Err(e) => warn!(
+					message = "Could not poll pending transaction",
+					purpose = transaction.purpose,
+					nonce = transaction.nonce.to_string(),
+					error = e.to_string(),
+				),
+			}
+		}
+	}
+
+	/// Check once whether the transaction was mined and replace it if it waited for too long.
+	///
+	/// Returns the outcome of the transaction once its nonce was used.
+	async fn poll_transaction(
+		&self,
+		transaction: &mut TransactionRecord,
+	) -> Result<Option<Result<TransactionReceipt>>> {
+		let mined_count = self
+			.web3
+			.eth()
+			.transaction_count(self.address(), Some(BlockNumber::Latest))
+			.await?;
+		if mined_count > transaction.nonce {
+			for hash in transaction.hashes.iter().rev() {
+				if let Some(receipt) = self.web3.eth().transaction_receipt(*hash).await? {
+					self.tracker.forget(transaction.nonce);
+					return Ok(Some(Ok(receipt)))
+				}
+			}
+			self.tracker.forget(transaction.nonce);
+			return Ok(Some(Err(ProxyError::Recoverable(format!(
+				"Nonce {} of {} was used by another transaction",
+				transaction.nonce, transaction.purpose,
+			)))))
+		}
+
+		let block_number = self.web3.eth().block_number().await?.into();
+		if self.tracker.needs_replacement(transaction, block_number) {
+			transaction.sent_at_block = block_number;
+			self.replace(transaction).await;
+		}
+		Ok(None)
+	}
+
+	/// Send the transaction again at the same nonce with fees high enough to replace it.
+	async fn replace(&self, transaction: &mut TransactionRecord) {
+		let priority = if transaction.time_critical {
+			TransactionPriority::TimeCritical
+		} else {
+			TransactionPriority::Normal
+		};
+		let previous =
+			GasFees::new(transaction.max_fee_per_gas, transaction.max_priority_fee_per_gas);
+		let current = self.gas_fees(priority).await.ok();
+		let fees = match self.tracker.replacement_fees(
+			previous,
+			current,
+			self.gas_strategies.max_fee_per_gas(priority),
+		) {
+			Some(fees) => fees,
+			None => {
+				warn!(
+					message = "Transaction is stuck at the highest allowed fee",
+					purpose = transaction.purpose,
+					nonce = transaction.nonce.to_string(),
+				);
+				return
+			},
+		};
+
+		let mut replacement = TransactionRecord {
+			max_fee_per_gas: fees.max_fee_per_gas,
+			max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+			..transaction.clone()
+		};
+		match self.broadcast(&mut replacement).await {
+			Ok(hash) => {
+				raiden_metrics::transaction_replaced(&transaction.purpose);
+				info!(
+					message = "Replaced stuck transaction",
+					purpose = transaction.purpose,
+					nonce = transaction.nonce.to_string(),
+					hash = format!("{:#x}", hash),
+				);
+				*transaction = replacement;
+			},
+			Err(e) => warn!(
+				message = "Could not replace stuck transaction",
+				purpose = transaction.purpose,
+				error = e.to_string(),
+			),
+		}
+	}
+
+	/// Check account's balance and check if eth balance is insufficient to pay the max fee of a
+	/// transaction of `priority`.
+	pub async fn check_for_insufficient_eth(
+		&self,
+		required_gas: U256,
+		block: U64,
+		priority: TransactionPriority,
+	) -> Result<()> {
 		let actual_balance = self
 			.web3
 			.eth()
 			.balance(self.address(), Some(BlockNumber::Number(block)))
 			.await?;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
-		let required_balance = required_gas * gas_price;
+		let max_fee_per_gas = self.gas_fees(priority).await?.max_fee_per_gas;
+		let required_balance = required_gas.saturating_mul(max_fee_per_gas);
 		if actual_balance < required_balance {
 			return Err(ProxyError::InsufficientEth(format!(
 				"Balance is not enough to execute transaction. Current: {}, required: {}",
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::errors::SignerError;
+
 /// The proxy error type.
 #[derive(Error, Debug)]
 pub enum ProxyError {
@@ -11,6 +13,8 @@ pub enum ProxyError {
 	Web3(#[from] web3::Error),
 	#[error(transparent)]
 	ChainError(#[from] web3::contract::Error),
+	#[error(transparent)]
+	Signer(#[from] SignerError),
 	#[error("Recoverable error: `{0}`")]
 	Recoverable(String),
 	#[error("Unrecoverable error: `{0}`")]
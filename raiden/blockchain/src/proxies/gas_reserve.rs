@@ -26,6 +26,7 @@ use crate::{
 		UNLOCK_TX_GAS_LIMIT,
 	},
 	contracts::GasMetadata,
+	gas::TransactionPriority,
 };
 
 const GAS_REQUIRED_FOR_CHANNEL_LIFECYCLE_AFTER_SETTLE: u64 = UNLOCK_TX_GAS_LIMIT;
@@ -126,15 +127,23 @@ impl GasReserve {
 		))
 	}
 
-	pub async fn get_estimate(
+	/// Estimate the ETH needed to go through the lifecycle of the channels, priced at the max fee
+	/// the gas strategies of `account` pay.
+	pub async fn get_estimate<T: Transport>(
 		&self,
+		account: &Account<T>,
 		chain_state: &ChainState,
 		channels_to_open: u64,
 	) -> Result<U256> {
 		let gas_estimate = self.get_required_gas_estimate(chain_state, channels_to_open).await?;
-		let gas_price = self.proxy_manager.web3().eth().gas_price().await?;
+		// The lifecycle includes time critical transactions, which may pay higher fees.
+		let max_fee_per_gas = account
+			.gas_fees(TransactionPriority::Normal)
+			.await?
+			.max_fee_per_gas
+			.max(account.gas_fees(TransactionPriority::TimeCritical).await?.max_fee_per_gas);
 
-		let reserve_amount: U256 = gas_price.mul(gas_estimate);
+		let reserve_amount: U256 = max_fee_per_gas.saturating_mul(gas_estimate.into());
 
 		Ok(reserve_amount.mul((100.0 * GAS_RESERVE_ESTIMATE_SECURITY_FACTOR).round() as u32 / 100))
 	}
@@ -145,7 +154,8 @@ impl GasReserve {
 		chain_state: &ChainState,
 		channels_to_open: u64,
 	) -> Result<(bool, U256)> {
-		let gas_reserve_estimate = self.get_estimate(chain_state, channels_to_open).await?;
+		let gas_reserve_estimate =
+			self.get_estimate(&account, chain_state, channels_to_open).await?;
 		let balance = self.proxy_manager.web3().eth().balance(account.address(), None).await?;
 
 		Ok((gas_reserve_estimate < balance, gas_reserve_estimate))
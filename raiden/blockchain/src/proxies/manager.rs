@@ -214,7 +214,7 @@ impl ProxyManager {
 			let token_web3_contract =
 				Contract::from_json(self.web3.eth(), token_address, token_contract.abi.as_slice())
 					.map_err(ContractDefError::ABI)?;
-			let proxy = TokenProxy::new(self.web3.clone(), token_web3_contract);
+			let proxy = TokenProxy::new(token_web3_contract);
 			let mut tokens = self.tokens.write().await;
 			tokens.insert(token_address, proxy);
 		}
@@ -8,6 +8,7 @@ mod services;
 mod token;
 mod token_network;
 mod token_network_registry;
+mod tracker;
 mod user_deposit;
 
 pub use common::*;
@@ -19,4 +20,5 @@ pub use services::*;
 pub use token::*;
 pub use token_network::*;
 pub use token_network_registry::*;
+pub use tracker::*;
 pub use user_deposit::*;
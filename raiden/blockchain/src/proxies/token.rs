@@ -13,13 +13,13 @@ use web3::{
 		Options,
 	},
 	Transport,
-	Web3,
 };
 
 use super::{
 	common::Account,
 	ProxyError,
 };
+use crate::gas::TransactionPriority;
 
 /// The token proxy error type.
 type Result<T> = std::result::Result<T, ProxyError>;
@@ -27,15 +27,14 @@ type Result<T> = std::result::Result<T, ProxyError>;
 /// Token proxy to interact with the on-chain contract.
 #[derive(Clone)]
 pub struct TokenProxy<T: Transport> {
-	web3: Web3<T>,
 	pub(crate) contract: Contract<T>,
 	pub(crate) lock: Arc<RwLock<bool>>,
 }
 
 impl<T: Transport> TokenProxy<T> {
 	/// Returns a new instance of `TokenProxy`.
-	pub fn new(web3: Web3<T>, contract: Contract<T>) -> Self {
-		Self { web3, contract, lock: Arc::new(RwLock::new(true)) }
+	pub fn new(contract: Contract<T>) -> Self {
+		Self { contract, lock: Arc::new(RwLock::new(true)) }
 	}
 
 	/// Returns the allowance amount for spender by the current account.
@@ -77,7 +76,7 @@ impl<T: Transport> TokenProxy<T> {
 		allowed_address: Address,
 		allowance: U256,
 	) -> Result<H256> {
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = account.gas_fees(TransactionPriority::Normal).await?;
 		let gas_estimate = self
 			.contract
 			.estimate_gas(
@@ -89,33 +88,26 @@ impl<T: Transport> TokenProxy<T> {
 			.await
 			.map_err(ProxyError::ChainError)?;
 
-		let nonce = account.peek_next_nonce().await;
-		account.next_nonce().await;
-
-		let receipt = self
-			.contract
-			.signed_call_with_confirmations(
+		let receipt = account
+			.signed_call(
+				&self.contract,
 				"approve",
 				(allowed_address, allowance),
 				Options::with(|opt| {
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				account.private_key(),
+				TransactionPriority::Normal,
 			)
-			.await
-			.map_err(ProxyError::Web3)?;
+			.await?;
 
 		Ok(receipt.transaction_hash)
 	}
 
 	/// Mint a specific amount and deposit into caller's account.
 	pub async fn mint(&self, account: Account<T>, amount: U256) -> Result<H256> {
-		let nonce = account.peek_next_nonce().await;
-		account.next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let nonce = account.next_nonce().await;
+		let gas_fees = account.gas_fees(TransactionPriority::Normal).await?;
 		let gas_estimate = self
 			.contract
 			.estimate_gas("mint", (amount,), account.address(), Options::default())
@@ -132,7 +124,7 @@ impl<T: Transport> TokenProxy<T> {
 				Options::with(|opt| {
 					opt.gas = Some(gas_estimate);
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
@@ -145,9 +137,8 @@ impl<T: Transport> TokenProxy<T> {
 
 	/// Mint a specific amount and deposit into `to`'s account.
 	pub async fn mint_for(&self, account: Account<T>, to: Address, amount: U256) -> Result<H256> {
-		let nonce = account.peek_next_nonce().await;
-		account.next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let nonce = account.next_nonce().await;
+		let gas_fees = account.gas_fees(TransactionPriority::Normal).await?;
 		let gas_estimate = self
 			.contract
 			.estimate_gas("mintFor", (amount, to), account.address(), Options::default())
@@ -164,7 +155,7 @@ impl<T: Transport> TokenProxy<T> {
 				Options::with(|opt| {
 					opt.gas = Some(gas_estimate);
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
@@ -0,0 +1,140 @@
+use std::{
+	sync::Arc,
+	time::Duration,
+};
+
+use raiden_primitives::types::{
+	BlockNumber,
+	U256,
+};
+use raiden_state_machine::storage::{
+	types::TransactionRecord,
+	Storage,
+};
+use tracing::warn;
+
+use crate::{
+	constants::MIN_REPLACEMENT_FEE_BUMP,
+	gas::GasFees,
+};
+
+/// Configuration of the replacement of transactions stuck in the mempool.
+#[derive(Clone, Debug)]
+pub struct TransactionTrackerConfig {
+	/// Number of blocks after which a transaction which was not mined is sent again with bumped
+	/// fees at the same nonce.
+	pub replacement_blocks: u64,
+	/// Percentage by which the fees of a replacement are raised, nodes require at least 10.
+	pub fee_bump: u8,
+	/// Time between two checks for the receipt of a transaction.
+	pub poll_interval: Duration,
+}
+
+impl Default for TransactionTrackerConfig {
+	fn default() -> Self {
+		Self { replacement_blocks: 5, fee_bump: 12, poll_interval: Duration::from_secs(3) }
+	}
+}
+
+/// Keeps the transactions sent by an account until they are mined.
+///
+/// Transactions are persisted when a storage is set, so that the ones still pending after a
+/// restart can be watched and replaced again.
+pub struct TransactionTracker {
+	config: TransactionTrackerConfig,
+	storage: Option<Arc<dyn Storage>>,
+}
+
+impl TransactionTracker {
+	/// Returns a new instance of `TransactionTracker`.
+	pub fn new(config: TransactionTrackerConfig, storage: Option<Arc<dyn Storage>>) -> Self {
+		Self { config, storage }
+	}
+
+	/// Returns the configuration of the tracker.
+	pub fn config(&self) -> &TransactionTrackerConfig {
+		&self.config
+	}
+
+	/// Record a transaction which was sent or replaced.
+	pub(crate) fn track(&self, transaction: &TransactionRecord) {
+		if let Some(storage) = &self.storage {
+			if let Err(e) = storage.store_transaction(transaction) {
+				warn!(message = "Could not persist transaction", error = e.to_string());
+			}
+		}
+	}
+
+	/// Stop tracking the transaction of `nonce` once it is mined.
+	pub(crate) fn forget(&self, nonce: U256) {
+		if let Some(storage) = &self.storage {
+			if let Err(e) = storage.delete_transaction(nonce) {
+				warn!(message = "Could not delete transaction", error = e.to_string());
+			}
+		}
+	}
+
+	/// Returns the transactions which were not mined yet, ordered by nonce.
+	pub fn pending(&self) -> Vec<TransactionRecord> {
+		match &self.storage {
+			Some(storage) => storage.get_pending_transactions().unwrap_or_else(|e| {
+				warn!(message = "Could not load pending transactions", error = e.to_string());
+				vec![]
+			}),
+			None => vec![],
+		}
+	}
+
+	/// Whether the transaction waited long enough at `block_number` to be replaced.
+	pub(crate) fn needs_replacement(
+		&self,
+		transaction: &TransactionRecord,
+		block_number: BlockNumber,
+	) -> bool {
+		block_number >= transaction.sent_at_block + self.config.replacement_blocks.into()
+	}
+
+	/// Returns the fees of `previous` raised by the configured bump.
+	pub(crate) fn bump_fees(&self, previous: GasFees) -> GasFees {
+		let bump = |fee: U256| {
+			fee.saturating_mul(U256::from(100 + u64::from(self.config.fee_bump))) / 100 + 1
+		};
+		GasFees::new(bump(previous.max_fee_per_gas), bump(previous.max_priority_fee_per_gas))
+	}
+
+	/// Returns the fees replacing a transaction sent with `previous`.
+	///
+	/// The fees are bumped, raised to the `current` fees of the market and the max fee is capped
+	/// at `max_fee_per_gas`. Returns `None` when the cap leaves no room for a replacement nodes
+	/// would accept, which requires both fees to be raised by at least 10%.
+	pub(crate) fn replacement_fees(
+		&self,
+		previous: GasFees,
+		current: Option<GasFees>,
+		max_fee_per_gas: Option<U256>,
+	) -> Option<GasFees> {
+		let mut fees = self.bump_fees(previous);
+		// The fee market may have moved further than the bump.
+		if let Some(current) = current {
+			fees = GasFees::new(
+				fees.max_fee_per_gas.max(current.max_fee_per_gas),
+				fees.max_priority_fee_per_gas.max(current.max_priority_fee_per_gas),
+			);
+		}
+		if let Some(max_fee_per_gas) = max_fee_per_gas {
+			let max_fee_per_gas = fees.max_fee_per_gas.min(max_fee_per_gas);
+			fees =
+				GasFees::new(max_fee_per_gas, fees.max_priority_fee_per_gas.min(max_fee_per_gas));
+		}
+
+		let accepted = |previous: U256, fee: U256| {
+			fee >= previous.saturating_mul(U256::from(100 + MIN_REPLACEMENT_FEE_BUMP)) / 100
+		};
+		if !accepted(previous.max_fee_per_gas, fees.max_fee_per_gas) ||
+			!accepted(previous.max_priority_fee_per_gas, fees.max_priority_fee_per_gas)
+		{
+			return None
+		}
+		Some(fees)
+	}
+}
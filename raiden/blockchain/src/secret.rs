@@ -12,7 +12,7 @@ use raiden_state_machine::types::DecryptedSecret;
 
 use crate::keys::{
 	self,
-	PrivateKey,
+	Signer,
 };
 
 /// Returns an encrypted secret from the original secret bytes and payment metadata.
@@ -42,9 +42,10 @@ pub fn encrypt_secret(
 /// Decrypts an already encrypted secret.
 pub fn decrypt_secret(
 	encrypted_secret: Vec<u8>,
-	private_key: &PrivateKey,
+	signer: &dyn Signer,
 ) -> Result<DecryptedSecret, String> {
-	let decrypted_secret = keys::decrypt(private_key, &encrypted_secret)
+	let decrypted_secret = signer
+		.decrypt(&encrypted_secret)
 		.map_err(|e| format!("Could not decrypt secret: {:?}", e))?;
 	let json = std::str::from_utf8(&decrypted_secret)
 		.map_err(|e| format!("Invalid UTF-8 sequence: {}", e))?;
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use raiden_primitives::types::{
+	GasPrice,
+	U256,
+};
+
+use crate::gas::{
+	CappedGasStrategy,
+	FeeMarket,
+	FixedGasStrategy,
+	GasFees,
+	GasStrategy,
+	OracleGasStrategy,
+	PercentileGasStrategy,
+};
+
+fn fees(max_fee_per_gas: u64, max_priority_fee_per_gas: u64) -> GasFees {
+	GasFees::new(max_fee_per_gas.into(), max_priority_fee_per_gas.into())
+}
+
+fn market(base_fee: u64, suggested_gas_price: u64, rewards: &[u64]) -> FeeMarket {
+	FeeMarket {
+		base_fee: base_fee.into(),
+		suggested_gas_price: suggested_gas_price.into(),
+		rewards: rewards.iter().map(|reward| GasPrice::from(*reward)).collect(),
+	}
+}
+
+#[test]
+fn test_gas_fees_from_base_fee() {
+	// Twice the base fee leaves room for it to rise, on top of the priority fee.
+	assert_eq!(GasFees::from_base_fee(100.into(), 10.into()), fees(210, 10));
+	assert_eq!(GasFees::from_base_fee(0.into(), 10.into()), fees(10, 10));
+	assert_eq!(GasFees::from_base_fee(U256::MAX, 10.into()), GasFees::new(U256::MAX, 10.into()));
+
+	// The priority fee never exceeds the max fee.
+	assert_eq!(fees(100, 200), fees(100, 100));
+}
+
+#[test]
+fn test_fee_market_median_reward() {
+	assert_eq!(market(0, 0, &[]).median_reward(), GasPrice::zero());
+	assert_eq!(market(0, 0, &[7]).median_reward(), GasPrice::from(7));
+	assert_eq!(market(0, 0, &[30, 10, 20]).median_reward(), GasPrice::from(20));
+	assert_eq!(market(0, 0, &[40, 10, 30, 20]).median_reward(), GasPrice::from(30));
+}
+
+#[test]
+fn test_oracle_gas_strategy() {
+	let strategy = OracleGasStrategy;
+
+	// The part of the suggested gas price above the base fee is tipped.
+	assert_eq!(strategy.fees(&market(100, 130, &[])), fees(230, 30));
+	// No tip when the suggestion is below the base fee.
+	assert_eq!(strategy.fees(&market(100, 80, &[])), fees(200, 0));
+	assert_eq!(strategy.max_fee_per_gas(), None);
+}
+
+#[test]
+fn test_percentile_gas_strategy() {
+	let strategy = PercentileGasStrategy { percentile: 90.0 };
+
+	assert_eq!(strategy.reward_percentile(), Some(90.0));
+	assert_eq!(strategy.fees(&market(100, 500, &[5, 15, 25])), fees(215, 15));
+}
+
+#[test]
+fn test_capped_gas_strategy() {
+	let strategy =
+		CappedGasStrategy { inner: Arc::new(OracleGasStrategy), max_fee_per_gas: 150.into() };
+
+	// Below the cap, the fees of the inner strategy are used.
+	assert_eq!(strategy.fees(&market(50, 60, &[])), fees(110, 10));
+	// Above it, the max fee is capped and the priority fee kept below it.
+	assert_eq!(strategy.fees(&market(100, 130, &[])), fees(150, 30));
+	assert_eq!(strategy.fees(&market(100, 300, &[])), fees(150, 150));
+	assert_eq!(strategy.max_fee_per_gas(), Some(150.into()));
+
+	// The percentile of the inner strategy is fetched.
+	let strategy = CappedGasStrategy {
+		inner: Arc::new(PercentileGasStrategy { percentile: 50.0 }),
+		max_fee_per_gas: 150.into(),
+	};
+	assert_eq!(strategy.reward_percentile(), Some(50.0));
+}
+
+#[test]
+fn test_capped_gas_strategy_nested() {
+	let inner: Arc<dyn GasStrategy> = Arc::new(CappedGasStrategy {
+		inner: Arc::new(FixedGasStrategy { fees: fees(500, 20) }),
+		max_fee_per_gas: 100.into(),
+	});
+
+	// The lowest cap wins.
+	let strategy = CappedGasStrategy { inner: inner.clone(), max_fee_per_gas: 200.into() };
+	assert_eq!(strategy.max_fee_per_gas(), Some(100.into()));
+	assert_eq!(strategy.fees(&market(0, 0, &[])), fees(100, 20));
+
+	let strategy = CappedGasStrategy { inner, max_fee_per_gas: 80.into() };
+	assert_eq!(strategy.max_fee_per_gas(), Some(80.into()));
+	assert_eq!(strategy.fees(&market(0, 0, &[])), fees(80, 20));
+}
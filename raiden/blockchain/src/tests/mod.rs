@@ -0,0 +1,3 @@
+mod gas;
+mod signer;
+mod tracker;
@@ -0,0 +1,225 @@
+use std::{
+	convert::Infallible,
+	net::SocketAddr,
+	sync::Arc,
+};
+
+use hyper::{
+	service::{
+		make_service_fn,
+		service_fn,
+	},
+	Body,
+	Request,
+	Response,
+	Server,
+};
+use raiden_primitives::{
+	signing::{
+		recover,
+		recover_pub_key,
+	},
+	traits::ToBytes,
+	types::{
+		Address,
+		Bytes,
+		U256,
+	},
+};
+use serde_json::{
+	json,
+	Value,
+};
+use web3::types::{
+	TransactionParameters,
+	TransactionRequest,
+	U64,
+};
+
+use crate::{
+	errors::SignerError,
+	keys::{
+		self,
+		KeystoreSigner,
+		RemoteSigner,
+		Signer,
+	},
+};
+
+fn keystore_signer() -> KeystoreSigner {
+	KeystoreSigner::from_raw(&[0x42; 32]).expect("Should be a valid private key")
+}
+
+fn decode(value: &Value) -> Vec<u8> {
+	hex::decode(value.as_str().expect("Should be a string").trim_start_matches("0x"))
+		.expect("Should be hex encoded")
+}
+
+fn encode(data: &[u8]) -> Value {
+	json!(format!("0x{}", hex::encode(data)))
+}
+
+/// Answer the requests of a remote signer with a keystore signer.
+///
+/// With `clef`, signed transactions are returned the way Clef does, along with the decoded
+/// transaction.
+async fn handle(
+	signer: Arc<KeystoreSigner>,
+	clef: bool,
+	request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+	let body = hyper::body::to_bytes(request.into_body()).await.expect("Should read body");
+	let call: Value = serde_json::from_slice(&body).expect("Should be JSON");
+	let params = call["params"].as_array().cloned().unwrap_or_default();
+
+	let result = match call["method"].as_str().expect("Should have a method") {
+		"eth_accounts" => json!([signer.address()]),
+		"eth_sign" => {
+			let signature = signer.sign_message(&decode(&params[1])).expect("Should sign");
+			encode(&signature.to_bytes())
+		},
+		"eth_signTransaction" => {
+			let request: TransactionRequest =
+				serde_json::from_value(params[0].clone()).expect("Should be a transaction");
+			let chain_id: U64 =
+				serde_json::from_value(params[0]["chainId"].clone()).expect("Should be set");
+			let parameters = TransactionParameters {
+				nonce: request.nonce,
+				to: request.to,
+				gas: request.gas.unwrap_or_default(),
+				value: request.value.unwrap_or_default(),
+				data: request.data.unwrap_or_default(),
+				chain_id: Some(chain_id.as_u64()),
+				transaction_type: request.transaction_type,
+				max_fee_per_gas: request.max_fee_per_gas,
+				max_priority_fee_per_gas: request.max_priority_fee_per_gas,
+				..Default::default()
+			};
+			let raw_transaction = signer.sign_transaction(parameters).await.expect("Should sign");
+			if clef {
+				json!({ "raw": encode(&raw_transaction.0), "tx": params[0] })
+			} else {
+				encode(&raw_transaction.0)
+			}
+		},
+		"raiden_decrypt" => encode(&signer.decrypt(&decode(&params[1])).expect("Should decrypt")),
+		method => panic!("Unexpected method {}", method),
+	};
+
+	let response = json!({ "jsonrpc": "2.0", "id": call["id"], "result": result });
+	Ok(Response::new(Body::from(response.to_string())))
+}
+
+/// Start a stand-in signer process and return its URL.
+fn start_signer(signer: KeystoreSigner) -> String {
+	serve(signer, false)
+}
+
+/// Start a stand-in signer process answering like Clef and return its URL.
+fn start_clef_signer(signer: KeystoreSigner) -> String {
+	serve(signer, true)
+}
+
+fn serve(signer: KeystoreSigner, clef: bool) -> String {
+	let signer = Arc::new(signer);
+	let make_service = make_service_fn(move |_| {
+		let signer = signer.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| handle(signer.clone(), clef, request)))
+		}
+	});
+	let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+	let url = format!("http://{}", server.local_addr());
+	tokio::spawn(server);
+	url
+}
+
+fn transaction() -> TransactionParameters {
+	TransactionParameters {
+		nonce: Some(U256::from(3)),
+		to: Some(Address::repeat_byte(0x11)),
+		gas: U256::from(100_000),
+		data: Bytes(vec![1, 2, 3]),
+		chain_id: Some(4321),
+		transaction_type: Some(U64::from(2)),
+		max_fee_per_gas: Some(U256::from(2_000_000_000u64)),
+		max_priority_fee_per_gas: Some(U256::from(1_000_000_000u64)),
+		..Default::default()
+	}
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remote_signer_requires_account() {
+	let url = start_signer(keystore_signer());
+
+	assert!(RemoteSigner::connect(&url, Address::repeat_byte(0x01)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remote_signer_signs_messages() {
+	let local = keystore_signer();
+	let url = start_signer(local.clone());
+	let remote = RemoteSigner::connect(&url, local.address()).await.expect("Should connect");
+
+	let signature = remote.sign_message(b"raiden").expect("Should sign");
+	assert_eq!(signature.to_bytes(), local.sign_message(b"raiden").unwrap().to_bytes());
+	assert_eq!(recover(b"raiden", &signature.to_bytes()), Ok(local.address()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remote_signer_signs_transactions() {
+	let local = keystore_signer();
+	let url = start_signer(local.clone());
+	let remote = RemoteSigner::connect(&url, local.address()).await.expect("Should connect");
+
+	let raw_transaction = remote.sign_transaction(transaction()).await.expect("Should sign");
+	assert_eq!(raw_transaction, local.sign_transaction(transaction()).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remote_signer_decrypts_secrets() {
+	let local = keystore_signer();
+	let url = start_signer(local.clone());
+	let remote = RemoteSigner::connect(&url, local.address()).await.expect("Should connect");
+
+	let signature = local.sign_message(b"raiden").unwrap().to_bytes();
+	let public_key = recover_pub_key(b"raiden", &signature).expect("Should recover");
+	let encrypted = keys::encrypt(&public_key.0, b"secret").expect("Should encrypt");
+	assert_eq!(remote.decrypt(&encrypted).expect("Should decrypt"), b"secret".to_vec());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remote_signer_signs_transactions_with_clef() {
+	let local = keystore_signer();
+	let url = start_clef_signer(local.clone());
+	let remote = RemoteSigner::connect(&url, local.address()).await.expect("Should connect");
+
+	let raw_transaction = remote.sign_transaction(transaction()).await.expect("Should sign");
+	assert_eq!(raw_transaction, local.sign_transaction(transaction()).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_remote_signer_fails_on_current_thread_runtime() {
+	let local = keystore_signer();
+	let url = start_signer(local.clone());
+	let remote = RemoteSigner::connect(&url, local.address()).await.expect("Should connect");
+
+	assert!(remote.sign_message(b"raiden").is_err());
+	assert!(matches!(remote.decrypt(b"secret"), Err(SignerError::Remote(_))));
+}
+
+#[test]
+fn test_remote_signer_fails_outside_of_runtime() {
+	let runtime = tokio::runtime::Builder::new_multi_thread()
+		.enable_all()
+		.build()
+		.expect("Should build runtime");
+	let local = keystore_signer();
+	let remote = runtime.block_on(async {
+		let url = start_signer(local.clone());
+		RemoteSigner::connect(&url, local.address()).await.expect("Should connect")
+	});
+
+	assert!(remote.sign_message(b"raiden").is_err());
+	assert!(matches!(remote.decrypt(b"secret"), Err(SignerError::Remote(_))));
+}
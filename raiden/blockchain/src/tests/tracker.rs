@@ -0,0 +1,107 @@
+use raiden_primitives::types::{
+	Address,
+	BlockNumber,
+	Bytes,
+	U256,
+};
+use raiden_state_machine::storage::types::TransactionRecord;
+
+use crate::{
+	gas::GasFees,
+	proxies::{
+		TransactionTracker,
+		TransactionTrackerConfig,
+	},
+};
+
+fn tracker() -> TransactionTracker {
+	TransactionTracker::new(TransactionTrackerConfig::default(), None)
+}
+
+fn fees(max_fee_per_gas: u64, max_priority_fee_per_gas: u64) -> GasFees {
+	GasFees::new(max_fee_per_gas.into(), max_priority_fee_per_gas.into())
+}
+
+fn transaction(sent_at_block: u64) -> TransactionRecord {
+	TransactionRecord {
+		nonce: U256::from(7),
+		hashes: vec![],
+		purpose: "closeChannel".to_owned(),
+		time_critical: true,
+		to: Address::zero(),
+		value: U256::zero(),
+		data: Bytes::default(),
+		gas_limit: U256::from(100_000),
+		max_fee_per_gas: U256::from(100),
+		max_priority_fee_per_gas: U256::from(10),
+		sent_at_block: sent_at_block.into(),
+	}
+}
+
+#[test]
+fn test_needs_replacement() {
+	let tracker = tracker();
+	let transaction = transaction(100);
+
+	assert!(!tracker.needs_replacement(&transaction, BlockNumber::from(100)));
+	assert!(!tracker.needs_replacement(&transaction, BlockNumber::from(104)));
+	assert!(tracker.needs_replacement(&transaction, BlockNumber::from(105)));
+	assert!(tracker.needs_replacement(&transaction, BlockNumber::from(110)));
+}
+
+#[test]
+fn test_bump_fees() {
+	let tracker = tracker();
+
+	assert_eq!(tracker.bump_fees(fees(100, 10)), fees(113, 12));
+	assert_eq!(tracker.bump_fees(fees(0, 0)), fees(1, 1));
+	assert_eq!(
+		tracker.bump_fees(GasFees::new(U256::MAX, U256::MAX)).max_fee_per_gas,
+		U256::MAX / 100 + 1
+	);
+}
+
+#[test]
+fn test_replacement_fees_bump() {
+	let tracker = tracker();
+
+	let replacement = tracker.replacement_fees(fees(100, 10), None, None);
+	assert_eq!(replacement, Some(fees(113, 12)));
+
+	// Fees of the market above the bump are used instead.
+	let replacement = tracker.replacement_fees(fees(100, 10), Some(fees(150, 11)), None);
+	assert_eq!(replacement, Some(fees(150, 12)));
+
+	// Fees of the market below the bump do not lower it.
+	let replacement = tracker.replacement_fees(fees(100, 10), Some(fees(50, 2)), None);
+	assert_eq!(replacement, Some(fees(113, 12)));
+}
+
+#[test]
+fn test_replacement_fees_cap() {
+	let tracker = tracker();
+
+	// The cap leaves room for a 10% raise.
+	let replacement =
+		tracker.replacement_fees(fees(100, 10), Some(fees(200, 11)), Some(111.into()));
+	assert_eq!(replacement, Some(fees(111, 12)));
+
+	// The priority fee is kept below the capped max fee.
+	let replacement = tracker.replacement_fees(fees(100, 100), None, Some(110.into()));
+	assert_eq!(replacement, Some(fees(110, 110)));
+}
+
+#[test]
+fn test_replacement_fees_stuck_at_highest_allowed_fee() {
+	let tracker = tracker();
+
+	// Already at the cap.
+	assert_eq!(tracker.replacement_fees(fees(100, 10), None, Some(100.into())), None);
+	// Below the cap, but the capped max fee is not raised by 10%.
+	assert_eq!(tracker.replacement_fees(fees(100, 10), None, Some(109.into())), None);
+	// Fees of the market above the cap do not help.
+	assert_eq!(
+		tracker.replacement_fees(fees(100, 10), Some(fees(500, 50)), Some(105.into())),
+		None
+	);
+}
@@ -16,7 +16,6 @@ use raiden_primitives::{
 		ChainID,
 		ChannelIdentifier,
 		GasLimit,
-		GasPrice,
 		MessageTypeId,
 		Nonce,
 		Signature,
@@ -34,6 +33,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ChannelData,
@@ -157,15 +157,12 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"closeChannel",
 				(
 					params.channel_identifier,
@@ -180,11 +177,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -211,6 +206,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.closeChannel").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -233,9 +229,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		self.token_network
 			.contract
@@ -255,11 +251,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 }
@@ -8,7 +8,6 @@ use raiden_primitives::types::{
 	BlockId,
 	ChannelIdentifier,
 	GasLimit,
-	GasPrice,
 	Signature,
 	TokenAmount,
 	TransactionHash,
@@ -28,6 +27,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ProxyError,
@@ -108,18 +108,15 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let withdraw_initiator = params.withdraw_initiator;
 		let withdraw_partner = params.withdraw_partner;
 
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"cooperativeSettle",
 				(
 					params.channel_identifier,
@@ -129,11 +126,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -160,6 +155,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.cooperativeSettle").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -174,9 +170,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		let withdraw_initiator = params.withdraw_initiator;
 		let withdraw_partner = params.withdraw_partner;
@@ -194,11 +190,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 }
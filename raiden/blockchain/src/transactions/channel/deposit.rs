@@ -6,7 +6,6 @@ use raiden_primitives::types::{
 	BlockId,
 	ChannelIdentifier,
 	GasLimit,
-	GasPrice,
 	TokenAmount,
 };
 use raiden_state_machine::types::ChannelStatus;
@@ -20,6 +19,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ChannelData,
@@ -278,14 +278,11 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
-		self.token_network
-			.contract
-			.signed_call_with_confirmations(
+		self.account
+			.signed_call(
+				&self.token_network.contract,
 				"setTotalDeposit",
 				(
 					params.channel_identifier,
@@ -296,11 +293,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -327,6 +322,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.setTotalDeposit").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -429,9 +425,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		self.token_network
 			.contract
@@ -447,11 +443,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 
@@ -6,7 +6,6 @@ use raiden_primitives::types::{
 	BlockId,
 	ChannelIdentifier,
 	GasLimit,
-	GasPrice,
 	SettleTimeout,
 	TokenAmount,
 	U256,
@@ -20,6 +19,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ProxyError,
@@ -133,9 +133,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		let settle_timeout: U256 = params.settle_timeout.into();
 		self.token_network
@@ -147,11 +147,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 
@@ -160,26 +160,21 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let settle_timeout: U256 = params.settle_timeout.into();
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"openChannel",
 				(self.account.address(), params.partner, settle_timeout),
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -210,6 +205,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.openChannel").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
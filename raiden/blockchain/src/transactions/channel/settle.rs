@@ -8,7 +8,6 @@ use raiden_primitives::{
 		BlockId,
 		ChannelIdentifier,
 		GasLimit,
-		GasPrice,
 		LockedAmount,
 		Locksroot,
 		TokenAmount,
@@ -26,6 +25,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ChannelData,
@@ -160,15 +160,12 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"settleChannel",
 				(
 					params.channel_identifier,
@@ -184,11 +181,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -215,6 +210,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.settle").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -278,9 +274,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		self.token_network
 			.contract
@@ -301,11 +297,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 }
@@ -8,7 +8,6 @@ use raiden_primitives::{
 		BlockId,
 		ChannelIdentifier,
 		GasLimit,
-		GasPrice,
 		TokenAmount,
 		TransactionHash,
 	},
@@ -29,6 +28,7 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::GasFees,
 	proxies::{
 		Account,
 		ChannelData,
@@ -133,19 +133,16 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let leaves_packed = params.pending_locks.locks.iter().fold(vec![], |mut current, lock| {
 			current.extend_from_slice(&lock.0);
 			current
 		});
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"unlock",
 				(
 					params.channel_identifier,
@@ -157,11 +154,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -188,6 +183,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.unlock").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -218,9 +214,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		let leaves_packed = params.pending_locks.locks.iter().fold(vec![], |mut current, lock| {
 			current.extend_from_slice(&lock.0);
@@ -241,11 +237,11 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
 }
@@ -15,7 +15,6 @@ use raiden_primitives::{
 		ChainID,
 		ChannelIdentifier,
 		GasLimit,
-		GasPrice,
 		MessageTypeId,
 		Nonce,
 		Signature,
@@ -34,6 +33,10 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::{
+		GasFees,
+		TransactionPriority,
+	},
 	proxies::{
 		Account,
 		ChannelData,
@@ -193,15 +196,12 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
 		let receipt = self
-			.token_network
-			.contract
-			.signed_call_with_confirmations(
+			.account
+			.signed_call(
+				&self.token_network.contract,
 				"updateNonClosingBalanceProof",
 				(
 					params.channel_identifier,
@@ -216,11 +216,9 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 
@@ -247,6 +245,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("TokenNetwork.updateNonClosingBalanceProof").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -301,9 +300,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		self.token_network
 			.contract
@@ -323,11 +322,16 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
+
+	/// The balance proof has to be updated before the settlement timeout expires.
+	fn priority(&self) -> TransactionPriority {
+		TransactionPriority::TimeCritical
+	}
 }
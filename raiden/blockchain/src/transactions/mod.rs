@@ -9,13 +9,19 @@ use raiden_primitives::types::{
 };
 use tokio::sync::RwLockWriteGuard;
 
-use crate::proxies::ProxyError;
 pub use crate::transactions::{
 	channel::*,
 	secret::*,
 	token::*,
 	user_deposit::*,
 };
+use crate::{
+	gas::{
+		GasFees,
+		TransactionPriority,
+	},
+	proxies::ProxyError,
+};
 
 /// A trait to be implemented by on-chain transactions.
 #[async_trait::async_trait]
@@ -48,7 +54,7 @@ pub trait Transaction {
 		params: Self::Params,
 		data: Self::Data,
 		gas_estimate: U256,
-		gas_price: U256,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError>;
 
 	/// Validate conditions after the execution of the transaction in case the execution failed.
@@ -58,12 +64,12 @@ pub trait Transaction {
 		at_block_hash: H256,
 	) -> Result<Self::Output, ProxyError>;
 
-	/// Estimate gas for the transaction.
+	/// Estimate gas for the transaction, along with the fees it is sent with.
 	async fn estimate_gas(
 		&self,
 		params: Self::Params,
 		data: Self::Data,
-	) -> Result<(U256, U256), ProxyError>;
+	) -> Result<(U256, GasFees), ProxyError>;
 
 	/// Execute transactions that are required prior to executing the current one.
 	///
@@ -88,13 +94,29 @@ pub trait Transaction {
 		let _lock_guard = self.acquire_lock().await;
 
 		self.execute_prerequisite(params.clone(), data.clone()).await?;
-		let (gas_estimate, gas_price) = self.estimate_gas(params.clone(), data.clone()).await?;
-		match self.submit(params.clone(), data, gas_estimate, gas_price).await {
+		let (gas_estimate, gas_fees) = self.estimate_gas(params.clone(), data.clone()).await?;
+		raiden_metrics::transaction_sent(self.name());
+		match self.submit(params.clone(), data, gas_estimate, gas_fees).await {
 			Ok(result) => Ok(result),
-			Err(_) => return self.validate_postconditions(params, at_block_hash).await,
+			Err(_) => {
+				raiden_metrics::transaction_failed(self.name());
+				self.validate_postconditions(params, at_block_hash).await
+			},
 		}
 	}
 
+	/// How urgently the transaction has to be mined, which selects its gas strategy.
+	fn priority(&self) -> TransactionPriority {
+		TransactionPriority::Normal
+	}
+
+	/// The name of the transaction type, without its module path and type parameters.
+	fn name(&self) -> &'static str {
+		let name = std::any::type_name::<Self>();
+		let name = name.split('<').next().unwrap_or(name);
+		name.rsplit("::").next().unwrap_or(name)
+	}
+
 	/// Acquire lock, if needed.
 	async fn acquire_lock(&self) -> Option<RwLockWriteGuard<bool>> {
 		None
@@ -6,7 +6,6 @@ use raiden_primitives::{
 		BlockHash,
 		BlockId,
 		GasLimit,
-		GasPrice,
 		Secret,
 		SecretHash,
 	},
@@ -20,6 +19,10 @@ use web3::{
 
 use crate::{
 	contracts::GasMetadata,
+	gas::{
+		GasFees,
+		TransactionPriority,
+	},
 	proxies::{
 		Account,
 		ProxyError,
@@ -83,24 +86,19 @@ where
 		params: Self::Params,
 		_data: Self::Data,
 		gas_estimate: GasLimit,
-		gas_price: GasPrice,
+		gas_fees: GasFees,
 	) -> Result<Self::Output, ProxyError> {
-		let nonce = self.account.peek_next_nonce().await;
-		self.account.next_nonce().await;
-
-		self.secret_registry
-			.contract
-			.signed_call_with_confirmations(
+		self.account
+			.signed_call(
+				&self.secret_registry.contract,
 				"registerSecret",
 				(params.secret,),
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.gas = Some(gas_estimate);
-					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
-				1,
-				self.account.private_key(),
+				self.priority(),
 			)
 			.await?;
 		Ok(())
@@ -125,6 +123,7 @@ where
 			.check_for_insufficient_eth(
 				self.gas_metadata.get("SecretRegistry.registerSecret").into(),
 				failed_at_blocknumber,
+				self.priority(),
 			)
 			.await?;
 
@@ -145,9 +144,9 @@ where
 		&self,
 		params: Self::Params,
 		_data: Self::Data,
-	) -> Result<(GasLimit, GasPrice), ProxyError> {
+	) -> Result<(GasLimit, GasFees), ProxyError> {
 		let nonce = self.account.peek_next_nonce().await;
-		let gas_price = self.web3.eth().gas_price().await.map_err(ProxyError::Web3)?;
+		let gas_fees = self.account.gas_fees(self.priority()).await?;
 
 		self.secret_registry
 			.contract
@@ -158,11 +157,16 @@ where
 				Options::with(|opt| {
 					opt.value = Some(GasLimit::from(0));
 					opt.nonce = Some(nonce);
-					opt.gas_price = Some(gas_price);
+					gas_fees.apply(opt);
 				}),
 			)
 			.await
-			.map(|estimate| (estimate, gas_price))
+			.map(|estimate| (estimate, gas_fees))
 			.map_err(ProxyError::ChainError)
 	}
+
+	/// The secret has to be registered before the lock expires.
+	fn priority(&self) -> TransactionPriority {
+		TransactionPriority::TimeCritical
+	}
 }
@@ -0,0 +1,242 @@
+#![warn(clippy::missing_docs_in_private_items)]
+
+//! Metrics crate collects the Prometheus metrics of the node.
+//!
+//! The metrics are kept in a process wide registry so that they can be recorded from any layer
+//! without passing a handle around, and are rendered by `gather` in the Prometheus text format.
+
+use std::{
+	collections::HashMap,
+	time::Instant,
+};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use prometheus::{
+	Encoder,
+	GaugeVec,
+	Histogram,
+	HistogramOpts,
+	IntCounter,
+	IntCounterVec,
+	IntGaugeVec,
+	Opts,
+	Registry,
+	TextEncoder,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Buckets of the payment duration histogram in seconds.
+const PAYMENT_DURATION_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// The metrics of the node and the registry they are exported from.
+struct Metrics {
+	/// The registry the metrics are gathered from.
+	registry: Registry,
+	/// State changes dispatched per type.
+	state_changes: IntCounterVec,
+	/// Events emitted per type.
+	events: IntCounterVec,
+	/// Sent payments per result.
+	payments: IntCounterVec,
+	/// Duration of the successful payments.
+	payment_duration: Histogram,
+	/// Open channels per account and token network.
+	open_channels: IntGaugeVec,
+	/// Capacity of the open channels per account and token network.
+	channels_capacity: GaugeVec,
+	/// Unacknowledged messages per transport queue.
+	queue_depth: IntGaugeVec,
+	/// Messages sent again by the transport.
+	message_retries: IntCounter,
+	/// Blocks the state of an account is behind the chain head.
+	sync_lag: IntGaugeVec,
+	/// On-chain transactions per type and result.
+	transactions: IntCounterVec,
+	/// Stuck on-chain transactions replaced with bumped fees per contract function.
+	transaction_replacements: IntCounterVec,
+	/// The start time of the payments which are still in flight, by payment identifier.
+	pending_payments: Mutex<HashMap<u64, Instant>>,
+}
+
+impl Metrics {
+	/// Create the metrics and register them.
+	fn new() -> Result<Self, prometheus::Error> {
+		let registry = Registry::new_custom(Some("raiden".to_owned()), None)?;
+
+		let state_changes = IntCounterVec::new(
+			Opts::new("state_changes_total", "State changes dispatched into the state machine"),
+			&["type"],
+		)?;
+		let events = IntCounterVec::new(
+			Opts::new("events_total", "Events emitted by the state machine"),
+			&["type"],
+		)?;
+		let payments = IntCounterVec::new(
+			Opts::new("payments_total", "Payments sent by the node by result"),
+			&["result"],
+		)?;
+		let payment_duration = Histogram::with_opts(
+			HistogramOpts::new(
+				"payment_duration_seconds",
+				"Time from initiating a payment until it succeeded",
+			)
+			.buckets(PAYMENT_DURATION_BUCKETS.to_vec()),
+		)?;
+		let open_channels = IntGaugeVec::new(
+			Opts::new("open_channels", "Open channels per token network"),
+			&["account", "token_network"],
+		)?;
+		let channels_capacity = GaugeVec::new(
+			Opts::new("channels_capacity", "Capacity of the open channels per token network"),
+			&["account", "token_network"],
+		)?;
+		let queue_depth = IntGaugeVec::new(
+			Opts::new("transport_queue_depth", "Messages waiting to be acknowledged per queue"),
+			&["queue"],
+		)?;
+		let message_retries = IntCounter::new(
+			"transport_message_retries_total",
+			"Messages sent again because they were not acknowledged",
+		)?;
+		let sync_lag = IntGaugeVec::new(
+			Opts::new("sync_lag_blocks", "Blocks between the chain head and the synced state"),
+			&["account"],
+		)?;
+		let transactions = IntCounterVec::new(
+			Opts::new("transactions_total", "On-chain transactions by type and result"),
+			&["type", "result"],
+		)?;
+		let transaction_replacements = IntCounterVec::new(
+			Opts::new(
+				"transaction_replacements_total",
+				"Stuck on-chain transactions replaced with bumped fees by contract function",
+			),
+			&["function"],
+		)?;
+
+		registry.register(Box::new(state_changes.clone()))?;
+		registry.register(Box::new(events.clone()))?;
+		registry.register(Box::new(payments.clone()))?;
+		registry.register(Box::new(payment_duration.clone()))?;
+		registry.register(Box::new(open_channels.clone()))?;
+		registry.register(Box::new(channels_capacity.clone()))?;
+		registry.register(Box::new(queue_depth.clone()))?;
+		registry.register(Box::new(message_retries.clone()))?;
+		registry.register(Box::new(sync_lag.clone()))?;
+		registry.register(Box::new(transactions.clone()))?;
+		registry.register(Box::new(transaction_replacements.clone()))?;
+
+		Ok(Self {
+			registry,
+			state_changes,
+			events,
+			payments,
+			payment_duration,
+			open_channels,
+			channels_capacity,
+			queue_depth,
+			message_retries,
+			sync_lag,
+			transactions,
+			transaction_replacements,
+			pending_payments: Mutex::new(HashMap::new()),
+		})
+	}
+}
+
+/// The process wide metrics.
+static METRICS: Lazy<Metrics> = Lazy::new(|| Metrics::new().expect("Metrics should register"));
+
+/// A state change of type `type_name` was dispatched into the state machine.
+pub fn state_change_dispatched(type_name: &str) {
+	METRICS.state_changes.with_label_values(&[type_name]).inc();
+}
+
+/// The state machine emitted an event of type `type_name`.
+pub fn event_emitted(type_name: &str) {
+	METRICS.events.with_label_values(&[type_name]).inc();
+}
+
+/// A payment with `identifier` was initiated.
+pub fn payment_started(identifier: u64) {
+	METRICS.pending_payments.lock().insert(identifier, Instant::now());
+}
+
+/// The payment with `identifier` succeeded.
+pub fn payment_succeeded(identifier: u64) {
+	METRICS.payments.with_label_values(&["success"]).inc();
+	if let Some(started) = METRICS.pending_payments.lock().remove(&identifier) {
+		METRICS.payment_duration.observe(started.elapsed().as_secs_f64());
+	}
+}
+
+/// The payment with `identifier` failed.
+pub fn payment_failed(identifier: u64) {
+	METRICS.payments.with_label_values(&["failure"]).inc();
+	METRICS.pending_payments.lock().remove(&identifier);
+}
+
+/// Set the number and the total capacity of the open channels of `account` in `token_network`.
+pub fn set_channels(account: &str, token_network: &str, open_channels: usize, capacity: f64) {
+	METRICS
+		.open_channels
+		.with_label_values(&[account, token_network])
+		.set(open_channels as i64);
+	METRICS
+		.channels_capacity
+		.with_label_values(&[account, token_network])
+		.set(capacity);
+}
+
+/// Set the number of messages waiting to be acknowledged in `queue`.
+///
+/// Empty queues are removed from the metrics, as queues are created per channel.
+pub fn set_queue_depth(queue: &str, depth: usize) {
+	if depth == 0 {
+		let _ = METRICS.queue_depth.remove_label_values(&[queue]);
+		return
+	}
+	METRICS.queue_depth.with_label_values(&[queue]).set(depth as i64);
+}
+
+/// A message was sent again because it was not acknowledged.
+pub fn message_retried() {
+	METRICS.message_retries.inc();
+}
+
+/// Set the number of blocks the state of `account` is behind the chain head.
+pub fn set_sync_lag(account: &str, blocks: u64) {
+	METRICS.sync_lag.with_label_values(&[account]).set(blocks as i64);
+}
+
+/// An on-chain transaction of type `type_name` was submitted.
+pub fn transaction_sent(type_name: &str) {
+	METRICS.transactions.with_label_values(&[type_name, "sent"]).inc();
+}
+
+/// An on-chain transaction of type `type_name` failed.
+pub fn transaction_failed(type_name: &str) {
+	METRICS.transactions.with_label_values(&[type_name, "failed"]).inc();
+}
+
+/// A stuck on-chain transaction calling `function` was replaced with bumped fees.
+pub fn transaction_replaced(function: &str) {
+	METRICS.transaction_replacements.with_label_values(&[function]).inc();
+}
+
+/// Render the metrics in the Prometheus text format.
+pub fn gather() -> Result<String, String> {
+	let mut buffer = vec![];
+	TextEncoder::new()
+		.encode(&METRICS.registry.gather(), &mut buffer)
+		.map_err(|e| e.to_string())?;
+	String::from_utf8(buffer).map_err(|e| e.to_string())
+}
+
+/// The content type of the rendered metrics.
+pub fn content_type() -> String {
+	TextEncoder::new().format_type().to_owned()
+}
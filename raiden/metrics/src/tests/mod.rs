@@ -0,0 +1,52 @@
+use crate::{
+	gather,
+	payment_failed,
+	payment_started,
+	payment_succeeded,
+	set_queue_depth,
+	state_change_dispatched,
+	transaction_failed,
+	transaction_sent,
+};
+
+#[test]
+fn test_gather_renders_recorded_metrics() {
+	state_change_dispatched("Block");
+	state_change_dispatched("Block");
+	transaction_sent("ChannelOpenTransaction");
+	transaction_failed("ChannelOpenTransaction");
+
+	let metrics = gather().expect("Should render metrics");
+	assert!(metrics.contains("raiden_state_changes_total{type=\"Block\"} 2"));
+	assert!(metrics
+		.contains("raiden_transactions_total{result=\"sent\",type=\"ChannelOpenTransaction\"} 1"));
+	assert!(metrics.contains(
+		"raiden_transactions_total{result=\"failed\",type=\"ChannelOpenTransaction\"} 1"
+	));
+}
+
+#[test]
+fn test_payment_duration_is_observed_once() {
+	payment_started(1);
+	payment_succeeded(1);
+	// The payment is no longer in flight, no duration is observed.
+	payment_succeeded(1);
+	payment_started(2);
+	payment_failed(2);
+
+	let metrics = gather().expect("Should render metrics");
+	assert!(metrics.contains("raiden_payments_total{result=\"success\"} 2"));
+	assert!(metrics.contains("raiden_payments_total{result=\"failure\"} 1"));
+	assert!(metrics.contains("raiden_payment_duration_seconds_count 1"));
+}
+
+#[test]
+fn test_empty_queues_are_removed() {
+	set_queue_depth("queue", 2);
+	assert!(gather()
+		.expect("Should render metrics")
+		.contains("raiden_transport_queue_depth{queue=\"queue\"} 2"));
+
+	set_queue_depth("queue", 0);
+	assert!(!gather().expect("Should render metrics").contains("queue=\"queue\""));
+}
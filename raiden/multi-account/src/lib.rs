@@ -0,0 +1,10 @@
+//! Hosts several Raiden accounts in a single process.
+//!
+//! Accounts share the contract and proxy managers, while each account keeps its own state storage,
+//! state manager and transport. Messages between local accounts are routed internally without
+//! going over the wire.
+pub mod manager;
+pub mod router;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,104 @@
+use std::{
+	collections::HashMap,
+	sync::Arc,
+};
+
+use parking_lot::RwLock;
+use raiden_api::{
+	api::Api,
+	raiden::Raiden,
+};
+use raiden_blockchain::{
+	contracts::ContractsManager,
+	proxies::ProxyManager,
+};
+use raiden_primitives::types::Address;
+use raiden_transition::Transitioner;
+use thiserror::Error;
+
+/// Multi-account error type.
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Account `{0:?}` is already registered")]
+	DuplicateAccount(Address),
+	#[error("Account `{0:?}` does not share the managers of the other accounts")]
+	UnsharedManagers(Address),
+}
+
+/// The components of a single account hosted by the node.
+pub struct LocalAccount {
+	pub raiden: Arc<Raiden>,
+	pub transition_service: Arc<Transitioner>,
+	pub api: Arc<Api>,
+}
+
+impl LocalAccount {
+	/// Returns the address of the account.
+	pub fn address(&self) -> Address {
+		self.raiden.config.account.address()
+	}
+}
+
+/// Owns the accounts hosted by the node, keyed by address.
+pub struct AccountsManager {
+	contracts_manager: Arc<ContractsManager>,
+	proxy_manager: Arc<ProxyManager>,
+	accounts: RwLock<HashMap<Address, Arc<LocalAccount>>>,
+}
+
+impl AccountsManager {
+	/// Create an instance of `AccountsManager`.
+	pub fn new(contracts_manager: Arc<ContractsManager>, proxy_manager: Arc<ProxyManager>) -> Self {
+		Self { contracts_manager, proxy_manager, accounts: RwLock::new(HashMap::new()) }
+	}
+
+	/// Returns the contracts manager shared by all accounts.
+	pub fn contracts_manager(&self) -> Arc<ContractsManager> {
+		self.contracts_manager.clone()
+	}
+
+	/// Returns the proxy manager shared by all accounts.
+	pub fn proxy_manager(&self) -> Arc<ProxyManager> {
+		self.proxy_manager.clone()
+	}
+
+	/// Register an account.
+	///
+	/// The account's `Raiden` instance has to be created with the shared managers.
+	pub fn add(&self, account: LocalAccount) -> Result<Arc<LocalAccount>, Error> {
+		let address = account.address();
+		if !Arc::ptr_eq(&account.raiden.contracts_manager, &self.contracts_manager) ||
+			!Arc::ptr_eq(&account.raiden.proxy_manager, &self.proxy_manager)
+		{
+			return Err(Error::UnsharedManagers(address))
+		}
+
+		let mut accounts = self.accounts.write();
+		if accounts.contains_key(&address) {
+			return Err(Error::DuplicateAccount(address))
+		}
+		let account = Arc::new(account);
+		accounts.insert(address, account.clone());
+		Ok(account)
+	}
+
+	/// Returns the account with `address` if it is hosted by the node.
+	pub fn get(&self, address: &Address) -> Option<Arc<LocalAccount>> {
+		self.accounts.read().get(address).cloned()
+	}
+
+	/// Returns true if `address` is hosted by the node.
+	pub fn is_local(&self, address: &Address) -> bool {
+		self.accounts.read().contains_key(address)
+	}
+
+	/// Returns the addresses of all hosted accounts.
+	pub fn addresses(&self) -> Vec<Address> {
+		self.accounts.read().keys().cloned().collect()
+	}
+
+	/// Returns all hosted accounts.
+	pub fn accounts(&self) -> Vec<Arc<LocalAccount>> {
+		self.accounts.read().values().cloned().collect()
+	}
+}
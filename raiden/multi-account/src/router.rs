@@ -0,0 +1,124 @@
+use std::{
+	collections::HashMap,
+	sync::Arc,
+};
+
+use parking_lot::RwLock;
+use raiden_network_messages::messages::TransportServiceMessage;
+use raiden_network_transport::{
+	config::TransportConfig,
+	memory::{
+		MemoryNetworkConfig,
+		MemoryRouter,
+		MemoryService,
+	},
+	IncomingMessageHandler,
+};
+use raiden_primitives::{
+	traits::Checksum,
+	types::Address,
+};
+use tokio::sync::mpsc::{
+	self,
+	UnboundedSender,
+};
+use tracing::{
+	debug,
+	error,
+};
+
+/// Routes messages between local accounts without going over the wire.
+///
+/// Every account sends its messages through a sender returned by `intercept`. Messages addressed
+/// to a local account are queued by the sending account's memory service and retried until the
+/// receiving account acknowledges them, the same way a transport does. They are handed to the
+/// receiving account's message handler, for example turning a `LockedTransfer` into an
+/// `ActionInitTarget` of the local target. Everything else is forwarded to the account's
+/// transport.
+#[derive(Clone)]
+pub struct LocalRouter {
+	config: TransportConfig,
+	router: MemoryRouter,
+	services: Arc<RwLock<HashMap<Address, UnboundedSender<TransportServiceMessage>>>>,
+}
+
+impl LocalRouter {
+	/// Create an instance of `LocalRouter`, local messages are retried as configured in
+	/// `config`.
+	pub fn new(config: TransportConfig) -> Self {
+		Self {
+			config,
+			router: MemoryRouter::new(MemoryNetworkConfig::default()),
+			services: Arc::new(RwLock::new(HashMap::new())),
+		}
+	}
+
+	/// Register the message handler which receives messages addressed to `address`.
+	pub fn register<H: IncomingMessageHandler + 'static>(
+		&self,
+		address: Address,
+		message_handler: H,
+	) {
+		let (service, sender) =
+			MemoryService::new(address, self.config.clone(), self.router.clone());
+		tokio::spawn(service.run(message_handler));
+		self.services.write().insert(address, sender);
+	}
+
+	/// Returns true if messages to `address` are routed locally.
+	pub fn is_local(&self, address: &Address) -> bool {
+		self.services.read().contains_key(address)
+	}
+
+	/// Returns a sender to be used by the account `address` in place of `transport`.
+	///
+	/// Enqueued messages addressed to a registered account, and clearing their queues, go
+	/// through the memory service of `address`. All other messages are forwarded to
+	/// `transport`.
+	pub fn intercept(
+		&self,
+		address: Address,
+		transport: UnboundedSender<TransportServiceMessage>,
+	) -> UnboundedSender<TransportServiceMessage> {
+		let (sender, mut receiver) = mpsc::unbounded_channel();
+		let services = self.services.clone();
+		tokio::spawn(async move {
+			while let Some(message) = receiver.recv().await {
+				let recipient = match &message {
+					TransportServiceMessage::Enqueue((_, outgoing_message)) =>
+						outgoing_message.recipient,
+					TransportServiceMessage::Clear(queue_identifier) => queue_identifier.recipient,
+					_ => {
+						let _ = transport.send(message);
+						continue
+					},
+				};
+
+				if !services.read().contains_key(&recipient) {
+					let _ = transport.send(message);
+					continue
+				}
+				let service = match services.read().get(&address) {
+					Some(service) => service.clone(),
+					None => {
+						error!(
+							message = "Sending account is not registered, dropping local message",
+							address = address.checksum(),
+						);
+						continue
+					},
+				};
+
+				if let TransportServiceMessage::Enqueue((_, outgoing_message)) = &message {
+					debug!(
+						message = "Routing message locally",
+						msg_type = outgoing_message.type_name(),
+						message_identifier = outgoing_message.message_identifier,
+					);
+				}
+				let _ = service.send(message);
+			}
+		});
+		sender
+	}
+}
@@ -0,0 +1,61 @@
+use super::Node;
+use crate::manager::{
+	AccountsManager,
+	Error,
+};
+
+fn accounts_manager(node: &Node) -> AccountsManager {
+	AccountsManager::new(node.contracts_manager.clone(), node.proxy_manager.clone())
+}
+
+#[test]
+fn test_add_accounts() {
+	let node = Node::new();
+	let accounts_manager = accounts_manager(&node);
+
+	let first = accounts_manager.add(node.account(1).account).expect("Should add account");
+	let second = accounts_manager.add(node.account(2).account).expect("Should add account");
+	assert_ne!(first.address(), second.address());
+
+	for account in [&first, &second] {
+		assert!(accounts_manager.is_local(&account.address()));
+		let registered = accounts_manager.get(&account.address()).expect("Should be registered");
+		assert_eq!(registered.address(), account.address());
+	}
+	let mut addresses = accounts_manager.addresses();
+	addresses.sort();
+	let mut expected = vec![first.address(), second.address()];
+	expected.sort();
+	assert_eq!(addresses, expected);
+	assert_eq!(accounts_manager.accounts().len(), 2);
+
+	let other = node.account(3).account.address();
+	assert!(!accounts_manager.is_local(&other));
+	assert!(accounts_manager.get(&other).is_none());
+}
+
+#[test]
+fn test_add_duplicate_account() {
+	let node = Node::new();
+	let accounts_manager = accounts_manager(&node);
+
+	let account = accounts_manager.add(node.account(1).account).expect("Should add account");
+	let result = accounts_manager.add(node.account(1).account);
+	assert!(
+		matches!(result, Err(Error::DuplicateAccount(address)) if address == account.address())
+	);
+	assert_eq!(accounts_manager.accounts().len(), 1);
+}
+
+#[test]
+fn test_add_account_with_unshared_managers() {
+	let node = Node::new();
+	let other_node = Node::new();
+	let accounts_manager = accounts_manager(&node);
+
+	let account = other_node.account(1).account;
+	let address = account.address();
+	let result = accounts_manager.add(account);
+	assert!(matches!(result, Err(Error::UnsharedManagers(unshared)) if unshared == address));
+	assert!(!accounts_manager.is_local(&address));
+}
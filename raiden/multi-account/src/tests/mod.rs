@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock as SyncRwLock;
+use raiden_api::{
+	api::Api,
+	raiden::{
+		Raiden,
+		RaidenConfig,
+	},
+};
+use raiden_blockchain::{
+	contracts::ContractsManager,
+	gas::{
+		GasStrategies,
+		OracleGasStrategy,
+	},
+	keys::{
+		KeystoreSigner,
+		Signer,
+	},
+	proxies::{
+		Account,
+		ProxyManager,
+		TransactionTracker,
+		TransactionTrackerConfig,
+	},
+};
+use raiden_network_messages::messages::TransportServiceMessage;
+use raiden_pathfinding::{
+	config::PFSConfig,
+	PFS,
+};
+use raiden_primitives::{
+	payments::PaymentsRegistry,
+	reachability::ReachabilityTracker,
+	types::{
+		Address,
+		AddressMetadata,
+		BlockTimeout,
+		ChainID,
+		DefaultAddresses,
+		RevealTimeout,
+		SettleTimeout,
+		TokenAmount,
+		U256,
+		U64,
+	},
+};
+use raiden_state_machine::{
+	storage::{
+		StateStorage,
+		Storage,
+	},
+	types::MediationFeeConfig,
+};
+use raiden_transition::{
+	events::EventHandler,
+	manager::StateManager,
+	messages::MessageHandler,
+	Transitioner,
+};
+use rusqlite::Connection;
+use tokio::sync::{
+	mpsc::{
+		self,
+		UnboundedReceiver,
+	},
+	RwLock,
+};
+use web3::{
+	transports::Http,
+	Web3,
+};
+
+use crate::manager::LocalAccount;
+
+mod manager;
+mod router;
+
+/// An address nothing listens on.
+const UNREACHABLE_URL: &str = "http://127.0.0.1:1";
+
+/// The managers shared by the accounts of a node.
+struct Node {
+	web3: Web3<Http>,
+	chain_id: ChainID,
+	contracts_manager: Arc<ContractsManager>,
+	proxy_manager: Arc<ProxyManager>,
+}
+
+/// An account which is not registered yet, with the message handler receiving its messages.
+struct TestAccount {
+	account: LocalAccount,
+	message_handler: MessageHandler,
+	/// Receives the messages the account sends through its transport.
+	transport: UnboundedReceiver<TransportServiceMessage>,
+}
+
+impl Node {
+	fn new() -> Self {
+		let web3 = Web3::new(Http::new(UNREACHABLE_URL).expect("Should be a valid URL"));
+		let chain_id = ChainID::Private(U256::from(61));
+		let contracts_manager =
+			Arc::new(ContractsManager::new(chain_id).expect("Should load contracts"));
+		let proxy_manager = Arc::new(
+			ProxyManager::new(web3.clone(), contracts_manager.clone())
+				.expect("Should create proxy manager"),
+		);
+		Self { web3, chain_id, contracts_manager, proxy_manager }
+	}
+
+	/// Create an account the same way the node initializes its accounts, with an in-memory
+	/// storage and without a transport.
+	fn account(&self, key: u8) -> TestAccount {
+		let signer: Arc<dyn Signer> =
+			Arc::new(KeystoreSigner::from_raw(&[key; 32]).expect("Should be a valid private key"));
+		let tracker = TransactionTracker::new(TransactionTrackerConfig::default(), None);
+		let gas_strategies =
+			GasStrategies::new(Arc::new(OracleGasStrategy), Arc::new(OracleGasStrategy));
+		let account = Account::new(
+			self.web3.clone(),
+			signer,
+			U256::zero(),
+			gas_strategies,
+			Arc::new(tracker),
+		);
+
+		let storage =
+			StateStorage::new(Connection::open_in_memory().expect("Should open database"));
+		storage.setup_database().expect("Should setup database");
+		let default_addresses = default_addresses();
+		let (state_manager, _) = StateManager::restore_or_init_state(
+			Arc::new(storage),
+			self.chain_id,
+			account.address(),
+			default_addresses.token_network_registry,
+			U64::zero(),
+		)
+		.expect("Should initialize state");
+		let state_manager = Arc::new(SyncRwLock::new(state_manager));
+
+		let (transport_sender, transport) = mpsc::unbounded_channel();
+		let pfs_config = PFSConfig {
+			url: UNREACHABLE_URL.to_owned(),
+			info: None,
+			maximum_fee: TokenAmount::zero(),
+			iou_timeout: BlockTimeout::from(100),
+			max_paths: 3,
+		};
+		let raiden = Arc::new(Raiden {
+			web3: self.web3.clone(),
+			config: RaidenConfig {
+				chain_id: self.chain_id,
+				account: account.clone(),
+				mediation_config: MediationFeeConfig::default(),
+				monitoring_enabled: false,
+				pfs_config: pfs_config.clone(),
+				metadata: Arc::new(SyncRwLock::new(AddressMetadata::default())),
+				addresses: default_addresses.clone(),
+				default_settle_timeout: SettleTimeout::from(500),
+				default_reveal_timeout: RevealTimeout::from(50),
+			},
+			contracts_manager: self.contracts_manager.clone(),
+			proxy_manager: self.proxy_manager.clone(),
+			state_manager: state_manager.clone(),
+			transport: transport_sender.clone(),
+			pfs: Arc::new(PFS::new(self.chain_id, pfs_config.clone(), account.signer())),
+			network_graph: None,
+			reachability: Arc::new(SyncRwLock::new(ReachabilityTracker::new())),
+		});
+
+		let payments_registry = Arc::new(RwLock::new(PaymentsRegistry::new()));
+		let event_handler = EventHandler::new(
+			self.web3.clone(),
+			account.clone(),
+			state_manager.clone(),
+			self.proxy_manager.clone(),
+			transport_sender.clone(),
+			default_addresses,
+			payments_registry.clone(),
+			false,
+		);
+		let transition_service =
+			Arc::new(Transitioner::new(state_manager.clone(), event_handler, false));
+		let message_handler = MessageHandler::new(
+			account.signer(),
+			pfs_config.url,
+			transport_sender,
+			state_manager,
+			transition_service.clone(),
+			None,
+		);
+		let api = Arc::new(Api::new(raiden.clone(), transition_service.clone(), payments_registry));
+
+		TestAccount {
+			account: LocalAccount { raiden, transition_service, api },
+			message_handler,
+			transport,
+		}
+	}
+}
+
+fn default_addresses() -> DefaultAddresses {
+	DefaultAddresses {
+		contracts_version: "0.50.0".to_owned(),
+		token_network_registry: Address::repeat_byte(1),
+		secret_registry: Address::repeat_byte(2),
+		one_to_n: Address::repeat_byte(3),
+		service_registry: Address::repeat_byte(4),
+		user_deposit: Address::repeat_byte(5),
+		monitoring_service: Address::repeat_byte(6),
+	}
+}
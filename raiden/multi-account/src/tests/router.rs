@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use raiden_network_messages::messages::{
+	Delivered,
+	IncomingMessage,
+	MessageInner,
+	OutgoingMessage,
+	SecretReveal,
+	SignedMessage,
+	TransportServiceMessage,
+};
+use raiden_network_transport::{
+	config::{
+		MatrixTransportConfig,
+		TcpTransportConfig,
+		TransportConfig,
+		TransportMode,
+	},
+	IncomingMessageHandler,
+};
+use raiden_primitives::{
+	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	types::{
+		Address,
+		AddressMetadata,
+		Bytes,
+		MessageIdentifier,
+		QueueIdentifier,
+		Signature,
+	},
+};
+use raiden_state_machine::types::StateChange;
+use tokio::{
+	sync::mpsc::{
+		self,
+		UnboundedSender,
+	},
+	time::timeout,
+};
+
+use super::Node;
+use crate::{
+	manager::LocalAccount,
+	router::LocalRouter,
+};
+
+/// A `Delivered` message signed by `sender`, enqueued for `recipient`.
+fn delivered(
+	sender: &LocalAccount,
+	recipient: Address,
+	message_identifier: MessageIdentifier,
+) -> TransportServiceMessage {
+	let mut delivered = Delivered {
+		delivered_message_identifier: message_identifier,
+		signature: Signature::default(),
+	};
+	delivered
+		.sign(sender.raiden.config.account.signer())
+		.expect("Should sign message");
+	let message = OutgoingMessage {
+		message_identifier,
+		recipient,
+		recipient_metadata: AddressMetadata::default(),
+		inner: MessageInner::Delivered(delivered),
+	};
+	let queue_identifier =
+		QueueIdentifier { recipient, canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE };
+	TransportServiceMessage::Enqueue((queue_identifier, message))
+}
+
+/// A `SecretReveal` enqueued for `recipient`.
+fn secret_reveal(
+	recipient: Address,
+	message_identifier: MessageIdentifier,
+) -> TransportServiceMessage {
+	let message = OutgoingMessage {
+		message_identifier,
+		recipient,
+		recipient_metadata: AddressMetadata::default(),
+		inner: MessageInner::SecretReveal(SecretReveal {
+			message_identifier,
+			secret: Bytes(vec![1; 32]),
+			signature: Signature::from(vec![0; 65]),
+		}),
+	};
+	let queue_identifier =
+		QueueIdentifier { recipient, canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE };
+	TransportServiceMessage::Enqueue((queue_identifier, message))
+}
+
+/// Retries local messages every second.
+fn transport_config() -> TransportConfig {
+	TransportConfig {
+		retry_timeout: 1,
+		retry_timeout_max: 1,
+		retry_count: 10,
+		mode: TransportMode::Matrix,
+		matrix: MatrixTransportConfig { homeserver_url: "http://test.com".to_owned() },
+		tcp: TcpTransportConfig {
+			listen_address: "127.0.0.1:0".parse().unwrap(),
+			advertised_address: None,
+		},
+		metrics: None,
+	}
+}
+
+/// Fails to handle the first message it receives, then acknowledges it with `ack`.
+struct FlakyHandler {
+	failed: bool,
+	sender: UnboundedSender<TransportServiceMessage>,
+	ack: Option<TransportServiceMessage>,
+	received: UnboundedSender<MessageIdentifier>,
+}
+
+#[async_trait::async_trait]
+impl IncomingMessageHandler for FlakyHandler {
+	async fn handle(&mut self, message: IncomingMessage) -> Result<(), String> {
+		let _ = self.received.send(message.message_identifier);
+		if !self.failed {
+			self.failed = true;
+			return Err("Could not handle message".to_owned())
+		}
+		if let Some(ack) = self.ack.take() {
+			let _ = self.sender.send(ack);
+		}
+		Ok(())
+	}
+}
+
+/// Returns true once `account` transitioned the delivery of `message_identifier` by `sender`.
+fn received_delivered(
+	account: &LocalAccount,
+	sender: Address,
+	message_identifier: MessageIdentifier,
+) -> bool {
+	let storage = account.raiden.state_manager.read().storage.clone();
+	storage
+		.state_changes()
+		.expect("Should read state changes")
+		.into_iter()
+		.any(|record| {
+			matches!(
+				record.data,
+				StateChange::ReceiveDelivered(delivered)
+					if delivered.sender == sender && delivered.message_identifier == message_identifier
+			)
+		})
+}
+
+#[tokio::test]
+async fn test_route_message_to_local_account() {
+	let node = Node::new();
+	let initiator = node.account(1);
+	let mut target = node.account(2);
+	let initiator_address = initiator.account.address();
+	let target_address = target.account.address();
+
+	let router = LocalRouter::new(transport_config());
+	router.register(initiator_address, initiator.message_handler);
+	assert!(router.is_local(&initiator_address));
+	assert!(!router.is_local(&target_address));
+	router.register(target_address, target.message_handler);
+	assert!(router.is_local(&target_address));
+
+	let (transport_sender, mut transport) = mpsc::unbounded_channel();
+	let sender = router.intercept(initiator_address, transport_sender);
+	sender
+		.send(delivered(&initiator.account, target_address, 42))
+		.expect("Should send message");
+
+	timeout(Duration::from_secs(5), async {
+		while !received_delivered(&target.account, initiator_address, 42) {
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+	})
+	.await
+	.expect("Should be handled by the local account");
+
+	// Nothing goes over the wire, and a `Delivered` is not answered.
+	assert!(transport.try_recv().is_err());
+	assert!(target.transport.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_forward_messages_to_transport() {
+	let node = Node::new();
+	let initiator = node.account(1);
+	let target = node.account(2);
+	let initiator_address = initiator.account.address();
+	let target_address = target.account.address();
+
+	let router = LocalRouter::new(transport_config());
+	router.register(initiator_address, initiator.message_handler);
+	router.register(target_address, target.message_handler);
+
+	let (transport_sender, mut transport) = mpsc::unbounded_channel();
+	let sender = router.intercept(initiator_address, transport_sender);
+
+	// Messages to remote nodes are sent by the transport.
+	let remote = Address::random();
+	sender
+		.send(delivered(&initiator.account, remote, 1))
+		.expect("Should send message");
+	let message = timeout(Duration::from_secs(5), transport.recv())
+		.await
+		.expect("Should forward message")
+		.expect("Should forward message");
+	assert!(matches!(
+		message,
+		TransportServiceMessage::Enqueue((queue_identifier, message))
+			if queue_identifier.recipient == remote && message.message_identifier == 1
+	));
+
+	// Other messages are meant for the transport, even when they concern a local account.
+	sender.send(TransportServiceMessage::Send(2)).expect("Should send message");
+	let message = timeout(Duration::from_secs(5), transport.recv())
+		.await
+		.expect("Should forward message")
+		.expect("Should forward message");
+	assert!(matches!(message, TransportServiceMessage::Send(2)));
+}
+
+#[tokio::test]
+async fn test_retry_local_message_until_acknowledged() {
+	let node = Node::new();
+	let initiator = node.account(1);
+	let target = node.account(2);
+	let initiator_address = initiator.account.address();
+	let target_address = target.account.address();
+
+	let router = LocalRouter::new(transport_config());
+	router.register(initiator_address, initiator.message_handler);
+	let (target_transport, mut target_transport_receiver) = mpsc::unbounded_channel();
+	let (received_sender, mut received) = mpsc::unbounded_channel();
+	router.register(
+		target_address,
+		FlakyHandler {
+			failed: false,
+			sender: router.intercept(target_address, target_transport),
+			ack: Some(delivered(&target.account, initiator_address, 7)),
+			received: received_sender,
+		},
+	);
+
+	let (transport_sender, mut transport) = mpsc::unbounded_channel();
+	let sender = router.intercept(initiator_address, transport_sender);
+	sender.send(secret_reveal(target_address, 7)).expect("Should send message");
+
+	// The first attempt fails, the message is sent again until it is acknowledged.
+	for _ in 0..2 {
+		let message_identifier = timeout(Duration::from_secs(5), received.recv())
+			.await
+			.expect("Should retry message")
+			.expect("Should retry message");
+		assert_eq!(message_identifier, 7);
+	}
+
+	// The `Delivered` of the target removed the message from the queue of the initiator.
+	tokio::time::sleep(Duration::from_secs(3)).await;
+	assert!(received.try_recv().is_err());
+	assert!(transport.try_recv().is_err());
+	assert!(target_transport_receiver.try_recv().is_err());
+}
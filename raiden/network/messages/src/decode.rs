@@ -7,6 +7,8 @@ use super::messages::{
 use crate::messages::{
 	Delivered,
 	LockExpired,
+	PFSCapacityUpdate,
+	PFSFeeUpdate,
 	Processed,
 	SecretRequest,
 	SecretReveal,
@@ -113,6 +115,22 @@ impl TryFrom<String> for IncomingMessage {
 					inner: crate::messages::MessageInner::Delivered(delivered),
 				})
 			},
+			"PFSCapacityUpdate" => {
+				let capacity_update: PFSCapacityUpdate = serde_json::from_str(&body)
+					.map_err(|e| format!("Could not parse PFSCapacityUpdate message: {:?}", e))?;
+				Ok(IncomingMessage {
+					message_identifier: 0,
+					inner: crate::messages::MessageInner::PFSCapacityUpdate(capacity_update),
+				})
+			},
+			"PFSFeeUpdate" => {
+				let fee_update: PFSFeeUpdate = serde_json::from_str(&body)
+					.map_err(|e| format!("Could not parse PFSFeeUpdate message: {:?}", e))?;
+				Ok(IncomingMessage {
+					message_identifier: 0,
+					inner: crate::messages::MessageInner::PFSFeeUpdate(fee_update),
+				})
+			},
 			_ => Err(format!("Message type {} is unknown", message_type)),
 		}
 	}
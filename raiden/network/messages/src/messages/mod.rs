@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::types::{
 	Address,
 	AddressMetadata,
@@ -13,7 +15,6 @@ use serde::{
 	Serialize,
 };
 use web3::signing::{
-	Key,
 	Signature,
 	SigningError,
 };
@@ -145,10 +146,10 @@ impl IncomingMessage {
 /// Trait to be implemented by the messages that have to be signed before being sent.
 pub trait SignedMessage {
 	fn bytes_to_sign(&self) -> Vec<u8>;
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError>;
-	fn sign_message(&self, key: PrivateKey) -> Result<Signature, SigningError> {
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError>;
+	fn sign_message(&self, signer: Arc<dyn Signer>) -> Result<Signature, SigningError> {
 		let bytes = self.bytes_to_sign();
-		key.sign_message(&bytes)
+		signer.sign_message(&bytes)
 	}
 }
 
@@ -160,7 +161,7 @@ pub trait SignedEnvelopeMessage: SignedMessage {
 /// Convert state machine event into a signed message.
 #[macro_export]
 macro_rules! to_message {
-	( $send_message_event:ident, $private_key:ident, $message_type:tt ) => {{
+	( $send_message_event:ident, $signer:ident, $message_type:tt ) => {{
 		let message_identifier = $send_message_event.inner.message_identifier;
 		let recipient = $send_message_event.inner.recipient;
 		let address_metadata = $send_message_event
@@ -169,7 +170,7 @@ macro_rules! to_message {
 			.clone()
 			.expect("Address metadata should be set at this point");
 		let mut message: $message_type = $send_message_event.into();
-		let _ = message.sign($private_key);
+		let _ = message.sign($signer);
 		OutgoingMessage {
 			message_identifier,
 			recipient,
@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
 	deserializers::u256_from_str,
 	hashing::hash_balance_data,
@@ -83,8 +85,8 @@ impl SignedMessage for SignedBlindedBalanceProof {
 		.0
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.non_closing_signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.non_closing_signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -136,10 +138,10 @@ impl SignedMessage for RequestMonitoring {
 		.0
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.balance_proof.sign(key.clone())?;
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.balance_proof.sign(signer.clone())?;
 		self.non_closing_signature = self.balance_proof.non_closing_signature.clone();
-		self.signature = self.sign_message(key)?.to_bytes().into();
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
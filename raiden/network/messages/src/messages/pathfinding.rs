@@ -1,6 +1,9 @@
+use std::sync::Arc;
+
 use chrono::Utc;
-use raiden_blockchain::keys::PrivateKey;
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
+	deserializers::u256_from_str,
 	serializers::u256_to_str,
 	traits::ToBytes,
 	types::{
@@ -36,13 +39,13 @@ pub struct PFSCapacityUpdate {
 	pub canonical_identifier: CanonicalIdentifier,
 	pub updating_participant: Address,
 	pub other_participant: Address,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub updating_nonce: Nonce,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub other_nonce: Nonce,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub updating_capacity: TokenAmount,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub other_capacity: TokenAmount,
 	pub reveal_timeout: RevealTimeout,
 	pub signature: Signature,
@@ -105,8 +108,8 @@ impl SignedMessage for PFSCapacityUpdate {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -166,8 +169,8 @@ impl SignedMessage for PFSFeeUpdate {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
 	deserializers::u64_from_str,
 	traits::ToBytes,
@@ -69,8 +71,8 @@ impl SignedMessage for Processed {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -101,8 +103,8 @@ impl SignedMessage for Delivered {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
 	deserializers::{
 		signature_from_str,
@@ -108,8 +110,8 @@ impl SignedMessage for SecretRequest {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -149,8 +151,8 @@ impl SignedMessage for SecretReveal {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -227,8 +229,8 @@ impl SignedMessage for LockExpired {
 		.0
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -331,8 +333,8 @@ impl SignedMessage for Unlock {
 		.0
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -455,8 +457,8 @@ impl SignedMessage for LockedTransfer {
 		.0
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
 	deserializers::{
 		signature_from_str,
@@ -100,8 +102,8 @@ impl SignedMessage for WithdrawRequest {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -167,8 +169,8 @@ impl SignedMessage for WithdrawConfirmation {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -240,8 +242,8 @@ impl SignedMessage for WithdrawExpired {
 		bytes
 	}
 
-	fn sign(&mut self, key: PrivateKey) -> Result<(), SigningError> {
-		self.signature = self.sign_message(key)?.to_bytes().into();
+	fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), SigningError> {
+		self.signature = self.sign_message(signer)?.to_bytes().into();
 		Ok(())
 	}
 }
@@ -1,10 +1,33 @@
+use std::{
+	net::SocketAddr,
+	sync::Arc,
+};
+
+use crate::metrics::QueueMetrics;
+
+/// The transports used to exchange messages with other nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransportMode {
+	/// Messages go through the matrix home server.
+	Matrix,
+	/// Messages go over direct TCP connections only.
+	Tcp,
+	/// Messages go over direct TCP connections to the nodes advertising a socket address and
+	/// through the matrix home server otherwise.
+	Combined,
+}
+
 /// Generic transport configuration.
 #[derive(Clone)]
 pub struct TransportConfig {
 	pub retry_timeout: u8,
 	pub retry_timeout_max: u8,
 	pub retry_count: u32,
+	pub mode: TransportMode,
 	pub matrix: MatrixTransportConfig,
+	pub tcp: TcpTransportConfig,
+	/// Hook receiving the depth of the message queues and the retries of their messages.
+	pub metrics: Option<Arc<dyn QueueMetrics>>,
 }
 
 /// Matrix specific configuration.
@@ -12,3 +35,11 @@ pub struct TransportConfig {
 pub struct MatrixTransportConfig {
 	pub homeserver_url: String,
 }
+
+/// Direct TCP connections specific configuration.
+#[derive(Clone)]
+pub struct TcpTransportConfig {
+	pub listen_address: SocketAddr,
+	/// The socket address advertised to other nodes, required to accept direct connections.
+	pub advertised_address: Option<SocketAddr>,
+}
@@ -1,12 +1,20 @@
 //! Implements Raiden protocol messages and matrix network integration to exchange messages between
 //! nodes over the wire.
 use matrix_sdk::HttpError;
-use raiden_network_messages::messages::OutgoingMessage;
+use raiden_network_messages::messages::{
+	IncomingMessage,
+	OutgoingMessage,
+};
 use raiden_primitives::types::QueueIdentifier;
+use raiden_transition::messages::MessageHandler;
 use thiserror::Error;
 
 pub mod config;
 pub mod matrix;
+pub mod memory;
+pub mod metrics;
+mod queues;
+pub mod tcp;
 #[cfg(test)]
 mod tests;
 pub mod types;
@@ -24,6 +32,7 @@ pub enum TransportError {
 	Other(String),
 }
 
+/// A transport exchanging messages with other nodes.
 #[async_trait::async_trait]
 pub trait Transport {
 	async fn init(&self) -> Result<(), TransportError>;
@@ -32,5 +41,18 @@ pub trait Transport {
 		queue_identifier: QueueIdentifier,
 		message: OutgoingMessage,
 	) -> Result<(), TransportError>;
-	async fn process(mut self);
+	async fn process(mut self, message_handler: MessageHandler);
+}
+
+/// Handles the messages a transport receives from other nodes.
+#[async_trait::async_trait]
+pub trait IncomingMessageHandler: Send {
+	async fn handle(&mut self, message: IncomingMessage) -> Result<(), String>;
+}
+
+#[async_trait::async_trait]
+impl IncomingMessageHandler for MessageHandler {
+	async fn handle(&mut self, message: IncomingMessage) -> Result<(), String> {
+		MessageHandler::handle(self, message).await
+	}
 }
@@ -4,6 +4,9 @@ use std::{
 		HashMap,
 	},
 	fmt::Display,
+	mem,
+	net::SocketAddr,
+	sync::Arc,
 	time::Duration,
 };
 
@@ -13,10 +16,15 @@ use matrix_sdk::{
 		SyncSettings,
 	},
 	ruma::{
-		api::client::to_device::send_event_to_device,
+		api::client::{
+			presence::get_presence,
+			to_device::send_event_to_device,
+		},
 		events::AnyToDeviceEvent,
+		presence::PresenceState,
 		serde::Raw,
 		to_device::DeviceIdOrAllDevices,
+		MxcUri,
 		OwnedUserId,
 		TransactionId,
 	},
@@ -24,7 +32,7 @@ use matrix_sdk::{
 	Error,
 };
 use raiden_blockchain::{
-	keys::PrivateKey,
+	keys::Signer,
 	proxies::ServiceRegistryProxy,
 };
 use raiden_network_messages::messages::{
@@ -32,6 +40,7 @@ use raiden_network_messages::messages::{
 	OutgoingMessage,
 };
 use raiden_primitives::{
+	reachability::Reachability,
 	traits::Stringify,
 	types::{
 		Address,
@@ -46,10 +55,7 @@ use tracing::{
 	debug,
 	error,
 };
-use web3::{
-	signing::Key,
-	transports::Http,
-};
+use web3::transports::Http;
 
 use crate::TransportError;
 
@@ -79,33 +85,57 @@ pub struct MessageContent {
 /// Matrix client type.
 pub struct MatrixClient {
 	client: Client,
-	private_key: PrivateKey,
+	signer: Arc<dyn Signer>,
+	homeserver_url: String,
 	server_name: String,
 	next_sync_token: String,
 	services_addresses: HashMap<Address, BlockNumber>,
+	socket_address: Option<SocketAddr>,
 }
 
 impl MatrixClient {
 	/// Create a new instance of `MatrixClient`.
-	pub async fn new(homeserver_url: String, private_key: PrivateKey) -> Self {
-		let homeserver_url =
-			Url::parse(&homeserver_url).expect("Couldn't parse the homeserver URL");
-		let mut server_name =
-			homeserver_url.host_str().expect("homeserver URL has no hostname").to_string();
-		if let Some(port) = homeserver_url.port() {
-			server_name = format!("{}:{}", server_name, port);
-		}
-		let client = Client::new(homeserver_url.clone()).await.unwrap();
+	pub async fn new(homeserver_url: String, signer: Arc<dyn Signer>) -> Self {
+		let (client, server_name) =
+			Self::connect(&homeserver_url).await.expect("Couldn't create the matrix client");
 
 		Self {
 			client,
-			private_key,
+			signer,
+			homeserver_url,
 			server_name,
 			next_sync_token: String::new(),
 			services_addresses: HashMap::new(),
+			socket_address: None,
 		}
 	}
 
+	/// Create a client of the home server at `homeserver_url`, along with its server name.
+	async fn connect(homeserver_url: &str) -> Result<(Client, String), TransportError> {
+		let homeserver_url = Url::parse(homeserver_url).map_err(|e| {
+			TransportError::Init(format!("Couldn't parse the homeserver URL: {}", e))
+		})?;
+		let mut server_name = homeserver_url
+			.host_str()
+			.ok_or_else(|| TransportError::Init("homeserver URL has no hostname".to_owned()))?
+			.to_string();
+		if let Some(port) = homeserver_url.port() {
+			server_name = format!("{}:{}", server_name, port);
+		}
+		let client = Client::new(homeserver_url)
+			.await
+			.map_err(|e| TransportError::Init(format!("Couldn't create the client: {}", e)))?;
+
+		Ok((client, server_name))
+	}
+
+	/// Advertise the socket address on which the node accepts direct connections.
+	///
+	/// Has to be set before the client is initialized to be published to the home server.
+	pub fn advertise_socket_address(&mut self, socket_address: SocketAddr) {
+		self.socket_address = Some(socket_address);
+	}
+
 	/// Set sync token based on the last sync.
 	pub fn set_sync_token(&mut self, sync_token: String) {
 		self.next_sync_token = sync_token;
@@ -116,16 +146,26 @@ impl MatrixClient {
 		self.next_sync_token.clone()
 	}
 
-	/// Return a copy of the private key.
-	pub fn private_key(&self) -> PrivateKey {
-		self.private_key.clone()
+	/// Return the URL of the home server in use.
+	pub fn homeserver_url(&self) -> String {
+		self.homeserver_url.clone()
+	}
+
+	/// Return the name of the home server in use.
+	pub fn server_name(&self) -> String {
+		self.server_name.clone()
+	}
+
+	/// Return the signer of the account.
+	pub fn signer(&self) -> Arc<dyn Signer> {
+		self.signer.clone()
 	}
 
 	/// Initialize the client by logging into the matrix home server.
 	pub async fn init(&self) -> Result<(), TransportError> {
-		let username = format!("{:#x}", self.private_key.address());
+		let username = format!("{:#x}", self.signer.address());
 		let signed_server_name =
-			self.private_key.sign_message(self.server_name.as_bytes()).map_err(|e| {
+			self.signer.sign_message(self.server_name.as_bytes()).map_err(|e| {
 				TransportError::Init(format!("Could not generate server password: {}", e))
 			})?;
 
@@ -139,7 +179,7 @@ impl MatrixClient {
 			.map_err(|e| TransportError::Init(format!("Error fetching matrix user info: {}", e)))?;
 
 		let display_name = self
-			.private_key
+			.signer
 			.sign_message(user_info.user_id.as_bytes())
 			.map_err(|e| TransportError::Init(format!("Error generating displayname: {}", e)))?;
 
@@ -149,6 +189,36 @@ impl MatrixClient {
 			.await
 			.map_err(|e| TransportError::Init(format!("Error setting displayname: {}", e)))?;
 
+		// The capabilities are published as the avatar URL, the services read them from there.
+		let capabilities = self.address_metadata().capabilities;
+		if !capabilities.is_empty() {
+			let avatar_url: &MxcUri = capabilities.as_str().into();
+			self.client
+				.account()
+				.set_avatar_url(Some(avatar_url))
+				.await
+				.map_err(|e| TransportError::Init(format!("Error setting capabilities: {}", e)))?;
+		}
+
+		Ok(())
+	}
+
+	/// Log into the home server at `homeserver_url` instead of the current one.
+	///
+	/// The current home server is kept if the login fails. The sync token is reset on success,
+	/// since sync tokens are only valid on the home server which issued them.
+	pub async fn switch_server(&mut self, homeserver_url: &str) -> Result<(), TransportError> {
+		let (client, server_name) = Self::connect(homeserver_url).await?;
+		let previous_client = mem::replace(&mut self.client, client);
+		let previous_server_name = mem::replace(&mut self.server_name, server_name);
+		if let Err(e) = self.init().await {
+			self.client = previous_client;
+			self.server_name = previous_server_name;
+			return Err(e)
+		}
+
+		self.homeserver_url = homeserver_url.to_owned();
+		self.next_sync_token = String::new();
 		Ok(())
 	}
 
@@ -212,9 +282,13 @@ impl MatrixClient {
 
 	/// Construct the address metadata of the current account.
 	pub fn address_metadata(&self) -> AddressMetadata {
-		let user_id = self.make_user_id(&self.private_key.address());
-		let displayname = self.private_key.sign_message(user_id.as_bytes()).unwrap().as_string();
-		AddressMetadata { user_id, displayname, capabilities: "".to_owned() }
+		let user_id = self.make_user_id(&self.signer.address());
+		let displayname = self.signer.sign_message(user_id.as_bytes()).unwrap().as_string();
+		let mut metadata = AddressMetadata { user_id, displayname, capabilities: "".to_owned() };
+		if let Some(socket_address) = self.socket_address {
+			metadata.set_socket_address(socket_address);
+		}
+		metadata
 	}
 
 	/// Return's the matrix user ID.
@@ -222,19 +296,48 @@ impl MatrixClient {
 		format!("@0x{}:{}", hex::encode(address), self.server_name)
 	}
 
-	/// Send message to the matrix home server.
+	/// Returns the reachability of the matrix user `user_id` based on its presence.
+	///
+	/// Users which are online or idle are reachable, offline users are not.
+	pub async fn get_presence(&self, user_id: &str) -> Result<Reachability, TransportError> {
+		let user_id: OwnedUserId =
+			user_id.try_into().map_err(|e| TransportError::Other(format!("{:?}", e)))?;
+		let request = get_presence::v3::Request::new(&user_id);
+		let response =
+			self.client.send(request, Some(RequestConfig::default())).await.map_err(|e| {
+				TransportError::Other(format!("Could not fetch presence of {}: {}", user_id, e))
+			})?;
+
+		let reachability = match response.presence {
+			PresenceState::Offline => Reachability::Unreachable,
+			_ => Reachability::Reachable,
+		};
+		Ok(reachability)
+	}
+
+	/// Send messages to the same recipient through the matrix home server.
+	///
+	/// The messages are sent in one event, one JSON encoded message per line of its body.
 	pub async fn send(
 		&self,
-		message: OutgoingMessage,
+		messages: &[OutgoingMessage],
 		receiver_metadata: AddressMetadata,
 	) -> Result<(), TransportError> {
-		let message_json = match serde_json::to_string(&message) {
-			Ok(json) => json,
-			Err(e) => {
-				error!("Could not serialize message: {:?}", e);
-				return Err(TransportError::Other(format!("{}", e)))
-			},
+		let recipient = match messages.first() {
+			Some(message) => message.recipient,
+			None => return Ok(()),
 		};
+		let mut lines = vec![];
+		for message in messages {
+			match serde_json::to_string(message) {
+				Ok(json) => lines.push(json),
+				Err(e) => {
+					error!("Could not serialize message: {:?}", e);
+					return Err(TransportError::Other(format!("{}", e)))
+				},
+			}
+		}
+		let message_json = lines.join("\n");
 		let content = MessageContent { msgtype: MessageType::Text.to_string(), body: message_json };
 		let json = match serde_json::to_string(&content) {
 			Ok(json) => json,
@@ -247,8 +350,13 @@ impl MatrixClient {
 			Ok(d) => d,
 			Err(e) => return Err(TransportError::Other(format!("{:?}", e))),
 		};
-		let user_id: OwnedUserId = receiver_metadata
-			.user_id
+		// Without metadata, the receiver is assumed to use the same home server.
+		let user_id = if receiver_metadata.user_id.is_empty() {
+			self.make_user_id(&recipient)
+		} else {
+			receiver_metadata.user_id
+		};
+		let user_id: OwnedUserId = user_id
 			.as_str()
 			.try_into()
 			.map_err(|e| TransportError::Other(format!("{:?}", e)))?;
@@ -1,6 +1,18 @@
+use std::time::Duration;
+
 /// Automatically select the home server.
 pub const MATRIX_AUTO_SELECT_SERVER: &str = "auto";
 /// The URL from which we can retrieve the production servers list.
 pub const MATRIX_DEFAULT_PRODUCTION_SERVERS_LIST_URL: &str = "https://raw.githubusercontent.com/raiden-network/raiden-service-bundle/master/known_servers/known_servers-production-v1.2.0.json";
 /// The URL from which we can retrieve the development servers list.
 pub const MATRIX_DEFAULT_DEVELOPMENT_SERVERS_LIST_URL: &str = "https://raw.githubusercontent.com/raiden-network/raiden-service-bundle/master/known_servers/known_servers-development-v1.2.0.json";
+/// The maximum number of messages sent to a partner in a single matrix event.
+pub const MATRIX_MAX_BATCH_SIZE: usize = 20;
+/// The time after which a home server which did not answer is considered unreachable.
+pub const MATRIX_RTT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The number of consecutive failed syncs after which another home server is used.
+pub const MATRIX_MAX_SYNC_FAILURES: u32 = 3;
+/// The time waited before syncing again after a failed sync.
+pub const MATRIX_SYNC_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// The time between two checks of the presence of the tracked nodes.
+pub const MATRIX_PRESENCE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
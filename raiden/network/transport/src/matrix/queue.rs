@@ -1,5 +1,6 @@
 use std::{
 	cmp::min,
+	sync::Arc,
 	time::Duration as StdDuration,
 };
 
@@ -10,7 +11,13 @@ use chrono::{
 };
 use futures::StreamExt;
 use raiden_network_messages::messages::TransportServiceMessage;
-use raiden_primitives::types::MessageIdentifier;
+use raiden_primitives::{
+	reachability::Reachability,
+	types::{
+		MessageIdentifier,
+		QueueIdentifier,
+	},
+};
 use serde::{
 	Deserialize,
 	Serialize,
@@ -27,7 +34,10 @@ use tokio::{
 use tokio_stream::wrappers::IntervalStream;
 use tracing::error;
 
-use crate::config::TransportConfig;
+use crate::{
+	config::TransportConfig,
+	metrics::QueueMetrics,
+};
 
 /// A generator for timeout which indicates if a message is ready for a retry.
 #[derive(Clone, Serialize, Deserialize)]
@@ -63,7 +73,7 @@ impl TimeoutGenerator {
 				// At this point, we know that we have reached the `retries_count`,
 				// so we start exponentially increasing the timeout.
 				if next <= now && reached_max_retries {
-					let timeout = min(self.timeout * 2, self.timeout_max);
+					let timeout = min(self.timeout.saturating_mul(2), self.timeout_max);
 
 					let set_timeout =
 						if timeout < self.timeout_max { timeout } else { self.timeout_max };
@@ -81,6 +91,15 @@ impl TimeoutGenerator {
 			},
 		}
 	}
+
+	/// Wait for the max timeout between the next retries.
+	pub(crate) fn back_off(&mut self) {
+		self.retries_count = 0;
+		self.timeout = self.timeout_max;
+		if self.next.is_some() {
+			self.next = Some(Local::now() + Duration::seconds(self.timeout as i64));
+		}
+	}
 }
 
 /// A queue operation.
@@ -88,6 +107,8 @@ impl TimeoutGenerator {
 pub(crate) enum QueueOp {
 	Enqueue(MessageIdentifier),
 	Dequeue(MessageIdentifier),
+	/// The reachability of the recipient changed.
+	Reachability(Reachability),
 	Stop,
 }
 
@@ -96,6 +117,8 @@ pub(crate) enum QueueOp {
 struct QueuedMessageData {
 	pub(self) message_identifier: MessageIdentifier,
 	pub(self) timeout_generator: TimeoutGenerator,
+	#[serde(default)]
+	pub(self) sends: u32,
 }
 
 /// A message queue which stores the message identifier and a timeout generator.
@@ -103,29 +126,37 @@ struct QueuedMessageData {
 /// If any messages in the queue is ready, a signal is sent back to the transport so that the
 /// message can be sent over the wire.
 pub(crate) struct RetryMessageQueue {
+	queue_identifier: QueueIdentifier,
 	transport_sender: UnboundedSender<TransportServiceMessage>,
 	queue: Vec<QueuedMessageData>,
 	channel_receiver: UnboundedReceiver<QueueOp>,
 	retry_timeout: u8,
 	retry_timeout_max: u8,
 	retry_count: u32,
+	metrics: Option<Arc<dyn QueueMetrics>>,
+	/// Messages are retried at the max timeout while the recipient is unreachable.
+	recipient_unreachable: bool,
 }
 
 impl RetryMessageQueue {
 	/// Create an instance of `RetryMessageQueue`.
 	pub fn new(
+		queue_identifier: QueueIdentifier,
 		transport_sender: UnboundedSender<TransportServiceMessage>,
 		transport_config: TransportConfig,
 	) -> (Self, UnboundedSender<QueueOp>) {
 		let (channel_sender, channel_receiver) = mpsc::unbounded_channel();
 		(
 			Self {
+				queue_identifier,
 				channel_receiver,
 				transport_sender,
 				queue: vec![],
 				retry_timeout: transport_config.retry_timeout,
 				retry_timeout_max: transport_config.retry_timeout_max,
 				retry_count: transport_config.retry_count,
+				metrics: transport_config.metrics,
+				recipient_unreachable: false,
 			},
 			channel_sender,
 		)
@@ -136,19 +167,51 @@ impl RetryMessageQueue {
 		if self.queue.iter().any(|m| m.message_identifier == message_identifier) {
 			return
 		}
-		self.queue.push(QueuedMessageData {
-			message_identifier,
-			timeout_generator: TimeoutGenerator::new(
-				self.retry_count,
-				self.retry_timeout,
-				self.retry_timeout_max,
-			),
-		});
+		let mut timeout_generator =
+			TimeoutGenerator::new(self.retry_count, self.retry_timeout, self.retry_timeout_max);
+		if self.recipient_unreachable {
+			timeout_generator.back_off();
+		}
+		self.queue
+			.push(QueuedMessageData { message_identifier, timeout_generator, sends: 0 });
+		self.report_depth();
 	}
 
 	/// Remove the message identifier from queue.
 	fn dequeue(&mut self, message_identifier: MessageIdentifier) {
 		self.queue.retain(|data| data.message_identifier != message_identifier);
+		self.report_depth();
+	}
+
+	/// Slow the retries down to the max timeout while the recipient is unreachable.
+	///
+	/// The reachability is only a hint, home servers without presence report every user as
+	/// offline, so the messages are still retried. Once the recipient is reachable again, the
+	/// timeouts start over so that the messages are sent right away.
+	fn set_reachability(&mut self, reachability: Reachability) {
+		let unreachable = reachability == Reachability::Unreachable;
+		if !self.recipient_unreachable && unreachable {
+			for message_data in self.queue.iter_mut() {
+				message_data.timeout_generator.back_off();
+			}
+		}
+		if self.recipient_unreachable && !unreachable {
+			for message_data in self.queue.iter_mut() {
+				message_data.timeout_generator = TimeoutGenerator::new(
+					self.retry_count,
+					self.retry_timeout,
+					self.retry_timeout_max,
+				);
+			}
+		}
+		self.recipient_unreachable = unreachable;
+	}
+
+	/// Report the number of queued messages to the metrics hook.
+	fn report_depth(&self) {
+		if let Some(metrics) = &self.metrics {
+			metrics.queue_depth(&self.queue_identifier, self.queue.len());
+		}
 	}
 
 	/// Loops forever and checks every certain interval for messages that are ready to be retried.
@@ -164,7 +227,10 @@ impl RetryMessageQueue {
 							self.enqueue(message_identifier);
 						},
 						QueueOp::Dequeue(message_identifier) => self.dequeue(message_identifier),
+						QueueOp::Reachability(reachability) => self.set_reachability(reachability),
 						QueueOp::Stop => {
+							self.queue.clear();
+							self.report_depth();
 							return;
 						}
 					}
@@ -175,6 +241,12 @@ impl RetryMessageQueue {
 					}
 					for message_data in self.queue.iter_mut().by_ref() {
 						if message_data.timeout_generator.ready() {
+							message_data.sends += 1;
+							if message_data.sends > 1 {
+								if let Some(metrics) = &self.metrics {
+									metrics.message_retried(&self.queue_identifier, message_data.message_identifier, message_data.sends - 1);
+								}
+							}
 							if let Err(e) = self.transport_sender.send(TransportServiceMessage::Send(message_data.message_identifier)) {
 								error!(
 									message = "Failed to send message to transport",
@@ -1,15 +1,22 @@
 use std::{
 	collections::HashMap,
 	pin::Pin,
+	sync::Arc,
+	time::Duration,
 };
 
 use futures::{
-	stream::FuturesUnordered,
+	future::join_all,
+	stream::{
+		FuturesUnordered,
+		ReadyChunks,
+	},
 	Future,
 	FutureExt,
 	StreamExt,
 };
 use matrix_sdk::ruma::to_device::DeviceIdOrAllDevices;
+use parking_lot::RwLock;
 use raiden_network_messages::messages::{
 	self,
 	IncomingMessage,
@@ -20,35 +27,50 @@ use raiden_network_messages::messages::{
 };
 use raiden_primitives::{
 	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	reachability::{
+		Reachability,
+		ReachabilityTracker,
+	},
 	signing,
 	traits::Checksum,
 	types::{
+		Address,
+		AddressMetadata,
 		MessageIdentifier,
 		QueueIdentifier,
 	},
 };
 use raiden_transition::messages::MessageHandler;
-use serde::{
-	Deserialize,
-	Serialize,
-};
 use tokio::{
 	select,
 	sync::mpsc::{
 		self,
 		UnboundedSender,
 	},
+	time::{
+		self,
+		MissedTickBehavior,
+	},
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{
 	debug,
 	error,
+	info,
 	trace,
+	warn,
 };
 
 use super::{
+	constants::{
+		MATRIX_MAX_BATCH_SIZE,
+		MATRIX_MAX_SYNC_FAILURES,
+		MATRIX_PRESENCE_CHECK_INTERVAL,
+		MATRIX_SYNC_RETRY_DELAY,
+	},
 	queue::RetryMessageQueue,
 	storage::MatrixStorage,
+	utils::rank_servers,
 	MatrixClient,
 };
 use crate::{
@@ -62,12 +84,6 @@ use crate::{
 
 type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + Sync + 'a>>;
 
-/// A intermediate type for storing messages
-#[derive(Serialize, Deserialize)]
-struct StorageMessages {
-	messages: HashMap<String, HashMap<MessageIdentifier, Vec<OutgoingMessage>>>,
-}
-
 /// Stores the messages for each queue as well as a sender to communicate with the queue.
 struct QueueInfo {
 	op_sender: UnboundedSender<QueueOp>,
@@ -86,9 +102,20 @@ pub struct MatrixService {
 	client: MatrixClient,
 	matrix_storage: MatrixStorage,
 	our_sender: UnboundedSender<TransportServiceMessage>,
-	queue_receiver: UnboundedReceiverStream<TransportServiceMessage>,
+	queue_receiver: ReadyChunks<UnboundedReceiverStream<TransportServiceMessage>>,
 	messages: HashMap<QueueIdentifier, QueueInfo>,
 	running_futures: FuturesUnordered<BoxFuture<'static, ()>>,
+	/// The metadata other nodes reach us with, updated when the home server changes.
+	our_metadata: Arc<RwLock<AddressMetadata>>,
+	/// Home servers to fail over to when the current one stops syncing.
+	fallback_servers: Vec<String>,
+	/// Number of syncs which failed in a row on the current home server.
+	sync_failures: u32,
+	/// The reachability of the nodes, derived from their presence.
+	reachability: Arc<RwLock<ReachabilityTracker>>,
+	/// The matrix users of the nodes messages were sent to, nodes which are missing are assumed
+	/// to use our home server.
+	user_ids: HashMap<Address, String>,
 }
 
 impl MatrixService {
@@ -97,6 +124,8 @@ impl MatrixService {
 		config: TransportConfig,
 		client: MatrixClient,
 		matrix_storage: MatrixStorage,
+		our_metadata: Arc<RwLock<AddressMetadata>>,
+		reachability: Arc<RwLock<ReachabilityTracker>>,
 	) -> (Self, UnboundedSender<TransportServiceMessage>) {
 		let (sender, receiver) = mpsc::unbounded_channel();
 
@@ -107,42 +136,59 @@ impl MatrixService {
 				matrix_storage,
 				messages: HashMap::new(),
 				our_sender: sender.clone(),
-				queue_receiver: UnboundedReceiverStream::new(receiver),
+				queue_receiver: UnboundedReceiverStream::new(receiver)
+					.ready_chunks(MATRIX_MAX_BATCH_SIZE),
 				running_futures: FuturesUnordered::new(),
+				our_metadata,
+				fallback_servers: vec![],
+				sync_failures: 0,
+				reachability,
+				user_ids: HashMap::new(),
 			},
 			sender,
 		)
 	}
 
+	/// Set the home servers to fail over to, in order of preference.
+	pub fn set_fallback_servers(&mut self, fallback_servers: Vec<String>) {
+		self.fallback_servers = fallback_servers;
+	}
+
+	/// Returns the client of the home server in use.
+	pub fn client(&self) -> &MatrixClient {
+		&self.client
+	}
+
 	/// Initialize the service from storage.
 	pub fn init_from_storage(&mut self) -> Result<(), String> {
 		// Get last sync token
-		let sync_token = self.matrix_storage.get_sync_token().unwrap_or(String::new());
+		let sync_token = self
+			.matrix_storage
+			.get_sync_token(&self.client.server_name())
+			.unwrap_or(String::new());
 		if !sync_token.trim().is_empty() {
 			self.client.set_sync_token(sync_token);
 		}
 
-		let storage_messages: HashMap<
-			QueueIdentifier,
-			HashMap<MessageIdentifier, Vec<OutgoingMessage>>,
-		> = self
+		let rows = self
 			.matrix_storage
 			.get_messages()
-			.map_err(|e| format!("Error initializing transport from storage: {:?}", e))
-			.and_then(|data| {
-				if !data.is_empty() {
-					let data: HashMap<String, HashMap<MessageIdentifier, Vec<OutgoingMessage>>> =
-						serde_json::from_str(&data).map_err(|e| {
-							format!("Error initializing transport from storage: {:?}", e)
-						})?;
-					Ok(data
-						.into_iter()
-						.map(|(k, v)| (serde_json::from_str(&k).expect("Should deserialize"), v))
-						.collect())
-				} else {
-					Ok(HashMap::new())
-				}
-			})?;
+			.map_err(|e| format!("Error initializing transport from storage: {:?}", e))?;
+
+		let mut storage_messages: HashMap<
+			QueueIdentifier,
+			HashMap<MessageIdentifier, Vec<OutgoingMessage>>,
+		> = HashMap::new();
+		for (queue_identifier, message_identifier, data) in rows {
+			let queue_identifier: QueueIdentifier = serde_json::from_str(&queue_identifier)
+				.map_err(|e| format!("Error initializing transport from storage: {:?}", e))?;
+			let messages: Vec<OutgoingMessage> = serde_json::from_str(&data)
+				.map_err(|e| format!("Error initializing transport from storage: {:?}", e))?;
+			storage_messages
+				.entry(queue_identifier)
+				.or_default()
+				.insert(message_identifier, messages);
+		}
 
 		for (queue_identifier, storage_messages) in storage_messages.iter() {
 			self.ensure_message_queue(queue_identifier.clone(), storage_messages.clone());
@@ -165,10 +211,21 @@ impl MatrixService {
 		messages: HashMap<MessageIdentifier, Vec<OutgoingMessage>>,
 	) {
 		if self.messages.get(&queue_identifier).is_none() {
-			let (queue, sender) =
-				RetryMessageQueue::new(self.our_sender.clone(), self.config.clone());
+			let (queue, sender) = RetryMessageQueue::new(
+				queue_identifier.clone(),
+				self.our_sender.clone(),
+				self.config.clone(),
+			);
 			self.running_futures.push(Box::pin(queue.run()));
 
+			// The presence of the recipient is checked from now on.
+			let reachability = {
+				let mut tracker = self.reachability.write();
+				tracker.watch(queue_identifier.recipient);
+				tracker.get(queue_identifier.recipient)
+			};
+			let _ = sender.send(QueueOp::Reachability(reachability));
+
 			self.messages
 				.entry(queue_identifier)
 				.or_insert(QueueInfo { op_sender: sender, messages });
@@ -179,18 +236,30 @@ impl MatrixService {
 	/// messages, send out outgoing messages or broadcast specific messages to the service
 	/// providers.
 	pub async fn run(mut self, mut message_handler: MessageHandler) {
+		let mut presence_interval = time::interval(MATRIX_PRESENCE_CHECK_INTERVAL);
+		presence_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 		loop {
+			let sync_delay =
+				if self.sync_failures > 0 { MATRIX_SYNC_RETRY_DELAY } else { Duration::ZERO };
 			select! {
 				() = self.running_futures.select_next_some(), if !self.running_futures.is_empty() => {},
-				incoming_messages = self.client.get_new_messages().fuse() => {
-					if let Err(e) = self.matrix_storage.set_sync_token(self.client.get_sync_token()) {
+				_ = presence_interval.tick() => self.check_presence().await,
+				incoming_messages = sync_after(&mut self.client, sync_delay).fuse() => {
+					if let Err(e) = self.matrix_storage.set_sync_token(&self.client.server_name(), self.client.get_sync_token()) {
 						error!("Could not store matrix sync token: {:?}", e);
 					}
 
 					let incoming_messages = match incoming_messages {
-						Ok(incoming_messages) => incoming_messages,
+						Ok(incoming_messages) => {
+							self.sync_failures = 0;
+							incoming_messages
+						},
 						Err(e) => {
-							error!("Sync error: {:?}", e);
+							self.sync_failures += 1;
+							error!(message = "Sync error", failures = self.sync_failures, error = format!("{:?}", e));
+							if self.sync_failures >= MATRIX_MAX_SYNC_FAILURES && !self.fallback_servers.is_empty() {
+								self.failover().await;
+							}
 							continue;
 						}
 					};
@@ -210,6 +279,7 @@ impl MatrixService {
 									continue
 								}
 							};
+							self.update_reachability(sender, Reachability::Reachable);
 							self.inplace_delete_message_queue(incoming_message.clone(), &QueueIdentifier {
 								recipient: sender,
 								canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
@@ -218,123 +288,234 @@ impl MatrixService {
 						let _ = message_handler.handle(incoming_message).await;
 					}
 				},
-				outgoing_message = self.queue_receiver.next() => {
-					match outgoing_message {
-						Some(TransportServiceMessage::Enqueue((queue_identifier, outgoing_message))) => {
-							if matches!(outgoing_message.inner, MessageInner::Delivered(_)) {
-								self.send_messages(vec![outgoing_message]).await;
-								continue
-							}
-							trace!(
-								message = "Enqueue message",
-								msg_type = outgoing_message.type_name(),
-								message_identifier = outgoing_message.message_identifier,
-								queue_id = queue_identifier.to_string(),
-							);
-							self.ensure_message_queue(queue_identifier.clone(), HashMap::new());
-							let queue = self.messages
-								.get_mut(&queue_identifier)
-								.expect("Queue should have been created before.");
-							if let Err(e) = queue
-								.op_sender
-								.send(QueueOp::Enqueue(outgoing_message.message_identifier)) {
-									error!(
-										message = "Failed to enqueue message for sending",
-										message_identifier = outgoing_message.message_identifier,
-										error = format!("{:?}", e)
+				outgoing_messages = self.queue_receiver.next() => {
+					// Messages to the same partner which are ready at the same time are sent
+					// together.
+					let mut batch: Vec<OutgoingMessage> = vec![];
+					for outgoing_message in outgoing_messages.unwrap_or_default() {
+						match outgoing_message {
+							TransportServiceMessage::Enqueue((queue_identifier, outgoing_message)) => {
+								if matches!(outgoing_message.inner, MessageInner::Delivered(_)) {
+									batch.push(outgoing_message);
+									continue
+								}
+								trace!(
+									message = "Enqueue message",
+									msg_type = outgoing_message.type_name(),
+									message_identifier = outgoing_message.message_identifier,
+									queue_id = queue_identifier.to_string(),
+								);
+								if !outgoing_message.recipient_metadata.user_id.is_empty() {
+									self.user_ids.insert(
+										outgoing_message.recipient,
+										outgoing_message.recipient_metadata.user_id.clone(),
 									);
 								}
+								self.ensure_message_queue(queue_identifier.clone(), HashMap::new());
+								let queue = self.messages
+									.get_mut(&queue_identifier)
+									.expect("Queue should have been created before.");
+								if let Err(e) = queue
+									.op_sender
+									.send(QueueOp::Enqueue(outgoing_message.message_identifier)) {
+										error!(
+											message = "Failed to enqueue message for sending",
+											message_identifier = outgoing_message.message_identifier,
+											error = format!("{:?}", e)
+										);
+									}
 
-							queue.messages
-								.entry(outgoing_message.message_identifier)
-								.or_insert(vec![]).push(outgoing_message.clone());
+								queue.messages
+									.entry(outgoing_message.message_identifier)
+									.or_insert(vec![]).push(outgoing_message.clone());
 
-							if matches!(outgoing_message.inner, MessageInner::Processed(_)) {
-								self.send_messages(vec![outgoing_message]).await;
-							}
+								self.store_message(&queue_identifier, outgoing_message.message_identifier);
 
-							self.store_messages();
-						},
-						Some(TransportServiceMessage::Send(message_identifier)) => {
-							let messages_by_identifier: Vec<OutgoingMessage> = self.messages
-								.values()
-								.flat_map(|queue_info| {
-									queue_info
-										 .messages
-										 .values()
-										 .flat_map(|messages| messages.iter().filter(|m| m.message_identifier == message_identifier).cloned().collect::<Vec<OutgoingMessage>>())
-										 .collect::<Vec<OutgoingMessage>>()
-								})
-								.collect();
-							self.send_messages(messages_by_identifier).await;
-						},
-						Some(TransportServiceMessage::Broadcast(message)) => {
-							let (message_json, device_id) = match message.inner {
-								messages::MessageInner::PFSCapacityUpdate(ref inner) => {
-									let message_json = match serde_json::to_string(&inner) {
-										Ok(json) => json,
-										Err(e) => {
-											error!("Could not serialize message: {:?}", e);
-											continue;
+								if matches!(outgoing_message.inner, MessageInner::Processed(_)) {
+									batch.push(outgoing_message);
+								}
+							},
+							TransportServiceMessage::Send(message_identifier) => {
+								let messages_by_identifier: Vec<OutgoingMessage> = self.messages
+									.values()
+									.flat_map(|queue_info| {
+										queue_info
+											 .messages
+											 .values()
+											 .flat_map(|messages| messages.iter().filter(|m| m.message_identifier == message_identifier).cloned().collect::<Vec<OutgoingMessage>>())
+											 .collect::<Vec<OutgoingMessage>>()
+									})
+									.collect();
+								for message in messages_by_identifier {
+									// The message may already be part of the batch if it was just enqueued.
+									if !batch.iter().any(|m| m.message_identifier == message.message_identifier && m.recipient == message.recipient) {
+										batch.push(message);
+									}
+								}
+							},
+							TransportServiceMessage::Broadcast(message) => {
+								// Updates addressed to a partner for its local routing.
+								if !message.recipient.is_zero() {
+									batch.push(message);
+									continue;
+								}
+
+									let (message_json, device_id) = match message.inner {
+										messages::MessageInner::PFSCapacityUpdate(ref inner) => {
+											let message_json = match serde_json::to_string(&inner) {
+												Ok(json) => json,
+												Err(e) => {
+													error!("Could not serialize message: {:?}", e);
+													continue;
+												}
+											};
+											(message_json, "PATH_FINDING")
+										},
+										messages::MessageInner::PFSFeeUpdate(ref inner) => {
+											let message_json = match serde_json::to_string(&inner) {
+												Ok(json) => json,
+												Err(e) => {
+													error!("Could not serialize message: {:?}", e);
+													continue;
+												}
+											};
+											(message_json, "PATH_FINDING")
+										},
+										messages::MessageInner::MSUpdate(ref inner) => {
+											let message_json = match serde_json::to_string(&inner) {
+												Ok(json) => json,
+												Err(e) => {
+													error!("Could not serialize message: {:?}", e);
+													continue;
+												}
+											};
+											(message_json, "MONITORING")
+										},
+										_ => {
+											// No other messages should be broadcasted
+											continue
 										}
 									};
-									(message_json, "PATH_FINDING")
-								},
-								messages::MessageInner::PFSFeeUpdate(ref inner) => {
-									let message_json = match serde_json::to_string(&inner) {
+
+									debug!(message = "Broadcast message", msg_type = message.type_name());
+
+									let content = MessageContent { msgtype: MessageType::Text.to_string(), body: message_json };
+									let json = match serde_json::to_string(&content) {
 										Ok(json) => json,
 										Err(e) => {
 											error!("Could not serialize message: {:?}", e);
 											continue;
 										}
 									};
-									(message_json, "PATH_FINDING")
-								},
-								messages::MessageInner::MSUpdate(ref inner) => {
-									let message_json = match serde_json::to_string(&inner) {
-										Ok(json) => json,
-										Err(e) => {
-											error!("Could not serialize message: {:?}", e);
-											continue;
-										}
+									if let Err(e) = self.client.broadcast(json, DeviceIdOrAllDevices::DeviceId(device_id.into())).await {
+										error!("Could not broadcast message {:?}", e);
 									};
-									(message_json, "MONITORING")
 								},
-								_ => {
-									// No other messages should be broadcasted
-									continue
+							TransportServiceMessage::Clear(queue_identifier) => {
+								if let Some(queue_info) = self.messages.get(&queue_identifier) {
+									let _ = queue_info.op_sender.send(QueueOp::Stop);
 								}
-							};
-
-							debug!(message = "Broadcast message", msg_type = message.type_name());
-
-							let content = MessageContent { msgtype: MessageType::Text.to_string(), body: message_json };
-							let json = match serde_json::to_string(&content) {
-								Ok(json) => json,
-								Err(e) => {
-									error!("Could not serialize message: {:?}", e);
-									continue;
+								self.messages.remove(&queue_identifier);
+								if let Err(e) = self.matrix_storage.delete_queue(&storage_key(&queue_identifier)) {
+									error!("Could not delete stored messages: {:?}", e);
 								}
-							};
-							if let Err(e) = self.client.broadcast(json, DeviceIdOrAllDevices::DeviceId(device_id.into())).await {
-								error!("Could not broadcast message {:?}", e);
-							};
-						},
-						Some(TransportServiceMessage::Clear(queue_identifier)) => {
-							if let Some(queue_info) = self.messages.get(&queue_identifier) {
-								let _ = queue_info.op_sender.send(QueueOp::Stop);
-							}
-							self.messages.remove(&queue_identifier);
+							},
+							_ => {}
 						}
-						_ => {}
 					}
+					self.send_messages(batch).await;
 				}
 			}
 		}
 	}
 
-	/// Send out a list of messages.
+	/// Log into the most responsive fallback home server which accepts us.
+	///
+	/// The queued messages are kept and sent through the new home server, where syncing resumes
+	/// from the last sync token stored for it. The current home server becomes a fallback.
+	pub(crate) async fn failover(&mut self) {
+		let current_server = self.client.homeserver_url();
+		for homeserver_url in rank_servers(self.fallback_servers.clone()).await {
+			if let Err(e) = self.client.switch_server(&homeserver_url).await {
+				warn!(
+					message = "Could not fail over to home server",
+					server = homeserver_url.as_str(),
+					error = e.to_string(),
+				);
+				continue
+			}
+			info!(
+				message = "Failed over to home server",
+				from = current_server.as_str(),
+				to = homeserver_url.as_str(),
+			);
+
+			if let Ok(sync_token) = self.matrix_storage.get_sync_token(&self.client.server_name()) {
+				self.client.set_sync_token(sync_token);
+			}
+			*self.our_metadata.write() = self.client.address_metadata();
+
+			self.fallback_servers.retain(|server| *server != homeserver_url);
+			self.fallback_servers.push(current_server);
+			self.sync_failures = 0;
+			return
+		}
+
+		error!(message = "No fallback home server is available", server = current_server.as_str());
+		self.sync_failures = 0;
+	}
+
+	/// Check the presence of the tracked nodes.
+	async fn check_presence(&self) {
+		let addresses = self.reachability.read().addresses();
+		if addresses.is_empty() {
+			return
+		}
+		let user_ids: Vec<String> = addresses
+			.iter()
+			.map(|address| {
+				self.user_ids
+					.get(address)
+					.cloned()
+					.unwrap_or_else(|| self.client.make_user_id(address))
+			})
+			.collect();
+		let presences =
+			join_all(user_ids.iter().map(|user_id| self.client.get_presence(user_id))).await;
+
+		for (address, presence) in addresses.into_iter().zip(presences) {
+			match presence {
+				Ok(reachability) => self.update_reachability(address, reachability),
+				Err(e) => debug!(
+					message = "Could not check presence",
+					address = address.checksum(),
+					error = e.to_string(),
+				),
+			}
+		}
+	}
+
+	/// Record the reachability of a node, pausing or resuming the queues of messages to it when
+	/// it changed.
+	fn update_reachability(&self, address: Address, reachability: Reachability) {
+		if !self.reachability.write().update(address, reachability) {
+			return
+		}
+		info!(
+			message = "Reachability changed",
+			address = address.checksum(),
+			reachability = format!("{:?}", reachability),
+		);
+		for (queue_identifier, queue_info) in self.messages.iter() {
+			if queue_identifier.recipient == address {
+				let _ = queue_info.op_sender.send(QueueOp::Reachability(reachability));
+			}
+		}
+	}
+
+	/// Send out a list of messages, batching the messages to the same recipient.
 	async fn send_messages(&self, messages: Vec<OutgoingMessage>) {
+		let mut batches: Vec<(Address, Vec<OutgoingMessage>)> = vec![];
 		for message in messages {
 			debug!(
 				message = "Sending message",
@@ -342,38 +523,48 @@ impl MatrixService {
 				msg_type = message.type_name(),
 				recipient = message.recipient.checksum()
 			);
-			self.send(message).await;
+			match batches.iter_mut().find(|(recipient, _)| *recipient == message.recipient) {
+				Some((_, batch)) => batch.push(message),
+				None => batches.push((message.recipient, vec![message])),
+			}
 		}
-	}
 
-	/// Instruct the client to send a message immediately.
-	async fn send(&self, message: OutgoingMessage) {
-		if let Err(e) = self.client.send(message.clone(), message.recipient_metadata).await {
-			error!("Could not send message {:?}", e);
-		};
+		for (_, batch) in batches {
+			for messages in batch.chunks(MATRIX_MAX_BATCH_SIZE) {
+				let receiver_metadata = messages[0].recipient_metadata.clone();
+				if let Err(e) = self.client.send(messages, receiver_metadata).await {
+					error!("Could not send messages {:?}", e);
+				}
+			}
+		}
 	}
 
-	/// Store messages through the matrix storage.
-	fn store_messages(&self) {
-		let messages: HashMap<String, HashMap<MessageIdentifier, Vec<OutgoingMessage>>> = self
+	/// Store the messages queued with `message_identifier` through the matrix storage.
+	fn store_message(
+		&self,
+		queue_identifier: &QueueIdentifier,
+		message_identifier: MessageIdentifier,
+	) {
+		let messages = match self
 			.messages
-			.iter()
-			.map(|(queue_identifier, queue_info)| {
-				(
-					serde_json::to_string(&queue_identifier.clone()).expect("Should serialize"),
-					queue_info.messages.clone(),
-				)
-			})
-			.collect();
-		let storage_messages = StorageMessages { messages };
-		let messages_data = match serde_json::to_string(&storage_messages) {
+			.get(queue_identifier)
+			.and_then(|queue_info| queue_info.messages.get(&message_identifier))
+		{
+			Some(messages) => messages,
+			None => return,
+		};
+		let messages_data = match serde_json::to_string(messages) {
 			Ok(data) => data,
 			Err(e) => {
 				error!("Could not serialize messages for storage: {:?}", e);
 				return
 			},
 		};
-		if let Err(e) = self.matrix_storage.store_messages(messages_data) {
+		if let Err(e) = self.matrix_storage.store_message(
+			&storage_key(queue_identifier),
+			message_identifier,
+			messages_data,
+		) {
 			error!("Could not store messages: {:?}", e);
 		}
 	}
@@ -444,8 +635,28 @@ impl MatrixService {
 							error = format!("{:?}", e)
 						);
 					}
+					if let Err(e) = self
+						.matrix_storage
+						.delete_message(&storage_key(queue_id), incoming_message_identifier)
+					{
+						error!("Could not delete stored message: {:?}", e);
+					}
 				}
 			}
 		}
 	}
 }
+
+/// Sync with the home server once `delay` has passed.
+async fn sync_after(
+	client: &mut MatrixClient,
+	delay: Duration,
+) -> Result<Vec<IncomingMessage>, matrix_sdk::Error> {
+	time::sleep(delay).await;
+	client.get_new_messages().await
+}
+
+/// The key of a queue in the matrix storage.
+fn storage_key(queue_identifier: &QueueIdentifier) -> String {
+	serde_json::to_string(queue_identifier).expect("Should serialize")
+}
@@ -1,11 +1,17 @@
-use std::sync::Mutex;
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+};
 
 use derive_more::Display;
+use raiden_primitives::types::MessageIdentifier;
 use rusqlite::{
 	params,
 	Connection,
-	Error,
+	Transaction,
 };
+use serde::Deserialize;
+use serde_json::Value;
 
 mod sqlite;
 
@@ -19,6 +25,15 @@ pub enum StorageError {
 	CannotLock,
 	#[display(fmt = "SQL Error: {}", _0)]
 	Sql(rusqlite::Error),
+	#[display(fmt = "Could not migrate legacy data: {}", _0)]
+	Migration(String),
+}
+
+/// The queued messages as they were stored in the legacy `matrix_messages` table, by queue and
+/// message identifier.
+#[derive(Deserialize)]
+struct LegacyStorageMessages {
+	messages: HashMap<String, HashMap<MessageIdentifier, Value>>,
 }
 
 /// Storage for the matrix transport layer.
@@ -33,83 +48,186 @@ impl MatrixStorage {
 	}
 
 	/// Initialize storage and create tables.
-	pub fn setup_database(&self) -> Result<()> {
-		let setup_db_sql = format!(
-			"
-			PRAGMA foreign_keys=off;
-			BEGIN TRANSACTION;
-			{}{}
-			COMMIT;
-			PRAGMA foreign_keys=on;
-			",
-			sqlite::DB_CREATE_MATRIX_CONFIG,
-			sqlite::DB_CREATE_MATRIX_MESSAGES,
-		);
-		self.conn
-			.lock()
-			.map_err(|_| StorageError::CannotLock)?
-			.execute_batch(&setup_db_sql)
+	///
+	/// The data of the legacy tables is migrated to the new ones before they are dropped, the
+	/// legacy sync token being kept for the home server `server_name` in use.
+	pub fn setup_database(&self, server_name: &str) -> Result<()> {
+		let mut conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let transaction = conn.transaction().map_err(StorageError::Sql)?;
+		transaction
+			.execute_batch(&format!(
+				"{}{}",
+				sqlite::DB_CREATE_MATRIX_SYNC_TOKENS,
+				sqlite::DB_CREATE_MATRIX_QUEUED_MESSAGES,
+			))
 			.map_err(StorageError::Sql)?;
+		migrate_legacy_messages(&transaction)?;
+		migrate_legacy_sync_token(&transaction, server_name)?;
+		transaction.commit().map_err(StorageError::Sql)?;
 
 		Ok(())
 	}
 
-	/// Retrieve the last known sync token.
-	pub fn get_sync_token(&self) -> Result<String> {
+	/// Retrieve the last known sync token of the home server `server_name`.
+	pub fn get_sync_token(&self, server_name: &str) -> Result<String> {
 		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
 		let mut stmt = conn
-			.prepare("SELECT sync_token FROM matrix_config")
+			.prepare("SELECT sync_token FROM matrix_sync_tokens WHERE server_name=?1")
 			.map_err(StorageError::Sql)?;
 
-		let sync_token: String = stmt.query_row([], |r| r.get(0)).map_err(StorageError::Sql)?;
+		let sync_token: String =
+			stmt.query_row(params![server_name], |r| r.get(0)).map_err(StorageError::Sql)?;
 		Ok(sync_token)
 	}
 
-	/// Set the last received sync token.
-	pub fn set_sync_token(&self, sync_token: String) -> Result<()> {
-		let sql = if self.get_sync_token().is_err() {
-			"INSERT INTO matrix_config(sync_token) VALUES(?1)".to_string()
-		} else {
-			"UPDATE matrix_config SET sync_token=?1".to_string()
-		};
+	/// Set the last received sync token of the home server `server_name`.
+	pub fn set_sync_token(&self, server_name: &str, sync_token: String) -> Result<()> {
 		self.conn
 			.lock()
 			.map_err(|_| StorageError::CannotLock)?
-			.execute(&sql, params![sync_token])
+			.execute(
+				"INSERT OR REPLACE INTO matrix_sync_tokens(server_name, sync_token) VALUES(?1, ?2)",
+				params![server_name, sync_token],
+			)
 			.map_err(StorageError::Sql)?;
 		Ok(())
 	}
 
-	/// Retrieve the list of queued messages.
-	pub fn get_messages(&self) -> Result<String> {
+	/// Retrieve the queued messages as `(queue_identifier, message_identifier, data)` rows.
+	pub fn get_messages(&self) -> Result<Vec<(String, MessageIdentifier, String)>> {
 		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
-		let mut stmt =
-			conn.prepare("SELECT data FROM matrix_messages").map_err(StorageError::Sql)?;
-
-		let messages: String = match stmt.query_row([], |r| r.get(0)) {
-			Ok(messages) => messages,
-			Err(e) =>
-				if let Error::QueryReturnedNoRows = e {
-					return Ok(String::new())
-				} else {
-					return Err(StorageError::Sql(e))
-				},
-		};
-		Ok(messages)
+		let mut stmt = conn
+			.prepare(
+				"SELECT queue_identifier, message_identifier, data FROM matrix_queued_messages",
+			)
+			.map_err(StorageError::Sql)?;
+
+		let rows = stmt
+			.query_map([], |r| {
+				// Message identifiers are stored bit for bit as signed integers.
+				let message_identifier: i64 = r.get(1)?;
+				Ok((r.get(0)?, message_identifier as MessageIdentifier, r.get(2)?))
+			})
+			.map_err(StorageError::Sql)?;
+		rows.collect::<std::result::Result<Vec<_>, _>>().map_err(StorageError::Sql)
 	}
 
-	/// Storage queued messages.
-	pub fn store_messages(&self, messages: String) -> Result<()> {
-		let sql = if self.get_messages().is_err() {
-			"INSERT INTO matrix_messages(data) VALUES(?1)".to_string()
-		} else {
-			"UPDATE matrix_messages SET data=?1".to_string()
-		};
+	/// Store the messages queued with `message_identifier`, replacing previously stored ones.
+	pub fn store_message(
+		&self,
+		queue_identifier: &str,
+		message_identifier: MessageIdentifier,
+		data: String,
+	) -> Result<()> {
 		self.conn
 			.lock()
 			.map_err(|_| StorageError::CannotLock)?
-			.execute(&sql, params![messages])
+			.execute(
+				"INSERT OR REPLACE INTO matrix_queued_messages(queue_identifier, message_identifier, data) VALUES(?1, ?2, ?3)",
+				params![queue_identifier, message_identifier as i64, data],
+			)
 			.map_err(StorageError::Sql)?;
 		Ok(())
 	}
+
+	/// Delete the messages queued with `message_identifier`.
+	pub fn delete_message(
+		&self,
+		queue_identifier: &str,
+		message_identifier: MessageIdentifier,
+	) -> Result<()> {
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(
+				"DELETE FROM matrix_queued_messages WHERE queue_identifier=?1 AND message_identifier=?2",
+				params![queue_identifier, message_identifier as i64],
+			)
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+
+	/// Delete all messages of a queue.
+	pub fn delete_queue(&self, queue_identifier: &str) -> Result<()> {
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(
+				"DELETE FROM matrix_queued_messages WHERE queue_identifier=?1",
+				params![queue_identifier],
+			)
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+}
+
+/// Returns true if the table `name` exists.
+fn table_exists(transaction: &Transaction, name: &str) -> Result<bool> {
+	let count: u32 = transaction
+		.query_row(
+			"SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+			params![name],
+			|r| r.get(0),
+		)
+		.map_err(StorageError::Sql)?;
+	Ok(count > 0)
+}
+
+/// Move the messages of the legacy `matrix_messages` table, which held all queued messages in a
+/// single row, to one row per queue and message identifier, then drop it.
+fn migrate_legacy_messages(transaction: &Transaction) -> Result<()> {
+	if !table_exists(transaction, "matrix_messages")? {
+		return Ok(())
+	}
+
+	let rows: Vec<Option<String>> = {
+		let mut stmt = transaction
+			.prepare("SELECT data FROM matrix_messages")
+			.map_err(StorageError::Sql)?;
+		let rows = stmt.query_map([], |r| r.get(0)).map_err(StorageError::Sql)?;
+		rows.collect::<std::result::Result<Vec<_>, _>>().map_err(StorageError::Sql)?
+	};
+	for data in rows.into_iter().flatten() {
+		if data.trim().is_empty() {
+			continue
+		}
+		let legacy: LegacyStorageMessages =
+			serde_json::from_str(&data).map_err(|e| StorageError::Migration(e.to_string()))?;
+		for (queue_identifier, messages) in legacy.messages {
+			for (message_identifier, messages) in messages {
+				transaction
+					.execute(
+						"INSERT OR REPLACE INTO matrix_queued_messages(queue_identifier, message_identifier, data) VALUES(?1, ?2, ?3)",
+						params![queue_identifier, message_identifier as i64, messages.to_string()],
+					)
+					.map_err(StorageError::Sql)?;
+			}
+		}
+	}
+
+	transaction
+		.execute_batch(sqlite::DB_DROP_MATRIX_MESSAGES)
+		.map_err(StorageError::Sql)?;
+	Ok(())
+}
+
+/// Keep the sync token of the legacy `matrix_config` table, which did not record the home server
+/// it was issued by, for `server_name`, then drop it.
+///
+/// A sync token already stored for `server_name` is newer and kept instead.
+fn migrate_legacy_sync_token(transaction: &Transaction, server_name: &str) -> Result<()> {
+	if !table_exists(transaction, "matrix_config")? {
+		return Ok(())
+	}
+
+	transaction
+		.execute(
+			"INSERT OR IGNORE INTO matrix_sync_tokens(server_name, sync_token) SELECT ?1, sync_token FROM matrix_config WHERE sync_token IS NOT NULL AND sync_token != '' LIMIT 1",
+			params![server_name],
+		)
+		.map_err(StorageError::Sql)?;
+	transaction
+		.execute_batch(sqlite::DB_DROP_MATRIX_CONFIG)
+		.map_err(StorageError::Sql)?;
+	Ok(())
 }
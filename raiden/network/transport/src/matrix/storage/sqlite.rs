@@ -1,9 +1,28 @@
-/// Matrix config table.
-pub(super) const DB_CREATE_MATRIX_CONFIG: &str = "
-CREATE TABLE IF NOT EXISTS matrix_config (sync_token TEXT);
+/// Matrix sync tokens table, one row per home server.
+pub(super) const DB_CREATE_MATRIX_SYNC_TOKENS: &str = "
+CREATE TABLE IF NOT EXISTS matrix_sync_tokens (
+	server_name TEXT NOT NULL PRIMARY KEY,
+	sync_token TEXT NOT NULL
+);
 ";
 
-/// Matrix messages table.
-pub(super) const DB_CREATE_MATRIX_MESSAGES: &str = "
-CREATE TABLE IF NOT EXISTS matrix_messages (data TEXT);
+/// Matrix queued messages table, one row per queue and message identifier.
+pub(super) const DB_CREATE_MATRIX_QUEUED_MESSAGES: &str = "
+CREATE TABLE IF NOT EXISTS matrix_queued_messages (
+	queue_identifier TEXT NOT NULL,
+	message_identifier INTEGER NOT NULL,
+	data TEXT NOT NULL,
+	PRIMARY KEY (queue_identifier, message_identifier)
+);
+";
+
+/// Drop the table which held all queued messages in a single row, once they were migrated.
+pub(super) const DB_DROP_MATRIX_MESSAGES: &str = "
+DROP TABLE IF EXISTS matrix_messages;
+";
+
+/// Drop the table which held a single sync token, regardless of the home server, once it was
+/// migrated.
+pub(super) const DB_DROP_MATRIX_CONFIG: &str = "
+DROP TABLE IF EXISTS matrix_config;
 ";
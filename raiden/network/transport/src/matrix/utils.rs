@@ -1,10 +1,19 @@
-use std::collections::HashMap;
+use std::{
+	collections::HashMap,
+	time::{
+		Duration,
+		Instant,
+	},
+};
 
+use futures::future::join_all;
 use reqwest;
+use tracing::debug;
 
 use super::constants::{
 	MATRIX_DEFAULT_DEVELOPMENT_SERVERS_LIST_URL,
 	MATRIX_DEFAULT_PRODUCTION_SERVERS_LIST_URL,
+	MATRIX_RTT_TIMEOUT,
 };
 use crate::types::EnvironmentType;
 
@@ -25,7 +34,41 @@ pub async fn get_default_matrix_servers(
 		.collect())
 }
 
-/// Returns which best server to use from the list provided.
-pub fn select_best_server(servers: Vec<String>) -> String {
-	servers.first().unwrap_or(&"".to_owned()).clone()
+/// Measure the round trip time of a request to the home server, `None` if it is unreachable.
+pub async fn measure_rtt(server: &str) -> Option<Duration> {
+	let client = reqwest::Client::builder().timeout(MATRIX_RTT_TIMEOUT).build().ok()?;
+	let url = format!("{}/_matrix/client/versions", server.trim_end_matches('/'));
+
+	let start = Instant::now();
+	let response = client.get(url).send().await.ok()?;
+	if !response.status().is_success() {
+		return None
+	}
+	Some(start.elapsed())
+}
+
+/// Returns the reachable servers from the list provided, fastest first.
+pub async fn rank_servers(servers: Vec<String>) -> Vec<String> {
+	let rtts = join_all(servers.iter().map(|server| measure_rtt(server))).await;
+
+	let mut reachable: Vec<(String, Duration)> = servers
+		.into_iter()
+		.zip(rtts)
+		.filter_map(|(server, rtt)| {
+			debug!(
+				message = "Measured home server round trip time",
+				server = server.as_str(),
+				rtt = ?rtt,
+			);
+			rtt.map(|rtt| (server, rtt))
+		})
+		.collect();
+	reachable.sort_by_key(|(_, rtt)| *rtt);
+	reachable.into_iter().map(|(server, _)| server).collect()
+}
+
+/// Returns which best server to use from the list provided, the one with the lowest round trip
+/// time.
+pub async fn select_best_server(servers: Vec<String>) -> Option<String> {
+	rank_servers(servers).await.into_iter().next()
 }
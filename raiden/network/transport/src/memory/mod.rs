@@ -0,0 +1,7 @@
+/// Router module
+pub mod router;
+/// Service module
+pub mod service;
+
+pub use router::*;
+pub use service::*;
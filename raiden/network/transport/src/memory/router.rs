@@ -0,0 +1,148 @@
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc,
+		Mutex,
+	},
+	time::Duration,
+};
+
+use raiden_network_messages::messages::{
+	IncomingMessage,
+	OutgoingMessage,
+};
+use raiden_primitives::{
+	traits::Checksum,
+	types::Address,
+};
+use rand::{
+	Rng,
+	SeedableRng,
+};
+use rand_chacha::ChaChaRng;
+use tokio::sync::mpsc::{
+	self,
+	UnboundedReceiver,
+	UnboundedSender,
+};
+use tracing::{
+	error,
+	trace,
+};
+
+/// Conditions of the simulated network.
+///
+/// Every message is delayed by `latency` plus a random part of `jitter`, messages sent within
+/// `jitter` of each other may therefore be delivered out of order. Drops and duplicates are decided
+/// per message with the given probabilities. All random decisions are taken from a generator
+/// seeded with `seed` so that a simulation can be reproduced.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryNetworkConfig {
+	pub latency: Duration,
+	pub jitter: Duration,
+	pub drop_rate: f64,
+	pub duplicate_rate: f64,
+	pub seed: u64,
+}
+
+/// The state shared by all handles of a router.
+struct RouterState {
+	config: MemoryNetworkConfig,
+	rng: ChaChaRng,
+	nodes: HashMap<Address, UnboundedSender<IncomingMessage>>,
+}
+
+/// Connects nodes of the same process through channels.
+///
+/// Outgoing messages are serialized and decoded again as they would be over the wire before being
+/// delivered to the recipient as incoming messages.
+#[derive(Clone)]
+pub struct MemoryRouter {
+	state: Arc<Mutex<RouterState>>,
+}
+
+impl MemoryRouter {
+	/// Create an instance of `MemoryRouter`.
+	pub fn new(config: MemoryNetworkConfig) -> Self {
+		let rng = ChaChaRng::seed_from_u64(config.seed);
+		Self { state: Arc::new(Mutex::new(RouterState { config, rng, nodes: HashMap::new() })) }
+	}
+
+	/// Connect a node, returning the receiver of the messages addressed to it.
+	///
+	/// Connecting an address again replaces the previous receiver.
+	pub fn connect(&self, address: Address) -> UnboundedReceiver<IncomingMessage> {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		self.state.lock().expect("Router lock poisoned").nodes.insert(address, sender);
+		receiver
+	}
+
+	/// Disconnect a node, its service stops and messages addressed to it are lost.
+	pub fn disconnect(&self, address: &Address) {
+		self.state.lock().expect("Router lock poisoned").nodes.remove(address);
+	}
+
+	/// Returns true if a node is connected with `address`.
+	pub fn is_connected(&self, address: &Address) -> bool {
+		self.state.lock().expect("Router lock poisoned").nodes.contains_key(address)
+	}
+
+	/// Deliver a message to its recipient under the conditions of the simulated network.
+	pub fn route(&self, message: OutgoingMessage) {
+		let message_json = match serde_json::to_string(&message) {
+			Ok(json) => json,
+			Err(e) => {
+				error!("Could not serialize message: {:?}", e);
+				return
+			},
+		};
+		let incoming_message: IncomingMessage = match message_json.try_into() {
+			Ok(message) => message,
+			Err(e) => {
+				error!("Could not decode message: {}", e);
+				return
+			},
+		};
+
+		let mut state = self.state.lock().expect("Router lock poisoned");
+		let sender = match state.nodes.get(&message.recipient) {
+			Some(sender) => sender.clone(),
+			None => {
+				trace!(
+					message = "Recipient is not connected",
+					recipient = message.recipient.checksum(),
+					msg_type = message.type_name(),
+				);
+				return
+			},
+		};
+
+		let config = state.config.clone();
+		if state.rng.gen_bool(config.drop_rate.clamp(0.0, 1.0)) {
+			trace!(
+				message = "Dropping message",
+				message_identifier = message.message_identifier,
+				msg_type = message.type_name(),
+			);
+			return
+		}
+		let copies = if state.rng.gen_bool(config.duplicate_rate.clamp(0.0, 1.0)) { 2 } else { 1 };
+
+		for _ in 0..copies {
+			let delay = config.latency + config.jitter.mul_f64(state.rng.gen_range(0.0..1.0));
+			deliver(sender.clone(), incoming_message.clone(), delay);
+		}
+	}
+}
+
+/// Hand the message to the recipient once `delay` has passed.
+fn deliver(sender: UnboundedSender<IncomingMessage>, message: IncomingMessage, delay: Duration) {
+	if delay.is_zero() {
+		let _ = sender.send(message);
+		return
+	}
+	tokio::spawn(async move {
+		tokio::time::sleep(delay).await;
+		let _ = sender.send(message);
+	});
+}
@@ -0,0 +1,143 @@
+use futures::StreamExt;
+use raiden_network_messages::messages::{
+	IncomingMessage,
+	MessageInner,
+	OutgoingMessage,
+	TransportServiceMessage,
+};
+use raiden_primitives::types::{
+	Address,
+	QueueIdentifier,
+};
+use raiden_transition::messages::MessageHandler;
+use tokio::{
+	select,
+	sync::mpsc::{
+		self,
+		UnboundedReceiver,
+		UnboundedSender,
+	},
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::debug;
+
+use super::MemoryRouter;
+use crate::{
+	config::TransportConfig,
+	queues::MessageQueues,
+	IncomingMessageHandler,
+	Transport,
+	TransportError,
+};
+
+/// Transport service of a node connected to a `MemoryRouter`.
+///
+/// Messages are queued and retried until they are acknowledged, the same way as the matrix
+/// service does, so that nodes recover from messages dropped by the simulated network.
+pub struct MemoryService {
+	address: Address,
+	router: MemoryRouter,
+	incoming_receiver: UnboundedReceiver<IncomingMessage>,
+	our_sender: UnboundedSender<TransportServiceMessage>,
+	queue_receiver: UnboundedReceiverStream<TransportServiceMessage>,
+	queues: MessageQueues,
+}
+
+impl MemoryService {
+	/// Creates a new instance of `MemoryService` and connects `address` to the router.
+	pub fn new(
+		address: Address,
+		config: TransportConfig,
+		router: MemoryRouter,
+	) -> (Self, UnboundedSender<TransportServiceMessage>) {
+		let (sender, receiver) = mpsc::unbounded_channel();
+		let incoming_receiver = router.connect(address);
+
+		(
+			Self {
+				address,
+				router,
+				incoming_receiver,
+				our_sender: sender.clone(),
+				queue_receiver: UnboundedReceiverStream::new(receiver),
+				queues: MessageQueues::new(config, sender.clone()),
+			},
+			sender,
+		)
+	}
+
+	/// Loops forever, delivering incoming messages to the message handler and routing outgoing
+	/// messages through the router.
+	pub async fn run<H: IncomingMessageHandler>(mut self, mut message_handler: H) {
+		loop {
+			select! {
+				incoming_message = self.incoming_receiver.recv() => {
+					let incoming_message = match incoming_message {
+						Some(incoming_message) => incoming_message,
+						None => {
+							debug!(message = "Node disconnected from the router");
+							return
+						}
+					};
+					debug!(message = "Incoming message", message_identifier = incoming_message.message_identifier, msg_type = incoming_message.type_name());
+					self.queues.acknowledge(&incoming_message);
+					let _ = message_handler.handle(incoming_message).await;
+				},
+				outgoing_message = self.queue_receiver.next() => {
+					match outgoing_message {
+						Some(TransportServiceMessage::Enqueue((queue_identifier, outgoing_message))) => {
+							if matches!(outgoing_message.inner, MessageInner::Delivered(_)) {
+								self.router.route(outgoing_message);
+								continue
+							}
+							self.queues.enqueue(queue_identifier, outgoing_message.clone());
+							if matches!(outgoing_message.inner, MessageInner::Processed(_)) {
+								self.router.route(outgoing_message);
+							}
+						},
+						Some(TransportServiceMessage::Send(message_identifier)) => {
+							for message in self.queues.messages(message_identifier) {
+								self.router.route(message);
+							}
+						},
+						Some(TransportServiceMessage::Broadcast(message)) => {
+							// There are no service providers in the simulated network, only updates
+							// addressed to a partner are delivered.
+							if !message.recipient.is_zero() {
+								self.router.route(message);
+							}
+						},
+						Some(TransportServiceMessage::Clear(queue_identifier)) => {
+							self.queues.clear(&queue_identifier);
+						},
+						_ => {}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Transport for MemoryService {
+	async fn init(&self) -> Result<(), TransportError> {
+		if !self.router.is_connected(&self.address) {
+			return Err(TransportError::Init("Node is not connected to the router".to_owned()))
+		}
+		Ok(())
+	}
+
+	async fn send(
+		&self,
+		queue_identifier: QueueIdentifier,
+		message: OutgoingMessage,
+	) -> Result<(), TransportError> {
+		self.our_sender
+			.send(TransportServiceMessage::Enqueue((queue_identifier, message)))
+			.map_err(|e| TransportError::Other(format!("{:?}", e)))
+	}
+
+	async fn process(self, message_handler: MessageHandler) {
+		self.run(message_handler).await
+	}
+}
@@ -0,0 +1,19 @@
+use raiden_primitives::types::{
+	MessageIdentifier,
+	QueueIdentifier,
+};
+
+/// Receives the metrics of the message queues, for example to export them to a monitoring system.
+pub trait QueueMetrics: Send + Sync {
+	/// The number of messages in a queue waiting to be acknowledged changed.
+	fn queue_depth(&self, queue_identifier: &QueueIdentifier, depth: usize);
+
+	/// A message which was not acknowledged is sent again, `retries` counts the times it was
+	/// sent again so far.
+	fn message_retried(
+		&self,
+		queue_identifier: &QueueIdentifier,
+		message_identifier: MessageIdentifier,
+		retries: u32,
+	);
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use raiden_network_messages::messages::{
+	IncomingMessage,
+	MessageInner,
+	OutgoingMessage,
+	TransportServiceMessage,
+};
+use raiden_primitives::types::{
+	Address,
+	MessageIdentifier,
+	QueueIdentifier,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{
+	error,
+	trace,
+};
+
+use crate::{
+	config::TransportConfig,
+	matrix::queue::{
+		QueueOp,
+		RetryMessageQueue,
+	},
+};
+
+/// Stores the messages for each queue as well as a sender to communicate with the queue.
+struct QueueInfo {
+	op_sender: UnboundedSender<QueueOp>,
+	messages: HashMap<MessageIdentifier, OutgoingMessage>,
+}
+
+/// Keeps the messages of the transports which talk to their peers directly until they are
+/// acknowledged.
+///
+/// Every queue is backed by a `RetryMessageQueue` which asks the transport to send its messages
+/// again until they are removed by a `Delivered`, `Processed` or `WithdrawConfirmation`.
+pub(crate) struct MessageQueues {
+	config: TransportConfig,
+	transport_sender: UnboundedSender<TransportServiceMessage>,
+	queues: HashMap<QueueIdentifier, QueueInfo>,
+}
+
+impl MessageQueues {
+	/// Create an instance of `MessageQueues`.
+	pub(crate) fn new(
+		config: TransportConfig,
+		transport_sender: UnboundedSender<TransportServiceMessage>,
+	) -> Self {
+		Self { config, transport_sender, queues: HashMap::new() }
+	}
+
+	/// Add a message to its queue, creating the queue if needed.
+	pub(crate) fn enqueue(&mut self, queue_identifier: QueueIdentifier, message: OutgoingMessage) {
+		trace!(
+			message = "Enqueue message",
+			msg_type = message.type_name(),
+			message_identifier = message.message_identifier,
+			queue_id = queue_identifier.to_string(),
+		);
+		let queue = self.queues.entry(queue_identifier.clone()).or_insert_with(|| {
+			let (queue, op_sender) = RetryMessageQueue::new(
+				queue_identifier,
+				self.transport_sender.clone(),
+				self.config.clone(),
+			);
+			tokio::spawn(queue.run());
+			QueueInfo { op_sender, messages: HashMap::new() }
+		});
+		if let Err(e) = queue.op_sender.send(QueueOp::Enqueue(message.message_identifier)) {
+			error!(
+				message = "Failed to enqueue message for sending",
+				message_identifier = message.message_identifier,
+				error = format!("{:?}", e)
+			);
+		}
+		queue.messages.insert(message.message_identifier, message);
+	}
+
+	/// Returns the queued messages with `message_identifier`.
+	pub(crate) fn messages(&self, message_identifier: MessageIdentifier) -> Vec<OutgoingMessage> {
+		self.queues
+			.values()
+			.filter_map(|queue| queue.messages.get(&message_identifier).cloned())
+			.collect()
+	}
+
+	/// Remove the messages acknowledged by `incoming_message` from the queues.
+	///
+	/// Returns the recipients of the acknowledged messages.
+	pub(crate) fn acknowledge(&mut self, incoming_message: &IncomingMessage) -> Vec<Address> {
+		let message_identifier = match &incoming_message.inner {
+			MessageInner::Delivered(inner) => inner.delivered_message_identifier,
+			MessageInner::Processed(inner) => inner.message_identifier,
+			MessageInner::WithdrawConfirmation(inner) => inner.message_identifier,
+			_ => return vec![],
+		};
+
+		let mut recipients = vec![];
+		for (queue_identifier, queue) in self.queues.iter_mut() {
+			let outgoing_message = match queue.messages.get(&message_identifier) {
+				Some(outgoing_message) => outgoing_message,
+				None => continue,
+			};
+			// A withdraw request is only confirmed by a withdraw confirmation.
+			if matches!(outgoing_message.inner, MessageInner::WithdrawRequest(_)) &&
+				!matches!(incoming_message.inner, MessageInner::WithdrawConfirmation(_))
+			{
+				continue
+			}
+
+			trace!(
+				message = "Poping message from queue",
+				incoming_message = incoming_message.type_name(),
+				outgoing_message = outgoing_message.type_name(),
+				message_identifier = message_identifier,
+				queue = queue_identifier.to_string(),
+			);
+			queue.messages.remove(&message_identifier);
+			if let Err(e) = queue.op_sender.send(QueueOp::Dequeue(message_identifier)) {
+				error!(
+					message = "Failed to dequeue message",
+					queue_identifier = queue_identifier.to_string(),
+					message_identifier = message_identifier,
+					error = format!("{:?}", e)
+				);
+			}
+			recipients.push(queue_identifier.recipient);
+		}
+		recipients
+	}
+
+	/// Stop the queues of `recipient` and return their messages, ordered by message identifier,
+	/// to hand them over to another transport.
+	pub(crate) fn take(&mut self, recipient: Address) -> Vec<(QueueIdentifier, OutgoingMessage)> {
+		let queue_identifiers: Vec<QueueIdentifier> = self
+			.queues
+			.keys()
+			.filter(|queue_identifier| queue_identifier.recipient == recipient)
+			.cloned()
+			.collect();
+
+		let mut messages = vec![];
+		for queue_identifier in queue_identifiers {
+			if let Some(queue) = self.queues.remove(&queue_identifier) {
+				let _ = queue.op_sender.send(QueueOp::Stop);
+				messages.extend(
+					queue.messages.into_values().map(|message| (queue_identifier.clone(), message)),
+				);
+			}
+		}
+		messages.sort_by_key(|(_, message)| message.message_identifier);
+		messages
+	}
+
+	/// Stop a queue and drop its messages.
+	pub(crate) fn clear(&mut self, queue_identifier: &QueueIdentifier) {
+		if let Some(queue) = self.queues.remove(queue_identifier) {
+			let _ = queue.op_sender.send(QueueOp::Stop);
+		}
+	}
+}
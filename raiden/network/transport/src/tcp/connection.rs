@@ -0,0 +1,218 @@
+use std::{
+	net::SocketAddr,
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::{
+	SinkExt,
+	StreamExt,
+};
+use raiden_blockchain::keys::Signer;
+use raiden_network_messages::messages::IncomingMessage;
+use raiden_primitives::{
+	signing,
+	traits::{
+		Checksum,
+		ToBytes,
+	},
+	types::Address,
+};
+use rand::Rng;
+use tokio::{
+	net::{
+		tcp::{
+			OwnedReadHalf,
+			OwnedWriteHalf,
+		},
+		TcpStream,
+	},
+	select,
+	sync::mpsc::{
+		self,
+		UnboundedSender,
+	},
+	time::timeout,
+};
+use tokio_util::codec::{
+	FramedRead,
+	FramedWrite,
+	LinesCodec,
+};
+use tracing::{
+	debug,
+	error,
+};
+
+use crate::TransportError;
+
+/// Domain of the handshake signatures, so that they can not be mistaken for other signed data.
+const HANDSHAKE_DOMAIN: &[u8] = b"Raiden TCP transport handshake";
+
+/// Length of the random challenge signed by the peer.
+const CHALLENGE_LENGTH: usize = 32;
+
+/// Length of a signature.
+const SIGNATURE_LENGTH: usize = 65;
+
+/// Time given to a peer to authenticate.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum length of a line, peers sending longer ones are disconnected.
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+/// Lines read from a peer.
+type LineReader = FramedRead<OwnedReadHalf, LinesCodec>;
+
+/// Lines written to a peer.
+type LineWriter = FramedWrite<OwnedWriteHalf, LinesCodec>;
+
+/// Events of the connections reported to the service.
+pub(crate) enum ConnectionEvent {
+	/// A peer is authenticated, lines sent through the sender are written to it.
+	Connected(Address, UnboundedSender<String>),
+	/// The connection to a peer was closed or could not be established.
+	Closed(Address),
+	/// A message was received from a peer.
+	Message(IncomingMessage),
+}
+
+/// Connect to `peer` at `socket_address` and handle the connection.
+pub(crate) async fn dial(
+	socket_address: SocketAddr,
+	peer: Address,
+	signer: Arc<dyn Signer>,
+	events: UnboundedSender<ConnectionEvent>,
+) {
+	match TcpStream::connect(socket_address).await {
+		Ok(stream) => handle(stream, Some(peer), signer, events).await,
+		Err(e) => {
+			error!(
+				message = "Could not connect to peer",
+				peer = peer.checksum(),
+				socket_address = socket_address.to_string(),
+				error = format!("{:?}", e),
+			);
+			let _ = events.send(ConnectionEvent::Closed(peer));
+		},
+	}
+}
+
+/// Authenticate the peer and exchange messages with it until the connection is closed.
+///
+/// If `expected_peer` is set, the connection is only kept if it is the authenticated peer.
+pub(crate) async fn handle(
+	stream: TcpStream,
+	expected_peer: Option<Address>,
+	signer: Arc<dyn Signer>,
+	events: UnboundedSender<ConnectionEvent>,
+) {
+	let (reader, writer) = stream.into_split();
+	let mut lines = FramedRead::new(reader, LinesCodec::new_with_max_length(MAX_LINE_LENGTH));
+	let mut writer = FramedWrite::new(writer, LinesCodec::new_with_max_length(MAX_LINE_LENGTH));
+
+	let handshake = timeout(HANDSHAKE_TIMEOUT, handshake(&mut lines, &mut writer, signer.as_ref()));
+	let peer = match handshake.await {
+		Ok(Ok(peer)) if expected_peer.map(|expected| expected == peer).unwrap_or(true) => peer,
+		result => {
+			let reason = match result {
+				Ok(Ok(peer)) => format!("Unexpected peer {}", peer.checksum()),
+				Ok(Err(e)) => e.to_string(),
+				Err(_) => "Handshake timed out".to_owned(),
+			};
+			error!(message = "Could not authenticate peer", reason = reason);
+			if let Some(expected_peer) = expected_peer {
+				let _ = events.send(ConnectionEvent::Closed(expected_peer));
+			}
+			return
+		},
+	};
+
+	debug!(message = "Connected to peer", peer = peer.checksum());
+	let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+	let _ = events.send(ConnectionEvent::Connected(peer, sender));
+
+	loop {
+		select! {
+			line = receiver.recv() => {
+				let line = match line {
+					Some(line) => line,
+					None => break,
+				};
+				if let Err(e) = write_line(&mut writer, &line).await {
+					error!(message = "Could not send message", peer = peer.checksum(), error = e.to_string());
+					break
+				}
+			},
+			line = lines.next() => {
+				let line = match line {
+					Some(Ok(line)) => line,
+					Some(Err(e)) => {
+						error!(message = "Could not read message", peer = peer.checksum(), error = e.to_string());
+						break
+					},
+					None => break,
+				};
+				match IncomingMessage::try_from(line) {
+					Ok(message) => {
+						let _ = events.send(ConnectionEvent::Message(message));
+					},
+					Err(e) => error!("Could not decode message: {}", e),
+				}
+			},
+		}
+	}
+
+	debug!(message = "Disconnected from peer", peer = peer.checksum());
+	let _ = events.send(ConnectionEvent::Closed(peer));
+}
+
+/// Prove our address to the peer and authenticate it.
+///
+/// Both sides send a random challenge and sign the challenge of the other side, the address of the
+/// peer is recovered from its signature.
+async fn handshake(
+	lines: &mut LineReader,
+	writer: &mut LineWriter,
+	signer: &dyn Signer,
+) -> Result<Address, TransportError> {
+	let challenge: [u8; CHALLENGE_LENGTH] = rand::thread_rng().gen();
+	write_line(writer, &hex::encode(challenge)).await?;
+
+	let peer_challenge = read_hex_line(lines, CHALLENGE_LENGTH).await?;
+	let signature = signer
+		.sign_message(&handshake_data(&peer_challenge))
+		.map_err(|e| TransportError::Init(format!("Could not sign challenge: {}", e)))?;
+	write_line(writer, &hex::encode(signature.to_bytes())).await?;
+
+	let peer_signature = read_hex_line(lines, SIGNATURE_LENGTH).await?;
+	signing::recover(&handshake_data(&challenge), &peer_signature)
+		.map_err(|e| TransportError::Init(format!("Invalid handshake signature: {}", e)))
+}
+
+/// The data signed to answer a challenge.
+fn handshake_data(challenge: &[u8]) -> Vec<u8> {
+	let mut data = HANDSHAKE_DOMAIN.to_vec();
+	data.extend_from_slice(challenge);
+	data
+}
+
+/// Read a line holding `length` hex encoded bytes.
+async fn read_hex_line(lines: &mut LineReader, length: usize) -> Result<Vec<u8>, TransportError> {
+	let line = lines
+		.next()
+		.await
+		.ok_or_else(|| TransportError::Init("Connection closed during handshake".to_owned()))?
+		.map_err(|e| TransportError::Init(format!("Invalid handshake: {}", e)))?;
+	let bytes = hex::decode(line.trim())
+		.map_err(|e| TransportError::Init(format!("Invalid handshake: {}", e)))?;
+	if bytes.len() != length {
+		return Err(TransportError::Init("Invalid handshake length".to_owned()))
+	}
+	Ok(bytes)
+}
+
+/// Write a line to the peer.
+async fn write_line(writer: &mut LineWriter, line: &str) -> Result<(), TransportError> {
+	writer.send(line).await.map_err(|e| TransportError::Other(e.to_string()))
+}
@@ -0,0 +1,6 @@
+/// Connection module
+mod connection;
+/// Service module
+pub mod service;
+
+pub use service::*;
@@ -0,0 +1,336 @@
+use std::{
+	collections::HashMap,
+	net::SocketAddr,
+	sync::Arc,
+};
+
+use futures::StreamExt;
+use parking_lot::RwLock;
+use raiden_blockchain::keys::Signer;
+use raiden_network_messages::messages::{
+	MessageInner,
+	OutgoingMessage,
+	TransportServiceMessage,
+};
+use raiden_primitives::{
+	reachability::{
+		Reachability,
+		ReachabilityTracker,
+	},
+	traits::Checksum,
+	types::{
+		Address,
+		QueueIdentifier,
+	},
+};
+use raiden_transition::messages::MessageHandler;
+use tokio::{
+	net::TcpListener,
+	select,
+	sync::mpsc::{
+		self,
+		UnboundedReceiver,
+		UnboundedSender,
+	},
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{
+	debug,
+	error,
+	info,
+};
+
+use super::connection::{
+	self,
+	ConnectionEvent,
+};
+use crate::{
+	config::TransportConfig,
+	queues::MessageQueues,
+	IncomingMessageHandler,
+	Transport,
+	TransportError,
+};
+
+/// A message waiting for the connection to its recipient.
+struct PendingMessage {
+	/// The serialized message.
+	line: String,
+	/// The message to hand over to the fallback transport if the connection fails, `None` for
+	/// queued messages which are handed over with their queue.
+	fallback: Option<TransportServiceMessage>,
+}
+
+/// TCP service which exchanges messages over direct connections to the peers.
+///
+/// Peers are reached at the socket address advertised in their address metadata and are
+/// authenticated by signing a challenge with their key. Messages are queued and retried until they
+/// are acknowledged, the same way as the matrix service does.
+///
+/// Messages to peers which can not be reached directly, as well as broadcasts to the service
+/// providers, are forwarded to the `fallback` transport if one is set. So are the messages to
+/// peers which can not be connected to.
+///
+/// Peers are reachable once connected or once they acknowledge a message, and unreachable when
+/// they can not be connected to.
+pub struct TcpService {
+	signer: Arc<dyn Signer>,
+	listener: TcpListener,
+	fallback: Option<UnboundedSender<TransportServiceMessage>>,
+	our_sender: UnboundedSender<TransportServiceMessage>,
+	queue_receiver: UnboundedReceiverStream<TransportServiceMessage>,
+	queues: MessageQueues,
+	peers: HashMap<Address, UnboundedSender<String>>,
+	pending: HashMap<Address, Vec<PendingMessage>>,
+	event_sender: UnboundedSender<ConnectionEvent>,
+	event_receiver: UnboundedReceiver<ConnectionEvent>,
+	reachability: Arc<RwLock<ReachabilityTracker>>,
+}
+
+impl TcpService {
+	/// Creates a new instance of `TcpService` listening on the configured socket address.
+	pub async fn new(
+		config: TransportConfig,
+		signer: Arc<dyn Signer>,
+		fallback: Option<UnboundedSender<TransportServiceMessage>>,
+		reachability: Arc<RwLock<ReachabilityTracker>>,
+	) -> Result<(Self, UnboundedSender<TransportServiceMessage>), TransportError> {
+		let listener = TcpListener::bind(config.tcp.listen_address).await.map_err(|e| {
+			TransportError::Init(format!(
+				"Could not listen on {}: {}",
+				config.tcp.listen_address, e
+			))
+		})?;
+		let (sender, receiver) = mpsc::unbounded_channel();
+		let (event_sender, event_receiver) = mpsc::unbounded_channel();
+
+		Ok((
+			Self {
+				signer,
+				listener,
+				fallback,
+				our_sender: sender.clone(),
+				queue_receiver: UnboundedReceiverStream::new(receiver),
+				queues: MessageQueues::new(config, sender.clone()),
+				peers: HashMap::new(),
+				pending: HashMap::new(),
+				event_sender,
+				event_receiver,
+				reachability,
+			},
+			sender,
+		))
+	}
+
+	/// The socket address the service listens on.
+	pub fn local_address(&self) -> Result<SocketAddr, TransportError> {
+		self.listener.local_addr().map_err(|e| TransportError::Other(e.to_string()))
+	}
+
+	/// Loops forever, accepting connections from peers, delivering their messages to the message
+	/// handler and sending out outgoing messages.
+	pub async fn run<H: IncomingMessageHandler>(mut self, mut message_handler: H) {
+		loop {
+			select! {
+				accepted = self.listener.accept() => {
+					match accepted {
+						Ok((stream, socket_address)) => {
+							debug!(message = "Incoming connection", socket_address = socket_address.to_string());
+							tokio::spawn(connection::handle(stream, None, self.signer.clone(), self.event_sender.clone()));
+						},
+						Err(e) => error!("Could not accept connection: {:?}", e),
+					}
+				},
+				Some(event) = self.event_receiver.recv() => {
+					match event {
+						ConnectionEvent::Connected(peer, sender) => {
+							self.update_reachability(peer, Reachability::Reachable);
+							for pending in self.pending.remove(&peer).unwrap_or_default() {
+								let _ = sender.send(pending.line);
+							}
+							self.peers.insert(peer, sender);
+						},
+						ConnectionEvent::Closed(peer) => {
+							// Messages are only pending while connecting, the connection failed.
+							if let Some(pending) = self.pending.remove(&peer) {
+								self.update_reachability(peer, Reachability::Unreachable);
+								self.forward_to_fallback(peer, pending);
+							}
+							// A newer connection to the peer may have replaced the closed one.
+							if self.peers.get(&peer).map(|sender| sender.is_closed()).unwrap_or(false) {
+								self.peers.remove(&peer);
+							}
+						},
+						ConnectionEvent::Message(incoming_message) => {
+							debug!(message = "Incoming message", message_identifier = incoming_message.message_identifier, msg_type = incoming_message.type_name());
+							for recipient in self.queues.acknowledge(&incoming_message) {
+								self.update_reachability(recipient, Reachability::Reachable);
+							}
+							let _ = message_handler.handle(incoming_message).await;
+						},
+					}
+				},
+				outgoing_message = self.queue_receiver.next() => {
+					match outgoing_message {
+						Some(TransportServiceMessage::Enqueue((queue_identifier, outgoing_message))) => {
+							if !self.is_reachable(&outgoing_message) {
+								self.forward(TransportServiceMessage::Enqueue((queue_identifier, outgoing_message)));
+								continue
+							}
+							if matches!(outgoing_message.inner, MessageInner::Delivered(_)) {
+								let fallback = TransportServiceMessage::Enqueue((queue_identifier, outgoing_message.clone()));
+								self.send_message(outgoing_message, Some(fallback));
+								continue
+							}
+							self.queues.enqueue(queue_identifier, outgoing_message.clone());
+							if matches!(outgoing_message.inner, MessageInner::Processed(_)) {
+								self.send_message(outgoing_message, None);
+							}
+						},
+						Some(TransportServiceMessage::Send(message_identifier)) => {
+							for message in self.queues.messages(message_identifier) {
+								self.send_message(message, None);
+							}
+						},
+						Some(TransportServiceMessage::Broadcast(message)) => {
+							// Updates addressed to a partner for its local routing.
+							if !message.recipient.is_zero() && self.is_reachable(&message) {
+								let fallback = TransportServiceMessage::Broadcast(message.clone());
+								self.send_message(message, Some(fallback));
+								continue
+							}
+							self.forward(TransportServiceMessage::Broadcast(message));
+						},
+						Some(TransportServiceMessage::Clear(queue_identifier)) => {
+							self.queues.clear(&queue_identifier);
+							self.forward(TransportServiceMessage::Clear(queue_identifier));
+						},
+						Some(message) => self.forward(message),
+						None => {},
+					}
+				}
+			}
+		}
+	}
+
+	/// Returns true if the recipient of the message is connected or advertises a socket address.
+	fn is_reachable(&self, message: &OutgoingMessage) -> bool {
+		self.peers.contains_key(&message.recipient) ||
+			message.recipient_metadata.socket_address().is_some()
+	}
+
+	/// Record the reachability of a peer.
+	fn update_reachability(&self, peer: Address, reachability: Reachability) {
+		if self.reachability.write().update(peer, reachability) {
+			info!(
+				message = "Reachability changed",
+				address = peer.checksum(),
+				reachability = format!("{:?}", reachability),
+			);
+		}
+	}
+
+	/// Hand a message over to the fallback transport.
+	fn forward(&self, message: TransportServiceMessage) {
+		if let Some(fallback) = &self.fallback {
+			let _ = fallback.send(message);
+		}
+	}
+
+	/// Hand the messages to a peer which could not be connected to over to the fallback
+	/// transport, along with the queued ones.
+	///
+	/// Without a fallback, the queued messages are kept to be retried over TCP.
+	fn forward_to_fallback(&mut self, peer: Address, pending: Vec<PendingMessage>) {
+		if self.fallback.is_none() {
+			return
+		}
+		for message in pending.into_iter().filter_map(|pending| pending.fallback) {
+			self.forward(message);
+		}
+		for (queue_identifier, message) in self.queues.take(peer) {
+			self.forward(TransportServiceMessage::Enqueue((queue_identifier, message)));
+		}
+	}
+
+	/// Send a message to its recipient, connecting to it first if needed.
+	///
+	/// `fallback` is handed over to the fallback transport if the recipient can not be connected
+	/// to, queued messages are handed over with their queue instead.
+	fn send_message(
+		&mut self,
+		message: OutgoingMessage,
+		fallback: Option<TransportServiceMessage>,
+	) {
+		debug!(
+			message = "Sending message",
+			message_identifier = message.message_identifier,
+			msg_type = message.type_name(),
+			recipient = message.recipient.checksum()
+		);
+		let mut line = match serde_json::to_string(&message) {
+			Ok(json) => json,
+			Err(e) => {
+				error!("Could not serialize message: {:?}", e);
+				return
+			},
+		};
+
+		if let Some(sender) = self.peers.get(&message.recipient) {
+			match sender.send(line) {
+				Ok(()) => return,
+				// The connection was closed, connect again.
+				Err(e) => line = e.0,
+			}
+			self.peers.remove(&message.recipient);
+		}
+		self.send_after_connecting(message, PendingMessage { line, fallback });
+	}
+
+	/// Keep the message until the connection to the recipient is established.
+	fn send_after_connecting(&mut self, message: OutgoingMessage, pending: PendingMessage) {
+		let recipient = message.recipient;
+		if let Some(pending_messages) = self.pending.get_mut(&recipient) {
+			pending_messages.push(pending);
+			return
+		}
+		let socket_address = match message.recipient_metadata.socket_address() {
+			Some(socket_address) => socket_address,
+			None => {
+				debug!(message = "Peer is not reachable", peer = recipient.checksum());
+				if let Some(fallback) = pending.fallback {
+					self.forward(fallback);
+				}
+				return
+			},
+		};
+		self.pending.insert(recipient, vec![pending]);
+		tokio::spawn(connection::dial(
+			socket_address,
+			recipient,
+			self.signer.clone(),
+			self.event_sender.clone(),
+		));
+	}
+}
+
+#[async_trait::async_trait]
+impl Transport for TcpService {
+	async fn init(&self) -> Result<(), TransportError> {
+		self.local_address().map(|_| ())
+	}
+
+	async fn send(
+		&self,
+		queue_identifier: QueueIdentifier,
+		message: OutgoingMessage,
+	) -> Result<(), TransportError> {
+		self.our_sender
+			.send(TransportServiceMessage::Enqueue((queue_identifier, message)))
+			.map_err(|e| TransportError::Other(format!("{:?}", e)))
+	}
+
+	async fn process(self, message_handler: MessageHandler) {
+		self.run(message_handler).await
+	}
+}
@@ -0,0 +1,223 @@
+use std::{
+	sync::Arc,
+	time::Duration,
+};
+
+use parking_lot::RwLock;
+use raiden_blockchain::keys::{
+	KeystoreSigner,
+	Signer,
+};
+use raiden_primitives::{
+	reachability::ReachabilityTracker,
+	types::AddressMetadata,
+};
+use rusqlite::Connection;
+use serde_json::{
+	json,
+	Value,
+};
+use tokio::{
+	io::{
+		AsyncReadExt,
+		AsyncWriteExt,
+	},
+	net::{
+		TcpListener,
+		TcpStream,
+	},
+};
+
+use crate::{
+	config::{
+		MatrixTransportConfig,
+		TcpTransportConfig,
+		TransportConfig,
+		TransportMode,
+	},
+	matrix::{
+		storage::MatrixStorage,
+		utils::rank_servers,
+		MatrixClient,
+		MatrixService,
+	},
+};
+
+/// An address nothing listens on.
+const UNREACHABLE_SERVER: &str = "http://127.0.0.1:1";
+
+/// A home server answering the requests needed to log in, after `delay`.
+struct FakeHomeServer {
+	url: String,
+	server_name: String,
+}
+
+impl FakeHomeServer {
+	/// Start a home server which accepts or rejects logins.
+	async fn start(accept_login: bool, delay: Duration) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.expect("Should bind");
+		let server_name = listener.local_addr().expect("Should have an address").to_string();
+		let name = server_name.clone();
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				tokio::spawn(serve(stream, name.clone(), accept_login, delay));
+			}
+		});
+		Self { url: format!("http://{}", server_name), server_name }
+	}
+}
+
+/// Answer one request.
+async fn serve(mut stream: TcpStream, server_name: String, accept_login: bool, delay: Duration) {
+	let mut request = vec![];
+	let mut chunk = [0; 4096];
+	let (head_length, content_length) = loop {
+		match stream.read(&mut chunk).await {
+			Ok(0) | Err(_) => return,
+			Ok(read) => request.extend_from_slice(&chunk[..read]),
+		}
+		if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+			let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+			let content_length = head
+				.lines()
+				.find_map(|line| line.strip_prefix("content-length:"))
+				.and_then(|length| length.trim().parse().ok())
+				.unwrap_or(0);
+			break (end + 4, content_length)
+		}
+	};
+	while request.len() < head_length + content_length {
+		match stream.read(&mut chunk).await {
+			Ok(0) | Err(_) => return,
+			Ok(read) => request.extend_from_slice(&chunk[..read]),
+		}
+	}
+
+	let head = String::from_utf8_lossy(&request[..head_length]).to_string();
+	let path = head.split_whitespace().nth(1).unwrap_or_default().to_owned();
+	let body: Value = serde_json::from_slice(&request[head_length..head_length + content_length])
+		.unwrap_or(Value::Null);
+
+	tokio::time::sleep(delay).await;
+	let (status, response) = if path.starts_with("/_matrix/client/versions") {
+		("200 OK", json!({ "versions": ["r0.6.1", "v1.1", "v1.2"] }))
+	} else if path.ends_with("/login") && accept_login {
+		let user = body["identifier"]["user"].as_str().unwrap_or_default();
+		(
+			"200 OK",
+			json!({
+				"user_id": format!("@{}:{}", user, server_name),
+				"access_token": "access_token",
+				"device_id": "RAIDEN",
+			}),
+		)
+	} else if path.ends_with("/login") {
+		("403 Forbidden", json!({ "errcode": "M_FORBIDDEN", "error": "Invalid password" }))
+	} else {
+		("200 OK", json!({}))
+	};
+
+	let response = response.to_string();
+	let _ = stream
+		.write_all(
+			format!(
+				"HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+				status,
+				response.len(),
+				response,
+			)
+			.as_bytes(),
+		)
+		.await;
+}
+
+fn signer() -> Arc<dyn Signer> {
+	Arc::new(KeystoreSigner::from_raw(&[0x42; 32]).expect("Should be a valid private key"))
+}
+
+fn transport_config(homeserver_url: &str) -> TransportConfig {
+	TransportConfig {
+		retry_timeout: 5,
+		retry_timeout_max: 60,
+		retry_count: 10,
+		mode: TransportMode::Matrix,
+		matrix: MatrixTransportConfig { homeserver_url: homeserver_url.to_owned() },
+		tcp: TcpTransportConfig {
+			listen_address: "127.0.0.1:0".parse().unwrap(),
+			advertised_address: None,
+		},
+		metrics: None,
+	}
+}
+
+#[tokio::test]
+async fn test_rank_servers() {
+	let slow = FakeHomeServer::start(true, Duration::from_millis(300)).await;
+	let fast = FakeHomeServer::start(true, Duration::ZERO).await;
+
+	let ranked =
+		rank_servers(vec![slow.url.clone(), UNREACHABLE_SERVER.to_owned(), fast.url.clone()]).await;
+	assert_eq!(ranked, vec![fast.url, slow.url]);
+	assert!(rank_servers(vec![UNREACHABLE_SERVER.to_owned()]).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_switch_server() {
+	let current = FakeHomeServer::start(true, Duration::ZERO).await;
+	let rejecting = FakeHomeServer::start(false, Duration::ZERO).await;
+	let accepting = FakeHomeServer::start(true, Duration::ZERO).await;
+
+	let mut client = MatrixClient::new(current.url.clone(), signer()).await;
+	client.init().await.expect("Should log in");
+	client.set_sync_token("s42".to_owned());
+
+	// The current home server is kept when the login fails.
+	assert!(client.switch_server(&rejecting.url).await.is_err());
+	assert_eq!(client.homeserver_url(), current.url);
+	assert_eq!(client.server_name(), current.server_name);
+	assert_eq!(client.get_sync_token(), "s42");
+
+	// The sync token of the previous home server is not valid on the new one.
+	client.switch_server(&accepting.url).await.expect("Should switch server");
+	assert_eq!(client.homeserver_url(), accepting.url);
+	assert_eq!(client.server_name(), accepting.server_name);
+	assert_eq!(client.get_sync_token(), "");
+}
+
+#[tokio::test]
+async fn test_failover() {
+	let current = FakeHomeServer::start(true, Duration::ZERO).await;
+	let rejecting = FakeHomeServer::start(false, Duration::ZERO).await;
+	let accepting = FakeHomeServer::start(true, Duration::from_millis(100)).await;
+
+	let client = MatrixClient::new(current.url.clone(), signer()).await;
+	client.init().await.expect("Should log in");
+	let storage = MatrixStorage::new(Connection::open_in_memory().expect("Should open database"));
+	storage.setup_database(&current.server_name).expect("Should setup database");
+	storage
+		.set_sync_token(&accepting.server_name, "s42".to_owned())
+		.expect("Should set sync token");
+
+	let (mut service, _) = MatrixService::new(
+		transport_config(&current.url),
+		client,
+		storage,
+		Arc::new(RwLock::new(AddressMetadata::default())),
+		Arc::new(RwLock::new(ReachabilityTracker::new())),
+	);
+	service.set_fallback_servers(vec![
+		rejecting.url.clone(),
+		UNREACHABLE_SERVER.to_owned(),
+		accepting.url.clone(),
+	]);
+
+	// The fastest home server rejects us, syncing resumes on the next one.
+	service.failover().await;
+	assert_eq!(service.client().homeserver_url(), accepting.url);
+	assert_eq!(service.client().get_sync_token(), "s42");
+
+	// The previous home server became a fallback.
+	service.failover().await;
+	assert_eq!(service.client().homeserver_url(), current.url);
+	assert_eq!(service.client().get_sync_token(), "");
+}
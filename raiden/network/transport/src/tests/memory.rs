@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use raiden_network_messages::messages::{
+	Delivered,
+	IncomingMessage,
+	MessageInner,
+	OutgoingMessage,
+	Processed,
+	SecretReveal,
+	TransportServiceMessage,
+};
+use raiden_primitives::{
+	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	types::{
+		Address,
+		AddressMetadata,
+		Bytes,
+		MessageIdentifier,
+		QueueIdentifier,
+		Signature,
+	},
+};
+use tokio::{
+	sync::mpsc::{
+		self,
+		UnboundedReceiver,
+		UnboundedSender,
+	},
+	time::timeout,
+};
+
+use crate::{
+	config::{
+		MatrixTransportConfig,
+		TcpTransportConfig,
+		TransportConfig,
+		TransportMode,
+	},
+	memory::{
+		MemoryNetworkConfig,
+		MemoryRouter,
+		MemoryService,
+	},
+	IncomingMessageHandler,
+};
+
+fn delivered(recipient: Address, message_identifier: MessageIdentifier) -> OutgoingMessage {
+	OutgoingMessage {
+		message_identifier,
+		recipient,
+		recipient_metadata: AddressMetadata::default(),
+		inner: MessageInner::Delivered(Delivered {
+			delivered_message_identifier: message_identifier,
+			signature: Signature::from(vec![0; 65]),
+		}),
+	}
+}
+
+fn processed(recipient: Address, message_identifier: MessageIdentifier) -> OutgoingMessage {
+	OutgoingMessage {
+		message_identifier,
+		recipient,
+		recipient_metadata: AddressMetadata::default(),
+		inner: MessageInner::Processed(Processed {
+			message_identifier,
+			signature: Signature::from(vec![0; 65]),
+		}),
+	}
+}
+
+fn secret_reveal(recipient: Address, message_identifier: MessageIdentifier) -> OutgoingMessage {
+	OutgoingMessage {
+		message_identifier,
+		recipient,
+		recipient_metadata: AddressMetadata::default(),
+		inner: MessageInner::SecretReveal(SecretReveal {
+			message_identifier,
+			secret: Bytes(vec![1; 32]),
+			signature: Signature::from(vec![0; 65]),
+		}),
+	}
+}
+
+fn received(receiver: &mut UnboundedReceiver<IncomingMessage>) -> Vec<MessageIdentifier> {
+	let mut message_identifiers = vec![];
+	while let Ok(message) = receiver.try_recv() {
+		message_identifiers.push(message.message_identifier);
+	}
+	message_identifiers
+}
+
+/// Route ten messages with jitter and return the order in which they are received.
+async fn jittered_order(seed: u64) -> Vec<MessageIdentifier> {
+	let router = MemoryRouter::new(MemoryNetworkConfig {
+		latency: Duration::from_millis(10),
+		jitter: Duration::from_millis(100),
+		seed,
+		..Default::default()
+	});
+	let recipient = Address::random();
+	let mut receiver = router.connect(recipient);
+
+	for message_identifier in 1..=10 {
+		router.route(delivered(recipient, message_identifier));
+	}
+	assert!(received(&mut receiver).is_empty());
+
+	tokio::time::sleep(Duration::from_millis(120)).await;
+	received(&mut receiver)
+}
+
+#[test]
+fn test_memory_router_delivers_messages() {
+	let router = MemoryRouter::new(MemoryNetworkConfig::default());
+	let (recipient, other) = (Address::random(), Address::random());
+	let mut receiver = router.connect(recipient);
+	let mut other_receiver = router.connect(other);
+
+	router.route(delivered(recipient, 1));
+	router.route(delivered(recipient, 2));
+
+	let message = receiver.try_recv().expect("Message should be delivered");
+	assert_eq!(message.message_identifier, 1);
+	assert!(matches!(
+		message.inner,
+		MessageInner::Delivered(Delivered { delivered_message_identifier: 1, .. })
+	));
+	assert_eq!(received(&mut receiver), vec![2]);
+	assert!(received(&mut other_receiver).is_empty());
+}
+
+#[test]
+fn test_memory_router_ignores_disconnected_nodes() {
+	let router = MemoryRouter::new(MemoryNetworkConfig::default());
+	let recipient = Address::random();
+	let mut receiver = router.connect(recipient);
+	assert!(router.is_connected(&recipient));
+
+	router.disconnect(&recipient);
+	assert!(!router.is_connected(&recipient));
+	router.route(delivered(recipient, 1));
+	assert!(received(&mut receiver).is_empty());
+}
+
+#[test]
+fn test_memory_router_drops_messages() {
+	let router = MemoryRouter::new(MemoryNetworkConfig { drop_rate: 1.0, ..Default::default() });
+	let recipient = Address::random();
+	let mut receiver = router.connect(recipient);
+
+	router.route(delivered(recipient, 1));
+	assert!(received(&mut receiver).is_empty());
+}
+
+#[test]
+fn test_memory_router_duplicates_messages() {
+	let router =
+		MemoryRouter::new(MemoryNetworkConfig { duplicate_rate: 1.0, ..Default::default() });
+	let recipient = Address::random();
+	let mut receiver = router.connect(recipient);
+
+	router.route(delivered(recipient, 1));
+	assert_eq!(received(&mut receiver), vec![1, 1]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_memory_router_reorders_messages_deterministically() {
+	let order = jittered_order(7).await;
+
+	let mut sorted = order.clone();
+	sorted.sort();
+	assert_eq!(sorted, (1..=10).collect::<Vec<_>>());
+	assert_ne!(order, sorted);
+
+	assert_eq!(jittered_order(7).await, order);
+}
+
+fn transport_config() -> TransportConfig {
+	TransportConfig {
+		retry_timeout: 1,
+		retry_timeout_max: 1,
+		retry_count: 10,
+		mode: TransportMode::Matrix,
+		matrix: MatrixTransportConfig { homeserver_url: "http://test.com".to_owned() },
+		tcp: TcpTransportConfig {
+			listen_address: "127.0.0.1:0".parse().unwrap(),
+			advertised_address: None,
+		},
+		metrics: None,
+	}
+}
+
+/// Answers the messages of a payment going from the initiator through the mediator to the target
+/// the way the state machine does.
+///
+/// The secret reveal received from `previous` is acknowledged with a `Delivered` and forwarded once
+/// to `next`, the target acknowledges it with a `Processed` instead.
+struct ScriptedHandler {
+	previous: Option<Address>,
+	next: Option<Address>,
+	sender: UnboundedSender<TransportServiceMessage>,
+	received: UnboundedSender<IncomingMessage>,
+	forwarded: bool,
+}
+
+impl ScriptedHandler {
+	fn enqueue(&self, message: OutgoingMessage) {
+		let queue_identifier = QueueIdentifier {
+			recipient: message.recipient,
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+		};
+		let _ = self.sender.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
+	}
+}
+
+#[async_trait::async_trait]
+impl IncomingMessageHandler for ScriptedHandler {
+	async fn handle(&mut self, message: IncomingMessage) -> Result<(), String> {
+		match &message.inner {
+			MessageInner::SecretReveal(_) => {
+				let previous = self.previous.ok_or("Unexpected secret reveal")?;
+				match self.next {
+					Some(next) => {
+						self.enqueue(delivered(previous, message.message_identifier));
+						if !self.forwarded {
+							self.forwarded = true;
+							self.enqueue(secret_reveal(next, message.message_identifier + 1));
+						}
+					},
+					None => self.enqueue(processed(previous, message.message_identifier)),
+				}
+			},
+			MessageInner::Processed(_) => {
+				let next = self.next.ok_or("Unexpected processed")?;
+				self.enqueue(delivered(next, message.message_identifier));
+			},
+			_ => {},
+		}
+		let _ = self.received.send(message);
+		Ok(())
+	}
+}
+
+/// A node of the simulated network.
+struct SimulatedNode {
+	address: Address,
+	sender: UnboundedSender<TransportServiceMessage>,
+	received: UnboundedReceiver<IncomingMessage>,
+}
+
+impl SimulatedNode {
+	fn start(
+		router: &MemoryRouter,
+		address: Address,
+		previous: Option<Address>,
+		next: Option<Address>,
+	) -> Self {
+		let (service, sender) = MemoryService::new(address, transport_config(), router.clone());
+		let (received_sender, received) = mpsc::unbounded_channel();
+		let handler = ScriptedHandler {
+			previous,
+			next,
+			sender: sender.clone(),
+			received: received_sender,
+			forwarded: false,
+		};
+		tokio::spawn(service.run(handler));
+		Self { address, sender, received }
+	}
+
+	/// Wait until a message of the expected kind with `message_identifier` is received.
+	async fn wait_for(
+		&mut self,
+		message_identifier: MessageIdentifier,
+		expected: fn(&MessageInner) -> bool,
+	) {
+		let wait = async {
+			while let Some(message) = self.received.recv().await {
+				if message.message_identifier == message_identifier && expected(&message.inner) {
+					return
+				}
+			}
+		};
+		timeout(Duration::from_secs(30), wait)
+			.await
+			.expect("Should receive the message");
+	}
+}
+
+/// Start the initiator, mediator and target of a payment and send the secret reveal of the
+/// initiator.
+fn start_payment(router: &MemoryRouter) -> (SimulatedNode, SimulatedNode, SimulatedNode) {
+	let (initiator, mediator, target) = (Address::random(), Address::random(), Address::random());
+	let initiator = SimulatedNode::start(router, initiator, None, Some(mediator));
+	let mediator = SimulatedNode::start(router, mediator, Some(initiator.address), Some(target));
+	let target = SimulatedNode::start(router, target, Some(mediator), None);
+
+	let queue_identifier = QueueIdentifier {
+		recipient: mediator.address,
+		canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	};
+	let _ = initiator.sender.send(TransportServiceMessage::Enqueue((
+		queue_identifier,
+		secret_reveal(mediator.address, 1),
+	)));
+	(initiator, mediator, target)
+}
+
+#[tokio::test]
+async fn test_memory_simulation_mediated_payment() {
+	let router = MemoryRouter::new(MemoryNetworkConfig {
+		latency: Duration::from_millis(10),
+		jitter: Duration::from_millis(20),
+		..Default::default()
+	});
+	let (mut initiator, mut mediator, mut target) = start_payment(&router);
+
+	mediator
+		.wait_for(1, |inner| matches!(inner, MessageInner::SecretReveal(_)))
+		.await;
+	initiator.wait_for(1, |inner| matches!(inner, MessageInner::Delivered(_))).await;
+	target.wait_for(2, |inner| matches!(inner, MessageInner::SecretReveal(_))).await;
+	mediator.wait_for(2, |inner| matches!(inner, MessageInner::Processed(_))).await;
+	target.wait_for(2, |inner| matches!(inner, MessageInner::Delivered(_))).await;
+
+	// Every queued message was acknowledged, nothing is retried anymore.
+	tokio::time::sleep(Duration::from_millis(2500)).await;
+	assert!(received(&mut initiator.received).is_empty());
+	assert!(received(&mut mediator.received).is_empty());
+	assert!(received(&mut target.received).is_empty());
+}
+
+#[tokio::test]
+async fn test_memory_simulation_retries_lost_messages() {
+	let router = MemoryRouter::new(MemoryNetworkConfig {
+		latency: Duration::from_millis(10),
+		jitter: Duration::from_millis(20),
+		drop_rate: 0.3,
+		duplicate_rate: 0.3,
+		seed: 42,
+	});
+	let (mut initiator, mut mediator, mut target) = start_payment(&router);
+
+	// Lost messages and acknowledgements are sent again until they get through, duplicates are
+	// acknowledged again.
+	initiator.wait_for(1, |inner| matches!(inner, MessageInner::Delivered(_))).await;
+	target.wait_for(2, |inner| matches!(inner, MessageInner::SecretReveal(_))).await;
+	mediator.wait_for(2, |inner| matches!(inner, MessageInner::Processed(_))).await;
+}
+
+#[tokio::test]
+async fn test_memory_simulation_retries_until_recipient_connects() {
+	let router = MemoryRouter::new(MemoryNetworkConfig::default());
+	let (initiator, mediator) = (Address::random(), Address::random());
+	let mut initiator = SimulatedNode::start(&router, initiator, None, Some(mediator));
+
+	let queue_identifier = QueueIdentifier {
+		recipient: mediator,
+		canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	};
+	let _ = initiator
+		.sender
+		.send(TransportServiceMessage::Enqueue((queue_identifier, secret_reveal(mediator, 1))));
+
+	// The first send is lost, the message is retried once the mediator is connected.
+	tokio::time::sleep(Duration::from_millis(500)).await;
+	let mut mediator = SimulatedNode::start(&router, mediator, Some(initiator.address), None);
+	mediator
+		.wait_for(1, |inner| matches!(inner, MessageInner::SecretReveal(_)))
+		.await;
+	initiator.wait_for(1, |inner| matches!(inner, MessageInner::Processed(_))).await;
+}
@@ -1 +1,5 @@
+mod matrix;
+mod memory;
 mod queue;
+mod storage;
+mod tcp;
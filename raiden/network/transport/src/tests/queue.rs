@@ -1,22 +1,38 @@
 use std::{
+	sync::{
+		Arc,
+		Mutex,
+	},
 	thread,
 	time::Duration,
 };
 
 use futures_util::FutureExt;
 use raiden_network_messages::messages::TransportServiceMessage;
+use raiden_primitives::{
+	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	reachability::Reachability,
+	types::{
+		Address,
+		MessageIdentifier,
+		QueueIdentifier,
+	},
+};
 use tokio::sync::mpsc;
 
 use crate::{
 	config::{
 		MatrixTransportConfig,
+		TcpTransportConfig,
 		TransportConfig,
+		TransportMode,
 	},
 	matrix::queue::{
 		QueueOp,
 		RetryMessageQueue,
 		TimeoutGenerator,
 	},
+	metrics::QueueMetrics,
 };
 
 #[test]
@@ -59,17 +75,53 @@ fn test_timeout_generator() {
 	assert!(timeout_generator.ready());
 }
 
+fn transport_config(retry_timeout: u8, metrics: Option<Arc<dyn QueueMetrics>>) -> TransportConfig {
+	TransportConfig {
+		retry_timeout,
+		retry_timeout_max: 60,
+		retry_count: 10,
+		mode: TransportMode::Matrix,
+		matrix: MatrixTransportConfig { homeserver_url: "http://test.com".to_owned() },
+		tcp: TcpTransportConfig {
+			listen_address: "127.0.0.1:0".parse().unwrap(),
+			advertised_address: None,
+		},
+		metrics,
+	}
+}
+
+/// Records the reported metrics.
+#[derive(Default)]
+struct RecordedMetrics {
+	depths: Mutex<Vec<usize>>,
+	retries: Mutex<Vec<(MessageIdentifier, u32)>>,
+}
+
+impl QueueMetrics for RecordedMetrics {
+	fn queue_depth(&self, _queue_identifier: &QueueIdentifier, depth: usize) {
+		self.depths.lock().unwrap().push(depth);
+	}
+
+	fn message_retried(
+		&self,
+		_queue_identifier: &QueueIdentifier,
+		message_identifier: MessageIdentifier,
+		retries: u32,
+	) {
+		self.retries.lock().unwrap().push((message_identifier, retries));
+	}
+}
+
 #[tokio::test]
 async fn test_retry_message_queue() {
 	let (transport_sender, mut transport_receiver) = mpsc::unbounded_channel();
 	let (message_queue, queue_sender) = RetryMessageQueue::new(
-		transport_sender,
-		TransportConfig {
-			retry_timeout: 5,
-			retry_timeout_max: 60,
-			retry_count: 10,
-			matrix: MatrixTransportConfig { homeserver_url: "http://test.com".to_owned() },
+		QueueIdentifier {
+			recipient: Address::random(),
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
 		},
+		transport_sender,
+		transport_config(5, None),
 	);
 	let (job, _handle) = FutureExt::remote_handle(message_queue.run());
 	tokio::spawn(job);
@@ -86,3 +138,89 @@ async fn test_retry_message_queue() {
 	let received_identifier = transport_receiver.try_recv();
 	assert!(received_identifier.is_err());
 }
+
+#[tokio::test]
+async fn test_retry_message_queue_metrics() {
+	let metrics = Arc::new(RecordedMetrics::default());
+	let (transport_sender, mut transport_receiver) = mpsc::unbounded_channel();
+	let (message_queue, queue_sender) = RetryMessageQueue::new(
+		QueueIdentifier {
+			recipient: Address::random(),
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+		},
+		transport_sender,
+		transport_config(1, Some(metrics.clone())),
+	);
+	let (job, _handle) = FutureExt::remote_handle(message_queue.run());
+	tokio::spawn(job);
+
+	let message_identifier = 1;
+	let _ = queue_sender.send(QueueOp::Enqueue(message_identifier));
+
+	// Sent once and retried once after the timeout of a second.
+	for _ in 0..2 {
+		assert_eq!(
+			transport_receiver.recv().await,
+			Some(TransportServiceMessage::Send(message_identifier))
+		);
+	}
+	let _ = queue_sender.send(QueueOp::Dequeue(message_identifier));
+	let _ = queue_sender.send(QueueOp::Stop);
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	assert_eq!(*metrics.retries.lock().unwrap(), vec![(message_identifier, 1)]);
+	assert_eq!(*metrics.depths.lock().unwrap(), vec![1, 0, 0]);
+}
+
+#[tokio::test]
+async fn test_retry_message_queue_backs_off_while_unreachable() {
+	let (transport_sender, mut transport_receiver) = mpsc::unbounded_channel();
+	let (message_queue, queue_sender) = RetryMessageQueue::new(
+		QueueIdentifier {
+			recipient: Address::random(),
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+		},
+		transport_sender,
+		transport_config(1, None),
+	);
+	let (job, _handle) = FutureExt::remote_handle(message_queue.run());
+	tokio::spawn(job);
+
+	let _ = queue_sender.send(QueueOp::Enqueue(1));
+	assert_eq!(transport_receiver.recv().await, Some(TransportServiceMessage::Send(1)));
+
+	// New messages are still sent, retries wait for the max timeout.
+	let _ = queue_sender.send(QueueOp::Reachability(Reachability::Unreachable));
+	let _ = queue_sender.send(QueueOp::Enqueue(2));
+	assert_eq!(transport_receiver.recv().await, Some(TransportServiceMessage::Send(2)));
+	tokio::time::sleep(Duration::from_millis(1500)).await;
+	assert!(transport_receiver.try_recv().is_err());
+
+	// Both messages are sent right away, without waiting for the max timeout.
+	let _ = queue_sender.send(QueueOp::Reachability(Reachability::Reachable));
+	assert_eq!(transport_receiver.recv().await, Some(TransportServiceMessage::Send(1)));
+	assert_eq!(transport_receiver.recv().await, Some(TransportServiceMessage::Send(2)));
+}
+
+#[tokio::test]
+async fn test_retry_message_queue_retries_while_unreachable() {
+	let (transport_sender, mut transport_receiver) = mpsc::unbounded_channel();
+	let mut config = transport_config(1, None);
+	config.retry_timeout_max = 1;
+	let (message_queue, queue_sender) = RetryMessageQueue::new(
+		QueueIdentifier {
+			recipient: Address::random(),
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+		},
+		transport_sender,
+		config,
+	);
+	let (job, _handle) = FutureExt::remote_handle(message_queue.run());
+	tokio::spawn(job);
+
+	let _ = queue_sender.send(QueueOp::Reachability(Reachability::Unreachable));
+	let _ = queue_sender.send(QueueOp::Enqueue(1));
+	for _ in 0..2 {
+		assert_eq!(transport_receiver.recv().await, Some(TransportServiceMessage::Send(1)));
+	}
+}
@@ -0,0 +1,133 @@
+use rusqlite::Connection;
+
+use crate::matrix::storage::MatrixStorage;
+
+fn storage() -> MatrixStorage {
+	let storage = MatrixStorage::new(Connection::open_in_memory().expect("Should open database"));
+	storage.setup_database("localhost").expect("Should setup database");
+	storage
+}
+
+#[test]
+fn test_store_and_delete_queued_messages() {
+	let storage = storage();
+
+	storage.store_message("queue1", 1, "[1]".to_owned()).expect("Should store");
+	storage.store_message("queue1", 2, "[2]".to_owned()).expect("Should store");
+	storage.store_message("queue2", 1, "[3]".to_owned()).expect("Should store");
+	// Storing the same message again replaces it.
+	storage.store_message("queue1", 1, "[1, 1]".to_owned()).expect("Should store");
+
+	let mut messages = storage.get_messages().expect("Should get messages");
+	messages.sort();
+	assert_eq!(
+		messages,
+		vec![
+			("queue1".to_owned(), 1, "[1, 1]".to_owned()),
+			("queue1".to_owned(), 2, "[2]".to_owned()),
+			("queue2".to_owned(), 1, "[3]".to_owned()),
+		]
+	);
+
+	storage.delete_message("queue1", 1).expect("Should delete");
+	storage.delete_queue("queue2").expect("Should delete");
+	assert_eq!(
+		storage.get_messages().expect("Should get messages"),
+		vec![("queue1".to_owned(), 2, "[2]".to_owned())]
+	);
+}
+
+#[test]
+fn test_store_message_identifier_above_i64() {
+	let storage = storage();
+
+	storage.store_message("queue", u64::MAX, "[]".to_owned()).expect("Should store");
+	assert_eq!(
+		storage.get_messages().expect("Should get messages"),
+		vec![("queue".to_owned(), u64::MAX, "[]".to_owned())]
+	);
+	storage.delete_message("queue", u64::MAX).expect("Should delete");
+	assert!(storage.get_messages().expect("Should get messages").is_empty());
+}
+
+#[test]
+fn test_sync_tokens_per_server() {
+	let storage = storage();
+
+	assert!(storage.get_sync_token("server1").is_err());
+
+	storage.set_sync_token("server1", "token1".to_owned()).expect("Should store");
+	storage.set_sync_token("server2", "token2".to_owned()).expect("Should store");
+	storage.set_sync_token("server1", "token3".to_owned()).expect("Should store");
+
+	assert_eq!(storage.get_sync_token("server1").expect("Should get token"), "token3");
+	assert_eq!(storage.get_sync_token("server2").expect("Should get token"), "token2");
+}
+
+#[test]
+fn test_migrate_legacy_queued_messages() {
+	let conn = Connection::open_in_memory().expect("Should open database");
+	conn.execute_batch(
+		r#"
+		CREATE TABLE matrix_messages (data TEXT);
+		INSERT INTO matrix_messages(data) VALUES('{"messages":{"queue1":{"1":[{"a":1}],"2":[]},"queue2":{"18446744073709551615":[{"b":2}]}}}');
+		"#,
+	)
+	.expect("Should create legacy table");
+	let storage = MatrixStorage::new(conn);
+	storage.setup_database("localhost").expect("Should setup database");
+
+	let mut messages = storage.get_messages().expect("Should get messages");
+	messages.sort();
+	let expected = vec![
+		("queue1".to_owned(), 1, r#"[{"a":1}]"#.to_owned()),
+		("queue1".to_owned(), 2, "[]".to_owned()),
+		("queue2".to_owned(), u64::MAX, r#"[{"b":2}]"#.to_owned()),
+	];
+	assert_eq!(messages, expected);
+
+	// The legacy table is gone, setting up again does not migrate twice.
+	storage.delete_message("queue1", 2).expect("Should delete");
+	storage.setup_database("localhost").expect("Should setup database");
+	let mut messages = storage.get_messages().expect("Should get messages");
+	messages.sort();
+	assert_eq!(messages, vec![expected[0].clone(), expected[2].clone()]);
+}
+
+#[test]
+fn test_migrate_legacy_sync_token() {
+	let conn = Connection::open_in_memory().expect("Should open database");
+	conn.execute_batch(
+		"
+		CREATE TABLE matrix_config (sync_token TEXT);
+		INSERT INTO matrix_config(sync_token) VALUES('s42_legacy');
+		",
+	)
+	.expect("Should create legacy table");
+	let storage = MatrixStorage::new(conn);
+	storage
+		.setup_database("transport.raiden.network")
+		.expect("Should setup database");
+
+	assert_eq!(
+		storage
+			.get_sync_token("transport.raiden.network")
+			.expect("Should get sync token"),
+		"s42_legacy"
+	);
+	assert!(storage.get_sync_token("other.raiden.network").is_err());
+
+	// The legacy table is gone, newer sync tokens are not replaced by setting up again.
+	storage
+		.set_sync_token("transport.raiden.network", "s43".to_owned())
+		.expect("Should set sync token");
+	storage
+		.setup_database("transport.raiden.network")
+		.expect("Should setup database");
+	assert_eq!(
+		storage
+			.get_sync_token("transport.raiden.network")
+			.expect("Should get sync token"),
+		"s43"
+	);
+}
@@ -0,0 +1,257 @@
+use std::{
+	collections::HashSet,
+	net::SocketAddr,
+	sync::Arc,
+	time::Duration,
+};
+
+use parking_lot::RwLock;
+use raiden_blockchain::keys::{
+	KeystoreSigner,
+	Signer,
+};
+use raiden_network_messages::messages::{
+	Delivered,
+	IncomingMessage,
+	MessageInner,
+	OutgoingMessage,
+	Processed,
+	TransportServiceMessage,
+};
+use raiden_primitives::{
+	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+	reachability::{
+		Reachability,
+		ReachabilityTracker,
+	},
+	types::{
+		Address,
+		AddressMetadata,
+		MessageIdentifier,
+		QueueIdentifier,
+		Signature,
+	},
+};
+use tokio::{
+	io::{
+		AsyncBufReadExt,
+		AsyncReadExt,
+		AsyncWriteExt,
+		BufReader,
+	},
+	net::TcpStream,
+	sync::mpsc::{
+		self,
+		UnboundedReceiver,
+		UnboundedSender,
+	},
+	time::timeout,
+};
+
+use crate::{
+	config::{
+		MatrixTransportConfig,
+		TcpTransportConfig,
+		TransportConfig,
+		TransportMode,
+	},
+	tcp::TcpService,
+	IncomingMessageHandler,
+};
+
+/// An address nothing listens on.
+const UNREACHABLE_ADDRESS: &str = "127.0.0.1:1";
+
+/// Passes the received messages on to the test.
+struct RecordingHandler(UnboundedSender<IncomingMessage>);
+
+#[async_trait::async_trait]
+impl IncomingMessageHandler for RecordingHandler {
+	async fn handle(&mut self, message: IncomingMessage) -> Result<(), String> {
+		let _ = self.0.send(message);
+		Ok(())
+	}
+}
+
+/// A node running a TCP service on a random local port.
+struct Node {
+	address: Address,
+	metadata: AddressMetadata,
+	sender: UnboundedSender<TransportServiceMessage>,
+	received: UnboundedReceiver<IncomingMessage>,
+	reachability: Arc<RwLock<ReachabilityTracker>>,
+}
+
+impl Node {
+	async fn start(key: u8, fallback: Option<UnboundedSender<TransportServiceMessage>>) -> Self {
+		let signer: Arc<dyn Signer> =
+			Arc::new(KeystoreSigner::from_raw(&[key; 32]).expect("Should be a valid private key"));
+		let address = signer.address();
+		let reachability = Arc::new(RwLock::new(ReachabilityTracker::new()));
+		let (service, sender) =
+			TcpService::new(transport_config(), signer, fallback, reachability.clone())
+				.await
+				.expect("Should listen");
+		let mut metadata = AddressMetadata::default();
+		metadata.set_socket_address(service.local_address().expect("Should have an address"));
+
+		let (handler_sender, received) = mpsc::unbounded_channel();
+		tokio::spawn(service.run(RecordingHandler(handler_sender)));
+
+		Self { address, metadata, sender, received, reachability }
+	}
+
+	/// Wait for the next message passed to the message handler.
+	async fn receive(&mut self) -> IncomingMessage {
+		timeout(Duration::from_secs(5), self.received.recv())
+			.await
+			.expect("Should receive a message")
+			.expect("Should receive a message")
+	}
+
+	/// Send `inner` to `recipient`, reached through `metadata`.
+	fn send(
+		&self,
+		recipient: Address,
+		metadata: &AddressMetadata,
+		message_identifier: MessageIdentifier,
+		inner: MessageInner,
+	) {
+		let message = OutgoingMessage {
+			message_identifier,
+			recipient,
+			recipient_metadata: metadata.clone(),
+			inner,
+		};
+		let _ = self
+			.sender
+			.send(TransportServiceMessage::Enqueue((queue_identifier(recipient), message)));
+	}
+}
+
+fn transport_config() -> TransportConfig {
+	TransportConfig {
+		retry_timeout: 1,
+		retry_timeout_max: 1,
+		retry_count: 10,
+		mode: TransportMode::Tcp,
+		matrix: MatrixTransportConfig { homeserver_url: "http://test.com".to_owned() },
+		tcp: TcpTransportConfig {
+			listen_address: "127.0.0.1:0".parse().unwrap(),
+			advertised_address: None,
+		},
+		metrics: None,
+	}
+}
+
+fn queue_identifier(recipient: Address) -> QueueIdentifier {
+	QueueIdentifier { recipient, canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE }
+}
+
+fn processed(message_identifier: MessageIdentifier) -> MessageInner {
+	MessageInner::Processed(Processed {
+		message_identifier,
+		signature: Signature::from(vec![0; 65]),
+	})
+}
+
+fn delivered(message_identifier: MessageIdentifier) -> MessageInner {
+	MessageInner::Delivered(Delivered {
+		delivered_message_identifier: message_identifier,
+		signature: Signature::from(vec![0; 65]),
+	})
+}
+
+#[tokio::test]
+async fn test_failed_dial_forwards_to_fallback() {
+	let (fallback_sender, mut fallback_receiver) = mpsc::unbounded_channel();
+	let node = Node::start(1, Some(fallback_sender)).await;
+
+	let recipient = Address::random();
+	let mut metadata = AddressMetadata::default();
+	metadata.set_socket_address(UNREACHABLE_ADDRESS.parse::<SocketAddr>().unwrap());
+	node.send(recipient, &metadata, 1, delivered(1));
+	node.send(recipient, &metadata, 2, processed(2));
+
+	// The message which is not queued is handed over as it is, the queued one with its queue.
+	let mut forwarded = HashSet::new();
+	while forwarded.len() < 2 {
+		let message = timeout(Duration::from_secs(5), fallback_receiver.recv())
+			.await
+			.expect("Should forward messages")
+			.expect("Should forward messages");
+		match message {
+			TransportServiceMessage::Enqueue((queue_identifier, message)) => {
+				assert_eq!(queue_identifier.recipient, recipient);
+				forwarded.insert(message.message_identifier);
+			},
+			message => panic!("Unexpected message {:?}", message),
+		}
+	}
+	assert_eq!(forwarded, HashSet::from([1, 2]));
+	assert_eq!(node.reachability.read().get(recipient), Reachability::Unreachable);
+
+	// The queued message is not retried over TCP anymore.
+	tokio::time::sleep(Duration::from_millis(1500)).await;
+	assert!(fallback_receiver.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_connected_nodes_exchange_messages() {
+	let mut initiator = Node::start(1, None).await;
+	let mut target = Node::start(2, None).await;
+
+	initiator.send(target.address, &target.metadata, 1, processed(1));
+	let message = target.receive().await;
+	assert_eq!(message.message_identifier, 1);
+	assert!(matches!(message.inner, MessageInner::Processed(_)));
+	assert_eq!(initiator.reachability.read().get(target.address), Reachability::Reachable);
+	assert_eq!(target.reachability.read().get(initiator.address), Reachability::Reachable);
+
+	// The answer goes through the connection accepted by the target.
+	target.send(initiator.address, &AddressMetadata::default(), 1, delivered(1));
+	let message = initiator.receive().await;
+	assert!(matches!(
+		message.inner,
+		MessageInner::Delivered(Delivered { delivered_message_identifier: 1, .. })
+	));
+
+	// The acknowledged message is not retried anymore.
+	tokio::time::sleep(Duration::from_millis(100)).await;
+	while target.received.try_recv().is_ok() {}
+	tokio::time::sleep(Duration::from_millis(1500)).await;
+	assert!(target.received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_handshake_rejects_unexpected_peer() {
+	let initiator = Node::start(1, None).await;
+	let mut target = Node::start(2, None).await;
+
+	// The target answers the handshake, but with another address than the expected one.
+	let recipient = Address::random();
+	initiator.send(recipient, &target.metadata, 1, processed(1));
+
+	tokio::time::sleep(Duration::from_millis(500)).await;
+	assert_eq!(initiator.reachability.read().get(recipient), Reachability::Unreachable);
+	assert!(target.received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_too_long_line_closes_connection() {
+	let target = Node::start(2, None).await;
+	let socket_address = target.metadata.socket_address().expect("Should have an address");
+
+	let stream = TcpStream::connect(socket_address).await.expect("Should connect");
+	let (reader, mut writer) = stream.into_split();
+	let mut reader = BufReader::new(reader);
+	let mut challenge = String::new();
+	reader.read_line(&mut challenge).await.expect("Should receive a challenge");
+
+	let _ = writer.write_all(&vec![b'a'; 2 * 1024 * 1024]).await;
+	let mut rest = vec![];
+	let read = timeout(Duration::from_secs(5), reader.read_to_end(&mut rest))
+		.await
+		.expect("Should close the connection");
+	assert!(read.map(|_| rest.is_empty()).unwrap_or(true));
+}
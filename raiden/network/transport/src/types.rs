@@ -1,4 +1,5 @@
 /// The environment type which the transport runs on.
+#[derive(Clone, Copy, Debug)]
 pub enum EnvironmentType {
 	Production,
 	Development,
@@ -50,7 +50,8 @@ pub struct PFSInfo {
 #[derive(Clone, Debug)]
 pub struct PFSConfig {
 	pub url: String,
-	pub info: PFSInfo,
+	/// Information of the service, not available when routing privately.
+	pub info: Option<PFSInfo>,
 	pub maximum_fee: TokenAmount,
 	pub iou_timeout: BlockTimeout,
 	pub max_paths: usize,
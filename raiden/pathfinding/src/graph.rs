@@ -0,0 +1,365 @@
+//! Network graph used to find routes locally when no pathfinding service is available.
+use std::{
+	cmp::Reverse,
+	collections::{
+		BinaryHeap,
+		HashMap,
+		HashSet,
+	},
+};
+
+use raiden_primitives::types::{
+	Address,
+	CanonicalIdentifier,
+	ChannelIdentifier,
+	Nonce,
+	TokenAmount,
+	TokenNetworkAddress,
+	U256,
+};
+use raiden_state_machine::types::FeeScheduleState;
+
+/// Denominator of the proportional mediation fee.
+const PROPORTIONAL_FEE_DENOMINATOR: u64 = 1_000_000;
+
+/// One direction of a channel, as announced by the participant sending through it.
+#[derive(Clone, Debug, Default)]
+struct ChannelView {
+	partner: Address,
+	nonce: Nonce,
+	capacity: TokenAmount,
+	fee_schedule: FeeScheduleState,
+	fee_timestamp: String,
+}
+
+impl ChannelView {
+	/// Mediation fee charged to forward `amount` through this direction of the channel.
+	fn fee(&self, amount: TokenAmount) -> TokenAmount {
+		let proportional = amount.saturating_mul(self.fee_schedule.proportional) /
+			U256::from(PROPORTIONAL_FEE_DENOMINATOR);
+		self.fee_schedule.flat.saturating_add(proportional)
+	}
+}
+
+/// A route found in the network graph.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path {
+	pub nodes: Vec<Address>,
+	pub estimated_fee: TokenAmount,
+}
+
+/// Adjacency list of the usable channels with the fee charged by the sending node.
+type Adjacency = HashMap<Address, Vec<(Address, TokenAmount)>>;
+
+/// The views of a channel by sending participant.
+type ChannelViews = HashMap<Address, ChannelView>;
+
+/// The channels of the token networks learned from capacity and fee updates of the participants.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkGraph {
+	token_networks: HashMap<TokenNetworkAddress, HashMap<ChannelIdentifier, ChannelViews>>,
+}
+
+impl NetworkGraph {
+	/// Create an empty instance of `NetworkGraph`.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Apply a capacity update of a channel.
+	///
+	/// Returns true if the update was newer than the known state of the channel.
+	#[allow(clippy::too_many_arguments)]
+	pub fn update_capacity(
+		&mut self,
+		canonical_identifier: &CanonicalIdentifier,
+		updating_participant: Address,
+		other_participant: Address,
+		updating_nonce: Nonce,
+		other_nonce: Nonce,
+		updating_capacity: TokenAmount,
+		other_capacity: TokenAmount,
+	) -> bool {
+		let views = self
+			.token_networks
+			.entry(canonical_identifier.token_network_address)
+			.or_default()
+			.entry(canonical_identifier.channel_identifier)
+			.or_default();
+
+		let mut updated = false;
+		for (participant, partner, nonce, capacity) in [
+			(updating_participant, other_participant, updating_nonce, updating_capacity),
+			(other_participant, updating_participant, other_nonce, other_capacity),
+		] {
+			let view = views
+				.entry(participant)
+				.or_insert_with(|| ChannelView { partner, ..Default::default() });
+			if nonce > view.nonce {
+				view.nonce = nonce;
+				view.capacity = capacity;
+				updated = true;
+			}
+		}
+		updated
+	}
+
+	/// Apply a fee schedule update of a channel participant.
+	///
+	/// Returns true if the update was newer than the known fee schedule.
+	pub fn update_fee(
+		&mut self,
+		canonical_identifier: &CanonicalIdentifier,
+		updating_participant: Address,
+		fee_schedule: FeeScheduleState,
+		timestamp: String,
+	) -> bool {
+		// The fee update does not name the partner, it is only known from capacity updates.
+		let view = match self
+			.token_networks
+			.get_mut(&canonical_identifier.token_network_address)
+			.and_then(|channels| channels.get_mut(&canonical_identifier.channel_identifier))
+			.and_then(|views| views.get_mut(&updating_participant))
+		{
+			Some(view) => view,
+			None => return false,
+		};
+		if timestamp <= view.fee_timestamp {
+			return false
+		}
+		view.fee_schedule = fee_schedule;
+		view.fee_timestamp = timestamp;
+		true
+	}
+
+	/// Find up to `max_paths` loopless routes from `from` to `to` which can carry `amount`,
+	/// cheapest first.
+	///
+	/// `our_channels` maps the partners of `from` to the capacity available towards them, it
+	/// replaces what was announced for the channels of `from`. There are no routes from a node
+	/// to itself, circular routes are found by `find_circular_routes`.
+	pub fn find_routes(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		from: Address,
+		to: Address,
+		amount: TokenAmount,
+		max_paths: usize,
+		our_channels: HashMap<Address, TokenAmount>,
+	) -> Vec<Path> {
+		let mut routes: Vec<Path> = vec![];
+		if from == to {
+			return routes
+		}
+
+		let adjacency = self.adjacency(token_network_address, from, amount, our_channels);
+		let mut candidates: Vec<Path> = vec![];
+		match shortest_path(&adjacency, from, to, &HashSet::new(), &HashSet::new()) {
+			Some(path) => routes.push(path),
+			None => return routes,
+		}
+
+		// Yen's algorithm: deviate from every node of the last found route.
+		while routes.len() < max_paths {
+			let last_route = routes[routes.len() - 1].nodes.clone();
+			for index in 0..last_route.len() - 1 {
+				let spur_node = last_route[index];
+				let root = &last_route[..=index];
+
+				let excluded_edges: HashSet<(Address, Address)> = routes
+					.iter()
+					.filter(|route| route.nodes.len() > index + 1 && route.nodes[..=index] == *root)
+					.map(|route| (route.nodes[index], route.nodes[index + 1]))
+					.collect();
+				let excluded_nodes: HashSet<Address> = root[..index].iter().cloned().collect();
+
+				let spur_path = match shortest_path(
+					&adjacency,
+					spur_node,
+					to,
+					&excluded_nodes,
+					&excluded_edges,
+				) {
+					Some(spur_path) => spur_path,
+					None => continue,
+				};
+
+				let mut nodes = root[..index].to_vec();
+				nodes.extend(spur_path.nodes);
+				let candidate = Path { estimated_fee: path_fee(&adjacency, &nodes), nodes };
+				if !routes.contains(&candidate) && !candidates.contains(&candidate) {
+					candidates.push(candidate);
+				}
+			}
+
+			if candidates.is_empty() {
+				break
+			}
+			candidates.sort_by(|a, b| {
+				(a.estimated_fee, a.nodes.len()).cmp(&(b.estimated_fee, b.nodes.len()))
+			});
+			routes.push(candidates.remove(0));
+		}
+
+		routes
+	}
+
+	/// Find up to `max_paths` routes leaving `our_address` through `first_hop` and coming back
+	/// through `last_hop`, cheapest first.
+	///
+	/// The capacities of our channels with both hops come from the local channel states, since
+	/// they are known better than what was announced for them.
+	#[allow(clippy::too_many_arguments)]
+	pub fn find_circular_routes(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		our_address: Address,
+		first_hop: Address,
+		first_hop_capacity: TokenAmount,
+		last_hop: Address,
+		last_hop_capacity: TokenAmount,
+		amount: TokenAmount,
+		max_paths: usize,
+	) -> Vec<Path> {
+		if first_hop == last_hop || last_hop_capacity < amount {
+			return vec![]
+		}
+
+		// The last hop charges its fee for forwarding the payment back to us.
+		let return_fee = self
+			.token_networks
+			.get(&token_network_address)
+			.and_then(|channels| {
+				channels.values().find_map(|views| {
+					views.get(&last_hop).filter(|view| view.partner == our_address)
+				})
+			})
+			.map(|view| view.fee(amount))
+			.unwrap_or_default();
+
+		self.find_routes(
+			token_network_address,
+			our_address,
+			last_hop,
+			amount,
+			max_paths,
+			HashMap::from([(first_hop, first_hop_capacity)]),
+		)
+		.into_iter()
+		.map(|mut path| {
+			path.nodes.push(our_address);
+			path.estimated_fee = path.estimated_fee.saturating_add(return_fee);
+			path
+		})
+		.collect()
+	}
+
+	/// Returns the participants of a token network other than `our_address`, ordered by the
+	/// capacity announced for their channels, largest first.
+	pub fn participants_by_capacity(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		our_address: Address,
+	) -> Vec<Address> {
+		let mut capacities: HashMap<Address, TokenAmount> = HashMap::new();
+		if let Some(channels) = self.token_networks.get(&token_network_address) {
+			for (participant, view) in channels.values().flatten() {
+				let capacity = capacities.entry(*participant).or_default();
+				*capacity = capacity.saturating_add(view.capacity);
+			}
+		}
+		capacities.remove(&our_address);
+
+		let mut participants: Vec<(Address, TokenAmount)> = capacities.into_iter().collect();
+		participants.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+		participants.into_iter().map(|(participant, _)| participant).collect()
+	}
+
+	/// Build the adjacency list of the channels which have enough capacity for `amount`.
+	fn adjacency(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		from: Address,
+		amount: TokenAmount,
+		our_channels: HashMap<Address, TokenAmount>,
+	) -> Adjacency {
+		let mut adjacency = Adjacency::new();
+		if let Some(channels) = self.token_networks.get(&token_network_address) {
+			for (sender, view) in channels.values().flatten() {
+				if *sender == from || view.capacity < amount {
+					continue
+				}
+				adjacency.entry(*sender).or_default().push((view.partner, view.fee(amount)));
+			}
+		}
+		// The initiator does not pay fees to itself.
+		for (partner, capacity) in our_channels {
+			if capacity >= amount {
+				adjacency.entry(from).or_default().push((partner, TokenAmount::zero()));
+			}
+		}
+		adjacency
+	}
+}
+
+/// Sum of the fees charged along `nodes`.
+fn path_fee(adjacency: &Adjacency, nodes: &[Address]) -> TokenAmount {
+	nodes
+		.windows(2)
+		.filter_map(|edge| {
+			adjacency
+				.get(&edge[0])
+				.and_then(|neighbours| neighbours.iter().find(|(node, _)| *node == edge[1]))
+				.map(|(_, fee)| *fee)
+		})
+		.fold(TokenAmount::zero(), |total, fee| total.saturating_add(fee))
+}
+
+/// Dijkstra's algorithm ordering paths by fee and then by number of hops.
+fn shortest_path(
+	adjacency: &Adjacency,
+	from: Address,
+	to: Address,
+	excluded_nodes: &HashSet<Address>,
+	excluded_edges: &HashSet<(Address, Address)>,
+) -> Option<Path> {
+	let mut costs: HashMap<Address, (TokenAmount, usize)> = HashMap::new();
+	let mut previous: HashMap<Address, Address> = HashMap::new();
+	let mut queue = BinaryHeap::new();
+
+	costs.insert(from, (TokenAmount::zero(), 0));
+	queue.push(Reverse((TokenAmount::zero(), 0, from)));
+
+	while let Some(Reverse((fee, hops, node))) = queue.pop() {
+		if node == to {
+			let mut nodes = vec![to];
+			let mut current = to;
+			while let Some(node) = previous.get(&current) {
+				nodes.push(*node);
+				current = *node;
+			}
+			nodes.reverse();
+			return Some(Path { nodes, estimated_fee: fee })
+		}
+		if costs.get(&node).map(|cost| *cost < (fee, hops)).unwrap_or(false) {
+			continue
+		}
+
+		for (neighbour, edge_fee) in adjacency.get(&node).into_iter().flatten() {
+			if *neighbour == from ||
+				excluded_nodes.contains(neighbour) ||
+				excluded_edges.contains(&(node, *neighbour))
+			{
+				continue
+			}
+			let cost = (fee.saturating_add(*edge_fee), hops + 1);
+			if costs.get(neighbour).map(|known| cost < *known).unwrap_or(true) {
+				costs.insert(*neighbour, cost);
+				previous.insert(*neighbour, node);
+				queue.push(Reverse((cost.0, cost.1, *neighbour)));
+			}
+		}
+	}
+
+	None
+}
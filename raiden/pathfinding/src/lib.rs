@@ -1,5 +1,8 @@
 //! Implements ways to interact with the pathfinding service to retrieve routes for payments.
-use std::collections::HashMap;
+use std::{
+	collections::HashMap,
+	sync::Arc,
+};
 
 use chrono::Utc;
 use derive_more::Display;
@@ -38,19 +41,19 @@ use tokio::{
 	},
 };
 use web3::{
-	signing::{
-		Key,
-		SigningError,
-	},
+	signing::SigningError,
 	transports::Http,
 };
 
 pub mod config;
+pub mod graph;
 pub mod routing;
+#[cfg(test)]
+mod tests;
 pub mod types;
 
 use raiden_blockchain::{
-	keys::PrivateKey,
+	keys::Signer,
 	proxies::{
 		ProxyError,
 		ServiceRegistryProxy,
@@ -141,18 +144,25 @@ pub struct PFSLastIOUResponse {
 	last_iou: IOU,
 }
 
+/// A partner suggested by the pathfinding service to open a channel with.
+#[derive(Debug, Deserialize)]
+pub struct PFSPartnerSuggestion {
+	pub address: Address,
+	pub score: f64,
+}
+
 /// Pathfinding service.
 pub struct PFS {
 	chain_id: ChainID,
 	pub config: PFSConfig,
-	private_key: PrivateKey,
+	signer: Arc<dyn Signer>,
 	iou_creation: Mutex<()>,
 }
 
 impl PFS {
 	/// Return an instance of `PFS`.
-	pub fn new(chain_id: ChainID, config: PFSConfig, private_key: PrivateKey) -> Self {
-		Self { chain_id, config, private_key, iou_creation: Mutex::new(()) }
+	pub fn new(chain_id: ChainID, config: PFSConfig, signer: Arc<dyn Signer>) -> Self {
+		Self { chain_id, config, signer, iou_creation: Mutex::new(()) }
 	}
 
 	/// Query for routes.
@@ -168,7 +178,7 @@ impl PFS {
 		value: TokenAmount,
 		pfs_wait_for_block: BlockNumber,
 	) -> Result<(Vec<PFSPath>, String), RoutingError> {
-		let offered_fee = self.config.info.price;
+		let offered_fee = self.info()?.price;
 		info!(
 			message = "Query PFS for paths",
 			route_from = route_from.checksum(),
@@ -234,6 +244,11 @@ impl PFS {
 		Ok((vec![], String::new()))
 	}
 
+	/// Return the information of the configured service.
+	fn info(&self) -> Result<&PFSInfo, RoutingError> {
+		self.config.info.as_ref().ok_or(RoutingError::PFServiceUnusable)
+	}
+
 	/// Retrieve the service's information.
 	pub async fn get_pfs_info(&self) -> Result<PFSInfo, RoutingError> {
 		get_pfs_info(self.config.url.clone()).await
@@ -268,6 +283,31 @@ impl PFS {
 		}
 	}
 
+	/// Retrieve the partners the service suggests to open channels with, best first.
+	pub async fn suggest_partners(
+		&self,
+		token_network_address: TokenNetworkAddress,
+	) -> Result<Vec<PFSPartnerSuggestion>, RoutingError> {
+		let token_network_address = token_network_address.checksum();
+		let response = reqwest::get(format!(
+			"{}/api/v1/{}/suggest_partner",
+			&self.config.url, token_network_address
+		))
+		.await
+		.map_err(|e| RoutingError::PFServiceRequestFailed(format!("Could not connect to {}", e)))?;
+
+		if response.status() == 200 {
+			Ok(response.json().await.map_err(|e| {
+				RoutingError::PFServiceRequestFailed(format!("Malformed json in response: {}", e))
+			})?)
+		} else {
+			let error_response: PFSErrorResponse = response.json().await.map_err(|e| {
+				RoutingError::PFServiceRequestFailed(format!("Malformed json in response: {}", e))
+			})?;
+			Err(RoutingError::PFServiceRequestFailed(format!("{}", error_response.msg)))
+		}
+	}
+
 	/// Create an IOU.
 	pub async fn create_current_iou(
 		&self,
@@ -299,9 +339,10 @@ impl PFS {
 		sender: Address,
 	) -> Result<Option<IOU>, RoutingError> {
 		let timestamp = Utc::now().naive_local().format("%Y-%m-%dT%H:%M:%S").to_string();
+		let payment_address = self.info()?.payment_address;
 
 		let signature = self
-			.iou_signature_data(sender, self.config.info.payment_address, timestamp.clone())
+			.iou_signature_data(sender, payment_address, timestamp.clone())
 			.map_err(RoutingError::Signing)?;
 
 		let client = reqwest::Client::new();
@@ -312,7 +353,7 @@ impl PFS {
 			)
 			.query(&[
 				("sender", sender.checksum()),
-				("receiver", self.config.info.payment_address.checksum()),
+				("receiver", payment_address.checksum()),
 				("timestamp", timestamp.to_string()),
 				("signature", signature.as_string()),
 			])
@@ -357,24 +398,25 @@ impl PFS {
 		offered_fee: TokenAmount,
 	) -> Result<IOU, RoutingError> {
 		let expiration_block = block_number + self.config.iou_timeout;
+		let payment_address = self.info()?.payment_address;
 
 		debug!(
 			message = "Create IOU",
-			receiver = self.config.info.payment_address.checksum(),
+			receiver = payment_address.checksum(),
 			amount = offered_fee.to_string(),
 			expiration = expiration_block.to_string()
 		);
 
 		let mut iou = IOU {
 			sender: our_address,
-			receiver: self.config.info.payment_address,
+			receiver: payment_address,
 			one_to_n_address,
 			amount: offered_fee,
 			expiration_block,
 			chain_id: self.chain_id,
 			signature: None,
 		};
-		iou.sign(self.private_key.clone()).map_err(RoutingError::Signing)?;
+		iou.sign(self.signer.clone()).map_err(RoutingError::Signing)?;
 		Ok(iou)
 	}
 
@@ -392,12 +434,12 @@ impl PFS {
 		}
 		debug!(
 			message = "Update IOU",
-			receiver = self.config.info.payment_address.checksum(),
+			receiver = self.info()?.payment_address.checksum(),
 			old_amount = old_amount.to_string(),
 			new_amount = iou.amount.to_string(),
 			expiration = iou.expiration_block.to_string()
 		);
-		iou.sign(self.private_key.clone()).map_err(RoutingError::Signing)?;
+		iou.sign(self.signer.clone()).map_err(RoutingError::Signing)?;
 		Ok(iou)
 	}
 
@@ -412,7 +454,7 @@ impl PFS {
 		data.extend_from_slice(sender.as_bytes());
 		data.extend_from_slice(receiver.as_bytes());
 		data.extend_from_slice(timestamp.as_bytes());
-		Ok(Bytes(self.private_key.sign_message(&data)?.to_bytes()))
+		Ok(Bytes(self.signer.sign_message(&data)?.to_bytes()))
 	}
 }
 
@@ -1,17 +1,22 @@
 use std::{
+	cmp::Ordering,
 	collections::HashMap,
 	sync::Arc,
 };
 
-use raiden_primitives::types::{
-	Address,
-	AddressMetadata,
-	BlockNumber,
-	ChannelIdentifier,
-	OneToNAddress,
-	TokenAmount,
-	TokenNetworkAddress,
-	U256,
+use parking_lot::RwLock;
+use raiden_primitives::{
+	reachability::ReachabilityTracker,
+	types::{
+		Address,
+		AddressMetadata,
+		BlockNumber,
+		ChannelIdentifier,
+		OneToNAddress,
+		TokenAmount,
+		TokenNetworkAddress,
+		U256,
+	},
 };
 use raiden_state_machine::{
 	types::{
@@ -24,15 +29,20 @@ use raiden_state_machine::{
 };
 
 use crate::{
+	graph::NetworkGraph,
 	PFSPath,
 	RoutingError,
 	PFS,
 };
 
 /// Get the best available route for a transfer.
+///
+/// Routes are found in `network_graph` if one is given, otherwise the PFS is queried. Routes
+/// through partners known to be unreachable are left out.
 #[allow(clippy::too_many_arguments)]
 pub async fn get_best_routes(
 	pfs: Arc<PFS>,
+	network_graph: Option<Arc<RwLock<NetworkGraph>>>,
 	chain_state: ChainState,
 	our_address_metadata: AddressMetadata,
 	token_network_address: TokenNetworkAddress,
@@ -41,6 +51,7 @@ pub async fn get_best_routes(
 	to_address: Address,
 	amount: U256,
 	previous_address: Option<Address>,
+	reachability: Arc<RwLock<ReachabilityTracker>>,
 ) -> Result<(Vec<RouteState>, String), RoutingError> {
 	let token_network =
 		match views::get_token_network_by_address(&chain_state, token_network_address) {
@@ -52,20 +63,19 @@ pub async fn get_best_routes(
 	// - There are no race conditions and the capacity is guaranteed to be available.
 	// - There will be no mediation fees
 	// - The transfer will be faster
-	if token_network.partneraddresses_to_channelidentifiers.contains_key(&to_address) {
+	if token_network.partneraddresses_to_channelidentifiers.contains_key(&to_address) &&
+		reachability.read().is_usable(to_address)
+	{
 		for channel_id in token_network.partneraddresses_to_channelidentifiers[&to_address].iter() {
 			let channel_state = &token_network.channelidentifiers_to_channels[channel_id];
 
 			// Direct channels don't have fees.
 			let payment_with_fee_amount = amount;
 			if channel_state.is_usable_for_new_transfer(payment_with_fee_amount, None) {
-				let mut address_to_address_metadata = HashMap::new();
+				let mut address_to_address_metadata =
+					query_route_metadata(&pfs, &[to_address], network_graph.is_none()).await?;
 				address_to_address_metadata.insert(from_address, our_address_metadata.clone());
 
-				let metadata =
-					super::query_address_metadata(pfs.config.url.clone(), to_address).await?;
-				address_to_address_metadata.insert(to_address, metadata);
-
 				return Ok((
 					vec![RouteState {
 						route: vec![from_address, to_address],
@@ -79,6 +89,22 @@ pub async fn get_best_routes(
 		}
 	}
 
+	if let Some(network_graph) = network_graph {
+		let routes = get_best_routes_local(
+			pfs,
+			network_graph,
+			chain_state,
+			our_address_metadata,
+			token_network_address,
+			from_address,
+			to_address,
+			amount,
+			previous_address,
+		)
+		.await?;
+		return Ok((filter_reachable_routes(routes, &reachability), String::new()))
+	}
+
 	let one_to_n_address = one_to_n_address.ok_or(RoutingError::PFServiceUnusable)?;
 
 	// Does any channel have sufficient capacity for the payment?
@@ -89,7 +115,10 @@ pub async fn get_best_routes(
 			channels
 				.iter()
 				.map(|channel_id| &token_network.channelidentifiers_to_channels[channel_id])
-				.filter(|channel: &&ChannelState| channel.is_usable_for_new_transfer(amount, None))
+				.filter(|channel: &&ChannelState| {
+					channel.is_usable_for_new_transfer(amount, None) &&
+						reachability.read().is_usable(channel.partner_state.address)
+				})
 				.collect::<Vec<&ChannelState>>()
 		})
 		.collect();
@@ -119,7 +148,19 @@ pub async fn get_best_routes(
 	)
 	.await?;
 
-	Ok((pfs_routes, pfs_feedback_token))
+	Ok((filter_reachable_routes(pfs_routes, &reachability), pfs_feedback_token))
+}
+
+/// Leave out the routes whose first hop is known to be unreachable.
+fn filter_reachable_routes(
+	routes: Vec<RouteState>,
+	reachability: &RwLock<ReachabilityTracker>,
+) -> Vec<RouteState> {
+	let reachability = reachability.read();
+	routes
+		.into_iter()
+		.filter(|route| route.route.get(1).map_or(true, |hop| reachability.is_usable(*hop)))
+		.collect()
 }
 
 /// Query PFS for best available routes.
@@ -160,6 +201,196 @@ pub async fn get_best_routes_pfs(
 	Ok((paths, feedback_token))
 }
 
+/// Find the best available routes in the local network graph.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_best_routes_local(
+	pfs: Arc<PFS>,
+	network_graph: Arc<RwLock<NetworkGraph>>,
+	chain_state: ChainState,
+	our_address_metadata: AddressMetadata,
+	token_network_address: TokenNetworkAddress,
+	from_address: Address,
+	to_address: Address,
+	amount: TokenAmount,
+	previous_address: Option<Address>,
+) -> Result<Vec<RouteState>, RoutingError> {
+	let token_network =
+		match views::get_token_network_by_address(&chain_state, token_network_address) {
+			Some(token_network) => token_network,
+			None => return Err(RoutingError::TokenNetworkUnknown),
+		};
+
+	let our_channels = token_network
+		.channelidentifiers_to_channels
+		.values()
+		.filter(|channel| channel.status() == ChannelStatus::Opened)
+		.map(|channel| {
+			(
+				channel.partner_state.address,
+				views::channel_distributable(&channel.our_state, &channel.partner_state),
+			)
+		})
+		.collect();
+
+	let paths = network_graph.read().find_routes(
+		token_network_address,
+		from_address,
+		to_address,
+		amount,
+		pfs.config.max_paths,
+		our_channels,
+	);
+
+	let mut routes = vec![];
+	for path in paths {
+		let partner_address = match path.nodes.get(1) {
+			Some(partner_address) => *partner_address,
+			None => continue,
+		};
+		// Prevent back routing
+		if Some(partner_address) == previous_address {
+			continue
+		}
+
+		// The first hop has to carry the fees of the mediators as well.
+		let channel_state = match views::get_channel_by_token_network_and_partner(
+			&chain_state,
+			token_network_address,
+			partner_address,
+		) {
+			Some(channel_state) => channel_state,
+			None => continue,
+		};
+		if !channel_state.is_usable_for_new_transfer(amount + path.estimated_fee, None) {
+			continue
+		}
+
+		let mut address_to_metadata = query_route_metadata(&pfs, &path.nodes[1..], false).await?;
+		address_to_metadata.insert(from_address, our_address_metadata.clone());
+
+		routes.push(RouteState {
+			route: path.nodes,
+			address_to_metadata,
+			swaps: HashMap::new(),
+			estimated_fee: path.estimated_fee,
+		});
+	}
+
+	Ok(routes)
+}
+
+/// Find routes in the local network graph for a payment from this node back to itself, leaving
+/// through the channel with `first_hop` and returning through the channel with `last_hop`.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_circular_routes_local(
+	pfs: Arc<PFS>,
+	network_graph: Arc<RwLock<NetworkGraph>>,
+	chain_state: ChainState,
+	our_address_metadata: AddressMetadata,
+	token_network_address: TokenNetworkAddress,
+	first_hop: Address,
+	last_hop: Address,
+	amount: TokenAmount,
+) -> Result<Vec<RouteState>, RoutingError> {
+	let our_address = chain_state.our_address;
+	let open_channel = |partner_address| {
+		views::get_channel_by_token_network_and_partner(
+			&chain_state,
+			token_network_address,
+			partner_address,
+		)
+		.filter(|channel| channel.status() == ChannelStatus::Opened)
+		.ok_or(RoutingError::NoUsableChannels)
+	};
+	let first_channel = open_channel(first_hop)?;
+	let first_hop_capacity =
+		views::channel_distributable(&first_channel.our_state, &first_channel.partner_state);
+	let last_channel = open_channel(last_hop)?;
+	let last_hop_capacity =
+		views::channel_distributable(&last_channel.partner_state, &last_channel.our_state);
+
+	let paths = network_graph.read().find_circular_routes(
+		token_network_address,
+		our_address,
+		first_hop,
+		first_hop_capacity,
+		last_hop,
+		last_hop_capacity,
+		amount,
+		pfs.config.max_paths,
+	);
+
+	let mut routes = vec![];
+	for path in paths {
+		let mediators = &path.nodes[1..path.nodes.len() - 1];
+		let mut address_to_metadata = query_route_metadata(&pfs, mediators, false).await?;
+		address_to_metadata.insert(our_address, our_address_metadata.clone());
+
+		routes.push(RouteState {
+			route: path.nodes,
+			address_to_metadata,
+			swaps: HashMap::new(),
+			estimated_fee: path.estimated_fee,
+		});
+	}
+
+	Ok(routes)
+}
+
+/// Returns the candidates for new channels in a token network, best first.
+///
+/// Candidates are taken from `network_graph` if one is given, otherwise the PFS is queried.
+pub async fn get_partner_candidates(
+	pfs: Arc<PFS>,
+	network_graph: Option<Arc<RwLock<NetworkGraph>>>,
+	token_network_address: TokenNetworkAddress,
+	our_address: Address,
+) -> Result<Vec<Address>, RoutingError> {
+	if let Some(network_graph) = network_graph {
+		return Ok(network_graph
+			.read()
+			.participants_by_capacity(token_network_address, our_address))
+	}
+
+	if pfs.config.url.is_empty() {
+		return Err(RoutingError::PFServiceUnusable)
+	}
+	let mut suggestions = pfs.suggest_partners(token_network_address).await?;
+	suggestions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+	Ok(suggestions
+		.into_iter()
+		.map(|suggestion| suggestion.address)
+		.filter(|address| *address != our_address)
+		.collect())
+}
+
+/// Query the metadata of the hops of a route.
+///
+/// Unless `required`, addresses whose metadata is unknown are left out and the transport falls
+/// back to its own lookup.
+async fn query_route_metadata(
+	pfs: &PFS,
+	addresses: &[Address],
+	required: bool,
+) -> Result<HashMap<Address, AddressMetadata>, RoutingError> {
+	let mut address_to_metadata = HashMap::new();
+	if !required && pfs.config.url.is_empty() {
+		return Ok(address_to_metadata)
+	}
+
+	for address in addresses {
+		match super::query_address_metadata(pfs.config.url.clone(), *address).await {
+			Ok(metadata) => {
+				address_to_metadata.insert(*address, metadata);
+			},
+			Err(e) if required => return Err(e),
+			Err(_) => continue,
+		}
+	}
+
+	Ok(address_to_metadata)
+}
+
 /// Create route states out of PFS response.
 #[allow(clippy::too_many_arguments)]
 pub fn make_route_state(
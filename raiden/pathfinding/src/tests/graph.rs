@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use raiden_primitives::types::{
+	Address,
+	CanonicalIdentifier,
+	ChainID,
+	ChannelIdentifier,
+	Nonce,
+	TokenAmount,
+	TokenNetworkAddress,
+	U256,
+};
+use raiden_state_machine::types::FeeScheduleState;
+
+use crate::graph::NetworkGraph;
+
+fn canonical_identifier(
+	token_network_address: TokenNetworkAddress,
+	channel_identifier: u64,
+) -> CanonicalIdentifier {
+	CanonicalIdentifier {
+		chain_identifier: ChainID::Private(U256::from(1)),
+		token_network_address,
+		channel_identifier: ChannelIdentifier::from(channel_identifier),
+	}
+}
+
+fn add_channel(
+	graph: &mut NetworkGraph,
+	canonical_identifier: &CanonicalIdentifier,
+	participant: Address,
+	partner: Address,
+	capacity: u64,
+	flat_fee: u64,
+) {
+	graph.update_capacity(
+		canonical_identifier,
+		participant,
+		partner,
+		Nonce::from(1),
+		Nonce::from(1),
+		TokenAmount::from(capacity),
+		TokenAmount::from(capacity),
+	);
+	for updating_participant in [participant, partner] {
+		graph.update_fee(
+			canonical_identifier,
+			updating_participant,
+			FeeScheduleState { flat: TokenAmount::from(flat_fee), ..Default::default() },
+			"2023-01-01T00:00:00".to_owned(),
+		);
+	}
+}
+
+#[test]
+fn test_find_multi_hop_route() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, mediator, target) = (Address::random(), Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, mediator, target, 100, 3);
+
+	let our_channels = HashMap::from([(mediator, TokenAmount::from(100))]);
+	let routes = graph.find_routes(
+		token_network_address,
+		us,
+		target,
+		TokenAmount::from(50),
+		3,
+		our_channels,
+	);
+
+	assert_eq!(routes.len(), 1);
+	assert_eq!(routes[0].nodes, vec![us, mediator, target]);
+	assert_eq!(routes[0].estimated_fee, TokenAmount::from(3));
+}
+
+#[test]
+fn test_find_routes_respects_capacity() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, mediator, target) = (Address::random(), Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, mediator, target, 10, 0);
+
+	let our_channels = HashMap::from([(mediator, TokenAmount::from(100))]);
+	let routes = graph.find_routes(
+		token_network_address,
+		us,
+		target,
+		TokenAmount::from(50),
+		3,
+		our_channels,
+	);
+
+	assert!(routes.is_empty());
+}
+
+#[test]
+fn test_find_routes_cheapest_first() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, expensive, cheap, target) =
+		(Address::random(), Address::random(), Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, expensive, target, 100, 10);
+	let channel2 = canonical_identifier(token_network_address, 2);
+	add_channel(&mut graph, &channel2, cheap, target, 100, 5);
+	let channel3 = canonical_identifier(token_network_address, 3);
+	add_channel(&mut graph, &channel3, expensive, cheap, 100, 1);
+
+	let our_channels =
+		HashMap::from([(expensive, TokenAmount::from(100)), (cheap, TokenAmount::from(100))]);
+	let routes = graph.find_routes(
+		token_network_address,
+		us,
+		target,
+		TokenAmount::from(50),
+		3,
+		our_channels,
+	);
+
+	assert_eq!(routes.len(), 3);
+	assert_eq!(routes[0].nodes, vec![us, cheap, target]);
+	assert_eq!(routes[0].estimated_fee, TokenAmount::from(5));
+	assert_eq!(routes[1].nodes, vec![us, expensive, cheap, target]);
+	assert_eq!(routes[1].estimated_fee, TokenAmount::from(6));
+	assert_eq!(routes[2].nodes, vec![us, expensive, target]);
+	assert_eq!(routes[2].estimated_fee, TokenAmount::from(10));
+}
+
+#[test]
+fn test_find_circular_route() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, first_hop, last_hop) = (Address::random(), Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, first_hop, last_hop, 100, 2);
+	let channel2 = canonical_identifier(token_network_address, 2);
+	add_channel(&mut graph, &channel2, last_hop, us, 100, 3);
+
+	let routes = graph.find_circular_routes(
+		token_network_address,
+		us,
+		first_hop,
+		TokenAmount::from(100),
+		last_hop,
+		TokenAmount::from(100),
+		TokenAmount::from(50),
+		3,
+	);
+
+	assert_eq!(routes.len(), 1);
+	assert_eq!(routes[0].nodes, vec![us, first_hop, last_hop, us]);
+	assert_eq!(routes[0].estimated_fee, TokenAmount::from(5));
+
+	let routes = graph.find_circular_routes(
+		token_network_address,
+		us,
+		first_hop,
+		TokenAmount::from(10),
+		last_hop,
+		TokenAmount::from(100),
+		TokenAmount::from(50),
+		3,
+	);
+	assert!(routes.is_empty());
+}
+
+#[test]
+fn test_participants_by_capacity() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, small, large) = (Address::random(), Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, us, small, 10, 0);
+	let channel2 = canonical_identifier(token_network_address, 2);
+	add_channel(&mut graph, &channel2, small, large, 100, 0);
+	let channel3 = canonical_identifier(token_network_address, 3);
+	add_channel(&mut graph, &channel3, us, large, 100, 0);
+
+	assert_eq!(graph.participants_by_capacity(token_network_address, us), vec![large, small]);
+	assert!(graph.participants_by_capacity(TokenNetworkAddress::random(), us).is_empty());
+}
+
+#[test]
+fn test_outdated_updates_are_ignored() {
+	let token_network_address = TokenNetworkAddress::random();
+	let canonical_identifier = canonical_identifier(token_network_address, 1);
+	let (participant, partner) = (Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	assert!(graph.update_capacity(
+		&canonical_identifier,
+		participant,
+		partner,
+		Nonce::from(2),
+		Nonce::from(2),
+		TokenAmount::from(100),
+		TokenAmount::from(100),
+	));
+	assert!(!graph.update_capacity(
+		&canonical_identifier,
+		participant,
+		partner,
+		Nonce::from(1),
+		Nonce::from(1),
+		TokenAmount::from(10),
+		TokenAmount::from(10),
+	));
+
+	let fee_schedule = FeeScheduleState::default();
+	let timestamp = "2023-01-01T00:00:00".to_owned();
+	assert!(graph.update_fee(
+		&canonical_identifier,
+		participant,
+		fee_schedule.clone(),
+		timestamp.clone()
+	));
+	assert!(!graph.update_fee(&canonical_identifier, participant, fee_schedule, timestamp));
+}
+
+#[test]
+fn test_updates_with_the_same_nonce_are_ignored() {
+	let token_network_address = TokenNetworkAddress::random();
+	let canonical_identifier = canonical_identifier(token_network_address, 1);
+	let (us, mediator, target) = (Address::random(), Address::random(), Address::random());
+	let our_channels = HashMap::from([(mediator, TokenAmount::from(100))]);
+
+	let mut graph = NetworkGraph::new();
+	add_channel(&mut graph, &canonical_identifier, mediator, target, 100, 0);
+
+	// A replayed update cannot change the capacity.
+	assert!(!graph.update_capacity(
+		&canonical_identifier,
+		mediator,
+		target,
+		Nonce::from(1),
+		Nonce::from(1),
+		TokenAmount::from(10),
+		TokenAmount::from(10),
+	));
+	let routes = graph.find_routes(
+		token_network_address,
+		us,
+		target,
+		TokenAmount::from(50),
+		3,
+		our_channels.clone(),
+	);
+	assert_eq!(routes.len(), 1);
+
+	assert!(graph.update_capacity(
+		&canonical_identifier,
+		mediator,
+		target,
+		Nonce::from(2),
+		Nonce::from(2),
+		TokenAmount::from(10),
+		TokenAmount::from(10),
+	));
+	let routes = graph.find_routes(
+		token_network_address,
+		us,
+		target,
+		TokenAmount::from(50),
+		3,
+		our_channels,
+	);
+	assert!(routes.is_empty());
+}
+
+#[test]
+fn test_no_routes_to_ourselves() {
+	let token_network_address = TokenNetworkAddress::random();
+	let (us, partner) = (Address::random(), Address::random());
+
+	let mut graph = NetworkGraph::new();
+	let channel1 = canonical_identifier(token_network_address, 1);
+	add_channel(&mut graph, &channel1, us, partner, 100, 0);
+
+	let our_channels = HashMap::from([(partner, TokenAmount::from(100))]);
+	let routes =
+		graph.find_routes(token_network_address, us, us, TokenAmount::from(50), 3, our_channels);
+	assert!(routes.is_empty());
+}
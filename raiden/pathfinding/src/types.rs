@@ -1,4 +1,6 @@
-use raiden_blockchain::keys::PrivateKey;
+use std::sync::Arc;
+
+use raiden_blockchain::keys::Signer;
 use raiden_primitives::{
 	deserializers::u256_from_str,
 	packing::pack_one_to_n_iou,
@@ -23,10 +25,7 @@ use serde::{
 	Deserialize,
 	Serialize,
 };
-use web3::signing::{
-	self,
-	Key,
-};
+use web3::signing;
 
 /// Raiden's routing mode.
 #[derive(Copy, Clone, PartialEq)]
@@ -52,8 +51,8 @@ pub struct IOU {
 }
 
 impl IOU {
-	/// Sign IOU with private key.
-	pub fn sign(&mut self, private_key: PrivateKey) -> Result<(), signing::SigningError> {
+	/// Sign IOU with the signer of the account.
+	pub fn sign(&mut self, signer: Arc<dyn Signer>) -> Result<(), signing::SigningError> {
 		let data = pack_one_to_n_iou(
 			self.one_to_n_address,
 			self.sender,
@@ -62,7 +61,7 @@ impl IOU {
 			self.expiration_block,
 			self.chain_id,
 		);
-		let signature = private_key.sign_message(&data.0)?;
+		let signature = signer.sign_message(&data.0)?;
 		self.signature = Some(Bytes(signature.to_bytes()));
 		Ok(())
 	}
@@ -14,6 +14,8 @@ pub mod impls;
 pub mod packing;
 /// Payment status collection.
 pub mod payments;
+/// Reachability of other nodes.
+pub mod reachability;
 /// Base serializers.
 pub mod serializers;
 /// Private key and signing utils.
@@ -12,9 +12,11 @@ use crate::types::{
 };
 
 /// Payment status variants.
+#[derive(Clone, Debug)]
 pub enum PaymentStatus {
 	Success(Address, PaymentIdentifier),
 	Error(Address, PaymentIdentifier, String),
+	Cancelled(Address, PaymentIdentifier),
 }
 
 /// Represents an ongoing payment with means to notify once the payment is completed.
@@ -23,6 +25,8 @@ pub struct Payment {
 	pub token_network_address: TokenNetworkAddress,
 	pub amount: TokenAmount,
 	pub notifier: Option<oneshot::Sender<PaymentStatus>>,
+	/// The final status, set if the payment failed before reaching the state machine.
+	pub status: Option<PaymentStatus>,
 }
 
 /// A collection of ongoing payments.
@@ -63,29 +67,62 @@ impl PaymentsRegistry {
 		let payments = self.payments.get_mut(&target).expect("Just created above");
 		payments.insert(
 			identifier,
-			Payment { identifier, token_network_address, amount, notifier: Some(sender) },
+			Payment {
+				identifier,
+				token_network_address,
+				amount,
+				notifier: Some(sender),
+				status: None,
+			},
 		);
 		receiver
 	}
 
-	/// Mark an ongoing payment as complete with status.
+	/// Mark an ongoing payment as complete with status and drop it.
+	///
+	/// The final status of a payment which reached the state machine is persisted with its
+	/// events.
 	pub fn complete(&mut self, status: PaymentStatus) {
 		let (target, identifier) = match status {
 			PaymentStatus::Success(target, identifier) => (target, identifier),
 			PaymentStatus::Error(target, identifier, _) => (target, identifier),
+			PaymentStatus::Cancelled(target, identifier) => (target, identifier),
 		};
 		let payments = match self.payments.get_mut(&target) {
 			Some(payments) => payments,
 			None => return,
 		};
 
-		let payment = match payments.get_mut(&identifier) {
+		// The first status wins, a cancelled payment also fails in the state machine.
+		let payment = match payments.remove(&identifier) {
 			Some(payment) => payment,
 			None => return,
 		};
+		if payments.is_empty() {
+			self.payments.remove(&target);
+		}
 
-		if let Some(notifier) = payment.notifier.take() {
+		if let Some(notifier) = payment.notifier {
 			let _ = notifier.send(status);
 		}
 	}
+
+	/// Mark an ongoing payment as failed before it reached the state machine.
+	///
+	/// Nothing is persisted for such a payment, so it is kept along with its status.
+	pub fn fail(&mut self, target: Address, identifier: PaymentIdentifier, error: String) {
+		let payment = match self.payments.get_mut(&target).and_then(|p| p.get_mut(&identifier)) {
+			Some(payment) => payment,
+			None => return,
+		};
+		if payment.status.is_some() {
+			return
+		}
+
+		let status = PaymentStatus::Error(target, identifier, error);
+		if let Some(notifier) = payment.notifier.take() {
+			let _ = notifier.send(status.clone());
+		}
+		payment.status = Some(status);
+	}
 }
@@ -0,0 +1,145 @@
+#![warn(clippy::missing_docs_in_private_items)]
+
+use std::{
+	collections::HashMap,
+	time::{
+		Duration,
+		Instant,
+	},
+};
+
+use serde::{
+	Deserialize,
+	Serialize,
+};
+use tokio::sync::broadcast;
+
+use crate::{
+	serializers::to_checksum_str,
+	types::Address,
+};
+
+/// The number of reachability changes buffered for subscribers.
+const REACHABILITY_CHANNEL_CAPACITY: usize = 256;
+/// The time after which a node observed as unreachable is unknown again.
+const UNREACHABLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Whether messages currently reach a node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+	Reachable,
+	Unreachable,
+	Unknown,
+}
+
+impl Default for Reachability {
+	fn default() -> Self {
+		Reachability::Unknown
+	}
+}
+
+/// The reachability of a node changed.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReachabilityChange {
+	#[serde(serialize_with = "to_checksum_str")]
+	pub address: Address,
+	pub reachability: Reachability,
+}
+
+/// Keeps the reachability of the nodes, as observed by the transports.
+///
+/// Nodes are unknown until a transport observes them, changes are published to the subscribers.
+///
+/// Observations of unreachable nodes are hints, home servers without presence report every user
+/// as offline. They expire after a timeout unless the node was observed as reachable in between,
+/// so that such nodes are not refused forever.
+pub struct ReachabilityTracker {
+	/// The last observed reachability by address.
+	reachability: HashMap<Address, Reachability>,
+	/// When the nodes which are unreachable were first observed as such.
+	unreachable_since: HashMap<Address, Instant>,
+	/// The time after which unreachable nodes are unknown again.
+	unreachable_timeout: Duration,
+	/// Publishes the changes of reachability.
+	changes: broadcast::Sender<ReachabilityChange>,
+}
+
+impl ReachabilityTracker {
+	/// Returns an instance of `ReachabilityTracker`.
+	pub fn new() -> Self {
+		Self::with_unreachable_timeout(UNREACHABLE_TIMEOUT)
+	}
+
+	/// Returns an instance of `ReachabilityTracker` with unreachable nodes being unknown again
+	/// after `unreachable_timeout`.
+	pub fn with_unreachable_timeout(unreachable_timeout: Duration) -> Self {
+		let (changes, _) = broadcast::channel(REACHABILITY_CHANNEL_CAPACITY);
+		Self {
+			reachability: HashMap::new(),
+			unreachable_since: HashMap::new(),
+			unreachable_timeout,
+			changes,
+		}
+	}
+
+	/// Returns the reachability of `address`.
+	pub fn get(&self, address: Address) -> Reachability {
+		match self.reachability.get(&address).copied().unwrap_or_default() {
+			Reachability::Unreachable if self.unreachable_expired(address) => Reachability::Unknown,
+			reachability => reachability,
+		}
+	}
+
+	/// Returns true unless `address` is known to be unreachable.
+	pub fn is_usable(&self, address: Address) -> bool {
+		self.get(address) != Reachability::Unreachable
+	}
+
+	/// Returns the addresses whose reachability is tracked.
+	pub fn addresses(&self) -> Vec<Address> {
+		self.reachability.keys().copied().collect()
+	}
+
+	/// Start tracking `address`, which transports then check the reachability of.
+	pub fn watch(&mut self, address: Address) {
+		self.reachability.entry(address).or_default();
+	}
+
+	/// Record the reachability of `address`, returns true and publishes the change if it
+	/// changed.
+	///
+	/// Observing an unreachable node again does not extend the timeout of the first observation.
+	pub fn update(&mut self, address: Address, reachability: Reachability) -> bool {
+		let previous = self.reachability.insert(address, reachability).unwrap_or_default();
+		if previous == reachability {
+			return false
+		}
+		if reachability == Reachability::Unreachable {
+			self.unreachable_since.insert(address, Instant::now());
+		} else {
+			self.unreachable_since.remove(&address);
+		}
+		let _ = self.changes.send(ReachabilityChange { address, reachability });
+		true
+	}
+
+	/// Whether `address` was observed as unreachable for longer than the timeout.
+	fn unreachable_expired(&self, address: Address) -> bool {
+		self.unreachable_since
+			.get(&address)
+			.map(|since| since.elapsed() >= self.unreachable_timeout)
+			.unwrap_or(false)
+	}
+
+	/// Subscribe to the changes of reachability.
+	pub fn subscribe(&self) -> broadcast::Receiver<ReachabilityChange> {
+		self.changes.subscribe()
+	}
+}
+
+impl Default for ReachabilityTracker {
+	fn default() -> Self {
+		Self::new()
+	}
+}
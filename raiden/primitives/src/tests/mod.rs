@@ -1 +1,3 @@
 mod deserializers;
+mod reachability;
+mod types;
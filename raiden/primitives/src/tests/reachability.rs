@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::{
+	reachability::{
+		Reachability,
+		ReachabilityTracker,
+	},
+	types::Address,
+};
+
+#[test]
+fn test_unknown_until_observed() {
+	let mut tracker = ReachabilityTracker::new();
+	let address = Address::from_low_u64_be(1);
+
+	assert_eq!(tracker.get(address), Reachability::Unknown);
+	assert!(tracker.is_usable(address));
+
+	tracker.watch(address);
+	assert_eq!(tracker.get(address), Reachability::Unknown);
+	assert_eq!(tracker.addresses(), vec![address]);
+}
+
+#[test]
+fn test_changes_are_published() {
+	let mut tracker = ReachabilityTracker::new();
+	let mut changes = tracker.subscribe();
+	let address = Address::from_low_u64_be(1);
+
+	assert!(tracker.update(address, Reachability::Unreachable));
+	assert!(!tracker.is_usable(address));
+	// The same reachability again is not a change.
+	assert!(!tracker.update(address, Reachability::Unreachable));
+	assert!(tracker.update(address, Reachability::Reachable));
+
+	let change = changes.try_recv().expect("Should publish change");
+	assert_eq!(change.address, address);
+	assert_eq!(change.reachability, Reachability::Unreachable);
+	let change = changes.try_recv().expect("Should publish change");
+	assert_eq!(change.reachability, Reachability::Reachable);
+	assert!(changes.try_recv().is_err());
+}
+
+#[test]
+fn test_unreachable_expires() {
+	let mut tracker = ReachabilityTracker::with_unreachable_timeout(Duration::ZERO);
+	let address = Address::from_low_u64_be(1);
+
+	assert!(tracker.update(address, Reachability::Unreachable));
+	assert_eq!(tracker.get(address), Reachability::Unknown);
+	assert!(tracker.is_usable(address));
+
+	// Observing it as unreachable again does not restart the timeout.
+	assert!(!tracker.update(address, Reachability::Unreachable));
+	assert!(tracker.is_usable(address));
+
+	// Until it was reachable in between.
+	let mut tracker = ReachabilityTracker::with_unreachable_timeout(Duration::from_secs(60));
+	assert!(tracker.update(address, Reachability::Unreachable));
+	assert!(!tracker.is_usable(address));
+	assert!(tracker.update(address, Reachability::Reachable));
+	assert!(tracker.update(address, Reachability::Unreachable));
+	assert_eq!(tracker.get(address), Reachability::Unreachable);
+}
@@ -0,0 +1,40 @@
+use std::net::SocketAddr;
+
+use crate::types::AddressMetadata;
+
+#[test]
+fn test_address_metadata_socket_address() {
+	let socket_address: SocketAddr = "10.0.0.1:5001".parse().unwrap();
+
+	let mut metadata = AddressMetadata::default();
+	assert_eq!(metadata.socket_address(), None);
+	metadata.set_socket_address(socket_address);
+	assert_eq!(metadata.capabilities, "mxc://raiden.network/cap?tcp=10.0.0.1:5001");
+	assert_eq!(metadata.socket_address(), Some(socket_address));
+
+	let mut metadata = AddressMetadata {
+		capabilities: "mxc://raiden.network/cap?Receive=1&tcp=127.0.0.1:1&Mediate=1".to_owned(),
+		..Default::default()
+	};
+	metadata.set_socket_address(socket_address);
+	assert_eq!(
+		metadata.capabilities,
+		"mxc://raiden.network/cap?Receive=1&Mediate=1&tcp=10.0.0.1:5001"
+	);
+	assert_eq!(metadata.socket_address(), Some(socket_address));
+}
+
+#[test]
+fn test_address_metadata_unspecified_socket_address() {
+	let metadata = AddressMetadata {
+		capabilities: "mxc://raiden.network/cap?tcp=0.0.0.0:5001".to_owned(),
+		..Default::default()
+	};
+	assert_eq!(metadata.socket_address(), None);
+
+	let metadata = AddressMetadata {
+		capabilities: "mxc://raiden.network/cap?tcp=[::]:5001".to_owned(),
+		..Default::default()
+	};
+	assert_eq!(metadata.socket_address(), None);
+}
@@ -1,5 +1,7 @@
 #![warn(clippy::missing_docs_in_private_items)]
 
+use std::net::SocketAddr;
+
 use derive_more::Display;
 use serde::{
 	Deserialize,
@@ -187,6 +189,46 @@ pub struct AddressMetadata {
 	pub capabilities: String,
 }
 
+/// Prefix of the capabilities when none are advertised yet.
+const CAPABILITIES_PREFIX: &str = "mxc://raiden.network/cap";
+
+/// Capability holding the socket address of a node accepting direct TCP connections.
+const TCP_CAPABILITY: &str = "tcp";
+
+impl AddressMetadata {
+	/// Returns the socket address advertised in the capabilities, if the node accepts direct
+	/// connections.
+	///
+	/// Unspecified addresses such as `0.0.0.0` can not be connected to and are ignored.
+	pub fn socket_address(&self) -> Option<SocketAddr> {
+		let (_, query) = self.capabilities.split_once('?')?;
+		query
+			.split('&')
+			.filter_map(|capability| capability.split_once('='))
+			.find(|(key, _)| *key == TCP_CAPABILITY)
+			.and_then(|(_, value)| value.parse::<SocketAddr>().ok())
+			.filter(|socket_address| !socket_address.ip().is_unspecified())
+	}
+
+	/// Advertise `socket_address` in the capabilities, replacing any previous one.
+	pub fn set_socket_address(&mut self, socket_address: SocketAddr) {
+		let (prefix, query) = match self.capabilities.split_once('?') {
+			Some((prefix, query)) => (prefix, query),
+			None if self.capabilities.is_empty() => (CAPABILITIES_PREFIX, ""),
+			None => (self.capabilities.as_str(), ""),
+		};
+		let mut capabilities: Vec<String> = query
+			.split('&')
+			.filter(|capability| {
+				!capability.is_empty() && capability.split('=').next() != Some(TCP_CAPABILITY)
+			})
+			.map(str::to_owned)
+			.collect();
+		capabilities.push(format!("{}={}", TCP_CAPABILITY, socket_address));
+		self.capabilities = format!("{}?{}", prefix, capabilities.join("&"));
+	}
+}
+
 /// Contains a list of deployed contract addresses vital for the operation of the node.
 #[derive(Clone, Serialize)]
 pub struct DefaultAddresses {
@@ -11,7 +11,7 @@
 //! when you want to quickly bootstrap a new project.
 //!
 //! ```rust
-//! use raiden-rs::prelude::*;
+//! use raiden_rs::prelude::*;
 //! ```
 //!
 //! ## Modules
@@ -34,6 +34,11 @@
 //!
 //! Provides simple macros for type conversions.
 //!
+//! ### `raiden-multi-account`
+//!
+//! Hosts several accounts in a single process, sharing contract proxies and block monitoring
+//! while routing payments between local accounts internally.
+//!
 //! ### `raiden-network_messages`, `raiden-network_transport`
 //!
 //! Implements Raiden protocol messages and matrix network integration to exchange messages between
@@ -70,6 +75,8 @@ pub use raiden_blockchain;
 #[doc(inline)]
 pub use raiden_macros;
 #[doc(inline)]
+pub use raiden_multi_account;
+#[doc(inline)]
 pub use raiden_network_messages;
 #[doc(inline)]
 pub use raiden_network_transport;
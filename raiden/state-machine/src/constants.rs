@@ -10,6 +10,8 @@ pub const ABSENT_SECRET: Bytes = Bytes(vec![]);
 
 pub const SECRET_LENGTH: u8 = 32;
 
+pub const PAYMENT_CANCELED_REASON: &str = "user canceled payment";
+
 pub const MIN_REVEAL_TIMEOUT: u32 = 1;
 
 pub const DEFAULT_REVEAL_TIMEOUT: u32 = 50;
@@ -18,6 +20,12 @@ pub const DEFAULT_SETTLE_TIMEOUT: u32 = 500;
 
 pub const DEFAULT_RETRY_TIMEOUT: u64 = 500;
 
+pub const DEFAULT_INVOICE_TIMEOUT: u64 = 1000;
+
+pub const DEFAULT_INITIAL_CHANNEL_TARGET: u16 = 3;
+
+pub const DEFAULT_JOINABLE_FUNDS_TARGET: u8 = 40; // 40% of the connection manager funds
+
 pub const DEFAULT_NUMBER_OF_BLOCK_CONFIRMATIONS: u64 = 5;
 
 pub const DEFAULT_WAIT_BEFORE_LOCK_REMOVAL: u64 = 2 * DEFAULT_NUMBER_OF_BLOCK_CONFIRMATIONS;
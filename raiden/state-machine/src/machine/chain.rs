@@ -1,5 +1,7 @@
 #![warn(clippy::missing_docs_in_private_items)]
 
+use std::collections::HashSet;
+
 use raiden_primitives::types::{
 	BlockNumber,
 	CanonicalIdentifier,
@@ -26,10 +28,13 @@ use crate::{
 	},
 	types::{
 		ActionCancelPayment,
+		ActionCreateInvoice,
 		ActionInitChain,
 		ActionInitInitiator,
 		ActionInitMediator,
 		ActionInitTarget,
+		ActionTokenNetworkConnect,
+		ActionTokenNetworkLeave,
 		ActionTransferReroute,
 		Block,
 		ChainState,
@@ -39,6 +44,7 @@ use crate::{
 		ContractSendEvent,
 		Event,
 		InitiatorTask,
+		InvoiceStatus,
 		MediatorTask,
 		ReceiveDelivered,
 		ReceiveLockExpired,
@@ -136,6 +142,29 @@ fn subdispatch_to_all_channels(
 	Ok(ChainTransition { new_state: chain_state, events })
 }
 
+/// Subdispatch state change to the target task of a circular payment.
+fn subdispatch_to_circular_target(
+	mut chain_state: ChainState,
+	mut target: TargetTask,
+	state_change: StateChange,
+	secrethash: SecretHash,
+) -> TransitionResult {
+	let sub_iteration =
+		target::state_transition(chain_state, Some(target.target_state), state_change)?;
+	chain_state = sub_iteration.chain_state;
+	if let Some(new_state) = sub_iteration.new_state {
+		target.target_state = new_state;
+		chain_state
+			.payment_mapping
+			.secrethashes_to_circular_target
+			.insert(secrethash, target);
+	} else {
+		chain_state.payment_mapping.secrethashes_to_circular_target.remove(&secrethash);
+	}
+
+	Ok(ChainTransition { new_state: chain_state, events: sub_iteration.events })
+}
+
 /// Subdispatch state change to payment tasks.
 fn subdispatch_to_payment_task(
 	mut chain_state: ChainState,
@@ -144,6 +173,40 @@ fn subdispatch_to_payment_task(
 ) -> TransitionResult {
 	let mut events = vec![];
 
+	// A circular payment has both an initiator and a target task for the same secrethash.
+	// Balance proofs received through the channel of the target belong to the target, blocks
+	// and on-chain secret reveals concern both and everything else is for the initiator.
+	if let Some(target) = chain_state
+		.payment_mapping
+		.secrethashes_to_circular_target
+		.get(&secrethash)
+		.cloned()
+	{
+		let target_channel =
+			target.target_state.transfer.balance_proof.canonical_identifier.clone();
+		let (for_target, only_target) = match &state_change {
+			StateChange::ReceiveUnlock(inner) =>
+				(inner.balance_proof.canonical_identifier == target_channel, true),
+			StateChange::ReceiveLockExpired(inner) =>
+				(inner.balance_proof.canonical_identifier == target_channel, true),
+			StateChange::Block(_) | StateChange::ContractReceiveSecretReveal(_) => (true, false),
+			_ => (false, false),
+		};
+		if for_target {
+			let result = subdispatch_to_circular_target(
+				chain_state,
+				target,
+				state_change.clone(),
+				secrethash,
+			)?;
+			chain_state = result.new_state;
+			events.extend(result.events);
+			if only_target {
+				return Ok(ChainTransition { new_state: chain_state, events })
+			}
+		}
+	}
+
 	if let Some(sub_task) =
 		chain_state.payment_mapping.secrethashes_to_task.get(&secrethash).cloned()
 	{
@@ -212,10 +275,16 @@ fn subdispatch_to_all_lockedtransfers(
 ) -> TransitionResult {
 	let mut events = vec![];
 
-	let payment_mapping = chain_state.payment_mapping.clone();
-	for secrethash in payment_mapping.secrethashes_to_task.keys() {
+	let payment_mapping = &chain_state.payment_mapping;
+	let secrethashes: HashSet<SecretHash> = payment_mapping
+		.secrethashes_to_task
+		.keys()
+		.chain(payment_mapping.secrethashes_to_circular_target.keys())
+		.cloned()
+		.collect();
+	for secrethash in secrethashes {
 		let result =
-			subdispatch_to_payment_task(chain_state.clone(), state_change.clone(), *secrethash)?;
+			subdispatch_to_payment_task(chain_state.clone(), state_change.clone(), secrethash)?;
 		chain_state = result.new_state;
 		events.extend(result.events);
 	}
@@ -350,6 +419,21 @@ fn subdispatch_target_task(
 	token_network_address: TokenNetworkAddress,
 	secrethash: SecretHash,
 ) -> TransitionResult {
+	// Only payments this node is currently initiating may come back to it.
+	let is_circular = state_change.transfer.initiator == chain_state.our_address &&
+		matches!(
+			chain_state.payment_mapping.secrethashes_to_task.get(&secrethash),
+			Some(TransferTask::Initiator(_))
+		);
+	if is_circular {
+		return subdispatch_circular_target_task(
+			chain_state,
+			state_change,
+			token_network_address,
+			secrethash,
+		)
+	}
+
 	let target_state = match chain_state.payment_mapping.secrethashes_to_task.get(&secrethash) {
 		Some(sub_task) => match sub_task {
 			TransferTask::Target(target_task) => Some(target_task.target_state.clone()),
@@ -382,6 +466,39 @@ fn subdispatch_target_task(
 	Ok(ChainTransition { new_state: chain_state, events })
 }
 
+/// Subdispatch state change to the target task of a payment initiated by this node.
+fn subdispatch_circular_target_task(
+	chain_state: ChainState,
+	state_change: ActionInitTarget,
+	token_network_address: TokenNetworkAddress,
+	secrethash: SecretHash,
+) -> TransitionResult {
+	let target_state = chain_state
+		.payment_mapping
+		.secrethashes_to_circular_target
+		.get(&secrethash)
+		.map(|target_task| target_task.target_state.clone());
+
+	let iteration = target::state_transition(chain_state, target_state, state_change.into())?;
+	let mut chain_state = iteration.chain_state;
+
+	if let Some(new_state) = iteration.new_state {
+		let target_task = TargetTask {
+			role: TransferRole::Target,
+			token_network_address,
+			target_state: new_state,
+		};
+		chain_state
+			.payment_mapping
+			.secrethashes_to_circular_target
+			.insert(secrethash, target_task);
+	} else {
+		chain_state.payment_mapping.secrethashes_to_circular_target.remove(&secrethash);
+	}
+
+	Ok(ChainTransition { new_state: chain_state, events: iteration.events })
+}
+
 /// Initialize chain information.
 fn handle_action_init_chain(state_change: ActionInitChain) -> TransitionResult {
 	Ok(ChainTransition {
@@ -451,9 +568,80 @@ fn handle_action_transfer_reroute(
 
 /// Handle `ActionCancelPayment` state change.
 fn handle_action_cancel_payment(
-	chain_state: ChainState,
-	_state_change: ActionCancelPayment,
+	mut chain_state: ChainState,
+	state_change: ActionCancelPayment,
+) -> TransitionResult {
+	let secrethashes: Vec<SecretHash> = chain_state
+		.payment_mapping
+		.secrethashes_to_task
+		.iter()
+		.filter(|(_, task)| match task {
+			TransferTask::Initiator(initiator) =>
+				initiator.manager_state.initiator_transfers.values().any(|transfer| {
+					let description = &transfer.transfer_description;
+					description.token_network_address == state_change.token_network_address &&
+						description.target == state_change.target &&
+						description.payment_identifier == state_change.payment_identifier
+				}),
+			_ => false,
+		})
+		.map(|(secrethash, _)| *secrethash)
+		.collect();
+
+	let mut events = vec![];
+	for secrethash in secrethashes {
+		let result =
+			subdispatch_to_payment_task(chain_state, state_change.clone().into(), secrethash)?;
+		chain_state = result.new_state;
+		events.extend(result.events);
+	}
+
+	Ok(ChainTransition { new_state: chain_state, events })
+}
+
+/// Handle `ActionCreateInvoice` state change.
+fn handle_action_create_invoice(
+	mut chain_state: ChainState,
+	state_change: ActionCreateInvoice,
 ) -> TransitionResult {
+	chain_state
+		.invoices
+		.insert(state_change.invoice.secrethash, state_change.invoice);
+
+	Ok(ChainTransition { new_state: chain_state, events: vec![] })
+}
+
+/// Handle `ActionTokenNetworkConnect` state change.
+fn handle_action_token_network_connect(
+	mut chain_state: ChainState,
+	state_change: ActionTokenNetworkConnect,
+) -> TransitionResult {
+	let connection_manager = state_change.connection_manager;
+	if views::get_token_network_by_address(&chain_state, connection_manager.token_network_address)
+		.is_none()
+	{
+		return Err(StateTransitionError {
+			msg: format!(
+				"Token network {} was not found",
+				connection_manager.token_network_address
+			),
+		})
+	}
+
+	chain_state
+		.connection_managers
+		.insert(connection_manager.token_network_address, connection_manager);
+
+	Ok(ChainTransition { new_state: chain_state, events: vec![] })
+}
+
+/// Handle `ActionTokenNetworkLeave` state change.
+fn handle_action_token_network_leave(
+	mut chain_state: ChainState,
+	state_change: ActionTokenNetworkLeave,
+) -> TransitionResult {
+	chain_state.connection_managers.remove(&state_change.token_network_address);
+
 	Ok(ChainTransition { new_state: chain_state, events: vec![] })
 }
 
@@ -462,6 +650,14 @@ fn handle_new_block(mut chain_state: ChainState, state_change: Block) -> Transit
 	chain_state.block_number = state_change.block_number;
 	chain_state.block_hash = state_change.block_hash;
 
+	for invoice in chain_state.invoices.values_mut() {
+		if invoice.status == InvoiceStatus::Pending &&
+			invoice.expiration < state_change.block_number
+		{
+			invoice.status = InvoiceStatus::Expired;
+		}
+	}
+
 	let channels_result = subdispatch_to_all_channels(
 		chain_state.clone(),
 		state_change.clone().into(),
@@ -972,6 +1168,11 @@ pub fn state_transition(
 		StateChange::ActionTransferReroute(inner) =>
 			handle_action_transfer_reroute(chain_state, inner),
 		StateChange::ActionCancelPayment(inner) => handle_action_cancel_payment(chain_state, inner),
+		StateChange::ActionCreateInvoice(inner) => handle_action_create_invoice(chain_state, inner),
+		StateChange::ActionTokenNetworkConnect(inner) =>
+			handle_action_token_network_connect(chain_state, inner),
+		StateChange::ActionTokenNetworkLeave(inner) =>
+			handle_action_token_network_leave(chain_state, inner),
 		StateChange::ActionChannelClose(ref inner) => {
 			let token_network_address = inner.canonical_identifier.token_network_address;
 			let block_number = chain_state.block_number;
@@ -150,13 +150,20 @@ fn send_locked_transfer(
 	let recipient_metadata = views::get_address_metadata(recipient_address, route_states.clone());
 	let our_address = channel_state.our_state.address;
 
+	// A secret held by the target is not sent along with the transfer.
+	let secret = if transfer_description.secret == ABSENT_SECRET {
+		None
+	} else {
+		Some(transfer_description.secret)
+	};
+
 	channel::send_locked_transfer(
 		channel_state,
 		transfer_description.initiator,
 		transfer_description.target,
 		total_amount,
 		lock_expiration,
-		Some(transfer_description.secret),
+		secret,
 		transfer_description.secrethash,
 		message_identifier,
 		transfer_description.payment_identifier,
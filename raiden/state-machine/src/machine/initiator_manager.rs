@@ -14,6 +14,7 @@ use super::{
 	utils,
 };
 use crate::{
+	constants::PAYMENT_CANCELED_REASON,
 	errors::StateTransitionError,
 	types::{
 		ActionCancelPayment,
@@ -57,8 +58,8 @@ pub struct InitiatorManagerTransition {
 }
 
 /// A transfer is only cancellable until the secret is revealed.
-fn can_cancel(initiator: &InitiatorTransferState) -> bool {
-	initiator.transfer_state != TransferState::Canceled
+pub fn can_cancel(initiator: &InitiatorTransferState) -> bool {
+	!matches!(initiator.transfer_state, TransferState::SecretRevealed | TransferState::Canceled)
 }
 
 /// Returns true if the transfer exists.
@@ -257,7 +258,7 @@ pub fn handle_action_cancel_payment(
 	};
 
 	let mut events = vec![];
-	for initiator_state in payment_state.initiator_transfers.clone().values_mut() {
+	for (secrethash, initiator_state) in payment_state.initiator_transfers.clone() {
 		let channel_identifier = initiator_state.channel_identifier;
 		let channel_state = match views::get_channel_by_canonical_identifier(
 			&chain_state,
@@ -271,18 +272,20 @@ pub fn handle_action_cancel_payment(
 			None => continue,
 		};
 
-		if can_cancel(initiator_state) {
+		if can_cancel(&initiator_state) {
 			let transfer_description = initiator_state.transfer_description.clone();
-			let mut cancel_events = cancel_current_route(&mut payment_state, initiator_state);
+			let mut cancel_events = cancel_current_route(&mut payment_state, &initiator_state);
 
-			initiator_state.transfer_state = TransferState::Canceled;
+			if let Some(initiator_state) = payment_state.initiator_transfers.get_mut(&secrethash) {
+				initiator_state.transfer_state = TransferState::Canceled;
+			}
 
 			let cancel = ErrorPaymentSentFailed {
 				token_network_registry_address: channel_state.token_network_registry_address,
 				token_network_address: channel_state.canonical_identifier.token_network_address,
 				identifier: transfer_description.payment_identifier,
 				target: transfer_description.target,
-				reason: "user canceled payment".to_string(),
+				reason: PAYMENT_CANCELED_REASON.to_owned(),
 			};
 
 			cancel_events.push(cancel.into());
@@ -5,6 +5,7 @@ use raiden_primitives::{
 	types::{
 		BlockHash,
 		BlockNumber,
+		Secret,
 	},
 };
 
@@ -27,6 +28,7 @@ use crate::{
 		ContractReceiveSecretReveal,
 		ErrorUnlockClaimFailed,
 		Event,
+		InvoiceStatus,
 		PaymentReceivedSuccess,
 		ReceiveLockExpired,
 		ReceiveSecretReveal,
@@ -87,6 +89,47 @@ fn events_for_onchain_secretreveal(
 	Ok(vec![])
 }
 
+/// Reveals the secret of an invoice to the previous hop of the transfer paying it.
+fn events_for_invoice_secret_reveal(
+	chain_state: &mut ChainState,
+	target_state: &mut TargetTransferState,
+	secret: Secret,
+) -> Result<Vec<Event>, StateTransitionError> {
+	let transfer = &target_state.transfer;
+	let secrethash = transfer.lock.secrethash;
+
+	let mut channel_state = match views::get_channel_by_canonical_identifier(
+		chain_state,
+		transfer.balance_proof.canonical_identifier.clone(),
+	) {
+		Some(channel_state) => channel_state.clone(),
+		None => return Ok(vec![]),
+	};
+	channel::register_offchain_secret(&mut channel_state, secret.clone(), secrethash);
+	update_channel(chain_state, channel_state).map_err(Into::into)?;
+
+	let message_identifier = chain_state.pseudo_random_number_generator.next();
+	let recipient = target_state.from_hop.node_address;
+	let reveal = SendSecretReveal {
+		inner: SendMessageEventInner {
+			recipient,
+			recipient_metadata: views::get_address_metadata(
+				recipient,
+				transfer.route_states.clone(),
+			),
+			canonical_identifier: CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
+			message_identifier,
+		},
+		secret: secret.clone(),
+		secrethash,
+	};
+
+	target_state.state = TargetState::OffchainSecretReveal;
+	target_state.secret = Some(secret);
+
+	Ok(vec![reveal.into()])
+}
+
 /// Handles an ActionInitTarget state change.
 fn handle_init_target(
 	mut chain_state: ChainState,
@@ -131,7 +174,7 @@ fn handle_init_target(
 			// proofs to be handled. This however, must only be done once, which is
 			// enforced by the nonce increasing sequentially, which is verified by
 			// the handler handle_receive_lockedtransfer.
-			let target_state = TargetTransferState {
+			let mut target_state = TargetTransferState {
 				from_hop,
 				transfer: transfer.clone(),
 				secret: None,
@@ -150,6 +193,28 @@ fn handle_init_target(
 				chain_state.block_number,
 			)
 			.is_ok();
+
+			// The secret of an invoice is held by this node, there is nobody to request it from.
+			if let Some(invoice) = chain_state.invoices.get(&transfer.lock.secrethash).cloned() {
+				if safe_to_wait && invoice.is_paid_by(transfer, chain_state.block_number) {
+					events.extend(events_for_invoice_secret_reveal(
+						&mut chain_state,
+						&mut target_state,
+						invoice.secret,
+					)?);
+				} else {
+					events.push(
+						ErrorUnlockClaimFailed {
+							identifier: transfer.payment_identifier,
+							secrethash: transfer.lock.secrethash,
+							reason: "Transfer does not pay the invoice".to_owned(),
+						}
+						.into(),
+					);
+				}
+				return Ok(TargetTransition { new_state: Some(target_state), chain_state, events })
+			}
+
 			if safe_to_wait {
 				let message_identifier = chain_state.pseudo_random_number_generator.next();
 				let recipient = transfer.initiator;
@@ -469,6 +534,10 @@ fn handle_unlock(
 
 	update_channel(&mut chain_state, channel_state.clone()).map_err(Into::into)?;
 
+	if let Some(invoice) = chain_state.invoices.get_mut(&transfer.lock.secrethash) {
+		invoice.status = InvoiceStatus::Paid;
+	}
+
 	let payment_received_success = PaymentReceivedSuccess {
 		token_network_registry_address: channel_state.token_network_registry_address,
 		token_network_address: channel_state.canonical_identifier.token_network_address,
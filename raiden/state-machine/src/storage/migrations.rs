@@ -0,0 +1,90 @@
+#![warn(clippy::missing_docs_in_private_items)]
+
+use serde_json::{
+	json,
+	Value,
+};
+
+use super::{
+	types::{
+		Result,
+		StorageError,
+	},
+	Storage,
+};
+
+/// Version of databases created before versioning was introduced.
+pub const INITIAL_VERSION: u32 = 1;
+
+/// Migrations applied in order to upgrade a database to the latest version.
+///
+/// Every change to the tables or to the serialized state types has to append a migration here.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+	version: 2,
+	sqlite: "",
+	postgresql: "",
+	state_change: None,
+	event: None,
+	snapshot: Some(add_chain_state_collections),
+}];
+
+/// Upgrade of the database from the previous version to `version`.
+pub struct Migration {
+	/// The version of the database after the migration.
+	pub version: u32,
+	/// SQLite statements altering the tables.
+	pub sqlite: &'static str,
+	/// PostgreSQL statements altering the tables.
+	pub postgresql: &'static str,
+	/// Upgrade of a serialized state change.
+	pub state_change: Option<fn(Value) -> Value>,
+	/// Upgrade of a serialized event.
+	pub event: Option<fn(Value) -> Value>,
+	/// Upgrade of a serialized chain state snapshot.
+	pub snapshot: Option<fn(Value) -> Value>,
+}
+
+/// Add the invoices, connection managers and circular payment targets to a chain state snapshot
+/// which was stored before they were introduced.
+fn add_chain_state_collections(mut snapshot: Value) -> Value {
+	if let Some(chain_state) = snapshot.as_object_mut() {
+		chain_state.entry("invoices").or_insert_with(|| json!({}));
+		chain_state.entry("connection_managers").or_insert_with(|| json!({}));
+	}
+	if let Some(payment_mapping) =
+		snapshot.get_mut("payment_mapping").and_then(Value::as_object_mut)
+	{
+		payment_mapping
+			.entry("secrethashes_to_circular_target")
+			.or_insert_with(|| json!({}));
+	}
+	snapshot
+}
+
+/// Return the version reached after applying all of `migrations`.
+pub fn latest_version(migrations: &[Migration]) -> u32 {
+	migrations.last().map(|migration| migration.version).unwrap_or(INITIAL_VERSION)
+}
+
+/// Bring the database to the latest version by applying the missing migrations in order.
+///
+/// Fails if the database was written by a newer version than the known migrations.
+pub fn migrate(storage: &dyn Storage, migrations: &'static [Migration]) -> Result<()> {
+	let latest_version = latest_version(migrations);
+	let version = match storage.get_version()? {
+		Some(version) => version,
+		None => {
+			storage.set_version(INITIAL_VERSION)?;
+			INITIAL_VERSION
+		},
+	};
+	if version > latest_version {
+		return Err(StorageError::UnsupportedVersion(version, latest_version))
+	}
+
+	for migration in migrations.iter().filter(|migration| migration.version > version) {
+		storage.apply_migration(migration)?;
+	}
+
+	Ok(())
+}
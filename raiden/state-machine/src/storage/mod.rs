@@ -12,22 +12,34 @@ use raiden_primitives::types::{
 	BalanceHash,
 	CanonicalIdentifier,
 	Locksroot,
+	PaymentIdentifier,
 	TokenNetworkAddress,
+	U256,
 };
 use rusqlite::{
 	params,
 	Connection,
 	ToSql,
 };
+use serde_json::Value;
 use ulid::Ulid;
 
-use self::types::{
-	EventRecord,
-	Result,
-	SnapshotRecord,
-	StateChangeRecord,
-	StorageError,
-	StorageID,
+use self::{
+	migrations::Migration,
+	types::{
+		CompactionStats,
+		EventFilter,
+		EventRecord,
+		NotificationRecord,
+		NotificationUrgency,
+		Result,
+		RetentionPolicy,
+		SnapshotRecord,
+		StateChangeRecord,
+		StorageError,
+		StorageID,
+		TransactionRecord,
+	},
 };
 use crate::types::{
 	ChainState,
@@ -35,14 +47,161 @@ use crate::types::{
 	StateChange,
 };
 
+/// Database versioning and migrations.
+pub mod migrations;
+/// PostgreSQL storage backend.
+#[cfg(feature = "postgresql")]
+mod postgresql;
 /// Sqlite constants.
 mod sqlite;
 pub mod types;
 
+#[cfg(feature = "postgresql")]
+pub use self::postgresql::PostgresStorage;
+
 /// The number of blocks before taking a snot of the chain state.
 pub const SNAPSHOT_STATE_CHANGE_COUNT: u16 = 500;
 
 /// Storage interface for the chain state.
+pub trait Storage: Send + Sync {
+	/// Create tables if not already created and migrate them to the latest version.
+	fn setup_database(&self) -> Result<()>;
+
+	/// Return the version of the database if it was recorded.
+	fn get_version(&self) -> Result<Option<u32>>;
+
+	/// Record the version of the database.
+	fn set_version(&self, version: u32) -> Result<()>;
+
+	/// Alter the tables and upgrade the serialized records as described by the migration, then
+	/// record its version.
+	fn apply_migration(&self, migration: &'static Migration) -> Result<()>;
+
+	/// Store chain state snapshot.
+	fn store_snapshot(&self, state: ChainState, state_change_id: Option<StorageID>) -> Result<()>;
+
+	/// Return all state changes.
+	fn state_changes(&self) -> Result<Vec<StateChangeRecord>>;
+
+	/// Store a state change.
+	fn store_state_change(&self, state_change: StateChange) -> Result<StorageID>;
+
+	/// Store a list of events.
+	fn store_events(&self, state_change_id: StorageID, events: Vec<Event>) -> Result<()>;
+
+	/// Get the last snapshot prior to a specific state change identifier.
+	fn get_snapshot_before_state_change(
+		&self,
+		state_change_id: StorageID,
+	) -> Result<SnapshotRecord>;
+
+	/// Get the list of state changes in range of ULIDs.
+	fn get_state_changes_in_range(
+		&self,
+		start_state_change: StorageID,
+		end_state_change: StorageID,
+	) -> Result<Vec<StateChangeRecord>>;
+
+	/// Get a state change that contains a balance proof that matches the provided `balance_hash`.
+	fn get_state_change_with_balance_proof_by_balance_hash(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		balance_hash: BalanceHash,
+		recipient: Address,
+	) -> Result<Option<StateChangeRecord>>;
+
+	/// Get a state change that contains a balance proof that matches the provided `locksroot`.
+	fn get_state_change_with_balance_proof_by_locksroot(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		locksroot: Locksroot,
+		recipient: Address,
+	) -> Result<Option<StateChangeRecord>>;
+
+	/// Get an event with a balance proof filtered by the `balance_hash`.
+	fn get_event_with_balance_proof_by_balance_hash(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		balance_hash: BalanceHash,
+		recipient: Address,
+	) -> Result<Option<EventRecord>>;
+
+	/// Get an event with a balance proof filtered by the `locksroot`.
+	fn get_event_with_balance_proof_by_locksroot(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		locksroot: Locksroot,
+		recipient: Address,
+	) -> Result<Option<EventRecord>>;
+
+	/// Return events with timestamps matching the filter.
+	fn get_events_with_timestamps(&self, filter: &EventFilter) -> Result<Vec<EventRecord>>;
+
+	/// Get events of payments with timestamps attached.
+	fn get_events_payment_history_with_timestamps(
+		&self,
+		token_network_address: Option<TokenNetworkAddress>,
+		partner_address: Option<Address>,
+		filter: &EventFilter,
+	) -> Result<Vec<EventRecord>>;
+
+	/// Return the latest event completing the payment `identifier` sent to `target`, either a
+	/// `PaymentSentSuccess` or an `ErrorPaymentSentFailed`.
+	fn get_payment_sent_result(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		target: Address,
+		identifier: PaymentIdentifier,
+	) -> Result<Option<Event>>;
+
+	/// Store a notification.
+	///
+	/// If a notification with the same identifier exists, its content is updated while the
+	/// acknowledged and dismissed flags are kept.
+	fn store_notification(
+		&self,
+		identifier: String,
+		summary: String,
+		body: String,
+		urgency: NotificationUrgency,
+	) -> Result<()>;
+
+	/// Return notifications, optionally including the dismissed ones.
+	fn get_notifications(&self, include_dismissed: bool) -> Result<Vec<NotificationRecord>>;
+
+	/// Mark a notification as acknowledged. Returns false if the notification does not exist.
+	fn acknowledge_notification(&self, identifier: String) -> Result<bool>;
+
+	/// Mark a notification as dismissed. Returns false if the notification does not exist.
+	fn dismiss_notification(&self, identifier: String) -> Result<bool>;
+
+	/// Delete a notification whose condition no longer applies.
+	fn delete_notification(&self, identifier: String) -> Result<()>;
+
+	/// Store a transaction sent on-chain, replacing the record of the same nonce.
+	fn store_transaction(&self, transaction: &TransactionRecord) -> Result<()>;
+
+	/// Return the transactions which were not mined yet, ordered by nonce.
+	fn get_pending_transactions(&self) -> Result<Vec<TransactionRecord>>;
+
+	/// Delete the transaction of `nonce` once it is mined.
+	fn delete_transaction(&self, nonce: U256) -> Result<()>;
+
+	/// Delete the snapshots exceeding the retention policy along with the state changes and
+	/// events preceding the oldest kept snapshot.
+	///
+	/// State changes and events holding a balance proof of one of `channels` are kept since they
+	/// are needed to settle disputes on-chain. The snapshot preceding the latest balance proof of
+	/// each of `channels` is kept as well along with the state changes following it, which
+	/// restore the channel to unlock its pending locks.
+	fn compact(
+		&self,
+		policy: &RetentionPolicy,
+		channels: Vec<CanonicalIdentifier>,
+	) -> Result<CompactionStats>;
+}
+
+/// SQLite implementation of `Storage`.
 pub struct StateStorage {
 	/// The rusqlite connection
 	conn: Mutex<Connection>,
@@ -54,13 +213,161 @@ impl StateStorage {
 		Self { conn: Mutex::new(conn) }
 	}
 
-	/// Create tables if not already created.
-	pub fn setup_database(&self) -> Result<()> {
+	/// Get a state change based on data field attributes.
+	pub fn get_latest_state_change_by_data_field(
+		&self,
+		criteria: Vec<(String, String)>,
+	) -> Result<Option<StateChangeRecord>> {
+		let mut where_cond = "".to_owned();
+		for (i, (field, _)) in criteria.iter().enumerate() {
+			where_cond.push_str(&format!("{}=?{}", field, i + 1));
+		}
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn
+			.prepare(&format!(
+				"SELECT identifier, data FROM state_changes
+                    WHERE {}
+                    ORDER BY identifier DESC
+                    LIMIT 1",
+				where_cond
+			))
+			.map_err(StorageError::Sql)?;
+
+		let query_values: Vec<_> = criteria.iter().map(|(_, v)| v as &dyn ToSql).collect();
+
+		let mut rows = stmt.query(query_values.as_slice()).map_err(StorageError::Sql)?;
+
+		let row = match rows.next().map_err(StorageError::Sql)? {
+			Some(row) => row,
+			None => return Err(StorageError::Other("State change not found")),
+		};
+		let identifier: String = row.get(0).map_err(StorageError::Sql)?;
+		let data: String = row.get(1).map_err(StorageError::Sql)?;
+		Ok(Some(StateChangeRecord {
+			identifier: identifier.try_into()?,
+			data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+		}))
+	}
+
+	/// Get the latest event filtered by criteria of data field attributes.
+	pub fn get_latest_event_by_data_field(
+		&self,
+		criteria: Vec<(String, String)>,
+	) -> Result<Option<EventRecord>> {
+		let mut where_cond = "".to_owned();
+		for (i, (field, _)) in criteria.iter().enumerate() {
+			where_cond.push_str(&format!("{}=?{}", field, i + 1));
+		}
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn
+			.prepare(&format!(
+				"SELECT identifier, source_statechange_id, data FROM state_events
+                    WHERE {}
+                    ORDER BY identifier DESC
+                    LIMIT 1",
+				where_cond
+			))
+			.map_err(StorageError::Sql)?;
+
+		let query_values: Vec<_> = criteria.iter().map(|(_, v)| v as &dyn ToSql).collect();
+
+		let mut rows = stmt.query(query_values.as_slice()).map_err(StorageError::Sql)?;
+
+		let row = match rows.next().map_err(StorageError::Sql)? {
+			Some(row) => row,
+			None => return Ok(None),
+		};
+		let identifier: StorageID =
+			row.get::<usize, String>(0).map_err(StorageError::Sql)?.try_into()?;
+		let state_change_identifier: StorageID =
+			row.get::<usize, String>(1).map_err(StorageError::Sql)?.try_into()?;
+		let data: String = row.get(2).map_err(StorageError::Sql)?;
+		Ok(Some(EventRecord {
+			identifier,
+			state_change_identifier,
+			data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+			timestamp: Utc::now().naive_local(),
+		}))
+	}
+
+	/// Query events matching both the given SQL conditions and the filter.
+	///
+	/// `conditions` reference `values` through numbered parameters.
+	fn query_events_with_timestamps(
+		&self,
+		mut conditions: Vec<String>,
+		mut values: Vec<Box<dyn ToSql>>,
+		filter: &EventFilter,
+	) -> Result<Vec<EventRecord>> {
+		if let Some(after) = filter.after {
+			values.push(Box::new(after.to_string()));
+			conditions.push(format!("identifier > ?{}", values.len()));
+		}
+		if let Some(from) = filter.from {
+			values.push(Box::new(from));
+			conditions.push(format!("timestamp >= ?{}", values.len()));
+		}
+		if let Some(to) = filter.to {
+			values.push(Box::new(to));
+			conditions.push(format!("timestamp <= ?{}", values.len()));
+		}
+		if !filter.event_types.is_empty() {
+			let mut placeholders = vec![];
+			for event_type in &filter.event_types {
+				values.push(Box::new(event_type.clone()));
+				placeholders.push(format!("?{}", values.len()));
+			}
+			conditions
+				.push(format!("json_extract(data, '$.type') IN ({})", placeholders.join(", ")));
+		}
+
+		let where_cond =
+			if conditions.is_empty() { "1".to_owned() } else { conditions.join(" AND ") };
+		let limit = filter.limit.map(|limit| limit as i64).unwrap_or(-1);
+		values.push(Box::new(limit));
+
+		let query = format!(
+			"SELECT identifier, data, source_statechange_id, timestamp FROM state_events
+                WHERE {}
+                ORDER BY identifier ASC
+                LIMIT ?{}",
+			where_cond,
+			values.len()
+		);
+
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn.prepare(&query).map_err(StorageError::Sql)?;
+		let params: Vec<&dyn ToSql> = values.iter().map(|value| value.as_ref()).collect();
+		let mut rows = stmt.query(params.as_slice()).map_err(StorageError::Sql)?;
+
+		let mut events = vec![];
+
+		while let Ok(Some(row)) = rows.next() {
+			let identifier: String = row.get(0).map_err(StorageError::Sql)?;
+			let data: String = row.get(1).map_err(StorageError::Sql)?;
+			let state_change_identifier: StorageID =
+				row.get::<usize, String>(2).map_err(StorageError::Sql)?.try_into()?;
+			let timestamp: NaiveDateTime = row.get(3).map_err(StorageError::Sql)?;
+
+			events.push(EventRecord {
+				identifier: identifier.try_into()?,
+				data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+				state_change_identifier,
+				timestamp,
+			})
+		}
+
+		Ok(events)
+	}
+}
+
+impl Storage for StateStorage {
+	fn setup_database(&self) -> Result<()> {
 		let setup_db_sql = format!(
 			"
 			PRAGMA foreign_keys=off;
 			BEGIN TRANSACTION;
-			{}{}{}{}{}
+			{}{}{}{}{}{}{}
 			COMMIT;
 			PRAGMA foreign_keys=on;
 			",
@@ -69,6 +376,8 @@ impl StateStorage {
 			sqlite::DB_CREATE_SNAPSHOT,
 			sqlite::DB_CREATE_STATE_EVENTS,
 			sqlite::DB_CREATE_RUNS,
+			sqlite::DB_CREATE_NOTIFICATIONS,
+			sqlite::DB_CREATE_TRANSACTIONS,
 		);
 		self.conn
 			.lock()
@@ -76,15 +385,58 @@ impl StateStorage {
 			.execute_batch(&setup_db_sql)
 			.map_err(StorageError::Sql)?;
 
+		migrations::migrate(self, migrations::MIGRATIONS)
+	}
+
+	fn get_version(&self) -> Result<Option<u32>> {
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn
+			.prepare("SELECT value FROM settings WHERE name='version'")
+			.map_err(StorageError::Sql)?;
+		let mut rows = stmt.query([]).map_err(StorageError::Sql)?;
+
+		let row = match rows.next().map_err(StorageError::Sql)? {
+			Some(row) => row,
+			None => return Ok(None),
+		};
+		let version: String = row.get(0).map_err(StorageError::Sql)?;
+		version
+			.parse()
+			.map(Some)
+			.map_err(|_| StorageError::Other("Invalid database version"))
+	}
+
+	fn set_version(&self, version: u32) -> Result<()> {
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(sqlite::DB_SET_VERSION, params![version.to_string()])
+			.map_err(StorageError::Sql)?;
 		Ok(())
 	}
 
-	/// Store chain state snapshot.
-	pub fn store_snapshot(
-		&self,
-		state: ChainState,
-		state_change_id: Option<StorageID>,
-	) -> Result<()> {
+	fn apply_migration(&self, migration: &'static Migration) -> Result<()> {
+		let mut conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let transaction = conn.transaction().map_err(StorageError::Sql)?;
+
+		transaction.execute_batch(migration.sqlite).map_err(StorageError::Sql)?;
+		for (table, upgrade) in [
+			("state_changes", migration.state_change),
+			("state_events", migration.event),
+			("state_snapshot", migration.snapshot),
+		] {
+			if let Some(upgrade) = upgrade {
+				upgrade_records(&transaction, table, upgrade)?;
+			}
+		}
+		transaction
+			.execute(sqlite::DB_SET_VERSION, params![migration.version.to_string()])
+			.map_err(StorageError::Sql)?;
+
+		transaction.commit().map_err(StorageError::Sql)
+	}
+
+	fn store_snapshot(&self, state: ChainState, state_change_id: Option<StorageID>) -> Result<()> {
 		let serialized_state =
 			serde_json::to_string(&state).map_err(StorageError::SerializationError)?;
 		let sql = "
@@ -108,8 +460,7 @@ impl StateStorage {
 		Ok(())
 	}
 
-	/// Return all state changes.
-	pub fn state_changes(&self) -> Result<Vec<StateChangeRecord>> {
+	fn state_changes(&self) -> Result<Vec<StateChangeRecord>> {
 		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
 		let mut stmt = conn
 			.prepare("SELECT identifier, data FROM state_changes")
@@ -131,8 +482,7 @@ impl StateStorage {
 		Ok(state_changes)
 	}
 
-	/// Store a state change.
-	pub fn store_state_change(&self, state_change: StateChange) -> Result<StorageID> {
+	fn store_state_change(&self, state_change: StateChange) -> Result<StorageID> {
 		let serialized_state_change =
 			serde_json::to_string(&state_change).map_err(StorageError::SerializationError)?;
 		let sql = "INSERT INTO state_changes(identifier, data) VALUES(?1, ?2)".to_owned();
@@ -145,8 +495,7 @@ impl StateStorage {
 		Ok(ulid.into())
 	}
 
-	/// Store a list of events.
-	pub fn store_events(&self, state_change_id: StorageID, events: Vec<Event>) -> Result<()> {
+	fn store_events(&self, state_change_id: StorageID, events: Vec<Event>) -> Result<()> {
 		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
 
 		for event in events {
@@ -168,8 +517,7 @@ impl StateStorage {
 		Ok(())
 	}
 
-	/// Get the last snapshot prior to a specific state change identifier.
-	pub fn get_snapshot_before_state_change(
+	fn get_snapshot_before_state_change(
 		&self,
 		state_change_id: StorageID,
 	) -> Result<SnapshotRecord> {
@@ -199,8 +547,7 @@ impl StateStorage {
 		})
 	}
 
-	/// Get the list of state changes in range of ULIDs.
-	pub fn get_state_changes_in_range(
+	fn get_state_changes_in_range(
 		&self,
 		start_state_change: StorageID,
 		end_state_change: StorageID,
@@ -234,44 +581,7 @@ impl StateStorage {
 		Ok(state_changes)
 	}
 
-	/// Get a state change based on data field attributes.
-	pub fn get_latest_state_change_by_data_field(
-		&self,
-		criteria: Vec<(String, String)>,
-	) -> Result<Option<StateChangeRecord>> {
-		let mut where_cond = "".to_owned();
-		for (i, (field, _)) in criteria.iter().enumerate() {
-			where_cond.push_str(&format!("{}=?{}", field, i + 1));
-		}
-		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
-		let mut stmt = conn
-			.prepare(&format!(
-				"SELECT identifier, data FROM state_changes
-                    WHERE {}
-                    ORDER BY identifier DESC
-                    LIMIT 1",
-				where_cond
-			))
-			.map_err(StorageError::Sql)?;
-
-		let query_values: Vec<_> = criteria.iter().map(|(_, v)| v as &dyn ToSql).collect();
-
-		let mut rows = stmt.query(query_values.as_slice()).map_err(StorageError::Sql)?;
-
-		let row = match rows.next().map_err(StorageError::Sql)? {
-			Some(row) => row,
-			None => return Err(StorageError::Other("State change not found")),
-		};
-		let identifier: String = row.get(0).map_err(StorageError::Sql)?;
-		let data: String = row.get(1).map_err(StorageError::Sql)?;
-		Ok(Some(StateChangeRecord {
-			identifier: identifier.try_into()?,
-			data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
-		}))
-	}
-
-	/// Get a state change that contains a balance proof that matches the provided `balance_hash`.
-	pub fn get_state_change_with_balance_proof_by_balance_hash(
+	fn get_state_change_with_balance_proof_by_balance_hash(
 		&self,
 		canonical_identifier: CanonicalIdentifier,
 		balance_hash: BalanceHash,
@@ -335,8 +645,7 @@ impl StateStorage {
 		}))
 	}
 
-	/// Get a state change that contains a balance proof that matches the provided `locksroot`.
-	pub fn get_state_change_with_balance_proof_by_locksroot(
+	fn get_state_change_with_balance_proof_by_locksroot(
 		&self,
 		canonical_identifier: CanonicalIdentifier,
 		locksroot: Locksroot,
@@ -400,49 +709,7 @@ impl StateStorage {
 		}))
 	}
 
-	/// Get the latest event filtered by criteria of data field attributes.
-	pub fn get_latest_event_by_data_field(
-		&self,
-		criteria: Vec<(String, String)>,
-	) -> Result<Option<EventRecord>> {
-		let mut where_cond = "".to_owned();
-		for (i, (field, _)) in criteria.iter().enumerate() {
-			where_cond.push_str(&format!("{}=?{}", field, i + 1));
-		}
-		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
-		let mut stmt = conn
-			.prepare(&format!(
-				"SELECT identifier, source_statechange_id, data FROM state_events
-                    WHERE {}
-                    ORDER BY identifier DESC
-                    LIMIT 1",
-				where_cond
-			))
-			.map_err(StorageError::Sql)?;
-
-		let query_values: Vec<_> = criteria.iter().map(|(_, v)| v as &dyn ToSql).collect();
-
-		let mut rows = stmt.query(query_values.as_slice()).map_err(StorageError::Sql)?;
-
-		let row = match rows.next().map_err(StorageError::Sql)? {
-			Some(row) => row,
-			None => return Ok(None),
-		};
-		let identifier: StorageID =
-			row.get::<usize, String>(0).map_err(StorageError::Sql)?.try_into()?;
-		let state_change_identifier: StorageID =
-			row.get::<usize, String>(1).map_err(StorageError::Sql)?.try_into()?;
-		let data: String = row.get(2).map_err(StorageError::Sql)?;
-		Ok(Some(EventRecord {
-			identifier,
-			state_change_identifier,
-			data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
-			timestamp: Utc::now().naive_local(),
-		}))
-	}
-
-	/// Get an event with a balance proof filtered by the `balance_hash`.
-	pub fn get_event_with_balance_proof_by_balance_hash(
+	fn get_event_with_balance_proof_by_balance_hash(
 		&self,
 		canonical_identifier: CanonicalIdentifier,
 		balance_hash: BalanceHash,
@@ -537,8 +804,7 @@ impl StateStorage {
 		}))
 	}
 
-	/// Get an event with a balance proof filtered by the `locksroot`.
-	pub fn get_event_with_balance_proof_by_locksroot(
+	fn get_event_with_balance_proof_by_locksroot(
 		&self,
 		canonical_identifier: CanonicalIdentifier,
 		locksroot: Locksroot,
@@ -633,143 +899,393 @@ impl StateStorage {
 		}))
 	}
 
-	/// Return events with timestamps.
-	pub fn get_events_with_timestamps(&self) -> Result<Vec<EventRecord>> {
-		let query = "
-            SELECT
-                identifier, data, source_statechange_id, timestamp
-            FROM
-                state_events
-            ORDER BY identifier ASC
-        ";
+	fn get_events_with_timestamps(&self, filter: &EventFilter) -> Result<Vec<EventRecord>> {
+		self.query_events_with_timestamps(vec![], vec![], filter)
+	}
 
-		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
-		let mut stmt = conn.prepare(query).map_err(StorageError::Sql)?;
-		let mut rows = stmt.query(params![]).map_err(StorageError::Sql)?;
+	fn get_events_payment_history_with_timestamps(
+		&self,
+		token_network_address: Option<TokenNetworkAddress>,
+		partner_address: Option<Address>,
+		filter: &EventFilter,
+	) -> Result<Vec<EventRecord>> {
+		let mut conditions = vec![
+			"json_extract(data, '$.type') IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')".to_owned(),
+		];
+		let mut values: Vec<Box<dyn ToSql>> = vec![];
+
+		if let Some(token_network_address) = token_network_address {
+			values.push(Box::new(format!("{:#x}", token_network_address)));
+			conditions.push(format!(
+				"json_extract(data, '$.token_network_address') LIKE ?{}",
+				values.len()
+			));
+		}
+		if let Some(partner_address) = partner_address {
+			values.push(Box::new(format!("{:#x}", partner_address)));
+			conditions.push(format!(
+				"(json_extract(data, '$.target') LIKE ?{0} OR json_extract(data, '$.initiator') LIKE ?{0})",
+				values.len()
+			));
+		}
 
-		let mut events = vec![];
+		self.query_events_with_timestamps(conditions, values, filter)
+	}
 
-		while let Ok(Some(row)) = rows.next() {
-			let identifier: String = row.get(0).map_err(StorageError::Sql)?;
-			let data: String = row.get(1).map_err(StorageError::Sql)?;
-			let state_change_identifier: StorageID =
-				row.get::<usize, String>(2).map_err(StorageError::Sql)?.try_into()?;
-			let timestamp: NaiveDateTime = row.get(3).map_err(StorageError::Sql)?;
+	fn get_payment_sent_result(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		target: Address,
+		identifier: PaymentIdentifier,
+	) -> Result<Option<Event>> {
+		let conditions = vec![
+			"json_extract(data, '$.type') IN ('ErrorPaymentSentFailed', 'PaymentSentSuccess')"
+				.to_owned(),
+			"json_extract(data, '$.token_network_address') LIKE ?1".to_owned(),
+			"json_extract(data, '$.target') LIKE ?2".to_owned(),
+		];
+		let values: Vec<Box<dyn ToSql>> = vec![
+			Box::new(format!("{:#x}", token_network_address)),
+			Box::new(format!("{:#x}", target)),
+		];
 
-			events.push(EventRecord {
-				identifier: identifier.try_into()?,
-				data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
-				state_change_identifier,
-				timestamp,
+		let events =
+			self.query_events_with_timestamps(conditions, values, &EventFilter::default())?;
+		Ok(payment_sent_result(events, identifier))
+	}
+
+	fn store_notification(
+		&self,
+		identifier: String,
+		summary: String,
+		body: String,
+		urgency: NotificationUrgency,
+	) -> Result<()> {
+		let sql = "
+			INSERT INTO notifications(identifier, summary, body, urgency)
+			VALUES(?1, ?2, ?3, ?4)
+			ON CONFLICT(identifier) DO UPDATE SET
+				summary=excluded.summary, body=excluded.body, urgency=excluded.urgency";
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(sql, params![identifier, summary, body, urgency.to_string()])
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+
+	fn get_notifications(&self, include_dismissed: bool) -> Result<Vec<NotificationRecord>> {
+		let sql = "
+			SELECT identifier, summary, body, urgency, acknowledged, dismissed, timestamp
+			FROM notifications
+			WHERE dismissed=0 OR ?1
+			ORDER BY timestamp ASC";
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn.prepare(sql).map_err(StorageError::Sql)?;
+		let mut rows = stmt.query(params![include_dismissed]).map_err(StorageError::Sql)?;
+
+		let mut notifications = vec![];
+		while let Ok(Some(row)) = rows.next() {
+			let urgency: String = row.get(3).map_err(StorageError::Sql)?;
+			notifications.push(NotificationRecord {
+				identifier: row.get(0).map_err(StorageError::Sql)?,
+				summary: row.get(1).map_err(StorageError::Sql)?,
+				body: row.get(2).map_err(StorageError::Sql)?,
+				urgency: urgency.try_into()?,
+				acknowledged: row.get(4).map_err(StorageError::Sql)?,
+				dismissed: row.get(5).map_err(StorageError::Sql)?,
+				timestamp: row.get(6).map_err(StorageError::Sql)?,
 			})
 		}
 
-		Ok(events)
+		Ok(notifications)
 	}
 
-	/// Get events of payments with timestamps attached.
-	pub fn get_events_payment_history_with_timestamps(
+	fn acknowledge_notification(&self, identifier: String) -> Result<bool> {
+		let updated = self
+			.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(
+				"UPDATE notifications SET acknowledged=1 WHERE identifier=?1",
+				params![identifier],
+			)
+			.map_err(StorageError::Sql)?;
+		Ok(updated > 0)
+	}
+
+	fn dismiss_notification(&self, identifier: String) -> Result<bool> {
+		let updated = self
+			.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(
+				"UPDATE notifications SET acknowledged=1, dismissed=1 WHERE identifier=?1",
+				params![identifier],
+			)
+			.map_err(StorageError::Sql)?;
+		Ok(updated > 0)
+	}
+
+	fn delete_notification(&self, identifier: String) -> Result<()> {
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute("DELETE FROM notifications WHERE identifier=?1", params![identifier])
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+
+	fn store_transaction(&self, transaction: &TransactionRecord) -> Result<()> {
+		let serialized_transaction =
+			serde_json::to_string(transaction).map_err(StorageError::SerializationError)?;
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute(
+				sqlite::DB_STORE_TRANSACTION,
+				params![transaction.nonce.as_u64() as i64, serialized_transaction],
+			)
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+
+	fn get_pending_transactions(&self) -> Result<Vec<TransactionRecord>> {
+		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let mut stmt = conn
+			.prepare("SELECT data FROM transactions ORDER BY nonce ASC")
+			.map_err(StorageError::Sql)?;
+		let mut rows = stmt.query([]).map_err(StorageError::Sql)?;
+
+		let mut transactions = vec![];
+		while let Ok(Some(row)) = rows.next() {
+			let data: String = row.get(0).map_err(StorageError::Sql)?;
+			transactions
+				.push(serde_json::from_str(&data).map_err(StorageError::SerializationError)?);
+		}
+
+		Ok(transactions)
+	}
+
+	fn delete_transaction(&self, nonce: U256) -> Result<()> {
+		self.conn
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.execute("DELETE FROM transactions WHERE nonce=?1", params![nonce.as_u64() as i64])
+			.map_err(StorageError::Sql)?;
+		Ok(())
+	}
+
+	fn compact(
 		&self,
-		token_network_address: Option<TokenNetworkAddress>,
-		partner_address: Option<Address>,
-	) -> Result<Vec<EventRecord>> {
-		let token_network_address = token_network_address.map(|a| a.to_string());
-		let partner_address = partner_address.map(|a| a.to_string());
-
-		let mut params: Vec<&dyn ToSql> = vec![];
-
-		let binding = (token_network_address, partner_address);
-		let query = match binding {
-			(Some(ref token_network_address), Some(ref partner_address)) => {
-				let query = "
-                        SELECT
-                            identifier, data, source_statechange_id, timestamp
-                        FROM
-                            state_events
-                        WHERE
-                            json_extract(data, '$.type') IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')
-                        AND
-                            json_extract(data, '$.token_network_address') LIKE ?1
-                        AND
-                            (
-                                json_extract(data, '$.target') LIKE ?2
-                                OR
-                                json_extract(data, '$.initiator') LIKE ?2
-                            )
-                        ORDER BY identifier ASC";
-
-				params.push(token_network_address);
-				params.push(partner_address);
-				query
-			},
-			(Some(ref token_network_address), None) => {
-				let query = "
-                        SELECT
-                            identifier, data, source_statechange_id, timestamp
-                        FROM
-                            state_events
-                        WHERE
-                            json_extract(data, '$.type') IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')
-                        AND
-                            json_extract(data, '$.token_network_address') LIKE ?1
-                        ORDER BY identifier ASC
-                        ";
-				params.push(token_network_address);
-				query
-			},
-			(None, Some(ref partner_address)) => {
-				let query = "
-                        SELECT
-                            identifier, data, source_statechange_id, timestamp
-                        FROM
-                            state_events
-                        WHERE
-                            json_extract(data, '$.type') IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')
-                        AND
-                            (
-                            json_extract(data, '$.target') LIKE ?1
-                            OR
-                            json_extract(data, '$.initiator') LIKE ?1
-                            )
-                        ORDER BY identifier ASC
-                        ";
-				params.push(partner_address);
-				query
-			},
-			(None, None) => {
-				"
-                    SELECT
-                        identifier, data, source_statechange_id, timestamp
-                    FROM
-                        state_events
-                    WHERE
-                        json_extract(data, '$.type') IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')
-                    ORDER BY identifier ASC
-                "
-			},
+		policy: &RetentionPolicy,
+		channels: Vec<CanonicalIdentifier>,
+	) -> Result<CompactionStats> {
+		let channels = channels
+			.iter()
+			.map(serde_json::to_value)
+			.collect::<std::result::Result<Vec<_>, _>>()
+			.map_err(StorageError::SerializationError)?;
+
+		let mut conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
+		let transaction = conn.transaction().map_err(StorageError::Sql)?;
+
+		let mut latest_balance_proofs = LatestBalanceProofs::new(&channels);
+		for (table, state_change_column) in
+			[("state_changes", "identifier"), ("state_events", "source_statechange_id")]
+		{
+			let mut stmt = transaction
+				.prepare(&format!(
+					"SELECT {}, data FROM {}
+					WHERE json_extract(data, '$.balance_proof') IS NOT NULL
+					OR json_extract(data, '$.transfer.balance_proof') IS NOT NULL",
+					state_change_column, table
+				))
+				.map_err(StorageError::Sql)?;
+			let mut rows = stmt.query([]).map_err(StorageError::Sql)?;
+			while let Some(row) = rows.next().map_err(StorageError::Sql)? {
+				let state_change_id: String = row.get(0).map_err(StorageError::Sql)?;
+				let data: String = row.get(1).map_err(StorageError::Sql)?;
+				let data: Value =
+					serde_json::from_str(&data).map_err(StorageError::SerializationError)?;
+				latest_balance_proofs.observe(state_change_id, &data);
+			}
+		}
+
+		let mut oldest_kept_snapshot = match query_snapshot(
+			&transaction,
+			"SELECT identifier, statechange_id FROM state_snapshot
+			ORDER BY identifier DESC
+			LIMIT 1 OFFSET ?1",
+			&policy.snapshots_to_keep.saturating_sub(1),
+		)? {
+			Some(snapshot) => snapshot,
+			None => return Ok(CompactionStats::default()),
 		};
+		if let Some(state_change_id) = latest_balance_proofs.oldest() {
+			// Without a snapshot to restore the channel from, nothing can be deleted.
+			let restorable_snapshot = match query_snapshot(
+				&transaction,
+				"SELECT identifier, statechange_id FROM state_snapshot
+				WHERE statechange_id <= ?1
+				ORDER BY identifier DESC
+				LIMIT 1",
+				&state_change_id,
+			)? {
+				Some(snapshot) => snapshot,
+				None => return Ok(CompactionStats::default()),
+			};
+			oldest_kept_snapshot = oldest_kept_snapshot.min(restorable_snapshot);
+		}
+		let (snapshot_id, state_change_id) = oldest_kept_snapshot;
 
-		let conn = self.conn.lock().map_err(|_| StorageError::CannotLock)?;
-		let mut stmt = conn.prepare(query).map_err(StorageError::Sql)?;
+		let snapshots = transaction
+			.execute("DELETE FROM state_snapshot WHERE identifier < ?1", params![snapshot_id])
+			.map_err(StorageError::Sql)?;
+		let events = delete_records(
+			&transaction,
+			"state_events",
+			sqlite::DB_EVENTS_BEFORE,
+			&state_change_id,
+			&channels,
+		)?;
+		let state_changes = delete_records(
+			&transaction,
+			"state_changes",
+			sqlite::DB_STATE_CHANGES_BEFORE,
+			&state_change_id,
+			&channels,
+		)?;
+
+		transaction.commit().map_err(StorageError::Sql)?;
+
+		Ok(CompactionStats { snapshots, state_changes, events })
+	}
+}
 
-		let mut rows = stmt.query(params.as_slice()).map_err(StorageError::Sql)?;
+/// Return the identifier and the state change identifier of the snapshot selected by `query`,
+/// which takes `param` as its only parameter.
+fn query_snapshot(
+	conn: &Connection,
+	query: &str,
+	param: &dyn ToSql,
+) -> Result<Option<(String, String)>> {
+	let mut stmt = conn.prepare(query).map_err(StorageError::Sql)?;
+	let mut rows = stmt.query(&[param][..]).map_err(StorageError::Sql)?;
+	match rows.next().map_err(StorageError::Sql)? {
+		Some(row) => Ok(Some((
+			row.get(0).map_err(StorageError::Sql)?,
+			row.get(1).map_err(StorageError::Sql)?,
+		))),
+		None => Ok(None),
+	}
+}
 
-		let mut events = vec![];
+/// Rewrite the serialized records of `table` using `upgrade`.
+fn upgrade_records(conn: &Connection, table: &str, upgrade: fn(Value) -> Value) -> Result<()> {
+	let mut stmt = conn
+		.prepare(&format!("SELECT identifier, data FROM {}", table))
+		.map_err(StorageError::Sql)?;
+	let mut rows = stmt.query([]).map_err(StorageError::Sql)?;
 
-		while let Ok(Some(row)) = rows.next() {
-			let identifier: String = row.get(0).map_err(StorageError::Sql)?;
-			let data: String = row.get(1).map_err(StorageError::Sql)?;
-			let state_change_identifier: StorageID =
-				row.get::<usize, String>(2).map_err(StorageError::Sql)?.try_into()?;
-			let timestamp: NaiveDateTime = row.get(3).map_err(StorageError::Sql)?;
-			events.push(EventRecord {
-				identifier: identifier.try_into()?,
-				data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
-				state_change_identifier,
-				timestamp,
-			})
+	let mut records = vec![];
+	while let Some(row) = rows.next().map_err(StorageError::Sql)? {
+		let identifier: String = row.get(0).map_err(StorageError::Sql)?;
+		let data: String = row.get(1).map_err(StorageError::Sql)?;
+		let data = upgrade(serde_json::from_str(&data).map_err(StorageError::SerializationError)?);
+		records.push((
+			identifier,
+			serde_json::to_string(&data).map_err(StorageError::SerializationError)?,
+		));
+	}
+
+	for (identifier, data) in records {
+		conn.execute(
+			&format!("UPDATE {} SET data=?1 WHERE identifier=?2", table),
+			params![data, identifier],
+		)
+		.map_err(StorageError::Sql)?;
+	}
+	Ok(())
+}
+
+/// Delete the records of `table` matching `condition` which hold no balance proof of `channels`.
+///
+/// `condition` references the state change identifier `before` as its first parameter.
+fn delete_records(
+	conn: &Connection,
+	table: &str,
+	condition: &str,
+	before: &str,
+	channels: &[Value],
+) -> Result<usize> {
+	let mut stmt = conn
+		.prepare(&format!("SELECT identifier, data FROM {} WHERE {}", table, condition))
+		.map_err(StorageError::Sql)?;
+	let mut rows = stmt.query(params![before]).map_err(StorageError::Sql)?;
+
+	let mut obsolete = vec![];
+	while let Some(row) = rows.next().map_err(StorageError::Sql)? {
+		let identifier: String = row.get(0).map_err(StorageError::Sql)?;
+		let data: String = row.get(1).map_err(StorageError::Sql)?;
+		let data: Value = serde_json::from_str(&data).map_err(StorageError::SerializationError)?;
+		if !holds_balance_proof(&data, channels) {
+			obsolete.push(identifier);
 		}
+	}
 
-		Ok(events)
+	for identifier in obsolete.iter() {
+		conn.execute(&format!("DELETE FROM {} WHERE identifier=?1", table), params![identifier])
+			.map_err(StorageError::Sql)?;
+	}
+	Ok(obsolete.len())
+}
+
+/// The latest of the `events` completing the payment `identifier`.
+fn payment_sent_result(events: Vec<EventRecord>, identifier: PaymentIdentifier) -> Option<Event> {
+	events.into_iter().rev().map(|record| record.data).find(|event| match event {
+		Event::PaymentSentSuccess(inner) => inner.identifier == identifier,
+		Event::ErrorPaymentSentFailed(inner) => inner.identifier == identifier,
+		_ => false,
+	})
+}
+
+/// Whether a serialized state change or event holds a balance proof of one of the serialized
+/// canonical identifiers.
+fn holds_balance_proof(data: &Value, channels: &[Value]) -> bool {
+	[&data["balance_proof"], &data["transfer"]["balance_proof"]]
+		.iter()
+		.any(|balance_proof| channels.contains(&balance_proof["canonical_identifier"]))
+}
+
+/// The latest state change holding a balance proof of each channel, the chain state preceding
+/// it is restored to unlock the pending locks of the channel on-chain.
+struct LatestBalanceProofs<'a> {
+	/// The serialized canonical identifiers of the channels.
+	channels: &'a [Value],
+	/// The identifier of the latest state change of each channel, in the order of `channels`.
+	latest: Vec<Option<String>>,
+}
+
+impl<'a> LatestBalanceProofs<'a> {
+	/// Create an instance of `LatestBalanceProofs`.
+	fn new(channels: &'a [Value]) -> Self {
+		Self { channels, latest: vec![None; channels.len()] }
+	}
+
+	/// Record the serialized state change or event `data`, produced by `state_change_id`.
+	fn observe(&mut self, state_change_id: String, data: &Value) {
+		for (channel, latest) in self.channels.iter().zip(self.latest.iter_mut()) {
+			if holds_balance_proof(data, std::slice::from_ref(channel)) &&
+				latest.as_ref().map_or(true, |latest| *latest < state_change_id)
+			{
+				*latest = Some(state_change_id.clone());
+			}
+		}
+	}
+
+	/// The oldest state change a channel has to be restored from.
+	fn oldest(&self) -> Option<String> {
+		self.latest.iter().flatten().min().cloned()
 	}
 }
@@ -0,0 +1,1014 @@
+#![warn(clippy::missing_docs_in_private_items)]
+
+use std::{
+	convert::TryInto,
+	sync::{
+		mpsc,
+		Mutex,
+	},
+	thread,
+};
+
+use chrono::{
+	NaiveDateTime,
+	Utc,
+};
+use postgres::{
+	types::ToSql,
+	Client,
+	NoTls,
+	Row,
+	Transaction,
+};
+use raiden_primitives::types::{
+	Address,
+	BalanceHash,
+	CanonicalIdentifier,
+	Locksroot,
+	PaymentIdentifier,
+	TokenNetworkAddress,
+	U256,
+};
+use serde_json::Value;
+use ulid::Ulid;
+
+use super::{
+	holds_balance_proof,
+	migrations::{
+		self,
+		Migration,
+	},
+	payment_sent_result,
+	types::{
+		CompactionStats,
+		EventFilter,
+		EventRecord,
+		NotificationRecord,
+		NotificationUrgency,
+		Result,
+		RetentionPolicy,
+		SnapshotRecord,
+		StateChangeRecord,
+		StorageError,
+		StorageID,
+		TransactionRecord,
+	},
+	LatestBalanceProofs,
+	Storage,
+};
+use crate::types::{
+	ChainState,
+	Event,
+	StateChange,
+};
+
+/// Create settings table SQL.
+const DB_CREATE_SETTINGS: &str = "
+CREATE TABLE IF NOT EXISTS settings (
+    name VARCHAR(24) PRIMARY KEY NOT NULL,
+    value TEXT
+);
+";
+
+/// Create state changes table SQL.
+const DB_CREATE_STATE_CHANGES: &str = r#"
+CREATE TABLE IF NOT EXISTS state_changes (
+    identifier TEXT COLLATE "C" PRIMARY KEY NOT NULL,
+    data JSONB,
+    timestamp TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') NOT NULL
+);
+"#;
+
+/// Create snapshots table SQL.
+const DB_CREATE_SNAPSHOT: &str = r#"
+CREATE TABLE IF NOT EXISTS state_snapshot (
+    identifier TEXT COLLATE "C" PRIMARY KEY NOT NULL,
+    statechange_id TEXT COLLATE "C" UNIQUE,
+    statechange_qty INTEGER,
+    data JSONB,
+    timestamp TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') NOT NULL,
+    FOREIGN KEY(statechange_id) REFERENCES state_changes(identifier)
+);
+"#;
+
+/// Create events table SQL.
+const DB_CREATE_STATE_EVENTS: &str = r#"
+CREATE TABLE IF NOT EXISTS state_events (
+    identifier TEXT COLLATE "C" PRIMARY KEY NOT NULL,
+    source_statechange_id TEXT COLLATE "C" NOT NULL,
+    data JSONB,
+    timestamp TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') NOT NULL,
+    FOREIGN KEY(source_statechange_id) REFERENCES state_changes(identifier)
+);
+"#;
+
+/// Create runs table SQL.
+const DB_CREATE_RUNS: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    started_at TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') PRIMARY KEY NOT NULL,
+    raiden_version TEXT NOT NULL
+);
+";
+
+/// Create notifications table SQL.
+const DB_CREATE_NOTIFICATIONS: &str = "
+CREATE TABLE IF NOT EXISTS notifications (
+    identifier TEXT PRIMARY KEY NOT NULL,
+    summary TEXT NOT NULL,
+    body TEXT NOT NULL,
+    urgency TEXT NOT NULL,
+    acknowledged BOOLEAN DEFAULT(FALSE) NOT NULL,
+    dismissed BOOLEAN DEFAULT(FALSE) NOT NULL,
+    timestamp TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') NOT NULL
+);
+";
+
+/// Create table of the transactions which were not mined yet SQL.
+const DB_CREATE_TRANSACTIONS: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    nonce BIGINT PRIMARY KEY NOT NULL,
+    data JSONB,
+    timestamp TIMESTAMP DEFAULT(NOW() AT TIME ZONE 'UTC') NOT NULL
+);
+";
+
+/// Record the database version SQL.
+const DB_SET_VERSION: &str = "
+INSERT INTO settings(name, value) VALUES('version', $1)
+ON CONFLICT(name) DO UPDATE SET value=excluded.value;
+";
+
+/// Condition selecting the events which precede a state change.
+const DB_EVENTS_BEFORE: &str = "source_statechange_id < $1";
+
+/// Condition selecting the state changes which precede a state change and are not referenced by
+/// remaining events or snapshots.
+const DB_STATE_CHANGES_BEFORE: &str = "
+identifier < $1
+AND identifier NOT IN (SELECT source_statechange_id FROM state_events)
+AND identifier NOT IN (SELECT statechange_id FROM state_snapshot WHERE statechange_id IS NOT NULL)
+";
+
+/// Work to be run by the thread owning the client.
+type Job = Box<dyn FnOnce(&mut Client) + Send>;
+
+/// A query parameter which can be moved to the client thread.
+type Param = Box<dyn ToSql + Send + Sync>;
+
+/// PostgreSQL implementation of `Storage`.
+///
+/// The blocking postgres client panics when used from within an async runtime, so it is owned by
+/// a dedicated thread which runs the queries on behalf of the callers.
+pub struct PostgresStorage {
+	/// Sender of jobs to the client thread.
+	jobs: Mutex<mpsc::Sender<Job>>,
+}
+
+impl PostgresStorage {
+	/// Connect to the database described by `params` and keep all tables in `schema`.
+	pub fn connect(params: &str, schema: &str) -> Result<Self> {
+		let params = params.to_owned();
+		let search_path =
+			format!("CREATE SCHEMA IF NOT EXISTS \"{0}\"; SET search_path TO \"{0}\";", schema);
+
+		let (jobs, receiver) = mpsc::channel::<Job>();
+		let (connected_sender, connected) = mpsc::channel();
+		thread::spawn(move || {
+			let client = Client::connect(&params, NoTls).and_then(|mut client| {
+				client.batch_execute(&search_path)?;
+				Ok(client)
+			});
+			let mut client = match client {
+				Ok(client) => {
+					let _ = connected_sender.send(Ok(()));
+					client
+				},
+				Err(e) => {
+					let _ = connected_sender.send(Err(e));
+					return
+				},
+			};
+			while let Ok(job) = receiver.recv() {
+				job(&mut client);
+			}
+		});
+
+		connected
+			.recv()
+			.map_err(|_| StorageError::Other("Postgres client thread stopped"))?
+			.map_err(StorageError::Postgres)?;
+
+		Ok(Self { jobs: Mutex::new(jobs) })
+	}
+
+	/// Run `f` on the client thread and wait for its result.
+	fn execute<T, F>(&self, f: F) -> Result<T>
+	where
+		T: Send + 'static,
+		F: FnOnce(&mut Client) -> Result<T> + Send + 'static,
+	{
+		let (sender, receiver) = mpsc::channel();
+		self.jobs
+			.lock()
+			.map_err(|_| StorageError::CannotLock)?
+			.send(Box::new(move |client| {
+				let _ = sender.send(f(client));
+			}))
+			.map_err(|_| StorageError::Other("Postgres client thread stopped"))?;
+		receiver
+			.recv()
+			.map_err(|_| StorageError::Other("Postgres client thread stopped"))?
+	}
+
+	/// Get the latest state change matching all criteria on its data fields.
+	fn get_latest_state_change_by_data_field(
+		&self,
+		criteria: Vec<(String, String)>,
+	) -> Result<Option<StateChangeRecord>> {
+		let mut values: Vec<Param> = vec![];
+		let where_cond = json_conditions(criteria, &mut values);
+		let query = format!(
+			"SELECT identifier, data::text FROM state_changes
+                WHERE {}
+                ORDER BY identifier DESC
+                LIMIT 1",
+			where_cond
+		);
+
+		self.execute(move |client| {
+			client
+				.query_opt(query.as_str(), &params(&values))
+				.map_err(StorageError::Postgres)?
+				.as_ref()
+				.map(state_change_record)
+				.transpose()
+		})
+	}
+
+	/// Get the latest event matching all criteria of any of the groups on its data fields.
+	fn get_latest_event_by_data_field(
+		&self,
+		criteria: Vec<Vec<(String, String)>>,
+	) -> Result<Option<EventRecord>> {
+		let mut values: Vec<Param> = vec![];
+		let where_cond = criteria
+			.into_iter()
+			.map(|group| format!("({})", json_conditions(group, &mut values)))
+			.collect::<Vec<_>>()
+			.join(" OR ");
+		let query = format!(
+			"SELECT identifier, source_statechange_id, data::text, timestamp FROM state_events
+                WHERE {}
+                ORDER BY identifier DESC
+                LIMIT 1",
+			where_cond
+		);
+
+		self.execute(move |client| {
+			client
+				.query_opt(query.as_str(), &params(&values))
+				.map_err(StorageError::Postgres)?
+				.as_ref()
+				.map(event_record)
+				.transpose()
+		})
+	}
+
+	/// Query events matching both the given SQL conditions and the filter.
+	///
+	/// `conditions` reference `values` through numbered parameters.
+	fn query_events_with_timestamps(
+		&self,
+		mut conditions: Vec<String>,
+		mut values: Vec<Param>,
+		filter: &EventFilter,
+	) -> Result<Vec<EventRecord>> {
+		if let Some(after) = filter.after {
+			values.push(Box::new(after.to_string()));
+			conditions.push(format!("identifier > ${}", values.len()));
+		}
+		if let Some(from) = filter.from {
+			values.push(Box::new(from));
+			conditions.push(format!("timestamp >= ${}", values.len()));
+		}
+		if let Some(to) = filter.to {
+			values.push(Box::new(to));
+			conditions.push(format!("timestamp <= ${}", values.len()));
+		}
+		if !filter.event_types.is_empty() {
+			let mut placeholders = vec![];
+			for event_type in &filter.event_types {
+				values.push(Box::new(event_type.clone()));
+				placeholders.push(format!("${}", values.len()));
+			}
+			conditions.push(format!("data->>'type' IN ({})", placeholders.join(", ")));
+		}
+
+		let where_cond =
+			if conditions.is_empty() { "TRUE".to_owned() } else { conditions.join(" AND ") };
+		values.push(Box::new(filter.limit.map(i64::from)));
+
+		let query = format!(
+			"SELECT identifier, source_statechange_id, data::text, timestamp FROM state_events
+                WHERE {}
+                ORDER BY identifier ASC
+                LIMIT ${}",
+			where_cond,
+			values.len()
+		);
+
+		self.execute(move |client| {
+			client
+				.query(query.as_str(), &params(&values))
+				.map_err(StorageError::Postgres)?
+				.iter()
+				.map(event_record)
+				.collect()
+		})
+	}
+}
+
+impl Storage for PostgresStorage {
+	fn setup_database(&self) -> Result<()> {
+		let setup_db_sql = format!(
+			"
+			BEGIN;
+			{}{}{}{}{}{}{}
+			COMMIT;
+			",
+			DB_CREATE_SETTINGS,
+			DB_CREATE_STATE_CHANGES,
+			DB_CREATE_SNAPSHOT,
+			DB_CREATE_STATE_EVENTS,
+			DB_CREATE_RUNS,
+			DB_CREATE_NOTIFICATIONS,
+			DB_CREATE_TRANSACTIONS,
+		);
+		self.execute(move |client| {
+			client.batch_execute(&setup_db_sql).map_err(StorageError::Postgres)
+		})?;
+
+		migrations::migrate(self, migrations::MIGRATIONS)
+	}
+
+	fn get_version(&self) -> Result<Option<u32>> {
+		let version: Option<String> = self.execute(|client| {
+			client
+				.query_opt("SELECT value FROM settings WHERE name='version'", &[])
+				.map_err(StorageError::Postgres)?
+				.map(|row| row.try_get(0).map_err(StorageError::Postgres))
+				.transpose()
+		})?;
+		version
+			.map(|version| {
+				version.parse().map_err(|_| StorageError::Other("Invalid database version"))
+			})
+			.transpose()
+	}
+
+	fn set_version(&self, version: u32) -> Result<()> {
+		self.execute(move |client| {
+			client
+				.execute(DB_SET_VERSION, &[&version.to_string()])
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn apply_migration(&self, migration: &'static Migration) -> Result<()> {
+		self.execute(move |client| {
+			let mut transaction = client.transaction().map_err(StorageError::Postgres)?;
+
+			transaction
+				.batch_execute(migration.postgresql)
+				.map_err(StorageError::Postgres)?;
+			for (table, upgrade) in [
+				("state_changes", migration.state_change),
+				("state_events", migration.event),
+				("state_snapshot", migration.snapshot),
+			] {
+				if let Some(upgrade) = upgrade {
+					upgrade_records(&mut transaction, table, upgrade)?;
+				}
+			}
+			transaction
+				.execute(DB_SET_VERSION, &[&migration.version.to_string()])
+				.map_err(StorageError::Postgres)?;
+
+			transaction.commit().map_err(StorageError::Postgres)
+		})
+	}
+
+	fn store_snapshot(&self, state: ChainState, state_change_id: Option<StorageID>) -> Result<()> {
+		let serialized_state =
+			serde_json::to_string(&state).map_err(StorageError::SerializationError)?;
+		let state_change_id = match state_change_id {
+			Some(sc) => sc.inner,
+			None => Ulid::nil(),
+		};
+		self.execute(move |client| {
+			client
+				.execute(
+					"INSERT INTO state_snapshot(identifier, statechange_id, statechange_qty, data)
+					VALUES($1, $2, $3, $4::text::jsonb)",
+					&[
+						&Ulid::new().to_string(),
+						&state_change_id.to_string(),
+						&0i32,
+						&serialized_state,
+					],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn state_changes(&self) -> Result<Vec<StateChangeRecord>> {
+		self.execute(|client| {
+			client
+				.query(
+					"SELECT identifier, data::text FROM state_changes ORDER BY identifier ASC",
+					&[],
+				)
+				.map_err(StorageError::Postgres)?
+				.iter()
+				.map(state_change_record)
+				.collect()
+		})
+	}
+
+	fn store_state_change(&self, state_change: StateChange) -> Result<StorageID> {
+		let serialized_state_change =
+			serde_json::to_string(&state_change).map_err(StorageError::SerializationError)?;
+		let ulid = Ulid::new();
+		self.execute(move |client| {
+			client
+				.execute(
+					"INSERT INTO state_changes(identifier, data) VALUES($1, $2::text::jsonb)",
+					&[&ulid.to_string(), &serialized_state_change],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(ulid.into())
+		})
+	}
+
+	fn store_events(&self, state_change_id: StorageID, events: Vec<Event>) -> Result<()> {
+		let serialized_events = events
+			.iter()
+			.map(|event| serde_json::to_string(event).map_err(StorageError::SerializationError))
+			.collect::<Result<Vec<String>>>()?;
+		self.execute(move |client| {
+			let mut transaction = client.transaction().map_err(StorageError::Postgres)?;
+			for serialized_event in serialized_events {
+				transaction
+					.execute(
+						"INSERT INTO state_events(identifier, source_statechange_id, data, timestamp)
+						VALUES($1, $2, $3::text::jsonb, $4)",
+						&[
+							&Ulid::new().to_string(),
+							&state_change_id.to_string(),
+							&serialized_event,
+							&Utc::now().naive_utc(),
+						],
+					)
+					.map_err(StorageError::Postgres)?;
+			}
+			transaction.commit().map_err(StorageError::Postgres)
+		})
+	}
+
+	fn get_snapshot_before_state_change(
+		&self,
+		state_change_id: StorageID,
+	) -> Result<SnapshotRecord> {
+		self.execute(move |client| {
+			let row = client
+				.query_opt(
+					"SELECT identifier, statechange_qty, statechange_id, data::text
+					FROM state_snapshot
+					WHERE statechange_id <= $1 OR statechange_id IS NULL
+					ORDER BY identifier DESC
+					LIMIT 1",
+					&[&state_change_id.to_string()],
+				)
+				.map_err(StorageError::Postgres)?
+				.ok_or(StorageError::Other("Snapshot not found"))?;
+
+			let identifier: String = row.try_get(0).map_err(StorageError::Postgres)?;
+			let statechange_qty: i32 = row.try_get(1).map_err(StorageError::Postgres)?;
+			let state_change_identifier: String = row.try_get(2).map_err(StorageError::Postgres)?;
+			let data: String = row.try_get(3).map_err(StorageError::Postgres)?;
+			Ok(SnapshotRecord {
+				identifier: identifier.try_into()?,
+				statechange_qty: statechange_qty as u32,
+				state_change_identifier: state_change_identifier.try_into()?,
+				data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+			})
+		})
+	}
+
+	fn get_state_changes_in_range(
+		&self,
+		start_state_change: StorageID,
+		end_state_change: StorageID,
+	) -> Result<Vec<StateChangeRecord>> {
+		let start_state_change: String = start_state_change.into();
+		let end_state_change: String = end_state_change.into();
+		self.execute(move |client| {
+			client
+				.query(
+					"SELECT identifier, data::text FROM state_changes
+					WHERE identifier >= $1 AND identifier <= $2
+					ORDER BY identifier ASC",
+					&[&start_state_change, &end_state_change],
+				)
+				.map_err(StorageError::Postgres)?
+				.iter()
+				.map(state_change_record)
+				.collect()
+		})
+	}
+
+	fn get_state_change_with_balance_proof_by_balance_hash(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		balance_hash: BalanceHash,
+		recipient: Address,
+	) -> Result<Option<StateChangeRecord>> {
+		let mut criteria = canonical_identifier_criteria("", &canonical_identifier);
+		criteria.push(("balance_hash".to_owned(), format!("0x{}", hex::encode(balance_hash))));
+		criteria.push(("recipient".to_owned(), format!("0x{}", hex::encode(recipient))));
+
+		match self.get_latest_state_change_by_data_field(criteria)? {
+			Some(record) => Ok(Some(record)),
+			None => Err(StorageError::Other("State change not found")),
+		}
+	}
+
+	fn get_state_change_with_balance_proof_by_locksroot(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		locksroot: Locksroot,
+		recipient: Address,
+	) -> Result<Option<StateChangeRecord>> {
+		let mut criteria = canonical_identifier_criteria("", &canonical_identifier);
+		criteria
+			.push(("balance_proof.locksroot".to_owned(), format!("0x{}", hex::encode(locksroot))));
+		criteria.push(("balance_proof.sender".to_owned(), format!("0x{}", hex::encode(recipient))));
+
+		self.get_latest_state_change_by_data_field(criteria)
+	}
+
+	fn get_event_with_balance_proof_by_balance_hash(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		balance_hash: BalanceHash,
+		recipient: Address,
+	) -> Result<Option<EventRecord>> {
+		let criteria = ["", "transfer."]
+			.iter()
+			.map(|prefix| {
+				let mut group = canonical_identifier_criteria(prefix, &canonical_identifier);
+				group.push((
+					format!("{}balance_hash", prefix),
+					format!("0x{}", hex::encode(balance_hash)),
+				));
+				group.push(("recipient".to_owned(), format!("0x{}", hex::encode(recipient))));
+				group
+			})
+			.collect();
+
+		match self.get_latest_event_by_data_field(criteria)? {
+			Some(record) => Ok(Some(record)),
+			None => Err(StorageError::Other("Event not found")),
+		}
+	}
+
+	fn get_event_with_balance_proof_by_locksroot(
+		&self,
+		canonical_identifier: CanonicalIdentifier,
+		locksroot: Locksroot,
+		recipient: Address,
+	) -> Result<Option<EventRecord>> {
+		let criteria = ["", "transfer."]
+			.iter()
+			.map(|prefix| {
+				let mut group = canonical_identifier_criteria(prefix, &canonical_identifier);
+				group.push((
+					format!("{}locksroot", prefix),
+					format!("0x{}", hex::encode(locksroot)),
+				));
+				group.push(("recipient".to_owned(), format!("0x{}", hex::encode(recipient))));
+				group
+			})
+			.collect();
+
+		match self.get_latest_event_by_data_field(criteria)? {
+			Some(record) => Ok(Some(record)),
+			None => Err(StorageError::Other("Event not found")),
+		}
+	}
+
+	fn get_events_with_timestamps(&self, filter: &EventFilter) -> Result<Vec<EventRecord>> {
+		self.query_events_with_timestamps(vec![], vec![], filter)
+	}
+
+	fn get_events_payment_history_with_timestamps(
+		&self,
+		token_network_address: Option<TokenNetworkAddress>,
+		partner_address: Option<Address>,
+		filter: &EventFilter,
+	) -> Result<Vec<EventRecord>> {
+		let mut conditions = vec![
+			"data->>'type' IN ('PaymentReceivedSuccess', 'PaymentSentFailed', 'PaymentSentSuccess')"
+				.to_owned(),
+		];
+		let mut values: Vec<Param> = vec![];
+
+		if let Some(token_network_address) = token_network_address {
+			values.push(Box::new(format!("{:#x}", token_network_address)));
+			conditions.push(format!("data->>'token_network_address' ILIKE ${}", values.len()));
+		}
+		if let Some(partner_address) = partner_address {
+			values.push(Box::new(format!("{:#x}", partner_address)));
+			conditions.push(format!(
+				"(data->>'target' ILIKE ${0} OR data->>'initiator' ILIKE ${0})",
+				values.len()
+			));
+		}
+
+		self.query_events_with_timestamps(conditions, values, filter)
+	}
+
+	fn get_payment_sent_result(
+		&self,
+		token_network_address: TokenNetworkAddress,
+		target: Address,
+		identifier: PaymentIdentifier,
+	) -> Result<Option<Event>> {
+		let conditions = vec![
+			"data->>'type' IN ('ErrorPaymentSentFailed', 'PaymentSentSuccess')".to_owned(),
+			"data->>'token_network_address' ILIKE $1".to_owned(),
+			"data->>'target' ILIKE $2".to_owned(),
+		];
+		let values: Vec<Param> = vec![
+			Box::new(format!("{:#x}", token_network_address)),
+			Box::new(format!("{:#x}", target)),
+		];
+
+		let events =
+			self.query_events_with_timestamps(conditions, values, &EventFilter::default())?;
+		Ok(payment_sent_result(events, identifier))
+	}
+
+	fn store_notification(
+		&self,
+		identifier: String,
+		summary: String,
+		body: String,
+		urgency: NotificationUrgency,
+	) -> Result<()> {
+		self.execute(move |client| {
+			client
+				.execute(
+					"INSERT INTO notifications(identifier, summary, body, urgency)
+					VALUES($1, $2, $3, $4)
+					ON CONFLICT(identifier) DO UPDATE SET
+						summary=excluded.summary, body=excluded.body, urgency=excluded.urgency",
+					&[&identifier, &summary, &body, &urgency.to_string()],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn get_notifications(&self, include_dismissed: bool) -> Result<Vec<NotificationRecord>> {
+		self.execute(move |client| {
+			client
+				.query(
+					"SELECT identifier, summary, body, urgency, acknowledged, dismissed, timestamp
+					FROM notifications
+					WHERE NOT dismissed OR $1
+					ORDER BY timestamp ASC",
+					&[&include_dismissed],
+				)
+				.map_err(StorageError::Postgres)?
+				.iter()
+				.map(|row| {
+					let urgency: String = row.try_get(3).map_err(StorageError::Postgres)?;
+					Ok(NotificationRecord {
+						identifier: row.try_get(0).map_err(StorageError::Postgres)?,
+						summary: row.try_get(1).map_err(StorageError::Postgres)?,
+						body: row.try_get(2).map_err(StorageError::Postgres)?,
+						urgency: urgency.try_into()?,
+						acknowledged: row.try_get(4).map_err(StorageError::Postgres)?,
+						dismissed: row.try_get(5).map_err(StorageError::Postgres)?,
+						timestamp: row.try_get(6).map_err(StorageError::Postgres)?,
+					})
+				})
+				.collect()
+		})
+	}
+
+	fn acknowledge_notification(&self, identifier: String) -> Result<bool> {
+		self.execute(move |client| {
+			let updated = client
+				.execute(
+					"UPDATE notifications SET acknowledged=TRUE WHERE identifier=$1",
+					&[&identifier],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(updated > 0)
+		})
+	}
+
+	fn dismiss_notification(&self, identifier: String) -> Result<bool> {
+		self.execute(move |client| {
+			let updated = client
+				.execute(
+					"UPDATE notifications SET acknowledged=TRUE, dismissed=TRUE WHERE identifier=$1",
+					&[&identifier],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(updated > 0)
+		})
+	}
+
+	fn delete_notification(&self, identifier: String) -> Result<()> {
+		self.execute(move |client| {
+			client
+				.execute("DELETE FROM notifications WHERE identifier=$1", &[&identifier])
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn store_transaction(&self, transaction: &TransactionRecord) -> Result<()> {
+		let nonce = transaction.nonce.as_u64() as i64;
+		let serialized_transaction =
+			serde_json::to_string(transaction).map_err(StorageError::SerializationError)?;
+		self.execute(move |client| {
+			client
+				.execute(
+					"INSERT INTO transactions(nonce, data) VALUES($1, $2::text::jsonb)
+					ON CONFLICT(nonce) DO UPDATE SET data=excluded.data",
+					&[&nonce, &serialized_transaction],
+				)
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn get_pending_transactions(&self) -> Result<Vec<TransactionRecord>> {
+		self.execute(|client| {
+			client
+				.query("SELECT data::text FROM transactions ORDER BY nonce ASC", &[])
+				.map_err(StorageError::Postgres)?
+				.iter()
+				.map(|row| {
+					let data: String = row.try_get(0).map_err(StorageError::Postgres)?;
+					serde_json::from_str(&data).map_err(StorageError::SerializationError)
+				})
+				.collect()
+		})
+	}
+
+	fn delete_transaction(&self, nonce: U256) -> Result<()> {
+		let nonce = nonce.as_u64() as i64;
+		self.execute(move |client| {
+			client
+				.execute("DELETE FROM transactions WHERE nonce=$1", &[&nonce])
+				.map_err(StorageError::Postgres)?;
+			Ok(())
+		})
+	}
+
+	fn compact(
+		&self,
+		policy: &RetentionPolicy,
+		channels: Vec<CanonicalIdentifier>,
+	) -> Result<CompactionStats> {
+		let channels = channels
+			.iter()
+			.map(serde_json::to_value)
+			.collect::<std::result::Result<Vec<_>, _>>()
+			.map_err(StorageError::SerializationError)?;
+		let offset = i64::from(policy.snapshots_to_keep.saturating_sub(1));
+
+		self.execute(move |client| {
+			let mut transaction = client.transaction().map_err(StorageError::Postgres)?;
+
+			let mut latest_balance_proofs = LatestBalanceProofs::new(&channels);
+			for (table, state_change_column) in
+				[("state_changes", "identifier"), ("state_events", "source_statechange_id")]
+			{
+				let rows = transaction
+					.query(
+						format!(
+							"SELECT {}, data::text FROM {}
+							WHERE data #> '{{balance_proof}}' IS NOT NULL
+							OR data #> '{{transfer,balance_proof}}' IS NOT NULL",
+							state_change_column, table
+						)
+						.as_str(),
+						&[],
+					)
+					.map_err(StorageError::Postgres)?;
+				for row in rows {
+					let state_change_id: String = row.try_get(0).map_err(StorageError::Postgres)?;
+					let data: String = row.try_get(1).map_err(StorageError::Postgres)?;
+					let data: Value =
+						serde_json::from_str(&data).map_err(StorageError::SerializationError)?;
+					latest_balance_proofs.observe(state_change_id, &data);
+				}
+			}
+
+			let mut oldest_kept_snapshot = match query_snapshot(
+				&mut transaction,
+				"SELECT identifier, statechange_id FROM state_snapshot
+				ORDER BY identifier DESC
+				LIMIT 1 OFFSET $1",
+				&offset,
+			)? {
+				Some(snapshot) => snapshot,
+				None => return Ok(CompactionStats::default()),
+			};
+			if let Some(state_change_id) = latest_balance_proofs.oldest() {
+				// Without a snapshot to restore the channel from, nothing can be deleted.
+				let restorable_snapshot = match query_snapshot(
+					&mut transaction,
+					"SELECT identifier, statechange_id FROM state_snapshot
+					WHERE statechange_id <= $1
+					ORDER BY identifier DESC
+					LIMIT 1",
+					&state_change_id,
+				)? {
+					Some(snapshot) => snapshot,
+					None => return Ok(CompactionStats::default()),
+				};
+				oldest_kept_snapshot = oldest_kept_snapshot.min(restorable_snapshot);
+			}
+			let (snapshot_id, state_change_id) = oldest_kept_snapshot;
+
+			let snapshots = transaction
+				.execute("DELETE FROM state_snapshot WHERE identifier < $1", &[&snapshot_id])
+				.map_err(StorageError::Postgres)? as usize;
+			let events = delete_records(
+				&mut transaction,
+				"state_events",
+				DB_EVENTS_BEFORE,
+				&state_change_id,
+				&channels,
+			)?;
+			let state_changes = delete_records(
+				&mut transaction,
+				"state_changes",
+				DB_STATE_CHANGES_BEFORE,
+				&state_change_id,
+				&channels,
+			)?;
+
+			transaction.commit().map_err(StorageError::Postgres)?;
+
+			Ok(CompactionStats { snapshots, state_changes, events })
+		})
+	}
+}
+
+/// Rewrite the serialized records of `table` using `upgrade`.
+fn upgrade_records(
+	transaction: &mut Transaction,
+	table: &str,
+	upgrade: fn(Value) -> Value,
+) -> Result<()> {
+	let rows = transaction
+		.query(format!("SELECT identifier, data::text FROM {}", table).as_str(), &[])
+		.map_err(StorageError::Postgres)?;
+
+	for row in rows {
+		let identifier: String = row.try_get(0).map_err(StorageError::Postgres)?;
+		let data: String = row.try_get(1).map_err(StorageError::Postgres)?;
+		let data = upgrade(serde_json::from_str(&data).map_err(StorageError::SerializationError)?);
+		let data = serde_json::to_string(&data).map_err(StorageError::SerializationError)?;
+		transaction
+			.execute(
+				format!("UPDATE {} SET data=$1::text::jsonb WHERE identifier=$2", table).as_str(),
+				&[&data, &identifier],
+			)
+			.map_err(StorageError::Postgres)?;
+	}
+	Ok(())
+}
+
+/// Return the identifier and the state change identifier of the snapshot selected by `query`,
+/// which takes `param` as its only parameter.
+fn query_snapshot(
+	transaction: &mut Transaction,
+	query: &str,
+	param: &(dyn ToSql + Sync),
+) -> Result<Option<(String, String)>> {
+	let row = transaction.query_opt(query, &[param]).map_err(StorageError::Postgres)?;
+	match row {
+		Some(row) => Ok(Some((
+			row.try_get(0).map_err(StorageError::Postgres)?,
+			row.try_get(1).map_err(StorageError::Postgres)?,
+		))),
+		None => Ok(None),
+	}
+}
+
+/// Delete the records of `table` matching `condition` which hold no balance proof of `channels`.
+///
+/// `condition` references the state change identifier `before` as its first parameter.
+fn delete_records(
+	transaction: &mut Transaction,
+	table: &str,
+	condition: &str,
+	before: &str,
+	channels: &[Value],
+) -> Result<usize> {
+	let rows = transaction
+		.query(
+			format!("SELECT identifier, data::text FROM {} WHERE {}", table, condition).as_str(),
+			&[&before],
+		)
+		.map_err(StorageError::Postgres)?;
+
+	let mut obsolete = vec![];
+	for row in rows {
+		let identifier: String = row.try_get(0).map_err(StorageError::Postgres)?;
+		let data: String = row.try_get(1).map_err(StorageError::Postgres)?;
+		let data: Value = serde_json::from_str(&data).map_err(StorageError::SerializationError)?;
+		if !holds_balance_proof(&data, channels) {
+			obsolete.push(identifier);
+		}
+	}
+
+	transaction
+		.execute(format!("DELETE FROM {} WHERE identifier = ANY($1)", table).as_str(), &[&obsolete])
+		.map_err(StorageError::Postgres)?;
+	Ok(obsolete.len())
+}
+
+/// Criteria matching the canonical identifier of the balance proof found under `prefix`.
+fn canonical_identifier_criteria(
+	prefix: &str,
+	canonical_identifier: &CanonicalIdentifier,
+) -> Vec<(String, String)> {
+	vec![
+		(
+			format!("{}balance_proof.canonical_identifier.chain_identifier", prefix),
+			canonical_identifier.chain_identifier.to_string(),
+		),
+		(
+			format!("{}balance_proof.canonical_identifier.token_network_address", prefix),
+			format!("0x{}", hex::encode(canonical_identifier.token_network_address)),
+		),
+		(
+			format!("{}balance_proof.canonical_identifier.channel_identifier", prefix),
+			canonical_identifier.channel_identifier.to_string(),
+		),
+	]
+}
+
+/// Build an SQL condition comparing the `data` fields to the criteria values, which are appended
+/// to `values` as numbered parameters.
+fn json_conditions(criteria: Vec<(String, String)>, values: &mut Vec<Param>) -> String {
+	criteria
+		.into_iter()
+		.map(|(field, value)| {
+			values.push(Box::new(value));
+			format!("data #>> '{{{}}}' = ${}", field.replace('.', ","), values.len())
+		})
+		.collect::<Vec<_>>()
+		.join(" AND ")
+}
+
+/// Borrow the parameters in the form expected by the client.
+fn params(values: &[Param]) -> Vec<&(dyn ToSql + Sync)> {
+	values.iter().map(|value| value.as_ref() as &(dyn ToSql + Sync)).collect()
+}
+
+/// Convert a `identifier, data` row into a state change record.
+fn state_change_record(row: &Row) -> Result<StateChangeRecord> {
+	let identifier: String = row.try_get(0).map_err(StorageError::Postgres)?;
+	let data: String = row.try_get(1).map_err(StorageError::Postgres)?;
+	Ok(StateChangeRecord {
+		identifier: identifier.try_into()?,
+		data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+	})
+}
+
+/// Convert a `identifier, source_statechange_id, data, timestamp` row into an event record.
+fn event_record(row: &Row) -> Result<EventRecord> {
+	let identifier: String = row.try_get(0).map_err(StorageError::Postgres)?;
+	let state_change_identifier: String = row.try_get(1).map_err(StorageError::Postgres)?;
+	let data: String = row.try_get(2).map_err(StorageError::Postgres)?;
+	let timestamp: NaiveDateTime = row.try_get(3).map_err(StorageError::Postgres)?;
+	Ok(EventRecord {
+		identifier: identifier.try_into()?,
+		state_change_identifier: state_change_identifier.try_into()?,
+		data: serde_json::from_str(&data).map_err(StorageError::SerializationError)?,
+		timestamp,
+	})
+}
@@ -47,3 +47,48 @@ CREATE TABLE IF NOT EXISTS runs (
     raiden_version TEXT NOT NULL
 );
 ";
+
+/// Create notifications table SQL.
+pub(super) const DB_CREATE_NOTIFICATIONS: &str = "
+CREATE TABLE IF NOT EXISTS notifications (
+    identifier TEXT PRIMARY KEY NOT NULL,
+    summary TEXT NOT NULL,
+    body TEXT NOT NULL,
+    urgency TEXT NOT NULL,
+    acknowledged BOOLEAN DEFAULT(0) NOT NULL,
+    dismissed BOOLEAN DEFAULT(0) NOT NULL,
+    timestamp TIMESTAMP DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')) NOT NULL
+);
+";
+
+/// Create table of the transactions which were not mined yet SQL.
+pub(super) const DB_CREATE_TRANSACTIONS: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    nonce INTEGER PRIMARY KEY NOT NULL,
+    data JSON,
+    timestamp TIMESTAMP DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')) NOT NULL
+);
+";
+
+/// Store a transaction, replacing the one of the same nonce SQL.
+pub(super) const DB_STORE_TRANSACTION: &str = "
+INSERT INTO transactions(nonce, data) VALUES(?1, ?2)
+ON CONFLICT(nonce) DO UPDATE SET data=excluded.data;
+";
+
+/// Record the database version SQL.
+pub(super) const DB_SET_VERSION: &str = "
+INSERT INTO settings(name, value) VALUES('version', ?1)
+ON CONFLICT(name) DO UPDATE SET value=excluded.value;
+";
+
+/// Condition selecting the events which precede a state change.
+pub(super) const DB_EVENTS_BEFORE: &str = "source_statechange_id < ?1";
+
+/// Condition selecting the state changes which precede a state change and are not referenced by
+/// remaining events or snapshots.
+pub(super) const DB_STATE_CHANGES_BEFORE: &str = "
+identifier < ?1
+AND identifier NOT IN (SELECT source_statechange_id FROM state_events)
+AND identifier NOT IN (SELECT statechange_id FROM state_snapshot WHERE statechange_id IS NOT NULL)
+";
@@ -4,6 +4,17 @@ use std::convert::TryFrom;
 
 use chrono::NaiveDateTime;
 use derive_more::Display;
+use raiden_primitives::types::{
+	Address,
+	BlockNumber,
+	Bytes,
+	H256,
+	U256,
+};
+use serde::{
+	Deserialize,
+	Serialize,
+};
 use ulid::{
 	DecodeError,
 	Ulid,
@@ -27,14 +38,19 @@ pub enum StorageError {
 	SerializationError(serde_json::Error),
 	#[display(fmt = "SQL Error: {}", _0)]
 	Sql(rusqlite::Error),
+	#[cfg(feature = "postgresql")]
+	#[display(fmt = "Postgres Error: {}", _0)]
+	Postgres(postgres::Error),
 	#[display(fmt = "Cannot convert value to Ulid: {}", _0)]
 	ID(DecodeError),
+	#[display(fmt = "Database version {} is newer than the supported version {}", _0, _1)]
+	UnsupportedVersion(u32, u32),
 	#[display(fmt = "Error: {}", _0)]
 	Other(&'static str),
 }
 
 /// Storage record identifier
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct StorageID {
 	/// The inner identifier
 	pub(crate) inner: Ulid,
@@ -101,6 +117,42 @@ pub struct EventRecord {
 	pub timestamp: NaiveDateTime,
 }
 
+/// Criteria to select a page of events.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+	/// Only return events stored after this identifier.
+	pub after: Option<StorageID>,
+	/// Maximum number of events to return.
+	pub limit: Option<u32>,
+	/// Only return events logged at or after this time.
+	pub from: Option<NaiveDateTime>,
+	/// Only return events logged at or before this time.
+	pub to: Option<NaiveDateTime>,
+	/// Only return events of these types, all types if empty.
+	pub event_types: Vec<String>,
+}
+
+/// How much history to keep when compacting the storage.
+#[derive(Clone, Debug)]
+pub struct RetentionPolicy {
+	/// Number of most recent snapshots to keep, at least one snapshot is always kept.
+	pub snapshots_to_keep: u32,
+}
+
+impl Default for RetentionPolicy {
+	fn default() -> Self {
+		Self { snapshots_to_keep: 2 }
+	}
+}
+
+/// Number of records deleted by a compaction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompactionStats {
+	pub snapshots: usize,
+	pub state_changes: usize,
+	pub events: usize,
+}
+
 /// A record of a snaoshot.
 #[derive(Debug, Clone)]
 pub struct SnapshotRecord {
@@ -109,3 +161,56 @@ pub struct SnapshotRecord {
 	pub state_change_identifier: StorageID,
 	pub data: ChainState,
 }
+
+/// The urgency of a notification.
+#[derive(Copy, Clone, Debug, Display, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationUrgency {
+	#[display(fmt = "low")]
+	Low,
+	#[display(fmt = "normal")]
+	Normal,
+}
+
+impl TryFrom<String> for NotificationUrgency {
+	type Error = StorageError;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"low" => Ok(Self::Low),
+			"normal" => Ok(Self::Normal),
+			_ => Err(StorageError::Other("Unknown notification urgency")),
+		}
+	}
+}
+
+/// A record of a notification.
+#[derive(Clone, Debug)]
+pub struct NotificationRecord {
+	pub identifier: String,
+	pub summary: String,
+	pub body: String,
+	pub urgency: NotificationUrgency,
+	pub acknowledged: bool,
+	pub dismissed: bool,
+	pub timestamp: NaiveDateTime,
+}
+
+/// A record of a transaction sent on-chain which was not mined yet.
+///
+/// Replacements of the transaction share its nonce, `hashes` holds every broadcast version
+/// since any of them may end up being mined.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+	pub nonce: U256,
+	pub hashes: Vec<H256>,
+	pub purpose: String,
+	pub time_critical: bool,
+	pub to: Address,
+	pub value: U256,
+	pub data: Bytes,
+	pub gas_limit: U256,
+	pub max_fee_per_gas: U256,
+	pub max_priority_fee_per_gas: U256,
+	pub sent_at_block: BlockNumber,
+}
@@ -1,13 +1,53 @@
-use raiden_primitives::types::{
-	H256,
-	U256,
-	U64,
+use std::collections::HashMap;
+
+use raiden_primitives::{
+	hashing::hash_secret,
+	types::{
+		BlockExpiration,
+		LockTimeout,
+		Nonce,
+		PaymentIdentifier,
+		SecretHash,
+		TokenAmount,
+		TokenNetworkAddress,
+		H256,
+		U256,
+		U64,
+	},
 };
 
 use crate::{
-	machine::chain,
-	tests::factories::ChainStateBuilder,
-	types::Block,
+	constants::ABSENT_SECRET,
+	machine::{
+		chain,
+		channel::utils::compute_locksroot,
+	},
+	tests::factories::{
+		make_balance_proof,
+		ChainStateBuilder,
+		Generator,
+		Keyring,
+	},
+	types::{
+		ActionCreateInvoice,
+		ActionInitInitiator,
+		ActionInitTarget,
+		ActionTokenNetworkConnect,
+		ActionTokenNetworkLeave,
+		Block,
+		ConnectionManagerState,
+		Event,
+		HashTimeLockState,
+		HopState,
+		InvoiceState,
+		InvoiceStatus,
+		LockedTransferState,
+		PendingLocksState,
+		ReceiveSecretReveal,
+		RouteState,
+		TransferDescriptionWithSecretState,
+		TransferTask,
+	},
 };
 
 #[test]
@@ -26,3 +66,171 @@ fn chain_state_new_block() {
 		.expect("State transition should succeed");
 	assert_eq!(result.new_state.block_number, U64::from(3u64));
 }
+
+#[test]
+fn chain_state_circular_payment() {
+	let chain_info = ChainStateBuilder::new()
+		.with_token_network_registry()
+		.with_token_network()
+		.with_channels(vec![
+			(
+				(Keyring::Alice.address(), TokenAmount::from(1000)),
+				(Keyring::Bob.address(), TokenAmount::zero()),
+			),
+			(
+				(Keyring::Alice.address(), TokenAmount::zero()),
+				(Keyring::Charlie.address(), TokenAmount::from(1000)),
+			),
+		])
+		.build();
+	let our_address = Keyring::Alice.address();
+	let return_channel = chain_info.canonical_identifiers[1].clone();
+
+	let secret = Generator::random_secret();
+	let secrethash = SecretHash::from_slice(&hash_secret(&secret.0));
+	let payment_identifier = PaymentIdentifier::from(1);
+	let amount = TokenAmount::from(100);
+
+	let create_invoice = ActionCreateInvoice {
+		invoice: InvoiceState {
+			token_network_address: chain_info.token_network_address,
+			payment_identifier,
+			amount,
+			secret: secret.clone(),
+			secrethash,
+			expiration: BlockExpiration::from(200),
+			status: InvoiceStatus::Pending,
+		},
+	};
+	let result = chain::state_transition(chain_info.chain_state, create_invoice.into())
+		.expect("State transition should succeed");
+
+	let route = vec![our_address, Keyring::Bob.address(), Keyring::Charlie.address(), our_address];
+	let init_initiator = ActionInitInitiator {
+		transfer: TransferDescriptionWithSecretState {
+			token_network_registry_address: chain_info.token_network_registry_address,
+			token_network_address: chain_info.token_network_address,
+			lock_timeout: Some(LockTimeout::from(100)),
+			payment_identifier,
+			amount,
+			initiator: our_address,
+			target: our_address,
+			secret: ABSENT_SECRET,
+			secrethash,
+		},
+		routes: vec![RouteState {
+			route: route.clone(),
+			address_to_metadata: HashMap::new(),
+			swaps: HashMap::new(),
+			estimated_fee: TokenAmount::zero(),
+		}],
+	};
+	let result = chain::state_transition(result.new_state, init_initiator.into())
+		.expect("State transition should succeed");
+	assert!(matches!(result.events[0], Event::SendLockedTransfer { .. }));
+
+	// The payment comes back through the channel with Charlie.
+	let lock = HashTimeLockState::create(amount, BlockExpiration::from(111), secrethash);
+	let locksroot = compute_locksroot(&PendingLocksState { locks: vec![lock.encoded.clone()] });
+	let balance_proof = make_balance_proof(
+		Keyring::Charlie.private_key(),
+		return_channel.clone(),
+		amount,
+		locksroot,
+		TokenAmount::zero(),
+		Keyring::Charlie.address(),
+		Nonce::from(1),
+	);
+	let init_target = ActionInitTarget {
+		sender: Keyring::Charlie.address(),
+		balance_proof: balance_proof.clone(),
+		from_hop: HopState {
+			node_address: Keyring::Charlie.address(),
+			channel_identifier: return_channel.channel_identifier,
+		},
+		transfer: LockedTransferState {
+			payment_identifier,
+			token: chain_info.token_address,
+			lock,
+			initiator: our_address,
+			target: our_address,
+			message_identifier: 1u64,
+			route_states: vec![RouteState {
+				route,
+				address_to_metadata: HashMap::new(),
+				swaps: HashMap::new(),
+				estimated_fee: TokenAmount::zero(),
+			}],
+			balance_proof,
+			secret: None,
+		},
+		received_valid_secret: false,
+	};
+	let result = chain::state_transition(result.new_state, init_target.into())
+		.expect("State transition should succeed");
+	match &result.events[1] {
+		Event::SendSecretReveal(reveal) => {
+			assert_eq!(reveal.inner.recipient, Keyring::Charlie.address());
+			assert_eq!(reveal.secret, secret);
+		},
+		event => panic!("Expected a secret reveal, got {:?}", event),
+	}
+	let payment_mapping = &result.new_state.payment_mapping;
+	assert!(matches!(
+		payment_mapping.secrethashes_to_task.get(&secrethash),
+		Some(TransferTask::Initiator(_))
+	));
+	assert!(payment_mapping.secrethashes_to_circular_target.contains_key(&secrethash));
+
+	// Once Bob learns the secret, the initiator unlocks while the target waits for Charlie.
+	let secret_reveal =
+		ReceiveSecretReveal { sender: Keyring::Bob.address(), secret: secret.clone(), secrethash };
+	let result = chain::state_transition(result.new_state, secret_reveal.into())
+		.expect("State transition should succeed");
+	assert!(result.events.iter().any(|event| matches!(event, Event::SendUnlock { .. })));
+	assert!(result
+		.events
+		.iter()
+		.any(|event| matches!(event, Event::PaymentSentSuccess { .. })));
+	let payment_mapping = &result.new_state.payment_mapping;
+	assert!(!payment_mapping.secrethashes_to_task.contains_key(&secrethash));
+	assert!(payment_mapping.secrethashes_to_circular_target.contains_key(&secrethash));
+}
+
+#[test]
+fn chain_state_connection_manager() {
+	let chain_info = ChainStateBuilder::new()
+		.with_token_network_registry()
+		.with_token_network()
+		.build();
+
+	let connection_manager = ConnectionManagerState {
+		token_network_address: chain_info.token_network_address,
+		funds: TokenAmount::from(1000),
+		initial_channel_target: 3,
+		joinable_funds_target: 40,
+	};
+	assert_eq!(connection_manager.initial_funding_per_partner(), TokenAmount::from(200));
+
+	let connect = ActionTokenNetworkConnect { connection_manager: connection_manager.clone() };
+	let result = chain::state_transition(chain_info.chain_state, connect.into())
+		.expect("State transition should succeed");
+	assert_eq!(
+		result.new_state.connection_managers.get(&chain_info.token_network_address),
+		Some(&connection_manager)
+	);
+
+	let leave = ActionTokenNetworkLeave { token_network_address: chain_info.token_network_address };
+	let result = chain::state_transition(result.new_state, leave.into())
+		.expect("State transition should succeed");
+	assert!(result.new_state.connection_managers.is_empty());
+
+	// Unknown token networks can not be joined.
+	let connect = ActionTokenNetworkConnect {
+		connection_manager: ConnectionManagerState {
+			token_network_address: TokenNetworkAddress::random(),
+			..connection_manager
+		},
+	};
+	assert!(chain::state_transition(result.new_state, connect.into()).is_err());
+}
@@ -71,9 +71,14 @@ impl ChainStateBuilder {
 				block_hash: BlockHash::zero(),
 				our_address: Keyring::Alice.address(),
 				identifiers_to_tokennetworkregistries: HashMap::new(),
-				payment_mapping: PaymentMappingState { secrethashes_to_task: HashMap::new() },
+				payment_mapping: PaymentMappingState {
+					secrethashes_to_task: HashMap::new(),
+					secrethashes_to_circular_target: HashMap::new(),
+				},
 				pending_transactions: vec![],
 				pseudo_random_number_generator: Random::new(),
+				invoices: HashMap::new(),
+				connection_managers: HashMap::new(),
 			},
 			token_network_registry_address: Address::random(),
 			token_network_address: Address::random(),
@@ -16,6 +16,7 @@ use raiden_primitives::{
 		SecretHash,
 		SecretRegistryAddress,
 		TokenAmount,
+		TokenNetworkAddress,
 		TransactionHash,
 		H256,
 	},
@@ -29,6 +30,7 @@ use crate::{
 		Keyring,
 	},
 	types::{
+		ActionCancelPayment,
 		ActionChannelClose,
 		ActionInitInitiator,
 		Block,
@@ -39,6 +41,8 @@ use crate::{
 		ReceiveSecretReveal,
 		RouteState,
 		TransferDescriptionWithSecretState,
+		TransferState,
+		TransferTask,
 	},
 	views,
 };
@@ -298,6 +302,112 @@ fn test_initiator_onchain_secret_reveal() {
 	assert!(result.new_state.payment_mapping.secrethashes_to_task.get(&secrethash).is_none());
 }
 
+#[test]
+fn test_initiator_cancel_payment() {
+	let (chain_state, canonical_identifier, transfer_identifier, _secret, secrethash) =
+		setup_initiator();
+
+	let channel_state =
+		views::get_channel_by_canonical_identifier(&chain_state, canonical_identifier.clone())
+			.expect("Channel state should exist");
+	let target = channel_state.partner_state.address;
+	let token_network_address = canonical_identifier.token_network_address;
+
+	// Unknown payment, nothing to cancel
+	let cancel_payment = ActionCancelPayment {
+		token_network_address,
+		target,
+		payment_identifier: PaymentIdentifier::from(2),
+	};
+	let result = chain::state_transition(chain_state.clone(), cancel_payment.into())
+		.expect("Should succeed");
+	assert_eq!(result.events, vec![]);
+
+	// Same identifier, but sent to another target
+	let cancel_payment = ActionCancelPayment {
+		token_network_address,
+		target: Address::random(),
+		payment_identifier: transfer_identifier,
+	};
+	let result =
+		chain::state_transition(result.new_state, cancel_payment.into()).expect("Should succeed");
+	assert_eq!(result.events, vec![]);
+
+	// Same identifier, but in another token network
+	let cancel_payment = ActionCancelPayment {
+		token_network_address: TokenNetworkAddress::random(),
+		target,
+		payment_identifier: transfer_identifier,
+	};
+	let result =
+		chain::state_transition(result.new_state, cancel_payment.into()).expect("Should succeed");
+	assert_eq!(result.events, vec![]);
+
+	let cancel_payment = ActionCancelPayment {
+		token_network_address,
+		target,
+		payment_identifier: transfer_identifier,
+	};
+	let result = chain::state_transition(result.new_state, cancel_payment.clone().into())
+		.expect("Should succeed");
+	assert!(result
+		.events
+		.iter()
+		.any(|event| matches!(event, Event::ErrorPaymentSentFailed { .. })));
+
+	let task = result
+		.new_state
+		.payment_mapping
+		.secrethashes_to_task
+		.get(&secrethash)
+		.expect("Task should still exist until the lock expires");
+	let initiator = match task {
+		TransferTask::Initiator(initiator) => initiator,
+		_ => panic!("Should be an initiator task"),
+	};
+	assert_eq!(
+		initiator.manager_state.initiator_transfers[&secrethash].transfer_state,
+		TransferState::Canceled
+	);
+
+	// Already cancelled
+	let result =
+		chain::state_transition(result.new_state, cancel_payment.into()).expect("Should succeed");
+	assert_eq!(result.events, vec![]);
+}
+
+#[test]
+fn test_initiator_cancel_payment_after_secret_reveal() {
+	let (chain_state, canonical_identifier, transfer_identifier, _secret, secrethash) =
+		setup_initiator();
+
+	let channel_state =
+		views::get_channel_by_canonical_identifier(&chain_state, canonical_identifier.clone())
+			.expect("Channel state should exist");
+	let target = channel_state.partner_state.address;
+
+	let secret_request = ReceiveSecretRequest {
+		sender: target,
+		payment_identifier: transfer_identifier,
+		amount: TokenAmount::from(100),
+		expiration: BlockExpiration::from(101),
+		secrethash,
+		revealsecret: None,
+	};
+	let result =
+		chain::state_transition(chain_state, secret_request.into()).expect("Should succeed");
+	assert!(matches!(result.events[0], Event::SendSecretReveal { .. }));
+
+	let cancel_payment = ActionCancelPayment {
+		token_network_address: canonical_identifier.token_network_address,
+		target,
+		payment_identifier: transfer_identifier,
+	};
+	let result =
+		chain::state_transition(result.new_state, cancel_payment.into()).expect("Should succeed");
+	assert_eq!(result.events, vec![]);
+}
+
 // #[test]
 // fn test_initiator_receive_lock_expired() {
 // 	let (chain_state, canonical_identifier, _transfer_identifier, _secret, secrethash) =
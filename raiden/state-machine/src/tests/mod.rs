@@ -3,5 +3,7 @@ mod channel;
 pub mod factories;
 mod initiator;
 mod mediator;
+#[cfg(feature = "storage")]
+mod storage;
 mod target;
 mod token_network;
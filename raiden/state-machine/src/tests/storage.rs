@@ -0,0 +1,589 @@
+use std::{
+	thread,
+	time::Duration,
+};
+
+use raiden_primitives::types::{
+	Address,
+	CanonicalIdentifier,
+	TokenAmount,
+	TokenNetworkAddress,
+	H256,
+	U256,
+	U64,
+};
+use rusqlite::Connection;
+use serde_json::Value;
+
+use super::factories::{
+	ChainStateBuilder,
+	Keyring,
+};
+use crate::{
+	machine::chain,
+	storage::{
+		migrations::{
+			self,
+			Migration,
+		},
+		types::{
+			EventFilter,
+			NotificationUrgency,
+			RetentionPolicy,
+			StorageError,
+			StorageID,
+			TransactionRecord,
+		},
+		StateStorage,
+		Storage,
+	},
+	types::{
+		BalanceProofState,
+		Block,
+		ChainState,
+		ChannelState,
+		ErrorPaymentSentFailed,
+		Event,
+		PaymentReceivedSuccess,
+		PaymentSentSuccess,
+		ReceiveLockExpired,
+		StateChange,
+	},
+	views,
+};
+
+fn storage() -> StateStorage {
+	let storage = StateStorage::new(Connection::open_in_memory().expect("Should open database"));
+	storage.setup_database().expect("Should setup database");
+	storage
+}
+
+fn payment_events(
+	token_network_address: TokenNetworkAddress,
+	partner_address: Address,
+	count: u64,
+) -> Vec<Event> {
+	(0..count)
+		.map(|i| {
+			if i % 2 == 0 {
+				Event::PaymentSentSuccess(PaymentSentSuccess {
+					token_network_registry_address: Address::zero(),
+					token_network_address,
+					identifier: U64::from(i),
+					amount: TokenAmount::from(i),
+					target: partner_address,
+					secret: Default::default(),
+					route: vec![],
+				})
+			} else {
+				Event::PaymentReceivedSuccess(PaymentReceivedSuccess {
+					token_network_registry_address: Address::zero(),
+					token_network_address,
+					identifier: U64::from(i),
+					amount: TokenAmount::from(i),
+					initiator: partner_address,
+				})
+			}
+		})
+		.collect()
+}
+
+fn block() -> StateChange {
+	Block { block_number: U64::from(1u64), block_hash: H256::zero(), gas_limit: U256::zero() }
+		.into()
+}
+
+/// Store a state change, identifiers are only ordered when created in different milliseconds.
+fn store_state_change(storage: &dyn Storage, state_change: StateChange) -> StorageID {
+	thread::sleep(Duration::from_millis(2));
+	storage.store_state_change(state_change).expect("Should store state change")
+}
+
+fn store_events(storage: &dyn Storage, events: Vec<Event>) {
+	let state_change_id = store_state_change(storage, block());
+	storage.store_events(state_change_id, events).expect("Should store events");
+}
+
+fn lock_expired(canonical_identifier: CanonicalIdentifier) -> StateChange {
+	ReceiveLockExpired {
+		sender: Address::random(),
+		secrethash: H256::random(),
+		message_identifier: 1,
+		balance_proof: BalanceProofState {
+			nonce: U256::one(),
+			transferred_amount: TokenAmount::zero(),
+			locked_amount: TokenAmount::zero(),
+			locksroot: H256::zero(),
+			canonical_identifier,
+			balance_hash: H256::zero(),
+			message_hash: None,
+			signature: None,
+			sender: None,
+		},
+	}
+	.into()
+}
+
+fn check_events_pagination(storage: &dyn Storage) {
+	store_events(storage, payment_events(Address::random(), Address::random(), 5));
+
+	let all_events = storage
+		.get_events_with_timestamps(&EventFilter::default())
+		.expect("Should get events");
+	assert_eq!(all_events.len(), 5);
+
+	let mut filter = EventFilter { limit: Some(2), ..Default::default() };
+	let mut pages = vec![];
+	loop {
+		let page = storage.get_events_with_timestamps(&filter).expect("Should get events");
+		if page.is_empty() {
+			break
+		}
+		assert!(page.len() <= 2);
+		filter.after = page.last().map(|event| event.identifier);
+		pages.extend(page);
+	}
+	assert_eq!(
+		pages.iter().map(|e| e.identifier.to_string()).collect::<Vec<_>>(),
+		all_events.iter().map(|e| e.identifier.to_string()).collect::<Vec<_>>(),
+	);
+}
+
+fn check_events_filter_by_type_and_time(storage: &dyn Storage) {
+	store_events(storage, payment_events(Address::random(), Address::random(), 4));
+
+	let filter =
+		EventFilter { event_types: vec!["PaymentSentSuccess".to_owned()], ..Default::default() };
+	let events = storage.get_events_with_timestamps(&filter).expect("Should get events");
+	assert_eq!(events.len(), 2);
+	assert!(events.iter().all(|e| matches!(e.data, Event::PaymentSentSuccess(_))));
+
+	let last_timestamp = events.last().expect("Should have events").timestamp;
+	let filter = EventFilter {
+		from: Some(last_timestamp + chrono::Duration::seconds(1)),
+		..Default::default()
+	};
+	assert!(storage
+		.get_events_with_timestamps(&filter)
+		.expect("Should get events")
+		.is_empty());
+
+	let filter = EventFilter {
+		to: Some(last_timestamp + chrono::Duration::seconds(1)),
+		..Default::default()
+	};
+	assert_eq!(storage.get_events_with_timestamps(&filter).expect("Should get events").len(), 4);
+}
+
+fn check_payment_history_filters(storage: &dyn Storage) {
+	let token_network_address = Address::random();
+	let partner_address = Address::random();
+	store_events(storage, payment_events(token_network_address, partner_address, 3));
+	store_events(storage, payment_events(Address::random(), Address::random(), 3));
+
+	let events = storage
+		.get_events_payment_history_with_timestamps(None, None, &EventFilter::default())
+		.expect("Should get events");
+	assert_eq!(events.len(), 6);
+
+	let events = storage
+		.get_events_payment_history_with_timestamps(
+			Some(token_network_address),
+			None,
+			&EventFilter::default(),
+		)
+		.expect("Should get events");
+	assert_eq!(events.len(), 3);
+
+	let events = storage
+		.get_events_payment_history_with_timestamps(
+			Some(token_network_address),
+			Some(partner_address),
+			&EventFilter { limit: Some(1), ..Default::default() },
+		)
+		.expect("Should get events");
+	assert_eq!(events.len(), 1);
+}
+
+fn check_payment_sent_result(storage: &dyn Storage) {
+	let token_network_address = Address::random();
+	let target = Address::random();
+	let failed = |identifier: u64| {
+		Event::ErrorPaymentSentFailed(ErrorPaymentSentFailed {
+			token_network_registry_address: Address::zero(),
+			token_network_address,
+			identifier: U64::from(identifier),
+			target,
+			reason: "no route available".to_owned(),
+		})
+	};
+	// Payments 0 and 2 succeed, 1 is received.
+	store_events(storage, payment_events(token_network_address, target, 3));
+	store_events(storage, vec![failed(3)]);
+
+	let result = |identifier: u64| {
+		storage
+			.get_payment_sent_result(token_network_address, target, U64::from(identifier))
+			.expect("Should get payment result")
+	};
+	match result(2) {
+		Some(Event::PaymentSentSuccess(event)) => assert_eq!(event.identifier, U64::from(2u64)),
+		event => panic!("Unexpected event {:?}", event),
+	}
+	assert!(matches!(result(3), Some(Event::ErrorPaymentSentFailed(_))));
+	assert!(result(1).is_none());
+	assert!(result(4).is_none());
+	assert!(storage
+		.get_payment_sent_result(token_network_address, Address::random(), U64::from(2u64))
+		.expect("Should get payment result")
+		.is_none());
+
+	// A retried payment with the same identifier reports the latest result.
+	store_events(storage, vec![failed(2)]);
+	assert!(matches!(result(2), Some(Event::ErrorPaymentSentFailed(_))));
+}
+
+fn check_notifications(storage: &dyn Storage) {
+	storage
+		.store_notification(
+			"low_udc_balance".to_owned(),
+			"UDC balance too low".to_owned(),
+			"".to_owned(),
+			NotificationUrgency::Normal,
+		)
+		.expect("Should store notification");
+	assert!(storage
+		.acknowledge_notification("low_udc_balance".to_owned())
+		.expect("Should acknowledge notification"));
+	assert!(!storage
+		.acknowledge_notification("unknown".to_owned())
+		.expect("Should acknowledge notification"));
+
+	// Storing the notification again keeps the acknowledgement.
+	storage
+		.store_notification(
+			"low_udc_balance".to_owned(),
+			"UDC balance too low".to_owned(),
+			"Updated".to_owned(),
+			NotificationUrgency::Normal,
+		)
+		.expect("Should store notification");
+	let notifications = storage.get_notifications(false).expect("Should get notifications");
+	assert_eq!(notifications.len(), 1);
+	assert!(notifications[0].acknowledged);
+	assert_eq!(notifications[0].body, "Updated");
+
+	assert!(storage
+		.dismiss_notification("low_udc_balance".to_owned())
+		.expect("Should dismiss notification"));
+	assert!(storage.get_notifications(false).expect("Should get notifications").is_empty());
+	assert_eq!(storage.get_notifications(true).expect("Should get notifications").len(), 1);
+}
+
+fn transaction(nonce: u64) -> TransactionRecord {
+	TransactionRecord {
+		nonce: U256::from(nonce),
+		hashes: vec![H256::random()],
+		purpose: "closeChannel".to_owned(),
+		time_critical: false,
+		to: Address::random(),
+		value: U256::zero(),
+		data: vec![1, 2, 3].into(),
+		gas_limit: U256::from(100_000),
+		max_fee_per_gas: U256::from(2_000_000_000u64),
+		max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+		sent_at_block: U64::from(10u64),
+	}
+}
+
+fn check_transactions(storage: &dyn Storage) {
+	let mut replaced = transaction(7);
+	storage.store_transaction(&transaction(8)).expect("Should store transaction");
+	storage.store_transaction(&replaced).expect("Should store transaction");
+
+	// A replacement overwrites the record of its nonce.
+	replaced.hashes.push(H256::random());
+	replaced.max_fee_per_gas = U256::from(2_200_000_000u64);
+	storage.store_transaction(&replaced).expect("Should store transaction");
+
+	let transactions = storage.get_pending_transactions().expect("Should get transactions");
+	assert_eq!(transactions.len(), 2);
+	assert_eq!(transactions[0], replaced);
+	assert_eq!(transactions[1].nonce, U256::from(8));
+
+	storage.delete_transaction(U256::from(7)).expect("Should delete transaction");
+	let transactions = storage.get_pending_transactions().expect("Should get transactions");
+	assert_eq!(transactions.len(), 1);
+	assert_eq!(transactions[0].nonce, U256::from(8));
+}
+
+/// Migration setting the identifier of stored payment events.
+static TEST_MIGRATIONS: [Migration; 1] = [Migration {
+	version: migrations::INITIAL_VERSION + 1,
+	sqlite: "ALTER TABLE notifications ADD COLUMN migrated BOOLEAN;",
+	postgresql: "ALTER TABLE notifications ADD COLUMN migrated BOOLEAN;",
+	state_change: None,
+	event: Some(set_payment_identifier),
+	snapshot: None,
+}];
+
+fn set_payment_identifier(mut event: Value) -> Value {
+	event["identifier"] = serde_json::to_value(U64::from(42u64)).expect("Should serialize");
+	event
+}
+
+fn check_version(storage: &dyn Storage) {
+	assert_eq!(
+		storage.get_version().expect("Should get version"),
+		Some(migrations::latest_version(migrations::MIGRATIONS))
+	);
+
+	let future_version = migrations::latest_version(migrations::MIGRATIONS) + 1;
+	storage.set_version(future_version).expect("Should set version");
+	assert!(matches!(
+		storage.setup_database(),
+		Err(StorageError::UnsupportedVersion(version, _)) if version == future_version
+	));
+}
+
+fn check_migrations(storage: &dyn Storage) {
+	store_events(storage, payment_events(Address::random(), Address::random(), 2));
+
+	migrations::migrate(storage, &TEST_MIGRATIONS).expect("Should migrate");
+	assert_eq!(
+		storage.get_version().expect("Should get version"),
+		Some(migrations::INITIAL_VERSION + 1)
+	);
+	let events = storage
+		.get_events_with_timestamps(&EventFilter::default())
+		.expect("Should get events");
+	assert_eq!(events.len(), 2);
+	assert!(events.iter().all(|e| match &e.data {
+		Event::PaymentSentSuccess(event) => event.identifier == U64::from(42u64),
+		Event::PaymentReceivedSuccess(event) => event.identifier == U64::from(42u64),
+		_ => false,
+	}));
+
+	// Migrations which were already applied are skipped.
+	migrations::migrate(storage, &TEST_MIGRATIONS).expect("Should migrate");
+}
+
+/// Restore a channel as it was before a state change, the way on-chain unlocks do.
+fn channel_state_until_state_change(
+	storage: &dyn Storage,
+	canonical_identifier: CanonicalIdentifier,
+	state_change_identifier: StorageID,
+) -> Option<ChannelState> {
+	let snapshot = storage.get_snapshot_before_state_change(state_change_identifier).ok()?;
+	let state_changes = storage
+		.get_state_changes_in_range(snapshot.state_change_identifier, state_change_identifier)
+		.ok()?;
+
+	let mut chain_state = snapshot.data;
+	for state_change in state_changes {
+		chain_state = chain::state_transition(chain_state, state_change.data).ok()?.new_state;
+	}
+	views::get_channel_by_canonical_identifier(&chain_state, canonical_identifier).cloned()
+}
+
+fn check_compaction(storage: &dyn Storage) {
+	let chain_info = ChainStateBuilder::new()
+		.with_token_network_registry()
+		.with_token_network()
+		.with_channels(vec![(
+			(Keyring::Alice.address(), TokenAmount::zero()),
+			(Keyring::Bob.address(), TokenAmount::zero()),
+		)])
+		.build();
+	let open_channel = chain_info.canonical_identifiers[0].clone();
+	let settled_channel =
+		CanonicalIdentifier { channel_identifier: U256::from(2u64), ..open_channel.clone() };
+	let store_snapshot = || {
+		let state_change_id = store_state_change(storage, block());
+		storage
+			.store_snapshot(chain_info.chain_state.clone(), Some(state_change_id))
+			.expect("Should store snapshot");
+	};
+
+	store_snapshot();
+	store_events(storage, payment_events(Address::random(), Address::random(), 2));
+	store_state_change(storage, lock_expired(settled_channel.clone()));
+	store_snapshot();
+	let balance_proof_id = store_state_change(storage, lock_expired(open_channel.clone()));
+	store_state_change(storage, lock_expired(settled_channel));
+	for _ in 0..2 {
+		store_snapshot();
+	}
+
+	let stats = storage
+		.compact(&RetentionPolicy { snapshots_to_keep: 1 }, vec![open_channel.clone()])
+		.expect("Should compact");
+	// The snapshot preceding the open channel's balance proof is kept along with the following
+	// state changes, even though the policy keeps only the latest snapshot.
+	assert_eq!(stats.snapshots, 1);
+	assert_eq!(stats.events, 2);
+	// The first snapshot's block, the payments block and the settled channel's lock expiry.
+	assert_eq!(stats.state_changes, 3);
+
+	let state_changes = storage.state_changes().expect("Should get state changes");
+	assert_eq!(state_changes.len(), 5);
+	assert!(state_changes.iter().any(|record| record.identifier == balance_proof_id));
+	assert!(storage
+		.get_events_with_timestamps(&EventFilter::default())
+		.expect("Should get events")
+		.is_empty());
+	assert!(
+		channel_state_until_state_change(storage, open_channel.clone(), balance_proof_id).is_some()
+	);
+
+	// Once the channel is gone, only the latest snapshot is kept.
+	let stats = storage
+		.compact(&RetentionPolicy { snapshots_to_keep: 1 }, vec![])
+		.expect("Should compact");
+	assert_eq!(stats.snapshots, 2);
+	assert!(channel_state_until_state_change(storage, open_channel, balance_proof_id).is_none());
+}
+
+#[test]
+fn test_events_pagination() {
+	check_events_pagination(&storage());
+}
+
+#[test]
+fn test_events_filter_by_type_and_time() {
+	check_events_filter_by_type_and_time(&storage());
+}
+
+#[test]
+fn test_payment_history_filters() {
+	check_payment_history_filters(&storage());
+}
+
+#[test]
+fn test_payment_sent_result() {
+	check_payment_sent_result(&storage());
+}
+
+#[test]
+fn test_notifications() {
+	check_notifications(&storage());
+}
+
+#[test]
+fn test_transactions() {
+	check_transactions(&storage());
+}
+
+#[test]
+fn test_version() {
+	check_version(&storage());
+}
+
+#[test]
+fn test_migrations() {
+	check_migrations(&storage());
+}
+
+#[test]
+fn test_chain_state_migration() {
+	let chain_state = ChainStateBuilder::new().with_token_network_registry().build().chain_state;
+
+	// Snapshots stored before the collections were introduced do not hold them.
+	let mut snapshot = serde_json::to_value(&chain_state).expect("Should serialize");
+	let object = snapshot.as_object_mut().expect("Should be an object");
+	object.remove("invoices");
+	object.remove("connection_managers");
+	object["payment_mapping"]
+		.as_object_mut()
+		.expect("Should be an object")
+		.remove("secrethashes_to_circular_target");
+	assert!(serde_json::from_value::<ChainState>(snapshot.clone()).is_err());
+
+	let upgrade = migrations::MIGRATIONS
+		.iter()
+		.find_map(|migration| migration.snapshot)
+		.expect("Should upgrade snapshots");
+	let upgraded: ChainState =
+		serde_json::from_value(upgrade(snapshot)).expect("Should deserialize upgraded snapshot");
+	assert_eq!(upgraded, chain_state);
+
+	// Snapshots which already hold them are kept as they are.
+	let current = serde_json::to_value(&chain_state).expect("Should serialize");
+	assert_eq!(upgrade(current.clone()), current);
+}
+
+#[test]
+fn test_compaction() {
+	check_compaction(&storage());
+}
+
+/// The PostgreSQL tests run against the server at `RAIDEN_TEST_POSTGRES_URL`, each in its own
+/// schema.
+#[cfg(feature = "postgresql")]
+mod postgresql {
+	use super::*;
+	use crate::storage::PostgresStorage;
+
+	fn storage() -> PostgresStorage {
+		let url = std::env::var("RAIDEN_TEST_POSTGRES_URL")
+			.unwrap_or_else(|_| "host=localhost user=postgres".to_owned());
+		let schema = format!("test_{}", ulid::Ulid::new().to_string().to_lowercase());
+		let storage = PostgresStorage::connect(&url, &schema).expect("Should connect to database");
+		storage.setup_database().expect("Should setup database");
+		storage
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_events_pagination() {
+		check_events_pagination(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_events_filter_by_type_and_time() {
+		check_events_filter_by_type_and_time(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_payment_history_filters() {
+		check_payment_history_filters(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_payment_sent_result() {
+		check_payment_sent_result(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_notifications() {
+		check_notifications(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_transactions() {
+		check_transactions(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_version() {
+		check_version(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_migrations() {
+		check_migrations(&storage());
+	}
+
+	#[test]
+	#[ignore = "requires a PostgreSQL server"]
+	fn test_compaction() {
+		check_compaction(&storage());
+	}
+}
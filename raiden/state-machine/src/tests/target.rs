@@ -30,6 +30,7 @@ use crate::{
 		Keyring,
 	},
 	types::{
+		ActionCreateInvoice,
 		ActionInitTarget,
 		Block,
 		ChainState,
@@ -37,6 +38,8 @@ use crate::{
 		Event,
 		HashTimeLockState,
 		HopState,
+		InvoiceState,
+		InvoiceStatus,
 		LockedTransferState,
 		PendingLocksState,
 		ReceiveLockExpired,
@@ -46,7 +49,8 @@ use crate::{
 	views,
 };
 
-fn setup_target() -> (ChainState, CanonicalIdentifier, PaymentIdentifier, Secret, SecretHash) {
+fn setup_target_init(
+) -> (ChainState, ActionInitTarget, CanonicalIdentifier, PaymentIdentifier, Secret, SecretHash) {
 	let chain_info = ChainStateBuilder::new()
 		.with_token_network_registry()
 		.with_token_network()
@@ -106,14 +110,114 @@ fn setup_target() -> (ChainState, CanonicalIdentifier, PaymentIdentifier, Secret
 		received_valid_secret: false,
 	};
 
-	let result = chain::state_transition(chain_info.chain_state.clone(), state_change.into())
-		.expect("Should succeed");
+	(
+		chain_info.chain_state,
+		state_change,
+		canonical_identifier,
+		transfer_identifier,
+		secret,
+		secrethash,
+	)
+}
+
+fn setup_target() -> (ChainState, CanonicalIdentifier, PaymentIdentifier, Secret, SecretHash) {
+	let (chain_state, state_change, canonical_identifier, transfer_identifier, secret, secrethash) =
+		setup_target_init();
+
+	let result = chain::state_transition(chain_state, state_change.into()).expect("Should succeed");
 	assert!(matches!(result.events[0], Event::SendProcessed { .. }));
 	assert!(matches!(result.events[1], Event::SendSecretRequest { .. }));
 
 	(result.new_state, canonical_identifier, transfer_identifier, secret, secrethash)
 }
 
+fn create_invoice(
+	chain_state: ChainState,
+	canonical_identifier: &CanonicalIdentifier,
+	payment_identifier: PaymentIdentifier,
+	amount: TokenAmount,
+	secret: Secret,
+	secrethash: SecretHash,
+) -> ChainState {
+	let create_invoice = ActionCreateInvoice {
+		invoice: InvoiceState {
+			token_network_address: canonical_identifier.token_network_address,
+			payment_identifier,
+			amount,
+			secret,
+			secrethash,
+			expiration: BlockExpiration::from(200),
+			status: InvoiceStatus::Pending,
+		},
+	};
+	chain::state_transition(chain_state, create_invoice.into())
+		.expect("Should succeed")
+		.new_state
+}
+
+#[test]
+fn test_target_reveals_invoice_secret() {
+	let (chain_state, state_change, canonical_identifier, transfer_identifier, secret, secrethash) =
+		setup_target_init();
+	let chain_state = create_invoice(
+		chain_state,
+		&canonical_identifier,
+		transfer_identifier,
+		TokenAmount::from(100),
+		secret.clone(),
+		secrethash,
+	);
+
+	let result = chain::state_transition(chain_state, state_change.into()).expect("Should succeed");
+	assert!(matches!(result.events[0], Event::SendProcessed { .. }));
+	match &result.events[1] {
+		Event::SendSecretReveal(reveal) => assert_eq!(reveal.secret, secret),
+		event => panic!("Expected a secret reveal, got {:?}", event),
+	}
+}
+
+#[test]
+fn test_target_keeps_invoice_secret_on_mismatch() {
+	let (chain_state, state_change, canonical_identifier, transfer_identifier, secret, secrethash) =
+		setup_target_init();
+	// The transfer locks less than the invoice requests
+	let chain_state = create_invoice(
+		chain_state,
+		&canonical_identifier,
+		transfer_identifier,
+		TokenAmount::from(200),
+		secret,
+		secrethash,
+	);
+
+	let result = chain::state_transition(chain_state, state_change.into()).expect("Should succeed");
+	assert_eq!(result.events.len(), 2);
+	assert!(matches!(result.events[1], Event::ErrorUnlockClaimFailed { .. }));
+	assert_eq!(result.new_state.invoices[&secrethash].status, InvoiceStatus::Pending);
+}
+
+#[test]
+fn test_invoice_expires() {
+	let (chain_state, _, canonical_identifier, transfer_identifier, secret, secrethash) =
+		setup_target_init();
+	let chain_state = create_invoice(
+		chain_state,
+		&canonical_identifier,
+		transfer_identifier,
+		TokenAmount::from(100),
+		secret,
+		secrethash,
+	);
+
+	let block = Block {
+		block_number: BlockNumber::from(201),
+		block_hash: BlockHash::random(),
+		gas_limit: GasLimit::zero(),
+	};
+	let result = chain::state_transition(chain_state, block.into()).expect("Should succeed");
+	assert_eq!(result.new_state.invoices[&secrethash].status, InvoiceStatus::Expired);
+}
+
 #[test]
 fn test_target_expires_target() {
 	let (chain_state, _, _, _, _) = setup_target();
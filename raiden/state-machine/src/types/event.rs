@@ -129,6 +129,114 @@ impl Event {
 			Event::ClearMessages(_) => "ClearMessages",
 		}
 	}
+
+	/// Returns the address of the token network the event refers to, if any.
+	pub fn token_network_address(&self) -> Option<TokenNetworkAddress> {
+		match self {
+			Event::ContractSendChannelClose(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::ContractSendChannelCoopSettle(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::ContractSendChannelWithdraw(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::ContractSendChannelSettle(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::ContractSendChannelUpdateTransfer(inner) =>
+				Some(inner.balance_proof.canonical_identifier.token_network_address),
+			Event::ContractSendChannelBatchUnlock(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::PaymentReceivedSuccess(inner) => Some(inner.token_network_address),
+			Event::PaymentSentSuccess(inner) => Some(inner.token_network_address),
+			Event::SendWithdrawExpired(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendWithdrawRequest(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendWithdrawConfirmation(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendLockedTransfer(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendLockExpired(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::SendSecretRequest(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendSecretReveal(inner) =>
+				Some(inner.canonical_identifier.token_network_address),
+			Event::SendUnlock(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::SendPFSUpdate(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::SendMSUpdate(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::SendProcessed(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::ErrorPaymentSentFailed(inner) => Some(inner.token_network_address),
+			Event::ErrorRouteFailed(inner) => Some(inner.token_network_address),
+			Event::ClearMessages(inner) => Some(inner.canonical_identifier.token_network_address),
+			Event::ContractSendSecretReveal(_) |
+			Event::UnlockSuccess(_) |
+			Event::UnlockClaimSuccess(_) |
+			Event::UpdatedServicesAddresses(_) |
+			Event::ExpireServicesAddresses(_) |
+			Event::ErrorInvalidActionWithdraw(_) |
+			Event::ErrorInvalidActionCoopSettle(_) |
+			Event::ErrorInvalidActionSetRevealTimeout(_) |
+			Event::ErrorInvalidSecretRequest(_) |
+			Event::ErrorInvalidReceivedLockedTransfer(_) |
+			Event::ErrorInvalidReceivedLockExpired(_) |
+			Event::ErrorInvalidReceivedTransferRefund(_) |
+			Event::ErrorInvalidReceivedUnlock(_) |
+			Event::ErrorInvalidReceivedWithdrawRequest(_) |
+			Event::ErrorInvalidReceivedWithdrawConfirmation(_) |
+			Event::ErrorInvalidReceivedWithdrawExpired(_) |
+			Event::ErrorUnlockClaimFailed(_) |
+			Event::ErrorUnlockFailed(_) |
+			Event::ErrorUnexpectedReveal(_) => None,
+		}
+	}
+
+	/// Returns the address of the channel partner or payment participant the event refers to, if
+	/// any.
+	pub fn partner(&self) -> Option<Address> {
+		match self {
+			Event::ContractSendChannelClose(inner) =>
+				inner.balance_proof.as_ref().and_then(|balance_proof| balance_proof.sender),
+			Event::ContractSendChannelUpdateTransfer(inner) => inner.balance_proof.sender,
+			Event::PaymentReceivedSuccess(inner) => Some(inner.initiator),
+			Event::PaymentSentSuccess(inner) => Some(inner.target),
+			Event::SendWithdrawExpired(inner) => Some(inner.recipient),
+			Event::SendWithdrawRequest(inner) => Some(inner.recipient),
+			Event::SendWithdrawConfirmation(inner) => Some(inner.recipient),
+			Event::SendLockedTransfer(inner) => Some(inner.recipient),
+			Event::SendLockExpired(inner) => Some(inner.recipient),
+			Event::SendSecretRequest(inner) => Some(inner.recipient),
+			Event::SendSecretReveal(inner) => Some(inner.recipient),
+			Event::SendUnlock(inner) => Some(inner.recipient),
+			Event::SendMSUpdate(inner) => inner.sender,
+			Event::SendProcessed(inner) => Some(inner.recipient),
+			Event::ErrorPaymentSentFailed(inner) => Some(inner.target),
+			Event::ClearMessages(inner) => Some(inner.recipient),
+			Event::ContractSendChannelCoopSettle(_) |
+			Event::ContractSendChannelWithdraw(_) |
+			Event::ContractSendChannelSettle(_) |
+			Event::ContractSendChannelBatchUnlock(_) |
+			Event::ContractSendSecretReveal(_) |
+			Event::SendPFSUpdate(_) |
+			Event::UnlockSuccess(_) |
+			Event::UnlockClaimSuccess(_) |
+			Event::UpdatedServicesAddresses(_) |
+			Event::ExpireServicesAddresses(_) |
+			Event::ErrorInvalidActionWithdraw(_) |
+			Event::ErrorInvalidActionCoopSettle(_) |
+			Event::ErrorInvalidActionSetRevealTimeout(_) |
+			Event::ErrorInvalidSecretRequest(_) |
+			Event::ErrorInvalidReceivedLockedTransfer(_) |
+			Event::ErrorInvalidReceivedLockExpired(_) |
+			Event::ErrorInvalidReceivedTransferRefund(_) |
+			Event::ErrorInvalidReceivedUnlock(_) |
+			Event::ErrorInvalidReceivedWithdrawRequest(_) |
+			Event::ErrorInvalidReceivedWithdrawConfirmation(_) |
+			Event::ErrorInvalidReceivedWithdrawExpired(_) |
+			Event::ErrorRouteFailed(_) |
+			Event::ErrorUnlockClaimFailed(_) |
+			Event::ErrorUnlockFailed(_) |
+			Event::ErrorUnexpectedReveal(_) => None,
+		}
+	}
 }
 
 /// An enum of the SendEvent variants.
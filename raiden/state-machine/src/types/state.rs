@@ -9,6 +9,7 @@ use derive_more::Display;
 use itertools::izip;
 use raiden_primitives::{
 	constants::LOCKSROOT_OF_NO_LOCKS,
+	deserializers::u256_from_str,
 	serializers::u256_to_str,
 	traits::ToBytes,
 	types::{
@@ -129,6 +130,14 @@ pub enum TargetState {
 	SecretRequest,
 }
 
+/// Variants of the invoice status.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum InvoiceStatus {
+	Pending,
+	Paid,
+	Expired,
+}
+
 /// Variants of the waiting transfer status.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub enum WaitingTransferStatus {
@@ -229,10 +238,60 @@ pub struct TargetTransferState {
 /// those that don't have a balance proof, e.g. SecretReveal.
 /// This mapping forces one task per secrethash, assuming that secrethash collision
 /// is unlikely. Features like token swaps, that span multiple networks, must
-/// be encapsulated in a single task to work with this structure.
+/// be encapsulated in a single task to work with this structure. Circular payments are the
+/// exception: this node is both their initiator and target, so their target tasks are kept
+/// apart from the initiator tasks.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct PaymentMappingState {
 	pub secrethashes_to_task: HashMap<SecretHash, TransferTask>,
+	pub secrethashes_to_circular_target: HashMap<SecretHash, TargetTask>,
+}
+
+/// A payment requested by this node which holds the secret until the payment arrives.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceState {
+	pub token_network_address: TokenNetworkAddress,
+	pub payment_identifier: PaymentIdentifier,
+	pub amount: TokenAmount,
+	pub secret: Secret,
+	pub secrethash: SecretHash,
+	pub expiration: BlockExpiration,
+	pub status: InvoiceStatus,
+}
+
+impl InvoiceState {
+	/// Returns true if a locked transfer received at `block_number` pays this invoice.
+	pub fn is_paid_by(&self, transfer: &LockedTransferState, block_number: BlockNumber) -> bool {
+		self.status == InvoiceStatus::Pending &&
+			block_number <= self.expiration &&
+			transfer.balance_proof.canonical_identifier.token_network_address ==
+				self.token_network_address &&
+			transfer.payment_identifier == self.payment_identifier &&
+			transfer.lock.amount >= self.amount
+	}
+}
+
+/// The funds a connection manager may spend to keep channels open in a token network.
+///
+/// `joinable_funds_target` is the percentage of the funds kept for channels opened by partners.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionManagerState {
+	pub token_network_address: TokenNetworkAddress,
+	pub funds: TokenAmount,
+	pub initial_channel_target: u16,
+	pub joinable_funds_target: u8,
+}
+
+impl ConnectionManagerState {
+	/// Deposit of each channel opened by this node, the share of the funds reserved for
+	/// channels opened by partners is left out.
+	pub fn initial_funding_per_partner(&self) -> TokenAmount {
+		if self.initial_channel_target == 0 {
+			return TokenAmount::zero()
+		}
+		let joinable_funds_target = u64::from(self.joinable_funds_target.min(100));
+		self.funds * (100 - joinable_funds_target) / 100 / u64::from(self.initial_channel_target)
+	}
 }
 
 /// Umbrella object that stores the per blockchain state.
@@ -248,6 +307,8 @@ pub struct ChainState {
 	pub payment_mapping: PaymentMappingState,
 	pub pending_transactions: Vec<ContractSendEvent>,
 	pub pseudo_random_number_generator: Random,
+	pub invoices: HashMap<SecretHash, InvoiceState>,
+	pub connection_managers: HashMap<TokenNetworkAddress, ConnectionManagerState>,
 }
 
 impl ChainState {
@@ -264,9 +325,14 @@ impl ChainState {
 			block_hash,
 			our_address,
 			identifiers_to_tokennetworkregistries: HashMap::new(),
-			payment_mapping: PaymentMappingState { secrethashes_to_task: HashMap::new() },
+			payment_mapping: PaymentMappingState {
+				secrethashes_to_task: HashMap::new(),
+				secrethashes_to_circular_target: HashMap::new(),
+			},
 			pending_transactions: vec![],
 			pseudo_random_number_generator: Random::new(),
+			invoices: HashMap::new(),
+			connection_managers: HashMap::new(),
 		}
 	}
 }
@@ -834,9 +900,9 @@ impl Interpolate {
 #[derive(Serialize, Deserialize, Clone, Default, Debug, Eq, PartialEq)]
 pub struct FeeScheduleState {
 	pub cap_fees: bool,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub flat: U256,
-	#[serde(serialize_with = "u256_to_str")]
+	#[serde(serialize_with = "u256_to_str", deserialize_with = "u256_from_str")]
 	pub proportional: U256,
 	pub imbalance_penalty: Option<Vec<(U256, U256)>>,
 	#[serde(skip)]
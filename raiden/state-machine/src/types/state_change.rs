@@ -21,6 +21,7 @@ use raiden_primitives::types::{
 	SecretRegistryAddress,
 	Signature,
 	TokenAmount,
+	TokenNetworkAddress,
 	TokenNetworkRegistryAddress,
 	TransactionHash,
 	U256,
@@ -34,7 +35,9 @@ use crate::types::{
 	event::SendSecretReveal,
 	state::{
 		BalanceProofState,
+		ConnectionManagerState,
 		HopState,
+		InvoiceState,
 		LockedTransferState,
 		RouteState,
 		TransactionChannelDeposit,
@@ -62,6 +65,9 @@ pub enum StateChange {
 	ActionChannelWithdraw(ActionChannelWithdraw),
 	ActionTransferReroute(ActionTransferReroute),
 	ActionCancelPayment(ActionCancelPayment),
+	ActionCreateInvoice(ActionCreateInvoice),
+	ActionTokenNetworkConnect(ActionTokenNetworkConnect),
+	ActionTokenNetworkLeave(ActionTokenNetworkLeave),
 	ContractReceiveTokenNetworkRegistry(ContractReceiveTokenNetworkRegistry),
 	ContractReceiveTokenNetworkCreated(ContractReceiveTokenNetworkCreated),
 	ContractReceiveChannelOpened(ContractReceiveChannelOpened),
@@ -102,6 +108,9 @@ impl StateChange {
 			StateChange::ActionChannelWithdraw(_) => "ActionChannelWithdraw",
 			StateChange::ActionTransferReroute(_) => "ActionTransferReroute",
 			StateChange::ActionCancelPayment(_) => "ActionCancelPayment",
+			StateChange::ActionCreateInvoice(_) => "ActionCreateInvoice",
+			StateChange::ActionTokenNetworkConnect(_) => "ActionTokenNetworkConnect",
+			StateChange::ActionTokenNetworkLeave(_) => "ActionTokenNetworkLeave",
 			StateChange::ContractReceiveTokenNetworkRegistry(_) =>
 				"ContractReceiveTokenNetworkRegistry",
 			StateChange::ContractReceiveTokenNetworkCreated(_) =>
@@ -346,9 +355,29 @@ pub struct ActionTransferReroute {
 /// state of the transfer.
 #[derive(Serialize, Deserialize, Clone, Debug, IntoStateChange)]
 pub struct ActionCancelPayment {
+	pub token_network_address: TokenNetworkAddress,
+	pub target: Address,
 	pub payment_identifier: PaymentIdentifier,
 }
 
+/// The user requests a payment to this node with a secret held locally.
+#[derive(Serialize, Deserialize, Clone, Debug, IntoStateChange)]
+pub struct ActionCreateInvoice {
+	pub invoice: InvoiceState,
+}
+
+/// The user wants channels in a token network to be opened and funded automatically.
+#[derive(Serialize, Deserialize, Clone, Debug, IntoStateChange)]
+pub struct ActionTokenNetworkConnect {
+	pub connection_manager: ConnectionManagerState,
+}
+
+/// The user leaves a token network, its channels are no longer managed automatically.
+#[derive(Serialize, Deserialize, Clone, Debug, IntoStateChange)]
+pub struct ActionTokenNetworkLeave {
+	pub token_network_address: TokenNetworkAddress,
+}
+
 /// A mediator sends us a refund due to a failed route.
 #[derive(Serialize, Deserialize, Clone, Debug, IntoStateChange)]
 pub struct ReceiveTransferCancelRoute {
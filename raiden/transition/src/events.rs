@@ -60,9 +60,11 @@ use raiden_primitives::{
 	},
 };
 use raiden_state_machine::{
+	constants::PAYMENT_CANCELED_REASON,
 	types::{
 		ChainState,
 		ChannelEndState,
+		ChannelStatus,
 		Event,
 		StateChange,
 	},
@@ -79,7 +81,6 @@ use tracing::{
 	warn,
 };
 use web3::{
-	signing::Key,
 	transports::Http,
 	types::BlockNumber,
 	Web3,
@@ -100,6 +101,8 @@ pub struct EventHandler {
 	transport: UnboundedSender<TransportServiceMessage>,
 	default_addresses: DefaultAddresses,
 	payment_registry: Arc<RwLock<PaymentsRegistry>>,
+	/// Whether capacity and fee updates are sent to channel partners for their local routing.
+	private_routing: bool,
 }
 
 impl EventHandler {
@@ -112,6 +115,7 @@ impl EventHandler {
 		transport: UnboundedSender<TransportServiceMessage>,
 		default_addresses: DefaultAddresses,
 		payment_registry: Arc<RwLock<PaymentsRegistry>>,
+		private_routing: bool,
 	) -> Self {
 		Self {
 			web3,
@@ -121,12 +125,13 @@ impl EventHandler {
 			transport,
 			default_addresses,
 			payment_registry,
+			private_routing,
 		}
 	}
 
 	/// Handle event
 	pub async fn handle_event(&self, event: Event) {
-		let private_key = self.account.private_key();
+		let signer = self.account.signer();
 		match event {
 			Event::ContractSendChannelClose(inner) => {
 				let (nonce, balance_hash, signature_in_proof, message_hash, canonical_identifier) =
@@ -174,17 +179,16 @@ impl EventHandler {
 					signature_in_proof.clone(),
 				);
 
-				let our_signature: Bytes =
-					match self.account.private_key().sign_message(&closing_data.0) {
-						Ok(sig) => Bytes(sig.to_bytes()),
-						Err(e) => {
-							error!(
-								message = "Close channel, signing failed",
-								error = format!("{:?}", e),
-							);
-							return
-						},
-					};
+				let our_signature: Bytes = match signer.sign_message(&closing_data.0) {
+					Ok(sig) => Bytes(sig.to_bytes()),
+					Err(e) => {
+						error!(
+							message = "Close channel, signing failed",
+							error = format!("{:?}", e),
+						);
+						return
+					},
+				};
 
 				let chain_state = self.state_manager.read().current_state.clone();
 				let channel_state = match views::get_channel_by_canonical_identifier(
@@ -233,17 +237,16 @@ impl EventHandler {
 					inner.expiration,
 				);
 
-				let our_signature =
-					match self.account.private_key().sign_message(&withdraw_confirmation.0) {
-						Ok(sig) => Bytes(sig.to_bytes()),
-						Err(e) => {
-							error!(
-								message = "Channel withdraw, signing failed",
-								error = format!("{:?}", e),
-							);
-							return
-						},
-					};
+				let our_signature = match signer.sign_message(&withdraw_confirmation.0) {
+					Ok(sig) => Bytes(sig.to_bytes()),
+					Err(e) => {
+						error!(
+							message = "Channel withdraw, signing failed",
+							error = format!("{:?}", e),
+						);
+						return
+					},
+				};
 
 				let chain_state = self.state_manager.read().current_state.clone();
 				let channel_state = match views::get_channel_by_canonical_identifier(
@@ -489,7 +492,7 @@ impl EventHandler {
 					inner.expiration,
 				);
 				let our_initiator_signature =
-					match self.account.private_key().sign_message(&participant_withdraw_data.0) {
+					match signer.sign_message(&participant_withdraw_data.0) {
 						Ok(signature) => signature,
 						Err(e) => {
 							error!("Could not sign our withdraw data: {:?}", e);
@@ -511,14 +514,13 @@ impl EventHandler {
 					inner.partner_total_withdraw,
 					inner.expiration,
 				);
-				let our_partner_signature =
-					match self.account.private_key().sign_message(&partner_withdraw_data.0) {
-						Ok(signature) => signature,
-						Err(e) => {
-							error!("Could not sign partner withdraw data: {:?}", e);
-							return
-						},
-					};
+				let our_partner_signature = match signer.sign_message(&partner_withdraw_data.0) {
+					Ok(signature) => signature,
+					Err(e) => {
+						error!("Could not sign partner withdraw data: {:?}", e);
+						return
+					},
+				};
 
 				let withdraw_partner = WithdrawInput {
 					initiator: channel_state.partner_state.address,
@@ -588,14 +590,13 @@ impl EventHandler {
 					MessageTypeId::BalanceProofUpdate,
 					partner_signature.clone(),
 				);
-				let our_signature =
-					match self.account.private_key().sign_message(&non_closing_data.0) {
-						Ok(sig) => Bytes(sig.to_bytes()),
-						Err(e) => {
-							error!("Error signing non-closing-data {:?}", e);
-							return
-						},
-					};
+				let our_signature = match signer.sign_message(&non_closing_data.0) {
+					Ok(sig) => Bytes(sig.to_bytes()),
+					Err(e) => {
+						error!("Error signing non-closing-data {:?}", e);
+						return
+					},
+				};
 
 				if let Err(e) = channel_proxy
 					.update_transfer(
@@ -919,63 +920,63 @@ impl EventHandler {
 			},
 			Event::SendWithdrawRequest(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, WithdrawRequest);
+				let message = to_message!(inner, signer, WithdrawRequest);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendWithdrawConfirmation(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, WithdrawConfirmation);
+				let message = to_message!(inner, signer, WithdrawConfirmation);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendWithdrawExpired(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, WithdrawExpired);
+				let message = to_message!(inner, signer, WithdrawExpired);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendLockedTransfer(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, LockedTransfer);
+				let message = to_message!(inner, signer, LockedTransfer);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendLockExpired(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, LockExpired);
+				let message = to_message!(inner, signer, LockExpired);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendSecretReveal(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, SecretReveal);
+				let message = to_message!(inner, signer, SecretReveal);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendUnlock(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, Unlock);
+				let message = to_message!(inner, signer, Unlock);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendProcessed(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, Processed);
+				let message = to_message!(inner, signer, Processed);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
 			},
 			Event::SendSecretRequest(inner) => {
 				let queue_identifier = inner.queue_identifier();
-				let message = to_message!(inner, private_key, SecretRequest);
+				let message = to_message!(inner, signer, SecretRequest);
 				let _ = self
 					.transport
 					.send(TransportServiceMessage::Enqueue((queue_identifier, message)));
@@ -990,29 +991,34 @@ impl EventHandler {
 					None => return,
 				};
 
-				let mut capacity_message: PFSCapacityUpdate = channel_state.clone().into();
-				let _ = capacity_message.sign(private_key.clone());
-				let message = OutgoingMessage {
-					message_identifier: 0,
-					recipient: Address::zero(),
-					recipient_metadata: AddressMetadata::default(),
-					inner: MessageInner::PFSCapacityUpdate(capacity_message),
+				let partners = if self.private_routing {
+					views::get_channels(chain_state)
+						.into_iter()
+						.filter(|channel| {
+							channel.canonical_identifier.token_network_address ==
+								channel_state.canonical_identifier.token_network_address &&
+								channel.status() == ChannelStatus::Opened
+						})
+						.map(|channel| channel.partner_state.address)
+						.collect()
+				} else {
+					vec![]
 				};
-				let _ = self.transport.send(TransportServiceMessage::Broadcast(message));
+
+				let mut capacity_message: PFSCapacityUpdate = channel_state.clone().into();
+				let _ = capacity_message.sign(signer.clone());
+				self.broadcast_pfs_update(
+					MessageInner::PFSCapacityUpdate(capacity_message),
+					&partners,
+				);
 
 				if !pfs_update.update_fee_schedule {
 					return
 				}
 
 				let mut fee_message: PFSFeeUpdate = channel_state.clone().into();
-				let _ = fee_message.sign(private_key);
-				let message = OutgoingMessage {
-					message_identifier: 0,
-					recipient: Address::zero(),
-					recipient_metadata: AddressMetadata::default(),
-					inner: MessageInner::PFSFeeUpdate(fee_message),
-				};
-				let _ = self.transport.send(TransportServiceMessage::Broadcast(message));
+				let _ = fee_message.sign(signer);
+				self.broadcast_pfs_update(MessageInner::PFSFeeUpdate(fee_message), &partners);
 			},
 			Event::SendMSUpdate(balance_proof) => {
 				let chain_state = self.state_manager.read().current_state.clone();
@@ -1068,7 +1074,7 @@ impl EventHandler {
 					*MONITORING_REWARD,
 					self.default_addresses.monitoring_service,
 				);
-				let _ = monitoring_message.sign(private_key);
+				let _ = monitoring_message.sign(signer);
 				let message = OutgoingMessage {
 					message_identifier: 0,
 					recipient: Address::zero(),
@@ -1098,11 +1104,14 @@ impl EventHandler {
 			},
 			Event::ErrorPaymentSentFailed(e) => {
 				error!(message = "Payment failed", reason = e.reason);
-				self.payment_registry.write().await.complete(PaymentStatus::Error(
-					e.target,
-					e.identifier,
-					e.reason,
-				));
+				// Cancelled payments are completed by the API once the cancellation succeeded.
+				if e.reason != PAYMENT_CANCELED_REASON {
+					self.payment_registry.write().await.complete(PaymentStatus::Error(
+						e.target,
+						e.identifier,
+						e.reason,
+					));
+				}
 			},
 			Event::ErrorRouteFailed(e) => {
 				error!(
@@ -1154,4 +1163,18 @@ impl EventHandler {
 			},
 		}
 	}
+
+	/// Broadcast a capacity or fee update to the services and send it to `partners`.
+	fn broadcast_pfs_update(&self, inner: MessageInner, partners: &[Address]) {
+		let recipients = std::iter::once(Address::zero()).chain(partners.iter().cloned());
+		for recipient in recipients {
+			let message = OutgoingMessage {
+				message_identifier: 0,
+				recipient,
+				recipient_metadata: AddressMetadata::default(),
+				inner: inner.clone(),
+			};
+			let _ = self.transport.send(TransportServiceMessage::Broadcast(message));
+		}
+	}
 }
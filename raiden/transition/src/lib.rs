@@ -3,11 +3,17 @@ use std::sync::Arc;
 
 use futures::future::join_all;
 use parking_lot::RwLock;
+use raiden_primitives::{
+	traits::Checksum,
+	types::TokenAmount,
+};
 use raiden_state_machine::types::{
+	ChannelStatus,
 	Event,
 	PFSUpdate,
 	StateChange,
 };
+use tokio::sync::broadcast;
 use tracing::trace;
 
 use crate::{
@@ -24,12 +30,16 @@ pub mod messages;
 /// Transition utils.
 pub mod utils;
 
+/// The number of events buffered for subscribers before slow subscribers start lagging behind.
+pub const EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
 /// Transitioner used to dispatch state changes into the state machine and transition events back to
 /// the event handler.
 pub struct Transitioner {
 	state_manager: Arc<RwLock<StateManager>>,
 	event_handler: EventHandler,
 	monitoring_enabled: bool,
+	events_sender: broadcast::Sender<Event>,
 }
 
 impl Transitioner {
@@ -39,7 +49,13 @@ impl Transitioner {
 		event_handler: EventHandler,
 		monitoring_enabled: bool,
 	) -> Self {
-		Self { state_manager, event_handler, monitoring_enabled }
+		let (events_sender, _) = broadcast::channel(EVENTS_CHANNEL_CAPACITY);
+		Self { state_manager, event_handler, monitoring_enabled, events_sender }
+	}
+
+	/// Subscribe to the events produced by the state machine as they are transitioned.
+	pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+		self.events_sender.subscribe()
 	}
 
 	/// Transition state changes into state machine and resulting events into event handler.
@@ -47,6 +63,10 @@ impl Transitioner {
 		let mut raiden_events = vec![];
 		for state_change in state_changes.clone() {
 			trace!(message = "Transition", state_change = state_change.type_name());
+			raiden_metrics::state_change_dispatched(state_change.type_name());
+			if let StateChange::ActionInitInitiator(ref inner) = state_change {
+				raiden_metrics::payment_started(inner.transfer.payment_identifier.as_u64());
+			}
 			let events =
 				self.state_manager.write().transition(state_change.clone()).map_err(|e| e.msg)?;
 			for event in events.iter() {
@@ -58,10 +78,51 @@ impl Transitioner {
 			}
 			raiden_events.extend(events);
 		}
+		for event in raiden_events.iter() {
+			raiden_metrics::event_emitted(event.type_name());
+			match event {
+				Event::PaymentSentSuccess(inner) =>
+					raiden_metrics::payment_succeeded(inner.identifier.as_u64()),
+				Event::ErrorPaymentSentFailed(inner) =>
+					raiden_metrics::payment_failed(inner.identifier.as_u64()),
+				_ => {},
+			}
+			// Sending only fails if there are no subscribers.
+			let _ = self.events_sender.send(event.clone());
+		}
+		self.update_channel_metrics();
 		self.trigger_state_change_effects(state_changes, raiden_events).await;
 		Ok(())
 	}
 
+	/// Update the number and capacity of the open channels per token network.
+	fn update_channel_metrics(&self) {
+		let state_manager = self.state_manager.read();
+		let chain_state = &state_manager.current_state;
+		let account = chain_state.our_address.checksum();
+		for token_network_registry in chain_state.identifiers_to_tokennetworkregistries.values() {
+			for token_network in
+				token_network_registry.tokennetworkaddresses_to_tokennetworks.values()
+			{
+				let open_channels: Vec<_> = token_network
+					.channelidentifiers_to_channels
+					.values()
+					.filter(|channel| channel.status() == ChannelStatus::Opened)
+					.collect();
+				let capacity =
+					open_channels.iter().fold(TokenAmount::zero(), |capacity, channel| {
+						capacity.saturating_add(channel.capacity())
+					});
+				raiden_metrics::set_channels(
+					&account,
+					&token_network.address.checksum(),
+					open_channels.len(),
+					capacity.to_string().parse().unwrap_or(f64::MAX),
+				);
+			}
+		}
+	}
+
 	/// Process state changes and event effects.
 	async fn trigger_state_change_effects(
 		&self,
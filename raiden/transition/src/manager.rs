@@ -15,7 +15,7 @@ use raiden_state_machine::{
 			StorageError,
 			StorageID,
 		},
-		StateStorage,
+		Storage,
 		SNAPSHOT_STATE_CHANGE_COUNT,
 	},
 	types::{
@@ -34,7 +34,7 @@ pub type Result<T> = std::result::Result<T, StateTransitionError>;
 
 /// Manage the chain state.
 pub struct StateManager {
-	pub storage: Arc<StateStorage>,
+	pub storage: Arc<dyn Storage>,
 	pub current_state: ChainState,
 	state_change_last_id: Option<StorageID>,
 	state_change_count: u16,
@@ -43,7 +43,7 @@ pub struct StateManager {
 impl StateManager {
 	/// Try to restore an existing state, otherwise initialize a new one.
 	pub fn restore_or_init_state(
-		storage: Arc<StateStorage>,
+		storage: Arc<dyn Storage>,
 		chain_id: ChainID,
 		our_address: Address,
 		token_network_registry_address: TokenNetworkRegistryAddress,
@@ -96,7 +96,7 @@ impl StateManager {
 
 	/// Initialize a new state machine.
 	fn init_state(
-		storage: Arc<StateStorage>,
+		storage: Arc<dyn Storage>,
 		chain_id: ChainID,
 		our_address: Address,
 		token_network_registry_address: TokenNetworkRegistryAddress,
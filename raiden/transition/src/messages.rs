@@ -5,7 +5,7 @@ use std::{
 
 use parking_lot::RwLock;
 use raiden_blockchain::{
-	keys::PrivateKey,
+	keys::Signer,
 	secret::decrypt_secret,
 };
 use raiden_network_messages::{
@@ -20,6 +20,7 @@ use raiden_network_messages::{
 		TransportServiceMessage,
 	},
 };
+use raiden_pathfinding::graph::NetworkGraph;
 use raiden_primitives::{
 	constants::CANONICAL_IDENTIFIER_UNORDERED_QUEUE,
 	hashing::{
@@ -34,6 +35,7 @@ use raiden_primitives::{
 		QueueIdentifier,
 		SecretHash,
 		Signature,
+		TokenNetworkAddress,
 	},
 };
 use raiden_state_machine::{
@@ -41,6 +43,7 @@ use raiden_state_machine::{
 		ActionInitMediator,
 		ActionInitTarget,
 		BalanceProofState,
+		ChannelStatus,
 		HashTimeLockState,
 		HopState,
 		LockedTransferState,
@@ -63,7 +66,6 @@ use tracing::{
 	debug,
 	error,
 };
-use web3::signing::Key;
 
 use crate::{
 	manager::StateManager,
@@ -72,29 +74,35 @@ use crate::{
 
 /// The message handler.
 pub struct MessageHandler {
-	private_key: PrivateKey,
+	signer: Arc<dyn Signer>,
 	pathfinding_service_url: String,
 	transport_sender: UnboundedSender<TransportServiceMessage>,
 	state_manager: Arc<RwLock<StateManager>>,
 	transition_service: Arc<Transitioner>,
+	network_graph: Option<Arc<RwLock<NetworkGraph>>>,
 	metadata_cache: HashMap<Address, AddressMetadata>,
 }
 
 impl MessageHandler {
 	/// Create an instance of `MessageHandler'.
+	///
+	/// Capacity and fee updates of other nodes are only used if a `network_graph` is kept for
+	/// private routing.
 	pub fn new(
-		private_key: PrivateKey,
+		signer: Arc<dyn Signer>,
 		pathfinding_service_url: String,
 		transport_sender: UnboundedSender<TransportServiceMessage>,
 		state_manager: Arc<RwLock<StateManager>>,
 		transition_service: Arc<Transitioner>,
+		network_graph: Option<Arc<RwLock<NetworkGraph>>>,
 	) -> Self {
 		Self {
-			private_key,
+			signer,
 			pathfinding_service_url,
 			transport_sender,
 			state_manager,
 			transition_service,
+			network_graph,
 			metadata_cache: HashMap::new(),
 		}
 	}
@@ -170,7 +178,7 @@ impl MessageHandler {
 					node_address: sender,
 					channel_identifier: message.channel_identifier,
 				};
-				let state_changes = if message.target == self.private_key.address() {
+				let state_changes = if message.target == self.signer.address() {
 					let mut init_target = ActionInitTarget {
 						sender,
 						balance_proof,
@@ -181,7 +189,7 @@ impl MessageHandler {
 
 					let secret_reveal = if let Some(encrypted_secret) = message.metadata.secret {
 						let decrypted_secret =
-							decrypt_secret(encrypted_secret.0, &self.private_key)?;
+							decrypt_secret(encrypted_secret.0, self.signer.as_ref())?;
 						if transfer.lock.amount < decrypted_secret.amount ||
 							transfer.payment_identifier != decrypted_secret.payment_identifier
 						{
@@ -208,7 +216,7 @@ impl MessageHandler {
 					let mut filtered_route_states = vec![];
 					for route_state in transfer.route_states.iter() {
 						if let Some(next_hope_address) =
-							route_state.hop_after(self.private_key.address())
+							route_state.hop_after(self.signer.address())
 						{
 							if views::get_channel_by_token_network_and_partner(
 								chain_state,
@@ -412,8 +420,55 @@ impl MessageHandler {
 					message_identifier: message.delivered_message_identifier,
 				})])
 			},
-			messages::MessageInner::PFSCapacityUpdate(_) |
-			messages::MessageInner::PFSFeeUpdate(_) |
+			messages::MessageInner::PFSCapacityUpdate(ref update) => {
+				let sender = get_sender(&update.bytes_to_sign(), &update.signature.0)?;
+				if sender != update.updating_participant {
+					return Err(
+						"PFSCapacityUpdate is not signed by the updating participant".to_owned()
+					)
+				}
+				let updated = self.network_graph.as_ref().map(|network_graph| {
+					network_graph.write().update_capacity(
+						&update.canonical_identifier,
+						update.updating_participant,
+						update.other_participant,
+						update.updating_nonce,
+						update.other_nonce,
+						update.updating_capacity,
+						update.other_capacity,
+					)
+				});
+				if updated == Some(true) {
+					self.relay_pfs_update(
+						sender,
+						update.canonical_identifier.token_network_address,
+						message.inner.clone(),
+					);
+				}
+				return Ok(vec![])
+			},
+			messages::MessageInner::PFSFeeUpdate(ref update) => {
+				let sender = get_sender(&update.bytes_to_sign(), &update.signature.0)?;
+				if sender != update.updating_participant {
+					return Err("PFSFeeUpdate is not signed by the updating participant".to_owned())
+				}
+				let updated = self.network_graph.as_ref().map(|network_graph| {
+					network_graph.write().update_fee(
+						&update.canonical_identifier,
+						update.updating_participant,
+						update.fee_schedule.clone(),
+						update.timestamp.clone(),
+					)
+				});
+				if updated == Some(true) {
+					self.relay_pfs_update(
+						sender,
+						update.canonical_identifier.token_network_address,
+						message.inner.clone(),
+					);
+				}
+				return Ok(vec![])
+			},
 			messages::MessageInner::MSUpdate(_) => {
 				// We should not receive those messages.
 				// IGNORE
@@ -431,7 +486,7 @@ impl MessageHandler {
 			delivered_message_identifier: message.message_identifier,
 			signature: Signature::default(),
 		};
-		let _ = delivered.sign(self.private_key.clone());
+		let _ = delivered.sign(self.signer.clone());
 		let delivered = OutgoingMessage {
 			message_identifier: message.message_identifier,
 			recipient: sender,
@@ -448,6 +503,38 @@ impl MessageHandler {
 
 		Ok(state_changes)
 	}
+
+	/// Forward a capacity or fee update of another node to our partners in the token network so
+	/// that it reaches every node of the network.
+	fn relay_pfs_update(
+		&self,
+		sender: Address,
+		token_network_address: TokenNetworkAddress,
+		inner: MessageInner,
+	) {
+		let partners: Vec<Address> = {
+			let chain_state = &self.state_manager.read().current_state;
+			views::get_channels(chain_state)
+				.into_iter()
+				.filter(|channel| {
+					channel.canonical_identifier.token_network_address == token_network_address &&
+						channel.status() == ChannelStatus::Opened &&
+						channel.partner_state.address != sender
+				})
+				.map(|channel| channel.partner_state.address)
+				.collect()
+		};
+
+		for partner in partners {
+			let message = OutgoingMessage {
+				message_identifier: 0,
+				recipient: partner,
+				recipient_metadata: self.metadata_cache.get(&partner).cloned().unwrap_or_default(),
+				inner: inner.clone(),
+			};
+			let _ = self.transport_sender.send(TransportServiceMessage::Broadcast(message));
+		}
+	}
 }
 
 /// Recover sender address from data and signature.
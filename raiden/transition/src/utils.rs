@@ -5,7 +5,7 @@ use raiden_state_machine::{
 	machine::chain,
 	storage::{
 		types::StorageID,
-		StateStorage,
+		Storage,
 	},
 	types::{
 		ChainState,
@@ -17,7 +17,7 @@ use raiden_state_machine::{
 /// Retore state from existing storage, detect unapplied changes and apply them on top of found
 /// snapshot.
 fn restore_state(
-	storage: Arc<StateStorage>,
+	storage: Arc<dyn Storage>,
 	state_change_identifier: StorageID,
 ) -> Option<ChainState> {
 	let snapshot = storage.get_snapshot_before_state_change(state_change_identifier).ok()?;
@@ -34,9 +34,14 @@ fn restore_state(
 	Some(chain_state)
 }
 
+/// Restore the latest chain state from storage.
+pub fn latest_state(storage: Arc<dyn Storage>) -> Option<ChainState> {
+	restore_state(storage, StorageID::max())
+}
+
 /// Return a channel state before a state change was applied.
 pub fn channel_state_until_state_change(
-	storage: Arc<StateStorage>,
+	storage: Arc<dyn Storage>,
 	canonical_identifier: CanonicalIdentifier,
 	state_change_identifier: StorageID,
 ) -> Option<ChannelState> {